@@ -0,0 +1,96 @@
+// Count the heap allocations made while finding split points. This
+// is not a timing benchmark: run it with
+//
+//     cargo bench --bench allocations --features hyphenation
+//
+// and compare the numbers for `split_points`, which returns a `Vec`
+// per word, and `split`, which keeps a few split points inline. The
+// `wrap` lines count the allocations of wrapping the whole corpus,
+// with and without hyphenation. They are made per paragraph and per
+// line, not per word.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use textwrap::{HyphenSplitter, Options, WordSplitter};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ENGLISH: &str = include_str!("corpus/english.txt");
+
+/// Return the number of allocations made by `f`.
+fn count<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn report<S: WordSplitter>(name: &str, splitter: &S, words: &[&str]) {
+    let vec = count(|| {
+        for word in words {
+            std::hint::black_box(splitter.split_points(word));
+        }
+    });
+    let inline = count(|| {
+        for word in words {
+            std::hint::black_box(splitter.split(word));
+        }
+    });
+    println!(
+        "{:<20} {:>6} words: split_points {:>6} allocations, split {:>6} allocations",
+        name,
+        words.len(),
+        vec,
+        inline
+    );
+}
+
+fn main() {
+    let words = ENGLISH.split_whitespace().collect::<Vec<_>>();
+    report("HyphenSplitter", &HyphenSplitter, &words);
+
+    // `split_points` goes through `Hyphenator::hyphenate`, which
+    // allocates several times per word, but `split` scores ASCII words
+    // on the stack.
+    #[cfg(feature = "hyphenation")]
+    {
+        use hyphenation::{Language, Load, Standard};
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        report("Standard(en-us)", &dictionary, &words);
+        report_wrap(
+            "wrap(en-us)",
+            &Options::new(60).splitter(dictionary),
+            &words,
+        );
+    }
+
+    report_wrap("wrap", &Options::new(60), &words);
+}
+
+fn report_wrap<S: WordSplitter>(name: &str, options: &Options<'_, S>, words: &[&str]) {
+    let wrap = count(|| {
+        std::hint::black_box(textwrap::wrap(ENGLISH, options));
+    });
+    println!(
+        "{:<20} {:>6} words: wrap {:>6} allocations",
+        name,
+        words.len(),
+        wrap
+    );
+}
@@ -0,0 +1,44 @@
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::{criterion_group, criterion_main};
+
+// The benchmarks here compare the fast path used for printable ASCII
+// text with the general path used for all other text. The general
+// path is forced by adding a single non-ASCII character to the end
+// of the text, which changes neither the words nor the wrapping.
+
+use lipsum::lipsum_words_from_seed;
+
+const LINE_LENGTH: usize = 60;
+
+pub fn benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ASCII fast path");
+    for length in [400, 1600, 6400].iter() {
+        let mut ascii = lipsum_words_from_seed(length / 5, 42);
+        ascii.truncate(*length);
+        let unicode = format!("{}…", ascii);
+
+        for (name, text) in [("ascii", &ascii), ("unicode", &unicode)].iter() {
+            let options = textwrap::Options::new(LINE_LENGTH)
+                .wrap_algorithm(textwrap::core::WrapAlgorithm::FirstFit);
+            group.bench_with_input(
+                BenchmarkId::new(format!("fill_{}", name), length),
+                text,
+                |b, text| {
+                    b.iter(|| textwrap::fill(text, &options));
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("display_width_{}", name), length),
+                text,
+                |b, text| {
+                    b.iter(|| textwrap::core::display_width(text));
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
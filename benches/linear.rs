@@ -62,10 +62,20 @@ pub fn benchmark(c: &mut Criterion) {
                 .join("benches")
                 .join("la.standard.bincode");
             let dictionary = Standard::from_path(Language::Latin, &path).unwrap();
+            let cached_options = options
+                .clone()
+                .splitter(textwrap::CachedSplitter::new(dictionary.clone()));
             let options = options.splitter(dictionary);
             group.bench_with_input(BenchmarkId::new("hyphenation", length), &text, |b, text| {
                 b.iter(|| textwrap::fill(text, &options));
             });
+            group.bench_with_input(
+                BenchmarkId::new("hyphenation_cached", length),
+                &text,
+                |b, text| {
+                    b.iter(|| textwrap::fill(text, &cached_options));
+                },
+            );
         };
     }
     group.finish();
@@ -0,0 +1,105 @@
+use criterion::Criterion;
+use criterion::{criterion_group, criterion_main};
+
+// The benchmarks here cover realistic workloads. The text comes from
+// the fixed corpora in benches/corpus/, which keeps the numbers
+// comparable between machines and between changes.
+
+use textwrap::core::WrapAlgorithm;
+use textwrap::{LongWords, Options};
+
+const ENGLISH: &str = include_str!("corpus/english.txt");
+const CJK: &str = include_str!("corpus/cjk.txt");
+const EMOJI: &str = include_str!("corpus/emoji.txt");
+
+const LINE_LENGTH: usize = 60;
+
+/// Join the lines of `text` into a single long paragraph.
+fn paragraph(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Indent each line of the English corpus like a large source file.
+fn large_file() -> String {
+    let mut text = String::new();
+    for level in 0..50 {
+        let prefix = " ".repeat(4 * (1 + level % 3));
+        text.push_str(&textwrap::indent(ENGLISH, &prefix));
+    }
+    text
+}
+
+pub fn benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Workloads");
+    let english = paragraph(ENGLISH);
+    let cjk = CJK.replace('\n', "");
+    let emoji = paragraph(EMOJI);
+
+    #[allow(unused_mut)]
+    let mut algorithms = vec![("first_fit", WrapAlgorithm::FirstFit)];
+    #[cfg(feature = "smawk")]
+    algorithms.push(("optimal_fit", WrapAlgorithm::OptimalFit));
+    for (name, algorithm) in algorithms {
+        let options = Options::new(LINE_LENGTH).wrap_algorithm(algorithm);
+        group.bench_function(format!("ascii_paragraph_{}", name), |b| {
+            b.iter(|| textwrap::wrap(&english, &options));
+        });
+    }
+
+    let options = Options::new(LINE_LENGTH)
+        .word_separator(textwrap::core::WordSeparator::UnicodeBreakProperties);
+    group.bench_function("cjk_paragraph", |b| {
+        b.iter(|| textwrap::wrap(&cjk, &options));
+    });
+
+    let options = Options::new(LINE_LENGTH);
+    group.bench_function("emoji_paragraph", |b| {
+        b.iter(|| textwrap::wrap(&emoji, &options));
+    });
+
+    #[cfg(feature = "hyphenation")]
+    {
+        use hyphenation::{Language, Load, Standard};
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(LINE_LENGTH).splitter(dictionary);
+        group.bench_function("hyphenation", |b| {
+            b.iter(|| textwrap::wrap(&english, &options));
+        });
+    }
+
+    let options = Options::new(LINE_LENGTH);
+    group.bench_function("fill_paragraphs_document", |b| {
+        b.iter(|| textwrap::fill_paragraphs(ENGLISH, &options));
+    });
+
+    #[cfg(feature = "rayon")]
+    {
+        let book = [ENGLISH; 20].join("\n");
+        let options = Options::new(LINE_LENGTH);
+        group.bench_function("fill_paragraphs_book", |b| {
+            b.iter(|| textwrap::fill_paragraphs(&book, &options));
+        });
+        group.bench_function("fill_par_book", |b| {
+            b.iter(|| textwrap::fill_par(&book, &options));
+        });
+    }
+
+    let file = large_file();
+    group.bench_function("dedent_large_file", |b| {
+        b.iter(|| textwrap::dedent(&file));
+    });
+    let dedented = textwrap::dedent(&file);
+    group.bench_function("indent_large_file", |b| {
+        b.iter(|| textwrap::indent(&dedented, "    "));
+    });
+
+    let options = Options::new(5).long_words(LongWords::Break);
+    group.bench_function("narrow_break_words", |b| {
+        b.iter(|| textwrap::wrap(&english, &options));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
@@ -1,324 +1,383 @@
-// The example only works on Linux since Termion does not yet support
-// Windows: https://gitlab.redox-os.org/redox-os/termion/-/issues/103
-// The precise library doesn't matter much, so feel free to send a PR
-// if there is a library with good Windows support.
-
-fn main() -> Result<(), std::io::Error> {
-    #[cfg(not(unix))]
-    panic!("Sorry, this example currently only works on Unix!");
-
-    #[cfg(unix)]
-    unix_only::main()
+use crossterm::cursor::{self, MoveTo, SetCursorStyle};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::fmt::Display;
+use std::io::{self, Write};
+#[cfg(feature = "smawk")]
+use textwrap::core::WrapAlgorithm::{FirstFit, OptimalFit};
+use textwrap::{wrap, HyphenSplitter, LongWords, NoHyphenation, Options, WordSplitter};
+
+#[cfg(feature = "hyphenation")]
+use hyphenation::{Language, Load, Standard};
+
+/// Number of lines next to the drop cap.
+const DROP_CAP_LINES: usize = 2;
+/// Number of columns taken up by the drop cap.
+const DROP_CAP_WIDTH: usize = 4;
+
+/// Move the cursor to the 1-based `col` and `row`.
+fn goto(col: u16, row: u16) -> MoveTo {
+    MoveTo(col - 1, row - 1)
 }
 
-#[cfg(unix)]
-mod unix_only {
-    use std::io::{self, Write};
-    use termion::event::Key;
-    use termion::input::TermRead;
-    use termion::raw::{IntoRawMode, RawTerminal};
-    use termion::screen::AlternateScreen;
-    use termion::{color, cursor, style};
-    #[cfg(feature = "smawk")]
-    use textwrap::core::WrapAlgorithm::{FirstFit, OptimalFit};
-    use textwrap::{wrap, HyphenSplitter, NoHyphenation, Options, WordSplitter};
-
-    #[cfg(feature = "hyphenation")]
-    use hyphenation::{Language, Load, Standard};
-
-    fn draw_margins(
-        row: u16,
-        col: u16,
-        line_width: u16,
-        left: char,
-        right: char,
-        stdout: &mut RawTerminal<io::Stdout>,
-    ) -> Result<(), io::Error> {
-        write!(
-            stdout,
-            "{}{}{}{}",
-            cursor::Goto(col - 1, row),
-            color::Fg(color::Red),
-            left,
-            color::Fg(color::Reset),
-        )?;
-        write!(
-            stdout,
-            "{}{}{}{}",
-            cursor::Goto(col + line_width, row),
-            color::Fg(color::Red),
-            right,
-            color::Fg(color::Reset),
-        )?;
-
-        Ok(())
-    }
-
-    fn draw_text<'a>(
-        text: &str,
-        options: &Options<'a>,
-        splitter_label: &str,
-        stdout: &mut RawTerminal<io::Stdout>,
-    ) -> Result<(), io::Error> {
-        let mut left_row: u16 = 1;
-        let left_col: u16 = 3;
-
-        write!(stdout, "{}", termion::clear::All)?;
-        write!(
-            stdout,
-            "{}{}Options:{}",
-            cursor::Goto(left_col, left_row),
-            style::Bold,
-            style::Reset,
-        )?;
-        left_row += 1;
-
-        write!(
-            stdout,
-            "{}- width: {}{}{} (use ← and → to change)",
-            cursor::Goto(left_col, left_row),
-            style::Bold,
-            options.width,
-            style::Reset,
-        )?;
-        left_row += 1;
-
-        write!(
-            stdout,
-            "{}- break_words: {}{:?}{} (toggle with Ctrl-b)",
-            cursor::Goto(left_col, left_row),
-            style::Bold,
-            options.break_words,
-            style::Reset,
-        )?;
-        left_row += 1;
+/// Draw `label` followed by the `value` in bold and a `hint`.
+fn draw_field(
+    col: u16,
+    row: u16,
+    label: &str,
+    value: impl Display,
+    hint: &str,
+    stdout: &mut impl Write,
+) -> Result<(), io::Error> {
+    queue!(
+        stdout,
+        goto(col, row),
+        Print(label),
+        SetAttribute(Attribute::Bold),
+        Print(value),
+        SetAttribute(Attribute::Reset),
+        Print(hint),
+    )
+}
 
-        write!(
-            stdout,
-            "{}- splitter: {}{}{} (cycle with Ctrl-s)",
-            cursor::Goto(left_col, left_row),
-            style::Bold,
-            splitter_label,
-            style::Reset,
-        )?;
-        left_row += 1;
+fn draw_margins(
+    row: u16,
+    col: u16,
+    line_width: u16,
+    left: char,
+    right: char,
+    stdout: &mut impl Write,
+) -> Result<(), io::Error> {
+    queue!(
+        stdout,
+        SetForegroundColor(Color::Red),
+        goto(col - 1, row),
+        Print(left),
+        goto(col + line_width, row),
+        Print(right),
+        ResetColor,
+    )
+}
 
-        #[cfg(feature = "smawk")]
-        {
-            write!(
-                stdout,
-                "{}- algorithm: {}{:?}{} (toggle with Ctrl-o)",
-                cursor::Goto(left_col, left_row),
-                style::Bold,
-                options.wrap_algorithm,
-                style::Reset,
-            )?;
-            left_row += 1;
-        }
+fn draw_text<'a>(
+    text: &str,
+    options: &Options<'a>,
+    splitter_label: &str,
+    drop_cap: bool,
+    stdout: &mut impl Write,
+) -> Result<(), io::Error> {
+    let mut left_row: u16 = 1;
+    let left_col: u16 = 3;
+
+    queue!(stdout, Clear(ClearType::All))?;
+    draw_field(left_col, left_row, "", "Options:", "", stdout)?;
+    left_row += 1;
+
+    let hint = " (use ← and → to change)";
+    draw_field(left_col, left_row, "- width: ", options.width, hint, stdout)?;
+    left_row += 1;
+
+    let long_words = format!("{:?}", options.long_words);
+    let hint = " (toggle with Ctrl-b)";
+    draw_field(
+        left_col,
+        left_row,
+        "- long_words: ",
+        long_words,
+        hint,
+        stdout,
+    )?;
+    left_row += 1;
+
+    let hint = " (cycle with Ctrl-s)";
+    draw_field(
+        left_col,
+        left_row,
+        "- splitter: ",
+        splitter_label,
+        hint,
+        stdout,
+    )?;
+    left_row += 1;
+
+    let hint = " (toggle with Ctrl-d)";
+    draw_field(left_col, left_row, "- drop cap: ", drop_cap, hint, stdout)?;
+    left_row += 1;
 
-        let now = std::time::Instant::now();
-        let mut lines = wrap(text, options);
-        let elapsed = now.elapsed();
-
-        let right_col: u16 = 55;
-        let mut right_row: u16 = 1;
-        write!(
-            stdout,
-            "{}{}Performance:{}",
-            cursor::Goto(right_col, right_row),
-            style::Bold,
-            style::Reset,
-        )?;
-        right_row += 1;
-
-        write!(
-            stdout,
-            "{}- build: {}{}{}",
-            cursor::Goto(right_col, right_row),
-            style::Bold,
-            if cfg!(debug_assertions) {
-                "debug"
-            } else {
-                "release"
-            },
-            style::Reset,
-        )?;
-        right_row += 1;
-
-        write!(
-            stdout,
-            "{}- words: {}{}{}",
-            cursor::Goto(right_col, right_row),
-            style::Bold,
-            text.split_whitespace().count(),
-            style::Reset,
-        )?;
-        right_row += 1;
-
-        write!(
-            stdout,
-            "{}- characters: {}{}{}",
-            cursor::Goto(right_col, right_row),
-            style::Bold,
-            text.chars().count(),
-            style::Reset,
-        )?;
-        right_row += 1;
-
-        write!(
-            stdout,
-            "{}- latency: {}{} usec{}",
-            cursor::Goto(right_col, right_row),
-            style::Bold,
-            elapsed.as_micros(),
-            style::Reset,
-        )?;
-
-        // Empty line.
+    #[cfg(feature = "smawk")]
+    {
+        let algorithm = format!("{:?}", options.wrap_algorithm);
+        let hint = " (toggle with Ctrl-o)";
+        draw_field(left_col, left_row, "- algorithm: ", algorithm, hint, stdout)?;
         left_row += 1;
+    }
 
-        if let Some(line) = lines.last_mut() {
-            let trailing_whitespace = &text[text.trim_end_matches(' ').len()..];
-            if !trailing_whitespace.is_empty() {
-                // Trailing whitespace is discarded by
-                // `textwrap::wrap`. We reinsert it here. If multiple
-                // spaces are added, this can overflow the margins
-                // which look a bit odd. Handling this would require
-                // some more tinkering...
-                *line = format!("{}{}", line, trailing_whitespace).into();
-            } else if line.ends_with('\n') {
-                // If `text` ends with a newline, the final wrapped line
-                // contains this newline. This will in turn leave the
-                // cursor hanging in the middle of the line. Pushing an
-                // extra empty line fixes this.
-                lines.push("".into());
-            }
+    // With a drop cap, the first character is drawn separately
+    // and the first lines are made narrower to leave room for it.
+    let (cap, body) = match text.chars().next() {
+        Some(ch) if drop_cap => text.split_at(ch.len_utf8()),
+        _ => ("", text),
+    };
+    let width = options.width;
+    let shape = move |line| {
+        if drop_cap && line < DROP_CAP_LINES {
+            width.saturating_sub(DROP_CAP_WIDTH)
         } else {
-            // No lines -> we add an empty line so we have a place
-            // where we can display the cursor.
-            lines.push("".into());
+            width
         }
-
-        // Draw margins above and below the wrapped text. We draw the
-        // margin before the text so that 1) the text can overwrite
-        // the margin if `break_words` is `false` and `width` is very
-        // small and 2) so the cursor remains at the end of the last
-        // line of text.
-        draw_margins(left_row, left_col, options.width as u16, '┌', '┐', stdout)?;
-        left_row += 1;
-        let final_row = left_row + lines.len() as u16;
-        draw_margins(final_row, left_col, options.width as u16, '└', '┘', stdout)?;
-
-        let (_, rows) = termion::terminal_size()?;
-        write!(stdout, "{}", cursor::Show)?;
-        for line in lines {
-            if left_row > rows {
-                // The text does not fits on the terminal -- we hide
-                // the cursor since it's supposed to be "below" the
-                // bottom of the terminal.
-                write!(stdout, "{}", cursor::Hide)?;
-                break;
-            }
-            draw_margins(left_row, left_col, options.width as u16, '│', '│', stdout)?;
-            write!(stdout, "{}{}", cursor::Goto(left_col, left_row), line)?;
-            left_row += 1;
+    };
+    let shaped_options = Options::from(options).width_fn(&shape);
+
+    let now = std::time::Instant::now();
+    let mut lines = wrap(body, &shaped_options);
+    let elapsed = now.elapsed();
+
+    let right_col: u16 = 55;
+    let mut right_row: u16 = 1;
+    draw_field(right_col, right_row, "", "Performance:", "", stdout)?;
+    right_row += 1;
+
+    let build = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    draw_field(right_col, right_row, "- build: ", build, "", stdout)?;
+    right_row += 1;
+
+    let words = text.split_whitespace().count();
+    draw_field(right_col, right_row, "- words: ", words, "", stdout)?;
+    right_row += 1;
+
+    let characters = text.chars().count();
+    draw_field(
+        right_col,
+        right_row,
+        "- characters: ",
+        characters,
+        "",
+        stdout,
+    )?;
+    right_row += 1;
+
+    let latency = format!("{} usec", elapsed.as_micros());
+    draw_field(right_col, right_row, "- latency: ", latency, "", stdout)?;
+
+    // Empty line.
+    left_row += 1;
+
+    if let Some(line) = lines.last_mut() {
+        let trailing_whitespace = &text[text.trim_end_matches(' ').len()..];
+        if !trailing_whitespace.is_empty() {
+            // Trailing whitespace is discarded by
+            // `textwrap::wrap`. We reinsert it here. If multiple
+            // spaces are added, this can overflow the margins
+            // which look a bit odd. Handling this would require
+            // some more tinkering...
+            *line = format!("{}{}", line, trailing_whitespace).into();
         }
-
-        stdout.flush()
+    } else {
+        // No lines -> we add an empty line so we have a place
+        // where we can display the cursor.
+        lines.push("".into());
     }
 
-    pub fn main() -> Result<(), io::Error> {
-        let mut labels = vec![
-            String::from("HyphenSplitter"),
-            String::from("NoHyphenation"),
-        ];
-
-        let mut splitters: Vec<Box<dyn WordSplitter>> =
-            vec![Box::new(HyphenSplitter), Box::new(NoHyphenation)];
-
-        // If you like, you can download more dictionaries from
-        // https://github.com/tapeinosyne/hyphenation/tree/master/dictionaries
-        // Place the dictionaries in the examples/ directory. Here we
-        // just load the embedded en-us dictionary.
-        #[cfg(feature = "hyphenation")]
-        for lang in &[Language::EnglishUS] {
-            let dictionary = Standard::from_embedded(*lang).or_else(|_| {
-                let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-                    .join("examples")
-                    .join(format!("{}.standard.bincode", lang.code()));
-                Standard::from_path(*lang, &path)
-            });
-
-            if let Ok(dict) = dictionary {
-                labels.push(format!("{} hyphenation", lang.code()));
-                splitters.push(Box::new(dict));
-            }
+    // Draw margins above and below the wrapped text. We draw the
+    // margin before the text so that 1) the text can overwrite
+    // the margin if `long_words` is `Overflow` and `width` is very
+    // small and 2) so the cursor remains at the end of the last
+    // line of text.
+    draw_margins(left_row, left_col, options.width as u16, '┌', '┐', stdout)?;
+    left_row += 1;
+    let final_row = left_row + lines.len() as u16;
+    draw_margins(final_row, left_col, options.width as u16, '└', '┘', stdout)?;
+
+    let (_, rows) = terminal::size()?;
+    queue!(stdout, cursor::Show)?;
+    if !cap.is_empty() {
+        draw_field(left_col, left_row, " ", cap, "", stdout)?;
+    }
+    for (idx, line) in lines.iter().enumerate() {
+        if left_row > rows {
+            // The text does not fits on the terminal -- we hide
+            // the cursor since it's supposed to be "below" the
+            // bottom of the terminal.
+            queue!(stdout, cursor::Hide)?;
+            break;
         }
+        draw_margins(left_row, left_col, options.width as u16, '│', '│', stdout)?;
+        let col = if drop_cap && idx < DROP_CAP_LINES {
+            left_col + DROP_CAP_WIDTH as u16
+        } else {
+            left_col
+        };
+        queue!(stdout, goto(col, left_row), Print(line))?;
+        left_row += 1;
+    }
 
-        let mut label = labels.pop().unwrap();
-        let mut options: Options = Options::new(35).splitter(Box::new(HyphenSplitter));
-        options.break_words = false;
-        options.splitter = splitters.pop().unwrap();
+    stdout.flush()
+}
 
-        let mut idx_iter = (0..splitters.len()).collect::<Vec<_>>().into_iter().cycle();
+fn main() -> Result<(), io::Error> {
+    let mut labels = vec![
+        String::from("HyphenSplitter"),
+        String::from("NoHyphenation"),
+    ];
 
-        let args = std::env::args().collect::<Vec<_>>();
-        let mut text = if args.len() > 1 {
-            args[1..].join(" ")
-        } else {
-            String::from(
-                "Welcome to the interactive demo! The following is from The \
-            Emperor’s New Clothes by Hans Christian Andersen. You can edit the \
-            text!\n\n\
-            Many years ago there was an Emperor, who was so excessively fond \
-            of new clothes that he spent all his money on them. He cared \
-            nothing about his soldiers, nor for the theatre, nor for driving \
-            in the woods except for the sake of showing off his new clothes. \
-            He had a costume for every hour in the day, and instead of saying, \
-            as one does about any other king or emperor, ‘He is in his council \
-            chamber,’ here one always said, ‘The Emperor is in his \
-            dressing-room.’",
-            )
-        };
+    let mut splitters: Vec<Box<dyn WordSplitter>> =
+        vec![Box::new(HyphenSplitter), Box::new(NoHyphenation)];
 
-        let stdin = io::stdin();
-        let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
-        write!(screen, "{}", cursor::BlinkingUnderline)?;
-        draw_text(&text, &options, &label, &mut screen)?;
-
-        for c in stdin.keys() {
-            match c? {
-                Key::Esc | Key::Ctrl('c') => break,
-                Key::Left => options.width = options.width.saturating_sub(1),
-                Key::Right => options.width = options.width.saturating_add(1),
-                Key::Ctrl('b') => options.break_words = !options.break_words,
+    // If you like, you can download more dictionaries from
+    // https://github.com/tapeinosyne/hyphenation/tree/master/dictionaries
+    // Place the dictionaries in the examples/ directory. Here we
+    // just load the embedded en-us dictionary.
+    #[cfg(feature = "hyphenation")]
+    {
+        let lang = Language::EnglishUS;
+        let dictionary = Standard::from_embedded(lang).or_else(|_| {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("examples")
+                .join(format!("{}.standard.bincode", lang.code()));
+            Standard::from_path(lang, &path)
+        });
+
+        if let Ok(dict) = dictionary {
+            labels.push(format!("{} hyphenation", lang.code()));
+            splitters.push(Box::new(dict));
+        }
+    }
+
+    let mut label = labels.pop().unwrap();
+    let mut options = Options::new(35)
+        .long_words(LongWords::Overflow)
+        .splitter(splitters.pop().unwrap());
+
+    let mut drop_cap = false;
+    let mut idx_iter = (0..splitters.len()).collect::<Vec<_>>().into_iter().cycle();
+
+    let args = std::env::args().collect::<Vec<_>>();
+    let mut text = if args.len() > 1 {
+        args[1..].join(" ")
+    } else {
+        String::from(
+            "Welcome to the interactive demo! The following is from The \
+        Emperor’s New Clothes by Hans Christian Andersen. You can edit the \
+        text!\n\n\
+        Many years ago there was an Emperor, who was so excessively fond \
+        of new clothes that he spent all his money on them. He cared \
+        nothing about his soldiers, nor for the theatre, nor for driving \
+        in the woods except for the sake of showing off his new clothes. \
+        He had a costume for every hour in the day, and instead of saying, \
+        as one does about any other king or emperor, ‘He is in his council \
+        chamber,’ here one always said, ‘The Emperor is in his \
+        dressing-room.’",
+        )
+    };
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        SetCursorStyle::BlinkingUnderScore
+    )?;
+
+    // Run the event loop in a closure so that the terminal is
+    // restored below even when drawing or reading a key fails.
+    let mut event_loop = |stdout: &mut io::Stdout| -> Result<(), io::Error> {
+        draw_text(&text, &options, &label, drop_cap, stdout)?;
+        loop {
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                Event::Resize(..) => {
+                    draw_text(&text, &options, &label, drop_cap, stdout)?;
+                    continue;
+                }
+                _ => continue,
+            };
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key {
+                KeyEvent {
+                    code: KeyCode::Esc, ..
+                } => break,
+                KeyEvent {
+                    code: KeyCode::Char('c'),
+                    ..
+                } if ctrl => break,
+                KeyEvent {
+                    code: KeyCode::Left,
+                    ..
+                } => options.width = options.width.saturating_sub(1),
+                KeyEvent {
+                    code: KeyCode::Right,
+                    ..
+                } => options.width = options.width.saturating_add(1),
+                KeyEvent {
+                    code: KeyCode::Char('b'),
+                    ..
+                } if ctrl => {
+                    options.long_words = match options.long_words {
+                        LongWords::Break => LongWords::Overflow,
+                        _ => LongWords::Break,
+                    }
+                }
+                KeyEvent {
+                    code: KeyCode::Char('d'),
+                    ..
+                } if ctrl => drop_cap = !drop_cap,
                 #[cfg(feature = "smawk")]
-                Key::Ctrl('o') => {
+                KeyEvent {
+                    code: KeyCode::Char('o'),
+                    ..
+                } if ctrl => {
                     options.wrap_algorithm = match options.wrap_algorithm {
                         OptimalFit => FirstFit,
                         FirstFit => OptimalFit,
                     }
                 }
-                Key::Ctrl('s') => {
+                KeyEvent {
+                    code: KeyCode::Char('s'),
+                    ..
+                } if ctrl => {
                     let idx = idx_iter.next().unwrap();
                     std::mem::swap(&mut options.splitter, &mut splitters[idx]);
                     std::mem::swap(&mut label, &mut labels[idx]);
                 }
-                Key::Char(c) => text.push(c),
-                Key::Backspace => {
+                // Also known as Ctrl-Backspace
+                KeyEvent {
+                    code: KeyCode::Char('h') | KeyCode::Backspace,
+                    ..
+                } if ctrl => text.truncate(text.rfind(' ').unwrap_or(0)),
+                KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                } if !ctrl => text.push(c),
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                } => text.push('\n'),
+                KeyEvent {
+                    code: KeyCode::Backspace,
+                    ..
+                } => {
                     text.pop();
                 }
-                // Also known as Ctrl-Backspace
-                Key::Ctrl('h') => text.truncate(text.rfind(' ').unwrap_or(0)),
                 _ => {}
             }
 
-            draw_text(&text, &options, &label, &mut screen)?;
+            draw_text(&text, &options, &label, drop_cap, stdout)?;
         }
-
-        // TODO: change to cursor::DefaultStyle if
-        // https://github.com/redox-os/termion/pull/157 is merged.
-        screen.write_all(b"\x1b[0 q")?;
-        screen.flush()
-    }
+        Ok(())
+    };
+    let result = event_loop(&mut stdout);
+
+    execute!(
+        stdout,
+        SetCursorStyle::DefaultUserShape,
+        LeaveAlternateScreen
+    )?;
+    terminal::disable_raw_mode()?;
+    result
 }
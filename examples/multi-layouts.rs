@@ -77,12 +77,13 @@ pub fn main() {
     // Place the dictionaries in the examples/ directory. Here we
     // just load the embedded en-us dictionary.
     #[cfg(feature = "hyphenation")]
-    for lang in &[Language::EnglishUS] {
-        let dictionary = Standard::from_embedded(*lang).or_else(|_| {
+    {
+        let lang = Language::EnglishUS;
+        let dictionary = Standard::from_embedded(lang).or_else(|_| {
             let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
                 .join("examples")
                 .join(format!("{}.standard.bincode", lang.code()));
-            Standard::from_path(*lang, &path)
+            Standard::from_path(lang, &path)
         });
 
         if let Ok(dict) = dictionary {
@@ -0,0 +1,326 @@
+//! Command line tool for re-flowing text, similar to `fmt` and `fold`.
+//!
+//! The text is read from the files given on the command line, or from
+//! the standard input if no files are given. Paragraphs are separated
+//! by blank lines, which are kept in the output. Only one paragraph
+//! is held in memory at a time, so large files can be processed.
+//!
+//! Run `textwrap --help` for the available options.
+//!
+//! **Note:** Only available when the `cli` Cargo feature is enabled.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process;
+
+use textwrap::{
+    dedent, fill_paragraphs, termwidth, HyphenSplitter, LongWords, Options, WordSplitter,
+};
+
+const USAGE: &str = "\
+Usage: textwrap [OPTIONS] [FILE]...
+
+Re-flow the paragraphs in each FILE, or the standard input if no FILE
+is given or if FILE is -. Paragraphs are separated by blank lines.
+
+Options:
+  -w, --width <WIDTH>           Wrap lines at WIDTH columns (default: 79)
+      --termwidth               Wrap lines at the width of the terminal
+  -b, --break-words             Break words which are longer than a line
+      --initial-indent <TEXT>   Indent the first line of each paragraph
+      --subsequent-indent <TEXT>
+                                Indent the other lines of each paragraph
+  -d, --dedent                  Remove common indentation from paragraphs
+      --hyphenate <LANG>        Hyphenate words using the language LANG
+  -h, --help                    Print this help and exit
+";
+
+/// Settings given on the command line.
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    width: Option<usize>,
+    termwidth: bool,
+    break_words: bool,
+    initial_indent: String,
+    subsequent_indent: String,
+    dedent: bool,
+    hyphenate: Option<String>,
+    files: Vec<String>,
+}
+
+/// What the program should do.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Run(Args),
+}
+
+/// Parse the command line `args`, excluding the program name.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Split `--flag=value` into the flag and its value.
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || match inline_value {
+            Some(value) => Ok(value.to_string()),
+            None => args.next().ok_or_else(|| format!("{} needs a value", flag)),
+        };
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-w" | "--width" => {
+                let width = value()?;
+                match width.parse() {
+                    Ok(width) if width > 0 => parsed.width = Some(width),
+                    _ => return Err(format!("invalid width: {}", width)),
+                }
+            }
+            "--termwidth" => parsed.termwidth = true,
+            "-b" | "--break-words" => parsed.break_words = true,
+            "--initial-indent" => parsed.initial_indent = value()?,
+            "--subsequent-indent" => parsed.subsequent_indent = value()?,
+            "-d" | "--dedent" => parsed.dedent = true,
+            "--hyphenate" => parsed.hyphenate = Some(value()?),
+            "--" => {
+                parsed.files.extend(args);
+                break;
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option: {}", arg))
+            }
+            _ => parsed.files.push(arg),
+        }
+    }
+    if parsed.termwidth && parsed.width.is_some() {
+        return Err(String::from("--width and --termwidth cannot be combined"));
+    }
+    Ok(Command::Run(parsed))
+}
+
+/// Find the splitter for the `--hyphenate` language.
+#[cfg(feature = "hyphenation")]
+fn hyphenation_splitter(lang: &str) -> Result<Box<dyn WordSplitter>, String> {
+    use hyphenation::{Language, Load, Standard};
+
+    let language = Language::try_from_code(lang)
+        .ok_or_else(|| format!("unknown hyphenation language: {}", lang))?;
+    let dictionary = Standard::from_embedded(language)
+        .map_err(|_| format!("no hyphenation dictionary for {}", lang))?;
+    Ok(Box::new(dictionary))
+}
+
+#[cfg(not(feature = "hyphenation"))]
+fn hyphenation_splitter(lang: &str) -> Result<Box<dyn WordSplitter>, String> {
+    Err(format!(
+        "cannot hyphenate {}: textwrap was built without the hyphenation feature",
+        lang
+    ))
+}
+
+/// Build the wrapping options from the command line settings.
+fn options(args: &Args) -> Result<Options<'_>, String> {
+    let width = match args.width {
+        Some(width) => width,
+        None if args.termwidth => termwidth(),
+        None => 79,
+    };
+    let splitter = match &args.hyphenate {
+        Some(lang) => hyphenation_splitter(lang)?,
+        None => Box::new(HyphenSplitter),
+    };
+    Ok(Options::new(width)
+        .long_words(match args.break_words {
+            true => LongWords::Break,
+            false => LongWords::Overflow,
+        })
+        .initial_indent(args.initial_indent.as_str())
+        .subsequent_indent(args.subsequent_indent.as_str())
+        .splitter(splitter))
+}
+
+/// Re-flow the paragraphs read from `reader` and write them to
+/// `writer`, one paragraph at a time.
+fn reflow<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    options: &Options<'_>,
+    dedent_paragraphs: bool,
+) -> io::Result<()> {
+    let flush = |paragraph: &mut String, writer: &mut W| -> io::Result<()> {
+        if paragraph.is_empty() {
+            return Ok(());
+        }
+        let filled = match dedent_paragraphs {
+            true => fill_paragraphs(&dedent(paragraph), options),
+            false => fill_paragraphs(paragraph, options),
+        };
+        paragraph.clear();
+        writer.write_all(filled.trim_end_matches('\n').as_bytes())?;
+        writer.write_all(b"\n")
+    };
+
+    let mut paragraph = String::new();
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        if line.trim().is_empty() {
+            flush(&mut paragraph, &mut writer)?;
+            writer.write_all(b"\n")?;
+        } else {
+            paragraph.push_str(line.trim_end_matches(['\r', '\n']));
+            paragraph.push('\n');
+        }
+        line.clear();
+    }
+    flush(&mut paragraph, &mut writer)?;
+    writer.flush()
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let options = options(args)?;
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let files = match args.files.is_empty() {
+        true => vec![String::from("-")],
+        false => args.files.clone(),
+    };
+    for path in &files {
+        let result = match path.as_str() {
+            "-" => reflow(io::stdin().lock(), &mut out, &options, args.dedent),
+            _ => File::open(path)
+                .and_then(|file| reflow(BufReader::new(file), &mut out, &options, args.dedent)),
+        };
+        result.map_err(|err| format!("{}: {}", path, err))?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Command::Help) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Ok(Command::Run(args)) => args,
+        Err(err) => {
+            eprintln!(
+                "textwrap: {}\nTry 'textwrap --help' for more information.",
+                err
+            );
+            process::exit(2);
+        }
+    };
+    if let Err(err) = run(&args) {
+        eprintln!("textwrap: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn reflow_str(text: &str, args: &Args) -> String {
+        let mut out = Vec::new();
+        reflow(
+            text.as_bytes(),
+            &mut out,
+            &options(args).unwrap(),
+            args.dedent,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn parse_flags_and_values() {
+        let args = Args {
+            width: Some(20),
+            break_words: true,
+            initial_indent: String::from("* "),
+            subsequent_indent: String::from("  "),
+            dedent: true,
+            files: vec![String::from("a.txt"), String::from("-")],
+            ..Args::default()
+        };
+        assert_eq!(
+            parse(&[
+                "-w",
+                "20",
+                "--break-words",
+                "--initial-indent=* ",
+                "--subsequent-indent",
+                "  ",
+                "-d",
+                "a.txt",
+                "-",
+            ]),
+            Ok(Command::Run(args))
+        );
+        assert_eq!(parse(&["--help", "--bogus"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse(&["--width"]),
+            Err(String::from("--width needs a value"))
+        );
+        assert_eq!(parse(&["--width=0"]), Err(String::from("invalid width: 0")));
+        assert_eq!(
+            parse(&["--bogus"]),
+            Err(String::from("unknown option: --bogus"))
+        );
+        assert!(parse(&["--width=10", "--termwidth"]).is_err());
+        assert_eq!(
+            parse(&["--", "--bogus"]),
+            Ok(Command::Run(Args {
+                files: vec![String::from("--bogus")],
+                ..Args::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn reflow_keeps_blank_lines() {
+        let args = Args {
+            width: Some(10),
+            ..Args::default()
+        };
+        assert_eq!(
+            reflow_str("foo bar\nbaz quux\n\n\nabc def ghi\r\n", &args),
+            "foo bar\nbaz quux\n\n\nabc def\nghi\n"
+        );
+        assert_eq!(reflow_str("", &args), "");
+        assert_eq!(reflow_str("no newline", &args), "no newline\n");
+    }
+
+    #[test]
+    fn reflow_dedent_and_indents() {
+        let args = Args {
+            width: Some(12),
+            subsequent_indent: String::from("> "),
+            dedent: true,
+            ..Args::default()
+        };
+        assert_eq!(
+            reflow_str("    foo bar baz\n    quux\n", &args),
+            "foo bar baz\n> quux\n"
+        );
+    }
+
+    #[test]
+    fn unknown_hyphenation_language() {
+        let args = Args {
+            hyphenate: Some(String::from("xx-bogus")),
+            ..Args::default()
+        };
+        assert!(options(&args).is_err());
+    }
+}
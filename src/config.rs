@@ -0,0 +1,383 @@
+//! Wrapping settings which can be saved and loaded with [serde].
+//!
+//! The [`Options`] struct borrows most of its strings and can hold
+//! arbitrary splitters, which makes it unsuitable for storing in
+//! configuration files. A [`Config`] owns its strings and names one
+//! of the built-in splitters instead. Use [`Config::options`] to wrap
+//! text with it:
+//!
+//! ```
+//! use textwrap::config::{Config, Splitter};
+//! use textwrap::wrap;
+//!
+//! let config: Config = serde_json::from_str(r#"{
+//!     "width": 12,
+//!     "initial_indent": "* ",
+//!     "splitter": "NoHyphenation"
+//! }"#).unwrap();
+//! assert_eq!(config.splitter, Splitter::NoHyphenation);
+//!
+//! let options = config.options().unwrap();
+//! assert_eq!(wrap("Memory safety without garbage collection.", &options),
+//!            vec!["* Memory", "safety", "without", "garbage", "collection."]);
+//! ```
+//!
+//! Fields which are left out get the same default values as with
+//! [`Options::new`], except that the width defaults to 80 columns.
+//!
+//! **Note:** Only available when the `serde` Cargo feature is
+//! enabled.
+//!
+//! [serde]: https://docs.rs/serde/
+
+use alloc::borrow::Cow;
+
+use crate::core::{BreakRules, Penalties, WidthMeasure, WordSeparator, WrapAlgorithm};
+use crate::{Alignment, LongWords, Options, UrlBreaks, WordSplitter};
+
+/// The built-in word splitters which can be named in a [`Config`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Splitter {
+    /// Use [`HyphenSplitter`](crate::HyphenSplitter).
+    HyphenSplitter,
+    /// Use [`NoHyphenation`](crate::NoHyphenation).
+    NoHyphenation,
+    /// Use the embedded hyphenation dictionary for the language with
+    /// the given code, such as `"en-us"`.
+    ///
+    /// **Note:** Only available when the `hyphenation` Cargo feature
+    /// is enabled.
+    #[cfg(feature = "hyphenation")]
+    Hyphenation(String),
+}
+
+impl Splitter {
+    /// Create the splitter.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the hyphenation language is unknown or if its
+    /// dictionary cannot be loaded.
+    pub fn load(&self) -> Result<Box<dyn WordSplitter>, ConfigError> {
+        match self {
+            Splitter::HyphenSplitter => Ok(Box::new(crate::HyphenSplitter)),
+            Splitter::NoHyphenation => Ok(Box::new(crate::NoHyphenation)),
+            #[cfg(feature = "hyphenation")]
+            Splitter::Hyphenation(code) => {
+                use hyphenation::{Language, Load, Standard};
+                let language = Language::try_from_code(code)
+                    .ok_or_else(|| ConfigError::UnknownLanguage(code.clone()))?;
+                let dictionary = Standard::from_embedded(language)
+                    .map_err(|_| ConfigError::MissingDictionary(code.clone()))?;
+                Ok(Box::new(dictionary))
+            }
+        }
+    }
+}
+
+/// Error returned when a [`Config`] cannot be turned into
+/// [`Options`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The hyphenation language code is not known.
+    #[cfg(feature = "hyphenation")]
+    UnknownLanguage(String),
+    /// No dictionary is embedded for the hyphenation language.
+    #[cfg(feature = "hyphenation")]
+    MissingDictionary(String),
+}
+
+impl ::core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        // Without hyphenation there are no variants to write to `f`.
+        #[cfg(not(feature = "hyphenation"))]
+        let _ = f;
+        match *self {
+            #[cfg(feature = "hyphenation")]
+            ConfigError::UnknownLanguage(ref code) => {
+                write!(f, "unknown hyphenation language: {:?}", code)
+            }
+            #[cfg(feature = "hyphenation")]
+            ConfigError::MissingDictionary(ref code) => {
+                write!(f, "no embedded hyphenation dictionary for {:?}", code)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+/// Owned wrapping settings, see the [module documentation](self).
+///
+/// The fields correspond to the fields of [`Options`] with the same
+/// names. The [`Options::break_rules`] are stored as two strings of
+/// characters, their [glue rules](crate::core::BreakRules::glue) are
+/// not stored.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The [`Options::width`](field@Options::width).
+    pub width: usize,
+    /// The [`Options::initial_indent`](field@Options::initial_indent).
+    pub initial_indent: String,
+    /// The [`Options::subsequent_indent`](field@Options::subsequent_indent).
+    pub subsequent_indent: String,
+    /// The [`Options::long_words`](field@Options::long_words).
+    pub long_words: LongWords,
+    /// The [`Options::wrap_algorithm`](field@Options::wrap_algorithm).
+    pub wrap_algorithm: WrapAlgorithm,
+    /// The [`Options::penalties`](field@Options::penalties).
+    pub penalties: Penalties,
+    /// The [`Options::max_lines`](field@Options::max_lines).
+    pub max_lines: Option<usize>,
+    /// The [`Options::placeholder`](field@Options::placeholder).
+    pub placeholder: String,
+    /// The [`Options::alignment`](field@Options::alignment).
+    pub alignment: Alignment,
+    /// The [`Options::detect_list_markers`](field@Options::detect_list_markers).
+    pub detect_list_markers: bool,
+    /// The [`Options::word_break_marker`](field@Options::word_break_marker).
+    pub word_break_marker: String,
+    /// The [`Options::min_last_line_width`](field@Options::min_last_line_width).
+    pub min_last_line_width: usize,
+    /// The [`Options::word_separator`](field@Options::word_separator).
+    pub word_separator: WordSeparator,
+    /// The [`BreakRules::no_break_before`] of the break rules.
+    pub no_break_before: String,
+    /// The [`BreakRules::no_break_after`] of the break rules.
+    pub no_break_after: String,
+    /// The [`Options::max_consecutive_hyphens`](field@Options::max_consecutive_hyphens).
+    pub max_consecutive_hyphens: Option<usize>,
+    /// The [`Options::reapply_ansi_styles`](field@Options::reapply_ansi_styles).
+    pub reapply_ansi_styles: bool,
+    /// The [`Options::expand_tabs`](field@Options::expand_tabs).
+    pub expand_tabs: Option<usize>,
+    /// The [`Options::line_ending`](field@Options::line_ending).
+    pub line_ending: String,
+    /// The [`Options::sanitize_control_chars`](field@Options::sanitize_control_chars).
+    pub sanitize_control_chars: bool,
+    /// The [`Options::hanging_indent_from_input`](field@Options::hanging_indent_from_input).
+    pub hanging_indent_from_input: bool,
+    /// The [`Options::url_breaks`](field@Options::url_breaks).
+    pub url_breaks: UrlBreaks,
+    /// The [`Options::balance`](field@Options::balance).
+    pub balance: Option<usize>,
+    /// The [`Options::respect_newlines`](field@Options::respect_newlines).
+    pub respect_newlines: bool,
+    /// The [`Options::sentence_per_line`](field@Options::sentence_per_line).
+    pub sentence_per_line: bool,
+    /// The [`Options::inline_markup`](field@Options::inline_markup).
+    pub inline_markup: bool,
+    /// The [`Options::bidi_reorder`](field@Options::bidi_reorder).
+    #[cfg(feature = "unicode-bidi")]
+    pub bidi_reorder: bool,
+    /// The [`Options::hanging_punctuation`](field@Options::hanging_punctuation).
+    pub hanging_punctuation: bool,
+    /// The [`Options::hanging_punctuation_chars`](field@Options::hanging_punctuation_chars).
+    pub hanging_punctuation_chars: String,
+    /// The [`Options::continuation_marker`](field@Options::continuation_marker).
+    pub continuation_marker: String,
+    /// The [`Options::width_measure`](field@Options::width_measure).
+    pub width_measure: WidthMeasure,
+    /// The [`Options::splitter`](field@Options::splitter).
+    pub splitter: Splitter,
+}
+
+impl Config {
+    /// Store the settings of `options` together with a `splitter`.
+    ///
+    /// The [`Options::width_fn`] and [`Options::abbreviations`] cannot
+    /// be stored and are ignored. A [`WidthMeasure::Custom`] function
+    /// cannot be stored either and is replaced by the default
+    /// [`WidthMeasure::UnicodeCells`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::config::{Config, Splitter};
+    /// use textwrap::{LongWords, Options};
+    ///
+    /// let options = Options::new(20).long_words(LongWords::Overflow);
+    /// let config = Config::from_options(&options, Splitter::HyphenSplitter);
+    /// assert_eq!(config.width, 20);
+    /// assert_eq!(config.long_words, LongWords::Overflow);
+    /// ```
+    pub fn from_options<S: ?Sized>(options: &Options<'_, S>, splitter: Splitter) -> Self {
+        Config {
+            width: options.width,
+            initial_indent: options.initial_indent.to_string(),
+            subsequent_indent: options.subsequent_indent.to_string(),
+            long_words: options.long_words,
+            wrap_algorithm: options.wrap_algorithm,
+            penalties: options.penalties,
+            max_lines: options.max_lines,
+            placeholder: options.placeholder.to_string(),
+            alignment: options.alignment,
+            detect_list_markers: options.detect_list_markers,
+            word_break_marker: options.word_break_marker.to_string(),
+            min_last_line_width: options.min_last_line_width,
+            word_separator: options.word_separator.clone(),
+            no_break_before: options.break_rules.no_break_before.to_string(),
+            no_break_after: options.break_rules.no_break_after.to_string(),
+            max_consecutive_hyphens: options.max_consecutive_hyphens,
+            reapply_ansi_styles: options.reapply_ansi_styles,
+            expand_tabs: options.expand_tabs,
+            line_ending: options.line_ending.to_string(),
+            sanitize_control_chars: options.sanitize_control_chars,
+            hanging_indent_from_input: options.hanging_indent_from_input,
+            url_breaks: options.url_breaks,
+            balance: options.balance,
+            respect_newlines: options.respect_newlines,
+            sentence_per_line: options.sentence_per_line,
+            inline_markup: options.inline_markup,
+            #[cfg(feature = "unicode-bidi")]
+            bidi_reorder: options.bidi_reorder,
+            hanging_punctuation: options.hanging_punctuation,
+            hanging_punctuation_chars: options.hanging_punctuation_chars.to_string(),
+            continuation_marker: options.continuation_marker.to_string(),
+            width_measure: match options.width_measure {
+                WidthMeasure::Custom(_) => WidthMeasure::UnicodeCells,
+                width_measure => width_measure,
+            },
+            splitter,
+        }
+    }
+
+    /// Create [`Options`] which borrow the strings of the config.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the splitter cannot be loaded, see
+    /// [`Splitter::load`].
+    pub fn options(&self) -> Result<Options<'_>, ConfigError> {
+        let splitter = self.splitter.load()?;
+        Ok(Options {
+            width: self.width,
+            initial_indent: Cow::Borrowed(&self.initial_indent),
+            subsequent_indent: Cow::Borrowed(&self.subsequent_indent),
+            long_words: self.long_words,
+            wrap_algorithm: self.wrap_algorithm,
+            penalties: self.penalties,
+            max_lines: self.max_lines,
+            placeholder: &self.placeholder,
+            alignment: self.alignment,
+            detect_list_markers: self.detect_list_markers,
+            word_break_marker: &self.word_break_marker,
+            min_last_line_width: self.min_last_line_width,
+            width_fn: None,
+            width_measure: self.width_measure,
+            word_separator: self.word_separator.clone(),
+            break_rules: BreakRules::new(&self.no_break_before, &self.no_break_after),
+            max_consecutive_hyphens: self.max_consecutive_hyphens,
+            reapply_ansi_styles: self.reapply_ansi_styles,
+            expand_tabs: self.expand_tabs,
+            line_ending: &self.line_ending,
+            sanitize_control_chars: self.sanitize_control_chars,
+            hanging_indent_from_input: self.hanging_indent_from_input,
+            url_breaks: self.url_breaks,
+            balance: self.balance,
+            respect_newlines: self.respect_newlines,
+            sentence_per_line: self.sentence_per_line,
+            abbreviations: crate::DEFAULT_ABBREVIATIONS,
+            inline_markup: self.inline_markup,
+            #[cfg(feature = "unicode-bidi")]
+            bidi_reorder: self.bidi_reorder,
+            hanging_punctuation: self.hanging_punctuation,
+            hanging_punctuation_chars: &self.hanging_punctuation_chars,
+            continuation_marker: &self.continuation_marker,
+            splitter,
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::from_options(&Options::new(80), Splitter::HyphenSplitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fill, wrap};
+
+    #[test]
+    fn json_round_trip() {
+        let options = Options::new(15)
+            .initial_indent("- ")
+            .subsequent_indent("  ")
+            .alignment(Alignment::Right)
+            .max_lines(3);
+        let config = Config::from_options(&options, Splitter::NoHyphenation);
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, config);
+
+        let text = "Memory safety without garbage collection. Concurrency without data races.";
+        let loaded_options = loaded.options().unwrap();
+        assert_eq!(
+            wrap(text, &loaded_options),
+            wrap(text, &options.splitter(crate::NoHyphenation))
+        );
+    }
+
+    #[test]
+    fn defaults() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.width, 80);
+        assert_eq!(config.splitter, Splitter::HyphenSplitter);
+        assert_eq!(fill("foo bar", &config.options().unwrap()), "foo bar");
+    }
+
+    #[test]
+    fn width_measure() {
+        let config: Config =
+            serde_json::from_str(r#"{"width": 8, "width_measure": "Bytes"}"#).unwrap();
+        assert_eq!(config.width_measure, WidthMeasure::Bytes);
+        assert_eq!(
+            wrap("Grüße aus Köln", &config.options().unwrap()),
+            vec!["Grüße", "aus", "Köln"]
+        );
+
+        let options = Options::new(8).width_measure(WidthMeasure::Chars);
+        let config = Config::from_options(&options, Splitter::HyphenSplitter);
+        assert_eq!(config.width_measure, WidthMeasure::Chars);
+
+        let options = Options::new(8).width_measure(WidthMeasure::Custom(str::len));
+        let config = Config::from_options(&options, Splitter::HyphenSplitter);
+        assert_eq!(config.width_measure, WidthMeasure::UnicodeCells);
+    }
+
+    #[test]
+    fn enums_by_name() {
+        let config: Config = serde_json::from_str(
+            r#"{"wrap_algorithm": "FirstFit", "word_separator": "UnicodeBreakProperties"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.wrap_algorithm, WrapAlgorithm::FirstFit);
+        assert_eq!(config.word_separator, WordSeparator::UnicodeBreakProperties);
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn hyphenation_language() {
+        let config: Config =
+            serde_json::from_str(r#"{"width": 10, "splitter": {"Hyphenation": "en-us"}}"#).unwrap();
+        assert_eq!(
+            wrap("Internationalization", &config.options().unwrap()),
+            vec!["Interna-", "tionaliza-", "tion"]
+        );
+
+        let config = Config {
+            splitter: Splitter::Hyphenation(String::from("xx")),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.options().unwrap_err(),
+            ConfigError::UnknownLanguage(String::from("xx"))
+        );
+    }
+}
@@ -9,7 +9,9 @@
 //!
 //! 1. Split your input into [`Fragment`]s. These are abstract blocks
 //!    of text or content which can be wrapped into lines. You can use
-//!    [`find_words`] to do this for text.
+//!    [`find_words`] or [`WordSeparator::find_words`] to do this for
+//!    text. Implement [`WordFinder`] to find the words in languages
+//!    without spaces, such as Thai.
 //!
 //! 2. Potentially split your fragments into smaller pieces. This
 //!    allows you to implement things like hyphenation. If wrapping
@@ -26,48 +28,128 @@
 //! 5. Iterate through the slices returned by the wrapping functions
 //!    and construct your lines of output.
 //!
+//! This is exactly how [`wrap`](super::wrap) works: each line of the
+//! text is split by [`WordSeparator::find_words`] (which is
+//! [`find_words`] for the default [`WordSeparator::AsciiSpace`]),
+//! the words are split by [`split_words`], too long words are broken
+//! by [`break_words`], and the words are finally wrapped by the
+//! chosen [`WrapAlgorithm`]. Layouts built on top of these functions
+//! will therefore agree with `wrap` on what the words are. Use
+//! [`Word::range_in`] to find the words in the original text.
+//!
 //! Please [open an issue](https://github.com/mgeisler/textwrap/) if
 //! the functionality here is not sufficient or if you have ideas for
 //! improving it. We would love to hear from you!
 
-use crate::{Options, WordSplitter};
+use crate::splitting::NO_HYPHEN;
+use crate::{Options, SplitPoints, UrlBreaks, WordSplitter};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 
-#[cfg(feature = "smawk")]
+#[cfg(feature = "unicode-bidi")]
+mod bidi;
+mod line_break;
 mod optimal_fit;
+
+#[cfg(feature = "unicode-bidi")]
+pub use bidi::{paragraph_direction, reorder_line, Direction};
 #[cfg(feature = "smawk")]
 pub use optimal_fit::wrap_optimal_fit;
+pub use optimal_fit::Penalties;
 
 /// The CSI or “Control Sequence Introducer” introduces an ANSI escape
 /// sequence. This is typically used for colored text and will be
 /// ignored when computing the text width.
 const CSI: (char, char) = ('\x1b', '[');
 /// The final bytes of an ANSI escape sequence must be in this range.
-const ANSI_FINAL_BYTE: std::ops::RangeInclusive<char> = '\x40'..='\x7e';
+const ANSI_FINAL_BYTE: ::core::ops::RangeInclusive<char> = '\x40'..='\x7e';
+/// The OSC or “Operating System Command” introduces an escape
+/// sequence which is used for hyperlinks and window titles. It is
+/// terminated by [`BEL`] or by [`ST`].
+const OSC: (char, char) = ('\x1b', ']');
+/// The bell character terminates an OSC sequence.
+const BEL: char = '\x07';
+/// The “String Terminator” terminates an OSC sequence.
+const ST: (char, char) = ('\x1b', '\\');
 
 /// Skip ANSI escape sequences. The `ch` is the current `char`, the
 /// `chars` provide the following characters. The `chars` will be
 /// modified if `ch` is the start of an ANSI escape sequence.
 #[inline]
 fn skip_ansi_escape_sequence<I: Iterator<Item = char>>(ch: char, chars: &mut I) -> bool {
-    if ch == CSI.0 && chars.next() == Some(CSI.1) {
-        // We have found the start of an ANSI escape code, typically
-        // used for colored terminal text. We skip until we find a
-        // "final byte" in the range 0x40–0x7E.
-        for ch in chars {
-            if ANSI_FINAL_BYTE.contains(&ch) {
-                return true;
+    if ch != CSI.0 {
+        return false;
+    }
+    match chars.next() {
+        Some(ch) if ch == CSI.1 => {
+            // We have found the start of an ANSI escape code, typically
+            // used for colored terminal text. We skip until we find a
+            // "final byte" in the range 0x40–0x7E.
+            for ch in chars {
+                if ANSI_FINAL_BYTE.contains(&ch) {
+                    return true;
+                }
+            }
+        }
+        Some(ch) if ch == OSC.1 => {
+            // An OSC sequence can contain arbitrary text, such as
+            // the target of a hyperlink. We skip until we find the
+            // BEL or ST which terminates it.
+            let mut prev = OSC.1;
+            for ch in chars {
+                if ch == BEL || (prev == ST.0 && ch == ST.1) {
+                    return true;
+                }
+                prev = ch;
             }
         }
+        _ => {}
     }
     false
 }
 
+/// Find the byte ranges of the ANSI escape sequences in `text`.
+///
+/// Words must never be split inside these ranges.
+pub(crate) fn ansi_escape_sequences(text: &str) -> Vec<::core::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut idx = 0;
+    while let Some(offset) = text[idx..].find(CSI.0) {
+        let start = idx + offset;
+        let mut chars = text[start + CSI.0.len_utf8()..].chars();
+        if skip_ansi_escape_sequence(CSI.0, &mut chars) {
+            idx = text.len() - chars.as_str().len();
+            ranges.push(start..idx);
+        } else {
+            idx = start + CSI.0.len_utf8();
+        }
+    }
+    ranges
+}
+
+/// Is `idx` strictly inside one of the `ranges`?
+#[inline]
+fn is_inside(ranges: &[::core::ops::Range<usize>], idx: usize) -> bool {
+    ranges
+        .iter()
+        .any(|range| range.start < idx && idx < range.end)
+}
+
 #[cfg(feature = "unicode-width")]
 #[inline]
 fn ch_width(ch: char) -> usize {
     unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0)
 }
 
+/// Like [`ch_width`], but characters with an ambiguous East Asian
+/// width are 2 columns wide.
+#[cfg(feature = "unicode-width")]
+#[inline]
+fn ch_width_cjk(ch: char) -> usize {
+    unicode_width::UnicodeWidthChar::width_cjk(ch).unwrap_or(0)
+}
+
 /// First character which [`ch_width`] will classify as double-width.
 /// Please see [`display_width`].
 #[cfg(not(feature = "unicode-width"))]
@@ -76,15 +158,175 @@ const DOUBLE_WIDTH_CUTOFF: char = '\u{1100}';
 #[cfg(not(feature = "unicode-width"))]
 #[inline]
 fn ch_width(ch: char) -> usize {
-    if ch < DOUBLE_WIDTH_CUTOFF {
+    if matches!(ch, SHY | ZWSP | WJ | ZWJ) || ch.is_control() {
+        0
+    } else if ch < DOUBLE_WIDTH_CUTOFF {
         1
     } else {
         2
     }
 }
 
+/// Ambiguous widths cannot be detected without the `unicode-width`
+/// Cargo feature, so this is the same as [`ch_width`].
+#[cfg(not(feature = "unicode-width"))]
+#[inline]
+fn ch_width_cjk(ch: char) -> usize {
+    ch_width(ch)
+}
+
+/// Zero width joiner, used to glue emojis together into a single
+/// glyph.
+const ZWJ: char = '\u{200d}';
+
+/// Zero width space, used to mark where a word can be broken
+/// without adding a hyphen.
+pub(crate) const ZWSP: char = '\u{200b}';
+
+/// Word joiner, used to mark where a word must not be broken. It is
+/// removed from the wrapped lines.
+pub(crate) const WJ: char = '\u{2060}';
+
+/// Soft hyphen, used to mark where a word can be hyphenated. It is
+/// only shown (as a `-`) if the word is broken there.
+pub(crate) const SHY: char = '\u{ad}';
+
+/// Characters which prevent a word from being split next to them:
+/// the word joiner and the non-breaking spaces.
+const NO_SPLIT: [char; 4] = [WJ, '\u{a0}', '\u{2007}', '\u{202f}'];
+
+/// The non-breaking spaces. A word which is too long for a line is
+/// broken at these before it is broken between two clusters.
+pub(crate) const NO_BREAK_SPACES: [char; 3] = ['\u{a0}', '\u{2007}', '\u{202f}'];
+
+/// Characters which take up no room and are not shown at the end of
+/// a line.
+const INVISIBLE: [char; 3] = [ZWSP, WJ, SHY];
+
+/// Check if `text` has no visible characters, such as a lone soft
+/// hyphen.
+fn is_invisible(text: &str) -> bool {
+    text.chars().all(|ch| INVISIBLE.contains(&ch))
+}
+
+/// Is `ch` a regional indicator? Pairs of these form flag emojis.
+#[inline]
+fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&ch)
+}
+
+/// Does `ch` extend the grapheme cluster which ends with `prev`?
+///
+/// This is used without the `unicode-segmentation` Cargo feature and
+/// only covers the common cases from [UAX #29]: combining marks in
+/// the blocks below, variation selectors, emoji modifiers and tags,
+/// emoji joined by a zero width joiner, and pairs of regional
+/// indicators. Other scripts, such as Hangul syllables written with
+/// conjoining jamo and Indic conjuncts, are split between their
+/// `char`s. The `regional_indicators` argument counts the regional
+/// indicators in the cluster so far.
+///
+/// [UAX #29]: https://www.unicode.org/reports/tr29/
+#[cfg(not(feature = "unicode-segmentation"))]
+fn extends_grapheme(prev: char, ch: char, regional_indicators: usize) -> bool {
+    matches!(ch,
+        ZWJ
+        | '\u{0300}'..='\u{036f}' // Combining Diacritical Marks
+        | '\u{1ab0}'..='\u{1aff}' // Combining Diacritical Marks Extended
+        | '\u{1dc0}'..='\u{1dff}' // Combining Diacritical Marks Supplement
+        | '\u{20d0}'..='\u{20ff}' // Combining Diacritical Marks for Symbols
+        | '\u{fe20}'..='\u{fe2f}' // Combining Half Marks
+        | '\u{fe00}'..='\u{fe0f}' // Variation Selectors
+        | '\u{1f3fb}'..='\u{1f3ff}' // Emoji Modifiers
+        | '\u{e0020}'..='\u{e007f}' // Tags
+        | '\u{e0100}'..='\u{e01ef}' // Variation Selectors Supplement
+    ) || prev == ZWJ
+        || (is_regional_indicator(ch) && regional_indicators % 2 == 1)
+}
+
+/// Does `ch` turn the grapheme cluster it extends into an emoji
+/// sequence? This is the case for a zero width joiner, the emoji
+/// presentation selector (U+FE0F), emoji modifiers, tags, and the
+/// second regional indicator of a flag.
+#[inline]
+fn makes_emoji_sequence(ch: char) -> bool {
+    matches!(ch,
+        ZWJ
+        | '\u{fe0f}' // Variation Selector-16
+        | '\u{1f3fb}'..='\u{1f3ff}' // Emoji Modifiers
+        | '\u{e0020}'..='\u{e007f}' // Tags
+    ) || is_regional_indicator(ch)
+}
+
+/// Return the end of the grapheme cluster which starts at `start`.
+#[cfg(feature = "unicode-segmentation")]
+fn cluster_end(text: &str, start: usize) -> usize {
+    use unicode_segmentation::UnicodeSegmentation;
+    start + text[start..].graphemes(true).next().map_or(0, str::len)
+}
+
+/// Return the end of the (approximate) grapheme cluster which starts
+/// at `start`, see [`extends_grapheme`].
+#[cfg(not(feature = "unicode-segmentation"))]
+fn cluster_end(text: &str, start: usize) -> usize {
+    let mut chars = text[start..].char_indices();
+    let mut prev = match chars.next() {
+        Some((_, ch)) => ch,
+        None => return start,
+    };
+    let mut regional_indicators = usize::from(is_regional_indicator(prev));
+    for (idx, ch) in chars {
+        if !extends_grapheme(prev, ch, regional_indicators) {
+            return start + idx;
+        }
+        regional_indicators += usize::from(is_regional_indicator(ch));
+        prev = ch;
+    }
+    text.len()
+}
+
+/// Iterate over the grapheme clusters of `text`. Each cluster is
+/// returned as its start index and its display width, with the width
+/// of each `char` given by `ch_width`. ANSI escape sequences are
+/// returned as clusters of width zero.
+///
+/// The clusters are found with the [unicode-segmentation] crate when
+/// the `unicode-segmentation` Cargo feature is enabled. Otherwise,
+/// they are approximated, see [`extends_grapheme`].
+///
+/// Emoji sequences, such as flags and emoji joined by a zero width
+/// joiner, are shown as a single glyph and they are therefore 2
+/// columns wide.
+///
+/// [unicode-segmentation]: https://docs.rs/unicode-segmentation/
+fn grapheme_clusters(
+    text: &str,
+    ch_width: fn(char) -> usize,
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut char_indices = text.char_indices().peekable();
+    ::core::iter::from_fn(move || {
+        let (start, prev) = char_indices.next()?;
+        if skip_ansi_escape_sequence(prev, &mut char_indices.by_ref().map(|(_, ch)| ch)) {
+            return Some((start, 0));
+        }
+
+        let end = cluster_end(text, start);
+        let mut width = ch_width(prev);
+        let mut emoji_sequence = false;
+        while let Some((_, ch)) = char_indices.next_if(|&(idx, _)| idx < end) {
+            width += ch_width(ch);
+            emoji_sequence |= makes_emoji_sequence(ch);
+        }
+        if emoji_sequence {
+            width = 2;
+        }
+        Some((start, width))
+    })
+}
+
 /// Compute the display width of `text` while skipping over ANSI
-/// escape sequences.
+/// escape sequences. Both CSI sequences, such as colors, and OSC
+/// sequences, such as hyperlinks, are skipped.
 ///
 /// # Examples
 ///
@@ -93,12 +335,26 @@ fn ch_width(ch: char) -> usize {
 ///
 /// assert_eq!(display_width("Café Plain"), 10);
 /// assert_eq!(display_width("\u{1b}[31mCafé Rouge\u{1b}[0m"), 10);
+/// assert_eq!(display_width("\u{1b}]8;;https://example.com\u{7}Link\u{1b}]8;;\u{7}"), 4);
+/// ```
+///
+/// Other control characters, such as BEL or backspace, have a width
+/// of zero. They can be removed before wrapping with
+/// [`Options::sanitize_control_chars`](crate::Options::sanitize_control_chars).
+///
+/// ```
+/// use textwrap::core::display_width;
+///
+/// assert_eq!(display_width("Ding\u{7}\u{8}!"), 5);
 /// ```
 ///
 /// **Note:** When the `unicode-width` Cargo feature is disabled, the
 /// width of a `char` is determined by a crude approximation which
 /// simply counts chars below U+1100 as 1 column wide, and all other
-/// characters as 2 columns wide. With the feature enabled, function
+/// characters as 2 columns wide. Only control characters and the
+/// invisible characters which control wrapping, such as the soft
+/// hyphen and the zero width space, are 0 columns wide. With the
+/// feature enabled, function
 /// will correctly deal with [combining characters] in their
 /// decomposed form (see [Unicode equivalence]).
 ///
@@ -130,6 +386,25 @@ fn ch_width(ch: char) -> usize {
 /// assert_eq!(display_width("你好"), 4);  // “Nǐ hǎo” or “Hello” in Chinese
 /// ```
 ///
+/// ## Emoji Sequences
+///
+/// The width is computed per grapheme cluster: the characters which
+/// are shown as a single glyph are measured together. Emoji sequences
+/// such as flags, emojis with skin tone modifiers, and emojis joined
+/// by a zero width joiner (U+200D) are 2 columns wide, regardless of
+/// the number of code points they consist of:
+///
+/// ```
+/// use textwrap::core::display_width;
+///
+/// assert_eq!(display_width("🇩🇰"), 2);  // Regional indicators D and K
+/// assert_eq!(display_width("👨‍👩‍👧"), 2);  // Man, woman, and girl joined by ZWJ
+/// assert_eq!(display_width("❤️"), 2);  // Heart followed by U+FE0F
+/// ```
+///
+/// This lets applications use `display_width` for their own padding
+/// and alignment with the same result as the wrapping functions.
+///
 /// # Limitations
 ///
 /// The displayed width of a string cannot always be computed from the
@@ -141,28 +416,24 @@ fn ch_width(ch: char) -> usize {
 ///
 /// A simple example is “❤️”, which consists of “❤” (U+2764: Black
 /// Heart Symbol) followed by U+FE0F (Variation Selector-16). By
-/// itself, “❤” is a black heart, but if you follow it with the
-/// variant selector, you may get a wider red heart.
+/// itself, “❤” is a black heart which is 1 column wide, but if you
+/// follow it with the variant selector, you may get a wider red
+/// heart. We assume that the wide emoji is shown.
 ///
 /// A more complex example would be “👨‍🦰” which should depict a man
-/// with red hair. Here the computed width is too large — and the
-/// width differs depending on the use of the `unicode-width` feature:
+/// with red hair. The grapheme consists of three code points: “👨”
+/// (U+1F468: Man), Zero Width Joiner (U+200D), and “🦰” (U+1F9B0: Red
+/// Hair). We assume the sequence is shown as a single glyph:
 ///
 /// ```
 /// use textwrap::core::display_width;
 ///
 /// assert_eq!("👨‍🦰".chars().collect::<Vec<char>>(), ['\u{1f468}', '\u{200d}', '\u{1f9b0}']);
-/// #[cfg(feature = "unicode-width")]
-/// assert_eq!(display_width("👨‍🦰"), 4);
-/// #[cfg(not(feature = "unicode-width"))]
-/// assert_eq!(display_width("👨‍🦰"), 6);
+/// assert_eq!(display_width("👨‍🦰"), 2);
 /// ```
 ///
-/// This happens because the grapheme consists of three code points:
-/// “👨” (U+1F468: Man), Zero Width Joiner (U+200D), and “🦰”
-/// (U+1F9B0: Red Hair). You can see them above in the test. With
-/// `unicode-width` enabled, the ZWJ is correctly seen as having zero
-/// width, without it is counted as a double-width character.
+/// A terminal which does not know the sequence shows the code points
+/// next to each other instead, see below.
 ///
 /// ## Terminal Support
 ///
@@ -177,15 +448,164 @@ fn ch_width(ch: char) -> usize {
 /// [emoji modifier sequences]: https://unicode.org/emoji/charts/full-emoji-modifiers.html
 #[inline]
 pub fn display_width(text: &str) -> usize {
-    let mut chars = text.chars();
-    let mut width = 0;
-    while let Some(ch) = chars.next() {
-        if skip_ansi_escape_sequence(ch, &mut chars) {
-            continue;
+    display_width_with(text, ch_width)
+}
+
+/// Compute the display width of `text` like [`display_width`], with
+/// the width of each `char` given by `ch_width`.
+#[inline]
+fn display_width_with(text: &str, ch_width: fn(char) -> usize) -> usize {
+    // Printable ASCII characters are one column wide in all
+    // terminals and they never combine into larger clusters.
+    if is_printable_ascii(text) {
+        return text.len();
+    }
+    grapheme_clusters(text, ch_width)
+        .map(|(_, width)| width)
+        .sum()
+}
+
+/// Does `text` consist of printable ASCII characters only? This
+/// excludes control characters such as `'\t'` and the escape
+/// character which starts ANSI escape sequences.
+#[inline]
+fn is_printable_ascii(text: &str) -> bool {
+    text.bytes().all(|b| (b' '..=b'~').contains(&b))
+}
+
+/// How the width of text is measured.
+///
+/// The same measure is used for the words, the indentation, the
+/// penalties, and when words are broken apart. This keeps the pieces
+/// consistent, regardless of what a “column” means.
+///
+/// # Examples
+///
+/// Some protocols limit the length of a line in bytes. SMTP, for
+/// example, allows at most 998 bytes per line:
+///
+/// ```
+/// use textwrap::core::WidthMeasure;
+/// use textwrap::{wrap, Options};
+///
+/// let options = Options::new(8).width_measure(WidthMeasure::Bytes);
+/// assert_eq!(wrap("Grüße aus Köln", &options), vec!["Grüße", "aus", "Köln"]);
+///
+/// let options = Options::new(8).width_measure(WidthMeasure::UnicodeCells);
+/// assert_eq!(wrap("Grüße aus Köln", &options), vec!["Grüße", "aus Köln"]);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WidthMeasure {
+    /// The number of columns used in a terminal, see
+    /// [`display_width`]. This is the default.
+    #[default]
+    UnicodeCells,
+    /// The number of columns used in a terminal which shows the
+    /// characters with an ambiguous [East Asian Width], such as `±`
+    /// and `②`, as 2 columns wide. This is common for terminals set up
+    /// for Chinese, Japanese, or Korean text. See
+    /// [`Options::cjk_ambiguous_wide`](crate::Options::cjk_ambiguous_wide).
+    ///
+    /// Without the `unicode-width` Cargo feature, this is the same as
+    /// [`WidthMeasure::UnicodeCells`].
+    ///
+    /// [East Asian Width]: https://www.unicode.org/reports/tr11/
+    UnicodeCellsCjk,
+    /// The number of Unicode scalar values, i.e., [`char`]s.
+    Chars,
+    /// The number of bytes in the UTF-8 encoding.
+    Bytes,
+    /// The width is computed by the given function.
+    ///
+    /// Words are still only broken between grapheme clusters, and the
+    /// width of each cluster is computed by the function.
+    ///
+    /// This cannot be serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(fn(&str) -> usize),
+}
+
+impl PartialEq for WidthMeasure {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WidthMeasure::UnicodeCells, WidthMeasure::UnicodeCells) => true,
+            (WidthMeasure::UnicodeCellsCjk, WidthMeasure::UnicodeCellsCjk) => true,
+            (WidthMeasure::Chars, WidthMeasure::Chars) => true,
+            (WidthMeasure::Bytes, WidthMeasure::Bytes) => true,
+            // Custom measures are equal if they are the same function.
+            (WidthMeasure::Custom(a), WidthMeasure::Custom(b)) => *a as usize == *b as usize,
+            _ => false,
         }
-        width += ch_width(ch);
     }
-    width
+}
+
+impl Eq for WidthMeasure {}
+
+impl WidthMeasure {
+    /// Measure the width of `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::WidthMeasure;
+    ///
+    /// assert_eq!(WidthMeasure::UnicodeCells.width("你好"), 4);
+    /// #[cfg(feature = "unicode-width")]
+    /// assert_eq!(WidthMeasure::UnicodeCellsCjk.width("±1"), 3);
+    /// assert_eq!(WidthMeasure::Chars.width("你好"), 2);
+    /// assert_eq!(WidthMeasure::Bytes.width("你好"), 6);
+    /// assert_eq!(WidthMeasure::Custom(|text| text.len() / 3).width("你好"), 2);
+    /// ```
+    pub fn width(&self, text: &str) -> usize {
+        match self {
+            WidthMeasure::UnicodeCells => display_width(text),
+            WidthMeasure::UnicodeCellsCjk => display_width_with(text, ch_width_cjk),
+            WidthMeasure::Chars => text.chars().count(),
+            WidthMeasure::Bytes => text.len(),
+            WidthMeasure::Custom(width) => width(text),
+        }
+    }
+
+    /// Measure the width of the whitespace following a word. Unused
+    /// soft hyphens are removed from the wrapped lines, so they take
+    /// up no room.
+    fn whitespace_width(&self, whitespace: &str) -> usize {
+        match self {
+            // We assume the whitespace consist of ' ' and zero width
+            // spaces only, see `Word::from`.
+            WidthMeasure::UnicodeCells | WidthMeasure::UnicodeCellsCjk => {
+                match whitespace.contains(NO_BREAK_SPACES) {
+                    // The non-breaking spaces of a broken word.
+                    true => self.width(whitespace),
+                    false => whitespace.bytes().filter(|&b| b == b' ').count(),
+                }
+            }
+            _ => self.width(whitespace.trim_start_matches(SHY)),
+        }
+    }
+
+    /// Iterate over the grapheme clusters of `text`, see
+    /// [`grapheme_clusters`], with the widths given by this measure.
+    pub(crate) fn clusters<'t>(&self, text: &'t str) -> impl Iterator<Item = (usize, usize)> + 't {
+        let measure = *self;
+        let ch_width = match measure {
+            WidthMeasure::UnicodeCellsCjk => ch_width_cjk,
+            _ => ch_width,
+        };
+        let mut clusters = grapheme_clusters(text, ch_width).peekable();
+        ::core::iter::from_fn(move || {
+            let (start, cells) = clusters.next()?;
+            let width = match measure {
+                WidthMeasure::UnicodeCells | WidthMeasure::UnicodeCellsCjk => cells,
+                _ => {
+                    let end = clusters.peek().map_or(text.len(), |&(idx, _)| idx);
+                    measure.width(&text[start..end])
+                }
+            };
+            Some((start, width))
+        })
+    }
 }
 
 /// A (text) fragment denotes the unit which we wrap into lines.
@@ -198,7 +618,7 @@ pub fn display_width(text: &str) -> usize {
 /// For wrapping purposes, the precise content of the word, the
 /// whitespace, and the penalty is irrelevant. All we need to know is
 /// the displayed width of each part, which this trait provides.
-pub trait Fragment: std::fmt::Debug {
+pub trait Fragment: ::core::fmt::Debug {
     /// Displayed width of word represented by this fragment.
     fn width(&self) -> usize;
 
@@ -220,10 +640,23 @@ pub struct Word<'a> {
     word: &'a str,
     width: usize,
     pub(crate) whitespace: &'a str,
-    pub(crate) penalty: &'a str,
+    whitespace_width: usize,
+    pub(crate) penalty: &'static str,
+    /// Letters shown before the penalty if the word is split after
+    /// this fragment, see [`Substitution`](crate::Substitution).
+    /// The replaced letters are the whitespace of the fragment.
+    pub(crate) before_break: &'a str,
+    /// Letters which start the next line if the word is split after
+    /// this fragment.
+    pub(crate) after_break: &'a str,
+    /// Width of the marker which is inserted after the word if it
+    /// falls at the end of a line. This is non-zero for all but the
+    /// last piece of a word broken by [`break_words`] when a word
+    /// break marker is used.
+    pub(crate) marker_width: usize,
 }
 
-impl std::ops::Deref for Word<'_> {
+impl ::core::ops::Deref for Word<'_> {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
@@ -234,15 +667,75 @@ impl std::ops::Deref for Word<'_> {
 impl<'a> Word<'a> {
     /// Construct a new `Word`.
     ///
-    /// A trailing stretch of `' '` is automatically taken to be the
-    /// whitespace part of the word.
+    /// A trailing stretch of `' '` and zero width space (U+200B) is
+    /// automatically taken to be the whitespace part of the word.
     pub fn from(word: &str) -> Word<'_> {
-        let trimmed = word.trim_end_matches(' ');
+        let trimmed = word.trim_end_matches(&[' ', ZWSP][..]);
+        let whitespace = &word[trimmed.len()..];
         Word {
             word: trimmed,
-            width: display_width(&trimmed),
-            whitespace: &word[trimmed.len()..],
+            width: display_width(trimmed),
+            whitespace,
+            whitespace_width: WidthMeasure::UnicodeCells.whitespace_width(whitespace),
             penalty: "",
+            before_break: "",
+            after_break: "",
+            marker_width: 0,
+        }
+    }
+
+    /// The whitespace following the word. This is dropped if the
+    /// word falls at the end of a line.
+    ///
+    /// For a fragment of a word split with a
+    /// [`Substitution`](crate::Substitution), this
+    /// holds the letters which are replaced if the word is split
+    /// there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// assert_eq!(Word::from("Hello  ").whitespace(), "  ");
+    /// ```
+    pub fn whitespace(&self) -> &'a str {
+        self.whitespace
+    }
+
+    /// The penalty inserted after the word if it falls at the end of
+    /// a line. This is `"-"` for a word split by a hyphenating
+    /// [`WordSplitter`], and otherwise `""`.
+    pub fn penalty(&self) -> &'static str {
+        self.penalty
+    }
+
+    /// Find the byte range of the word in `line`, which is the text
+    /// the word was found in. The range does not include the
+    /// whitespace, which follows right after the range. Returns
+    /// `None` if the word is not part of `line`.
+    ///
+    /// This makes it possible to map the words found by
+    /// [`find_words`] back to positions in the text, e.g., to move a
+    /// cursor word by word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::find_words;
+    ///
+    /// let line = "Hello  World!";
+    /// let ranges = find_words(line)
+    ///     .map(|word| word.range_in(line).unwrap())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(ranges, vec![0..5, 7..13]);
+    /// assert_eq!(find_words("Hello").next().unwrap().range_in(line), None);
+    /// ```
+    pub fn range_in(&self, line: &str) -> Option<::core::ops::Range<usize>> {
+        let start = (self.word.as_ptr() as usize).checked_sub(line.as_ptr() as usize)?;
+        let end = start + self.word.len();
+        match line.get(start..end) {
+            Some(word) if ::core::ptr::eq(word, self.word) => Some(start..end),
+            _ => None,
         }
     }
 
@@ -250,6 +743,11 @@ impl<'a> Word<'a> {
     /// `line_width`. The whitespace and penalty from this `Word` is
     /// added to the last piece.
     ///
+    /// The word is only broken between grapheme clusters. This keeps
+    /// combining accents together with their base character and
+    /// emoji sequences intact. A single cluster wider than
+    /// `line_width` becomes a piece of its own.
+    ///
     /// # Examples
     ///
     /// ```
@@ -260,29 +758,95 @@ impl<'a> Word<'a> {
     /// );
     /// ```
     pub fn break_apart<'b>(&'b self, line_width: usize) -> impl Iterator<Item = Word<'a>> + 'b {
-        let mut char_indices = self.word.char_indices();
+        self.break_apart_with_marker(
+            move |_| line_width,
+            0,
+            WidthMeasure::UnicodeCells,
+            BreakRules::none(),
+        )
+    }
+
+    /// Break this word like [`Word::break_apart`], but leave room for
+    /// a marker of `marker_width` columns after all but the last
+    /// piece. The `line_width` of each piece is given by its index,
+    /// and the widths of the pieces are given by `measure`.
+    ///
+    /// A piece is made shorter when it would otherwise end with one
+    /// of the [`BreakRules::no_break_after`] characters, or when the
+    /// next piece would start with one of the
+    /// [`BreakRules::no_break_before`] characters. The piece is kept
+    /// as long as possible if the `rules` leave no other choice, or if
+    /// the next piece would become too wide.
+    fn break_apart_with_marker<'b>(
+        &'b self,
+        line_width: impl Fn(usize) -> usize + 'b,
+        marker_width: usize,
+        measure: WidthMeasure,
+        rules: BreakRules<'b>,
+    ) -> impl Iterator<Item = Word<'a>> + 'b {
+        let mut pieces = 0;
+        let mut piece_width = line_width(0).saturating_sub(marker_width);
+        let mut clusters = measure.clusters(self.word);
         let mut offset = 0;
         let mut width = 0;
-
-        std::iter::from_fn(move || {
-            while let Some((idx, ch)) = char_indices.next() {
-                if skip_ansi_escape_sequence(ch, &mut char_indices.by_ref().map(|(_, ch)| ch)) {
-                    continue;
-                }
-
-                if width > 0 && width + ch_width(ch) > line_width {
+        // The start and width of the clusters in the current piece
+        // after the first one, where the piece can be cut short.
+        let mut cuts: Vec<(usize, usize)> = Vec::new();
+        let restricted = !rules.no_break_before.is_empty() || !rules.no_break_after.is_empty();
+
+        ::core::iter::from_fn(move || {
+            for (idx, cluster_width) in clusters.by_ref() {
+                if width > 0 && width + cluster_width > piece_width {
+                    // The clusters moved to the next piece, last first.
+                    let mut moved = Vec::new();
+                    let mut end = idx;
+                    while !rules.allows_break(&self.word[offset..end], &self.word[end..]) {
+                        match cuts.pop() {
+                            Some(cut) => {
+                                end = cut.0;
+                                moved.push(cut);
+                            }
+                            None => {
+                                end = idx;
+                                moved.clear();
+                                break;
+                            }
+                        }
+                    }
+                    let mut rest_width = moved.iter().map(|&(_, width)| width).sum::<usize>();
+                    pieces += 1;
+                    piece_width = line_width(pieces).saturating_sub(marker_width);
+                    // The rules are ignored if the clusters moved to
+                    // the next piece would make it too wide.
+                    if rest_width + cluster_width > piece_width {
+                        end = idx;
+                        moved.clear();
+                        rest_width = 0;
+                    }
                     let word = Word {
-                        word: &self.word[offset..idx],
-                        width: width,
+                        word: &self.word[offset..end],
+                        width: width - rest_width,
                         whitespace: "",
+                        whitespace_width: 0,
                         penalty: "",
+                        before_break: "",
+                        after_break: "",
+                        marker_width: marker_width,
                     };
-                    offset = idx;
-                    width = ch_width(ch);
+                    cuts.clear();
+                    cuts.extend(moved.iter().rev().skip(1));
+                    if !moved.is_empty() {
+                        cuts.push((idx, cluster_width));
+                    }
+                    offset = end;
+                    width = rest_width + cluster_width;
                     return Some(word);
                 }
 
-                width += ch_width(ch);
+                if restricted && width > 0 {
+                    cuts.push((idx, cluster_width));
+                }
+                width += cluster_width;
             }
 
             if offset < self.word.len() {
@@ -290,7 +854,11 @@ impl<'a> Word<'a> {
                     word: &self.word[offset..],
                     width: width,
                     whitespace: self.whitespace,
+                    whitespace_width: self.whitespace_width,
                     penalty: self.penalty,
+                    before_break: self.before_break,
+                    after_break: self.after_break,
+                    marker_width: self.marker_width,
                 };
                 offset = self.word.len();
                 return Some(word);
@@ -299,6 +867,32 @@ impl<'a> Word<'a> {
             None
         })
     }
+
+    /// Replace the whitespace of the word. The width of the
+    /// whitespace is computed with `measure`.
+    pub(crate) fn with_whitespace(self, whitespace: &'a str, measure: WidthMeasure) -> Word<'a> {
+        Word {
+            whitespace,
+            whitespace_width: measure.whitespace_width(whitespace),
+            ..self
+        }
+    }
+
+    /// Replace the text of the word, keeping the whitespace and
+    /// penalty. Used to join consecutive fragments of a split word.
+    /// The widths are computed with `measure`.
+    pub(crate) fn with_word(&self, word: &'a str, measure: WidthMeasure) -> Word<'a> {
+        Word {
+            word,
+            width: measure.width(word),
+            whitespace: self.whitespace,
+            whitespace_width: measure.whitespace_width(self.whitespace),
+            penalty: self.penalty,
+            before_break: self.before_break,
+            after_break: self.after_break,
+            marker_width: 0,
+        }
+    }
 }
 
 impl Fragment for Word<'_> {
@@ -307,23 +901,39 @@ impl Fragment for Word<'_> {
         self.width
     }
 
-    // We assume the whitespace consist of ' ' only. This allows us to
-    // compute the display width in constant time.
     #[inline]
     fn whitespace_width(&self) -> usize {
-        self.whitespace.len()
+        self.whitespace_width
     }
 
     // We assume the penalty is `""` or `"-"`. This allows us to
-    // compute the display width in constant time.
+    // compute the display width in constant time, except for the
+    // rare fragments which are respelled when the word is split.
     #[inline]
     fn penalty_width(&self) -> usize {
-        self.penalty.len()
+        let respelled = match self.before_break.is_empty() {
+            true => 0,
+            false => display_width(self.before_break),
+        };
+        respelled + self.penalty.len() + self.marker_width
     }
 }
 
 /// Split line into words separated by regions of `' '` characters.
 ///
+/// Other whitespace does not separate words. In particular, words
+/// joined by a non-breaking space (U+00A0) or a narrow non-breaking
+/// space (U+202F) are kept together as a single word. If such a word
+/// is too long for a line, it is broken at the non-breaking space,
+/// which is removed at the end of the line like a space.
+///
+/// A zero width space (U+200B) also separates words. This can be used
+/// to mark where a long word can be broken. The zero width space is
+/// removed if the line is broken there, and otherwise it is kept in
+/// the output. This happens regardless of the [`WordSplitter`] used:
+/// even [`NoHyphenation`](crate::NoHyphenation) will break lines at
+/// zero width spaces.
+///
 /// # Examples
 ///
 /// ```
@@ -333,13 +943,59 @@ impl Fragment for Word<'_> {
 /// assert_eq!(words[0].width(), 5);
 /// assert_eq!(words[0].whitespace_width(), 1);
 /// assert_eq!(words[0].penalty_width(), 0);
+///
+/// let words = find_words("foo\u{200b}bar").collect::<Vec<_>>();
+/// assert_eq!(words, vec![Word::from("foo\u{200b}"), Word::from("bar")]);
+/// assert_eq!(words[0].whitespace_width(), 0);
 /// ```
-pub fn find_words(line: &str) -> impl Iterator<Item = Word> {
+pub fn find_words(line: &str) -> impl Iterator<Item = Word<'_>> {
+    // Lines with printable ASCII characters only are split by
+    // scanning the bytes directly. This gives the same words as the
+    // general code below, which decodes the characters and looks
+    // for escape sequences.
+    let ascii = is_printable_ascii(line);
+    let mut start = 0;
+    let mut words = find_words_separated_by(line, |ch| ch == ' ' || ch == ZWSP);
+    ::core::iter::from_fn(move || {
+        if !ascii {
+            return words.next();
+        }
+        if start == line.len() {
+            return None;
+        }
+        let rest = &line.as_bytes()[start..];
+        let word_len = rest.iter().position(|&b| b == b' ').unwrap_or(rest.len());
+        let whitespace_len = rest[word_len..]
+            .iter()
+            .position(|&b| b != b' ')
+            .unwrap_or(rest.len() - word_len);
+        let end = start + word_len + whitespace_len;
+        let word = Word {
+            word: &line[start..start + word_len],
+            width: word_len,
+            whitespace: &line[start + word_len..end],
+            whitespace_width: whitespace_len,
+            penalty: "",
+            before_break: "",
+            after_break: "",
+            marker_width: 0,
+        };
+        start = end;
+        Some(word)
+    })
+}
+
+/// Split line into words separated by regions of characters for
+/// which `is_separator` returns `true`.
+pub(crate) fn find_words_separated_by(
+    line: &str,
+    is_separator: impl Fn(char) -> bool,
+) -> impl Iterator<Item = Word<'_>> {
     let mut start = 0;
     let mut in_whitespace = false;
     let mut char_indices = line.char_indices();
 
-    std::iter::from_fn(move || {
+    ::core::iter::from_fn(move || {
         // for (idx, ch) in char_indices does not work, gives this
         // error:
         //
@@ -347,14 +1003,21 @@ pub fn find_words(line: &str) -> impl Iterator<Item = Word> {
         // > an `FnMut` closure
         #[allow(clippy::while_let_on_iterator)]
         while let Some((idx, ch)) = char_indices.next() {
-            if in_whitespace && ch != ' ' {
+            let at_word_start = in_whitespace && !is_separator(ch);
+
+            // Separators inside an escape sequence, such as a space
+            // in a window title, do not separate words.
+            if skip_ansi_escape_sequence(ch, &mut char_indices.by_ref().map(|(_, ch)| ch)) {
+                in_whitespace = false;
+            } else {
+                in_whitespace = is_separator(ch);
+            }
+
+            if at_word_start {
                 let word = Word::from(&line[start..idx]);
                 start = idx;
-                in_whitespace = ch == ' ';
                 return Some(word);
             }
-
-            in_whitespace = ch == ' ';
         }
 
         if start < line.len() {
@@ -367,6 +1030,527 @@ pub fn find_words(line: &str) -> impl Iterator<Item = Word> {
     })
 }
 
+/// Turn the break opportunities in `line` into words. The `breaks`
+/// are byte offsets in increasing order, each on a `char` boundary.
+/// Offsets inside ANSI escape sequences are ignored, and the
+/// whitespace after each word is found as in [`Word::from`].
+///
+/// This is a building block for a custom [`WordFinder`] which gets
+/// its break opportunities from, e.g., a dictionary based segmenter.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::{words_at_breaks, Word};
+///
+/// let words = words_at_breaks("foo bar", vec![2, 4]).collect::<Vec<_>>();
+/// assert_eq!(words, vec![Word::from("fo"), Word::from("o "), Word::from("bar")]);
+/// ```
+pub fn words_at_breaks<'a, I>(line: &'a str, breaks: I) -> impl Iterator<Item = Word<'a>> + 'a
+where
+    I: IntoIterator<Item = usize>,
+    I::IntoIter: 'a,
+{
+    let escapes = ansi_escape_sequences(line);
+    let mut breaks = breaks
+        .into_iter()
+        .filter(move |&idx| !is_inside(&escapes, idx))
+        .chain(::core::iter::once(line.len()));
+    let mut start = 0;
+    ::core::iter::from_fn(move || loop {
+        let end = breaks.next()?.min(line.len());
+        if end <= start {
+            continue;
+        }
+        let word = Word::from(&line[start..end]);
+        start = end;
+        return Some(word);
+    })
+}
+
+/// Find the words in a line of text.
+///
+/// Implement this trait to plug in word segmentation for languages
+/// which are written without spaces between words, such as Thai,
+/// Khmer, or Lao. Segmenting these languages needs a dictionary (or
+/// a statistical model) which this crate does not provide, but a
+/// segmenter such as the one from the `icu_segmenter` crate can be
+/// wrapped in a `WordFinder` and used with
+/// [`WordSeparator::Custom`]. The [`words_at_breaks`] function turns
+/// the break opportunities of a segmenter into words.
+///
+/// The built-in [`WordSeparator`]s implement this trait too.
+///
+/// # Separators and Splitters
+///
+/// The `WordFinder` and the [`WordSplitter`] work together: the
+/// `WordFinder` finds the words, which are the places where a line
+/// can be broken without any marker. The [`WordSplitter`] then finds
+/// the places where each word can be split further, such as at the
+/// hyphenation points, and a `-` is added when a line is broken at
+/// one of those. Lastly, words which are still too long are broken
+/// according to [`Options::long_words`](crate::Options::long_words).
+///
+/// # Examples
+///
+/// A toy segmenter which finds the longest word from a dictionary:
+///
+/// ```
+/// use std::sync::Arc;
+/// use textwrap::core::{words_at_breaks, Word, WordFinder, WordSeparator};
+/// use textwrap::{wrap, Options};
+///
+/// #[derive(Debug)]
+/// struct Dictionary(&'static [&'static str]);
+///
+/// impl WordFinder for Dictionary {
+///     fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+///         let mut breaks = Vec::new();
+///         let mut idx = 0;
+///         while let Some(ch) = line[idx..].chars().next() {
+///             let rest = &line[idx..];
+///             let words = self.0.iter().filter(|word| rest.starts_with(*word));
+///             idx += words.map(|word| word.len()).max().unwrap_or(ch.len_utf8());
+///             breaks.push(idx);
+///         }
+///         Box::new(words_at_breaks(line, breaks))
+///     }
+/// }
+///
+/// let thai = Arc::new(Dictionary(&["สวัสดี", "ครับ", "ขอบคุณ", "มาก"]));
+///
+/// let text = "สวัสดีครับขอบคุณมาก";
+/// // Without spaces, the text is one long word which is broken
+/// // in the middle of ขอบคุณ.
+/// #[cfg(feature = "unicode-width")]
+/// assert_eq!(wrap(text, 10), vec!["สวัสดีครับขอบ", "คุณมาก"]);
+///
+/// let options = Options::new(10).word_separator(WordSeparator::Custom(thai));
+/// assert_eq!(wrap(text, &options), vec!["สวัสดีครับ", "ขอบคุณมาก"]);
+/// ```
+pub trait WordFinder: ::core::fmt::Debug + Send + Sync {
+    /// Split `line` into words. The words must follow each other and
+    /// cover all of `line`, and the whitespace after a word belongs
+    /// to the word.
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a>;
+}
+
+impl WordFinder for WordSeparator {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        WordSeparator::find_words(self, line)
+    }
+}
+
+/// Describes where a line of text can be split into words.
+///
+/// Use [`WordSeparator::find_words`] to split a line according to
+/// the chosen method.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WordSeparator {
+    /// Split words on regions of `' '` characters, see
+    /// [`find_words`]. This is the default and it is fast, but it
+    /// will not find break opportunities in text without spaces,
+    /// such as Chinese or Japanese text.
+    AsciiSpace,
+    /// Split words on the break opportunities found by the Unicode
+    /// line breaking algorithm from [UAX #14]. Lines can then be
+    /// broken between ideographs, after hyphens and dashes, and at
+    /// zero width spaces, but never before closing punctuation such
+    /// as `)` or `。`.
+    ///
+    /// Use this for Chinese, Japanese, and other text without spaces
+    /// between words: a line can be broken between any two CJK
+    /// ideographs, kana, or full-width characters. These are two
+    /// columns wide. Words in other scripts, such as Latin words
+    /// embedded in Japanese text, are kept whole.
+    ///
+    /// Mandatory breaks such as `'\n'` and `'\u{85}'` (next line)
+    /// always start a new line when wrapping text.
+    ///
+    /// [UAX #14]: https://www.unicode.org/reports/tr14/
+    UnicodeBreakProperties,
+    /// Split words with a custom [`WordFinder`], such as a
+    /// dictionary based segmenter for Thai or Khmer text. Only
+    /// `'\n'` (with an optional `'\r'` before it) is a mandatory line
+    /// break, like for [`WordSeparator::AsciiSpace`].
+    ///
+    /// The finder is shared, so cloning the separator is cheap. This
+    /// cannot be serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn WordFinder>),
+}
+
+impl PartialEq for WordSeparator {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WordSeparator::AsciiSpace, WordSeparator::AsciiSpace) => true,
+            (WordSeparator::UnicodeBreakProperties, WordSeparator::UnicodeBreakProperties) => true,
+            // Custom separators are equal if they share the same finder.
+            (WordSeparator::Custom(a), WordSeparator::Custom(b)) => {
+                ::core::ptr::eq(Arc::as_ptr(a) as *const u8, Arc::as_ptr(b) as *const u8)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for WordSeparator {}
+
+impl WordSeparator {
+    /// Split `line` into words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::{Word, WordSeparator};
+    ///
+    /// let words = WordSeparator::UnicodeBreakProperties
+    ///     .find_words("Hello 世界")
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     words,
+    ///     vec![Word::from("Hello "), Word::from("世"), Word::from("界")]
+    /// );
+    /// ```
+    pub fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        match self {
+            WordSeparator::AsciiSpace => Box::new(find_words(line)),
+            WordSeparator::UnicodeBreakProperties => {
+                Box::new(words_at_breaks(line, line_break::break_opportunities(line)))
+            }
+            WordSeparator::Custom(finder) => finder.find_words(line),
+        }
+    }
+
+    /// Find the first mandatory line break in `text`. The start and
+    /// end of the line break characters are returned. Only `'\n'`
+    /// is a mandatory line break for [`WordSeparator::AsciiSpace`]
+    /// and [`WordSeparator::Custom`],
+    /// but a `'\r'` right before it is part of the line break.
+    pub(crate) fn find_line_break(&self, text: &str) -> Option<(usize, usize)> {
+        match self {
+            WordSeparator::AsciiSpace | WordSeparator::Custom(_) => {
+                text.find('\n')
+                    .map(|idx| match text[..idx].ends_with('\r') {
+                        true => (idx - 1, idx + 1),
+                        false => (idx, idx + 1),
+                    })
+            }
+            WordSeparator::UnicodeBreakProperties => {
+                let idx = text.find(|ch| {
+                    matches!(
+                        ch,
+                        '\n' | '\r' | '\u{0b}' | '\u{0c}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+                    )
+                })?;
+                let len = if text[idx..].starts_with("\r\n") {
+                    2
+                } else {
+                    text[idx..].chars().next().map_or(1, char::len_utf8)
+                };
+                Some((idx, idx + len))
+            }
+        }
+    }
+}
+
+/// Characters which must not start or end a line.
+///
+/// The rules are applied after the words have been found by the
+/// [`WordSeparator`]: a word which starts with one of the
+/// `no_break_before` characters is joined with the previous word,
+/// and a word which ends with one of the `no_break_after` characters
+/// is joined with the next word. This can make a line a little
+/// shorter than it could otherwise be.
+///
+/// The [`GlueRule`]s set with [`BreakRules::glue`] join two words
+/// based on their contents, e.g., a number and its unit.
+///
+/// # Examples
+///
+/// French puts spaces inside the guillemets, but they must stay on
+/// the same line as the quoted text:
+///
+/// ```
+/// use textwrap::core::BreakRules;
+/// use textwrap::{wrap, Options};
+///
+/// let rules = BreakRules::new("»:;!?", "«");
+/// let options = Options::new(14).break_rules(rules);
+/// assert_eq!(wrap("Il a dit « bonjour » !", &options),
+///            vec!["Il a dit", "« bonjour » !"]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BreakRules<'a> {
+    /// Characters which must not start a line.
+    pub no_break_before: &'a str,
+    /// Characters which must not end a line.
+    pub no_break_after: &'a str,
+    /// Pairs of words which must not be separated.
+    pub glue: &'a [GlueRule],
+}
+
+impl<'a> BreakRules<'a> {
+    /// Create new break rules from the characters which must not
+    /// start and end a line, respectively.
+    pub const fn new(no_break_before: &'a str, no_break_after: &'a str) -> Self {
+        BreakRules {
+            no_break_before,
+            no_break_after,
+            glue: &[],
+        }
+    }
+
+    /// Change the [`GlueRule`]s. Use [`GlueRule::DEFAULTS`] for a
+    /// small set of rules for English text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::{BreakRules, GlueRule};
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let rules = BreakRules::none().glue(GlueRule::DEFAULTS);
+    /// let options = Options::new(15).break_rules(rules);
+    /// assert_eq!(wrap("The file is 100 MB in size.", &options),
+    ///            vec!["The file is", "100 MB in size."]);
+    /// ```
+    ///
+    /// Custom rules are added by putting them in a slice, possibly
+    /// together with the default rules:
+    ///
+    /// ```
+    /// use textwrap::core::{BreakRules, GlueRule};
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let version = GlueRule::new(|word| word == "version", |word| {
+    ///     word.starts_with(|ch: char| ch.is_ascii_digit())
+    /// });
+    /// let glue = [GlueRule::DEFAULTS, &[version]].concat();
+    /// let options = Options::new(13).break_rules(BreakRules::none().glue(&glue));
+    /// assert_eq!(wrap("Install the version 1.2.3 now.", &options),
+    ///            vec!["Install the", "version 1.2.3", "now."]);
+    /// ```
+    pub const fn glue(self, glue: &'a [GlueRule]) -> Self {
+        BreakRules { glue, ..self }
+    }
+
+    /// No additional restrictions. This is the default.
+    pub const fn none() -> Self {
+        BreakRules::new("", "")
+    }
+
+    /// Rules for Japanese text (_kinsoku shori_). Closing brackets,
+    /// punctuation, iteration marks, and small kana must not start
+    /// a line, and opening brackets must not end a line.
+    ///
+    /// Use this together with
+    /// [`WordSeparator::UnicodeBreakProperties`] to find the break
+    /// opportunities between the Japanese characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::{BreakRules, WordSeparator, WrapAlgorithm};
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(6)
+    ///     .wrap_algorithm(WrapAlgorithm::FirstFit)
+    ///     .word_separator(WordSeparator::UnicodeBreakProperties);
+    /// assert_eq!(wrap("あちょっと", &options), vec!["あちょ", "っと"]);
+    ///
+    /// let options = options.break_rules(BreakRules::japanese());
+    /// assert_eq!(wrap("あちょっと", &options), vec!["あ", "ちょっ", "と"]);
+    /// ```
+    pub const fn japanese() -> Self {
+        BreakRules::new(
+            "、。，．・：；？！‼⁇⁈⁉゛゜ヽヾゝゞ々〻ー‐゠–〜～」』）］｝〕〉》】〙〗〟’”｠»\
+             ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶㇰㇱㇲㇳㇴㇵㇶㇷㇸㇹㇺㇻㇼㇽㇾㇿ)]}",
+            "「『（［｛〔〈《【〘〖〝‘“｟«([{",
+        )
+    }
+
+    /// Rules for French typography. French puts a space before the
+    /// high punctuation `;`, `:`, `!`, and `?` and inside the
+    /// guillemets `«` and `»`. The punctuation and the closing
+    /// guillemet must not start a line and the opening guillemet must
+    /// not end a line, even when the space is a plain `' '`.
+    ///
+    /// Text which already uses a no-break space (U+00A0) or a narrow
+    /// no-break space (U+202F) at these places is kept together
+    /// without these rules. If such a group is too long for a line,
+    /// it is broken at its spaces before the words are broken.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::{BreakRules, WrapAlgorithm};
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Vraiment ? Il a dit « oui » et puis « non » !";
+    /// let options = Options::new(12).wrap_algorithm(WrapAlgorithm::FirstFit);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Vraiment ?", "Il a dit «", "oui » et", "puis « non »", "!"]);
+    ///
+    /// let options = options.break_rules(BreakRules::french());
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Vraiment ?", "Il a dit", "« oui » et", "puis", "« non » !"]);
+    /// ```
+    pub const fn french() -> Self {
+        BreakRules::new(";:!?»›‽", "«‹")
+    }
+
+    /// Check if a line may end with `before` and the next line may
+    /// start with `after`. Whitespace and invisible characters at the
+    /// break are ignored, and the [`GlueRule`]s are not checked.
+    pub(crate) fn allows_break(&self, before: &str, after: &str) -> bool {
+        let ignored = |ch: char| ch.is_whitespace() || INVISIBLE.contains(&ch);
+        !after
+            .trim_start_matches(ignored)
+            .starts_with(|ch| self.no_break_before.contains(ch))
+            && !before
+                .trim_end_matches(ignored)
+                .ends_with(|ch| self.no_break_after.contains(ch))
+    }
+
+    /// Check if a [`GlueRule`] joins `before` and `after`.
+    pub(crate) fn glued(&self, before: &str, after: &str) -> bool {
+        self.glue.iter().any(|rule| rule.matches(before, after))
+    }
+
+    /// Join the `words` of `line` where a line break is not allowed.
+    /// The words must be consecutive slices of `line`. Words without
+    /// visible characters are skipped by the rules: they are joined
+    /// only together with the visible word after them.
+    pub(crate) fn join_words<'b, I>(&self, line: &'b str, words: I) -> Vec<Word<'b>>
+    where
+        I: IntoIterator<Item = Word<'b>>,
+    {
+        let mut joined: Vec<Word<'b>> = Vec::new();
+        let mut start = 0;
+        for word in words {
+            let len = word.len() + word.whitespace.len();
+            let visible = joined.iter().rposition(|prev| !is_invisible(prev));
+            let join = match visible {
+                Some(_) if is_invisible(&word) => false,
+                Some(idx) => {
+                    let prev = &joined[idx];
+                    !self.allows_break(prev, &word) || self.glued(prev, &word)
+                }
+                None => false,
+            };
+            if let (true, Some(idx)) = (join, visible) {
+                let prev_len: usize = joined
+                    .drain(idx..)
+                    .map(|prev| prev.len() + prev.whitespace.len())
+                    .sum();
+                joined.push(Word::from(&line[start - prev_len..start + len]));
+            } else {
+                joined.push(word);
+            }
+            start += len;
+        }
+        joined
+    }
+}
+
+impl Default for BreakRules<'_> {
+    fn default() -> Self {
+        BreakRules::none()
+    }
+}
+
+/// A rule which prevents a line break between two words.
+///
+/// The rule consists of two patterns, given as functions which check
+/// a single word. A line break is not allowed between two words when
+/// the first pattern matches the word before the break and the
+/// second pattern matches the word after the break. The words are
+/// given without their trailing whitespace. If the word before the
+/// break has already been joined with other words, only the part
+/// after its last space is given. See [`BreakRules::glue`].
+#[derive(Clone, Copy, Debug)]
+pub struct GlueRule {
+    /// Pattern for the word before the break.
+    pub before: fn(&str) -> bool,
+    /// Pattern for the word after the break.
+    pub after: fn(&str) -> bool,
+}
+
+impl PartialEq for GlueRule {
+    fn eq(&self, other: &Self) -> bool {
+        ::core::ptr::fn_addr_eq(self.before, other.before)
+            && ::core::ptr::fn_addr_eq(self.after, other.after)
+    }
+}
+
+impl Eq for GlueRule {}
+
+/// Units which are kept together with a preceding number by
+/// [`GlueRule::NUMBER_UNIT`].
+const UNITS: &[&str] = &[
+    "%", "‰", "°", "°C", "°F", "K", "B", "kB", "KB", "MB", "GB", "TB", "KiB", "MiB", "GiB", "TiB",
+    "bit", "bits", "byte", "bytes", "ns", "µs", "ms", "s", "sec", "min", "h", "Hz", "kHz", "MHz",
+    "GHz", "mm", "cm", "m", "km", "ft", "mi", "mg", "g", "kg", "lb", "lbs", "oz", "ml", "mL", "l",
+    "L", "V", "mV", "kV", "mA", "W", "kW", "MW", "px", "pt", "em", "dpi", "€", "$",
+];
+
+/// Is `word` an initial, such as `"J."`?
+pub(crate) fn is_initial(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(ch), Some('.'), None) if ch.is_uppercase()
+    )
+}
+
+impl GlueRule {
+    /// Create a new rule from the patterns for the words before and
+    /// after the break.
+    pub const fn new(before: fn(&str) -> bool, after: fn(&str) -> bool) -> Self {
+        GlueRule { before, after }
+    }
+
+    /// A number followed by a unit, such as `"100 MB"` or `"20 °C"`.
+    pub const NUMBER_UNIT: GlueRule = GlueRule::new(
+        |word| word.ends_with(|ch: char| ch.is_ascii_digit()),
+        |word| UNITS.contains(&word.trim_end_matches(|ch: char| ".,;:!?)".contains(ch))),
+    );
+
+    /// An abbreviation followed by a name or a number, such as
+    /// `"Dr. Watson"` or `"fig. 3"`. The abbreviations are the
+    /// [`DEFAULT_ABBREVIATIONS`](crate::DEFAULT_ABBREVIATIONS).
+    pub const ABBREVIATION: GlueRule = GlueRule::new(
+        |word| crate::DEFAULT_ABBREVIATIONS.contains(&word),
+        |word| word.starts_with(|ch: char| ch.is_uppercase() || ch.is_ascii_digit()),
+    );
+
+    /// An initial followed by a name or another initial, such as
+    /// `"J. R. R. Tolkien"`.
+    pub const INITIAL: GlueRule =
+        GlueRule::new(is_initial, |word| word.starts_with(char::is_uppercase));
+
+    /// The default rules: [`GlueRule::NUMBER_UNIT`],
+    /// [`GlueRule::ABBREVIATION`], and [`GlueRule::INITIAL`].
+    pub const DEFAULTS: &'static [GlueRule] = &[
+        GlueRule::NUMBER_UNIT,
+        GlueRule::ABBREVIATION,
+        GlueRule::INITIAL,
+    ];
+
+    /// Check if the rule prevents a break between `before` and
+    /// `after`.
+    fn matches(&self, before: &str, after: &str) -> bool {
+        (self.before)(last_token(before)) && (self.after)(after)
+    }
+}
+
+/// Return the part of `word` after the last space.
+fn last_token(word: &str) -> &str {
+    word.rsplit(' ').next().unwrap_or(word)
+}
+
 /// Split words into smaller words according to the split points given
 /// by `options`.
 ///
@@ -374,6 +1558,23 @@ pub fn find_words(line: &str) -> impl Iterator<Item = Word> {
 /// to more cleanly separate the business of splitting (including
 /// automatic hyphenation) from the business of word wrapping.
 ///
+/// Split points next to a word joiner (U+2060) or a non-breaking
+/// space (U+00A0, U+2007, U+202F) are ignored, regardless of the
+/// splitter used. Use a word joiner to protect words which must never
+/// be hyphenated. Split points inside ANSI escape sequences, such as
+/// the `-` in the target of an OSC 8 hyperlink, are also ignored.
+///
+/// When a split point follows a soft hyphen (U+00AD), the soft hyphen
+/// is moved into the whitespace of the fragment and a `-` penalty is
+/// used instead. This way, the soft hyphen takes up no room on the
+/// line and it is only shown if the word is broken there. Please see
+/// [`SoftHyphenSplitter`](crate::SoftHyphenSplitter).
+///
+/// The strings borrowed by the `options` can have any lifetime. The
+/// fragments can borrow the [`Substitution`](crate::Substitution)s
+/// of the splitter, so they cannot outlive the borrow of the
+/// `options`.
+///
 /// # Examples
 ///
 /// ```
@@ -393,37 +1594,108 @@ pub fn find_words(line: &str) -> impl Iterator<Item = Word> {
 ///     split_words(vec![Word::from("foo-bar")], &options).collect::<Vec<_>>(),
 ///     vec![Word::from("foo-bar")]
 /// );
+///
+/// // A word joiner prevents the split:
+/// let options = Options::new(80);
+/// assert_eq!(
+///     split_words(vec![Word::from("foo-\u{2060}bar")], &options).collect::<Vec<_>>(),
+///     vec![Word::from("foo-\u{2060}bar")]
+/// );
 /// ```
-pub fn split_words<'a, I, S, Opt>(words: I, options: Opt) -> impl Iterator<Item = Word<'a>>
+pub fn split_words<'a, 'o, I, S>(
+    words: I,
+    options: &'o Options<'_, S>,
+) -> impl Iterator<Item = Word<'a>> + 'a
 where
+    'o: 'a,
     I: IntoIterator<Item = Word<'a>>,
-    S: WordSplitter,
-    Opt: Into<Options<'a, S>>,
+    I::IntoIter: 'a,
+    S: WordSplitter + ?Sized,
 {
-    let options = options.into();
-
     words.into_iter().flat_map(move |word| {
         let mut prev = 0;
-        let mut split_points = options.splitter.split_points(&word).into_iter();
-        std::iter::from_fn(move || {
-            if let Some(idx) = split_points.next() {
+        let escapes = ansi_escape_sequences(&word);
+        let split_points = match options.url_breaks {
+            UrlBreaks::Anywhere => options.splitter.split(&word),
+            _ if !is_url_like(&word) => options.splitter.split(&word),
+            UrlBreaks::Never => SplitPoints::new(),
+            UrlBreaks::AfterSeparators => url_split_points(&word)
+                .into_iter()
+                .map(|idx| (idx, Some(NO_HYPHEN)))
+                .collect(),
+        };
+        let mut split_points = split_points.into_iter().filter(move |&(idx, _)| {
+            !word.word[..idx].ends_with(NO_SPLIT)
+                && !word.word[idx..].starts_with(NO_SPLIT)
+                && !is_inside(&escapes, idx)
+        });
+        // Width of the respelled start of the fragment after a split
+        // with a substitution.
+        let mut prefix_width = 0;
+        ::core::iter::from_fn(move || {
+            if let Some((idx, substitution)) = split_points.next() {
+                let substitution = substitution.filter(|substitution| {
+                    idx >= prev + substitution.left
+                        && word.word.is_char_boundary(idx - substitution.left)
+                        && word.word.get(idx + substitution.right..).is_some()
+                });
+                if let Some(substitution) = substitution {
+                    // The replaced letters are only shown if the word
+                    // is not split, just like whitespace.
+                    let end = idx - substitution.left;
+                    let next = idx + substitution.right;
+                    let after_width = display_width(substitution.after_break);
+                    let w = Word {
+                        word: &word.word[prev..end],
+                        width: display_width(&word[prev..end]) + prefix_width,
+                        whitespace: &word.word[end..next],
+                        whitespace_width: display_width(&word[end..next])
+                            .saturating_sub(after_width),
+                        penalty: substitution.hyphen,
+                        before_break: substitution.before_break,
+                        after_break: substitution.after_break,
+                        marker_width: 0,
+                    };
+                    prefix_width = after_width;
+                    prev = next;
+                    return Some(w);
+                }
+
+                // A soft hyphen becomes invisible whitespace after the
+                // fragment and is replaced by the penalty if we break.
+                let end = if word[..idx].ends_with(SHY) {
+                    idx - SHY.len_utf8()
+                } else {
+                    idx
+                };
                 let need_hyphen = !word[..idx].ends_with('-');
                 let w = Word {
-                    word: &word.word[prev..idx],
-                    width: display_width(&word[prev..idx]),
-                    whitespace: "",
+                    word: &word.word[prev..end],
+                    width: display_width(&word[prev..end]) + prefix_width,
+                    whitespace: &word.word[end..idx],
+                    whitespace_width: 0,
                     penalty: if need_hyphen { "-" } else { "" },
+                    before_break: "",
+                    after_break: "",
+                    marker_width: 0,
                 };
+                prefix_width = 0;
                 prev = idx;
                 return Some(w);
             }
 
-            if prev < word.word.len() || prev == 0 {
+            // A substitution can replace the rest of the word, which
+            // leaves an empty fragment with the respelled letters.
+            if prev <= word.word.len() {
                 let w = Word {
                     word: &word.word[prev..],
-                    width: display_width(&word[prev..]),
+                    width: display_width(&word[prev..]) + prefix_width,
                     whitespace: word.whitespace,
+                    whitespace_width: word.whitespace_width,
                     penalty: word.penalty,
+                    before_break: word.before_break,
+                    after_break: word.after_break,
+                    marker_width: word.marker_width,
                 };
                 prev = word.word.len() + 1;
                 return Some(w);
@@ -434,26 +1706,283 @@ where
     })
 }
 
+/// Separators in a URL after which it can be broken, see
+/// [`UrlBreaks::AfterSeparators`].
+const URL_SEPARATORS: [char; 4] = ['/', '?', '&', '#'];
+
+/// Check if `word` looks like a URL or an email address.
+///
+/// A URL has a scheme of ASCII letters, digits, `+`, `-`, and `.`
+/// followed by `://` and more text. Punctuation such as `<` or `(`
+/// before the scheme is allowed. An email address has text before a
+/// `@` and a domain with a `.` after it.
+pub(crate) fn is_url_like(word: &str) -> bool {
+    if let Some(idx) = word.find("://") {
+        let scheme = word[..idx].trim_start_matches(|ch: char| ch.is_ascii_punctuation());
+        return scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || "+-.".contains(ch))
+            && idx + 3 < word.len();
+    }
+    match word.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain
+                    .find('.')
+                    .is_some_and(|dot| dot > 0 && dot + 1 < domain.len())
+        }
+        None => false,
+    }
+}
+
+/// Return the positions after the [`URL_SEPARATORS`] in `url`. A run
+/// of separators, such as the `//` after the scheme, is kept
+/// together.
+fn url_split_points(url: &str) -> Vec<usize> {
+    url.match_indices(URL_SEPARATORS)
+        .map(|(idx, sep)| idx + sep.len())
+        .filter(|&end| end < url.len() && !url[end..].starts_with(URL_SEPARATORS))
+        .collect()
+}
+
 /// Forcibly break words wider than `line_width` into smaller words.
 ///
 /// This simply calls [`Word::break_apart`] on words that are too
 /// wide. This means that no extra `'-'` is inserted, the word is
-/// simply broken into smaller pieces.
+/// simply broken into smaller pieces. A word which contains spaces
+/// is broken at its spaces first.
 pub fn break_words<'a, I>(words: I, line_width: usize) -> Vec<Word<'a>>
 where
     I: IntoIterator<Item = Word<'a>>,
 {
+    break_words_with_marker(
+        words,
+        |_| line_width,
+        0,
+        WidthMeasure::UnicodeCells,
+        &WordSeparator::AsciiSpace,
+        BreakRules::none(),
+    )
+}
+
+/// Forcibly break words like [`break_words`], but leave room for a
+/// marker of `marker_width` columns at the end of all but the last
+/// piece of each broken word. No room is left if the marker would
+/// take up the entire first line. The `line_width` of each piece of a
+/// word is given by its index, and the pieces are measured with
+/// `measure`.
+///
+/// A word which is too long is first split into its parts at the
+/// breaks found by the `separator` and at its non-breaking spaces.
+/// The spaces become the whitespace of the pieces, so they are
+/// removed at the end of a line. Only a part which is still too long
+/// is broken between two clusters. Parts which the `rules` keep
+/// together are then joined again as long as they fit on a line.
+pub(crate) fn break_words_with_marker<'a, I>(
+    words: I,
+    line_width: impl Fn(usize) -> usize,
+    marker_width: usize,
+    measure: WidthMeasure,
+    separator: &WordSeparator,
+    rules: BreakRules<'_>,
+) -> Vec<Word<'a>>
+where
+    I: IntoIterator<Item = Word<'a>>,
+{
+    let marker_width = if marker_width < line_width(0) {
+        marker_width
+    } else {
+        0
+    };
     let mut shortened_words = Vec::new();
     for word in words {
-        if word.width() > line_width {
-            shortened_words.extend(word.break_apart(line_width));
-        } else {
+        if word.width() <= line_width(0) {
             shortened_words.push(word);
+            continue;
+        }
+
+        // A word without visible characters becomes part of the
+        // whitespace before it, so that its whitespace does not end
+        // up at the start of a line.
+        let offset = |text: &str| text.as_ptr() as usize - word.word.as_ptr() as usize;
+        let mut found_words: Vec<Word<'a>> = Vec::new();
+        for found in separator.find_words(word.word) {
+            match found_words.last_mut() {
+                Some(prev) if is_invisible(&found) => {
+                    let end = offset(found.whitespace) + found.whitespace.len();
+                    *prev = prev.with_whitespace(&word.word[offset(prev.whitespace)..end], measure);
+                }
+                _ => found_words.push(found),
+            }
         }
+
+        let mut parts = Vec::new();
+        let mut separated = found_words.into_iter().peekable();
+        while let Some(found) = separated.next() {
+            let last_found = separated.peek().is_none();
+            let mut spaces = split_at_no_break_spaces(found.word).peekable();
+            while let Some((text, space)) = spaces.next() {
+                let last_part = spaces.peek().is_none();
+                let part = match (last_found, last_part) {
+                    (true, true) => Word {
+                        marker_width: word.marker_width,
+                        ..word.with_word(text, measure)
+                    },
+                    _ => Word {
+                        penalty: "",
+                        before_break: "",
+                        after_break: "",
+                        marker_width: 0,
+                        ..word.with_word(text, measure)
+                    }
+                    .with_whitespace(if last_part { found.whitespace } else { space }, measure),
+                };
+                parts.push((part, !last_found && last_part));
+            }
+        }
+        join_parts(
+            &mut shortened_words,
+            word.word,
+            &parts,
+            &line_width,
+            marker_width,
+            measure,
+            rules,
+        );
     }
     shortened_words
 }
 
+/// Split `text` at its runs of non-breaking spaces. Returns the
+/// pieces of text with the spaces after them. Spaces at the start or
+/// the end of `text`, ignoring invisible characters, are kept in the
+/// pieces.
+fn split_at_no_break_spaces(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = Some(text);
+    ::core::iter::from_fn(move || {
+        let current = rest.take()?;
+        let leading = |ch| NO_BREAK_SPACES.contains(&ch) || INVISIBLE.contains(&ch);
+        let skipped = current.len() - current.trim_start_matches(leading).len();
+        let idx = match current[skipped..].find(NO_BREAK_SPACES) {
+            Some(idx) => skipped + idx,
+            None => return Some((current, "")),
+        };
+        let after = current[idx..].trim_start_matches(NO_BREAK_SPACES);
+        if is_invisible(after) {
+            return Some((current, ""));
+        }
+        rest = Some(after);
+        Some((&current[..idx], &current[idx..current.len() - after.len()]))
+    })
+}
+
+/// Join consecutive `parts` of `text` into pieces which fit in their
+/// `line_width`, and add them to `pieces`. The flag of a part tells
+/// if it ends at a break found by the separator. The parts are only
+/// joined where there is no such break or where the `rules` forbid
+/// it. A part which is too wide on its own is broken with room for a
+/// marker of `marker_width` columns.
+fn join_parts<'a>(
+    pieces: &mut Vec<Word<'a>>,
+    text: &'a str,
+    parts: &[(Word<'a>, bool)],
+    line_width: &dyn Fn(usize) -> usize,
+    marker_width: usize,
+    measure: WidthMeasure,
+    rules: BreakRules<'_>,
+) {
+    let offset = |part: &Word<'_>| part.word.as_ptr() as usize - text.as_ptr() as usize;
+    let piece = |first: &Word<'a>, last: &Word<'a>| Word {
+        marker_width: last.marker_width,
+        ..last.with_word(&text[offset(first)..offset(last) + last.len()], measure)
+    };
+
+    let start = pieces.len();
+    // The first and last part of the current piece, and if the last
+    // part ends at a break found by the separator.
+    let mut current: Option<(Word<'a>, Word<'a>, bool)> = None;
+    for &(part, separated) in parts {
+        if let Some((first, last, last_separated)) = current {
+            if !last_separated || !rules.allows_break(&last, &part) || rules.glued(&last, &part) {
+                let candidate = piece(&first, &part);
+                if candidate.width() + candidate.marker_width <= line_width(pieces.len() - start) {
+                    current = Some((first, part, separated));
+                    continue;
+                }
+            }
+            pieces.push(piece(&first, &last));
+        }
+
+        let index = pieces.len() - start;
+        if part.width() > line_width(index) {
+            let width = move |k| line_width(index + k);
+            let mut broken = part
+                .break_apart_with_marker(width, marker_width, measure, rules)
+                .peekable();
+            while let Some(chunk) = broken.next() {
+                if broken.peek().is_none() {
+                    current = Some((chunk, chunk, separated));
+                } else {
+                    pieces.push(chunk);
+                }
+            }
+        } else {
+            current = Some((part, part, separated));
+        }
+    }
+    if let Some((first, last, _)) = current {
+        pieces.push(piece(&first, &last));
+    }
+}
+
+/// Account for the [`Substitution`](crate::Substitution) of the split
+/// `words`. The width of the letters after the break is moved from
+/// the replaced letters, which are only shown if the word is not
+/// split, to the next word. The widths are given by `measure`.
+pub(crate) fn respell_split_words(words: &mut [Word<'_>], measure: WidthMeasure) {
+    for i in 1..words.len() {
+        let Word {
+            before_break,
+            after_break,
+            ..
+        } = words[i - 1];
+        if !before_break.is_empty() || !after_break.is_empty() {
+            let after_width = measure.width(after_break);
+            words[i - 1].whitespace_width = measure
+                .width(words[i - 1].whitespace)
+                .saturating_sub(after_width);
+            words[i].width += after_width;
+        }
+    }
+}
+
+/// Let the first character of the `words` hang into the margin. The
+/// `hanging_width` is the width of the character at the start of a
+/// word which hangs, or zero.
+///
+/// The hanging character only takes up room when the word is not at
+/// the start of a line, which is exactly when the whitespace before
+/// the word counts. The width of the character is therefore moved
+/// from the word to the whitespace of the previous word.
+pub(crate) fn hang_punctuation(words: &mut [Word<'_>], hanging_width: impl Fn(&str) -> usize) {
+    for i in 0..words.len() {
+        let hanging = hanging_width(words[i].word);
+        if hanging > 0 {
+            words[i].width = words[i].width.saturating_sub(hanging);
+            // An empty word without whitespace, such as the one
+            // inserted before the first word of a paragraph, does not
+            // separate the word from the start of the line.
+            let prev = words[..i]
+                .iter()
+                .rposition(|word| !word.is_empty() || !word.whitespace.is_empty());
+            if let Some(prev) = prev {
+                words[prev].whitespace_width += hanging;
+            }
+        }
+    }
+}
+
 /// Wrapping algorithms.
 ///
 /// After a text has been broken into [`Fragment`]s, the one now has
@@ -471,6 +2000,7 @@ where
 /// While both algorithms run in linear time, the first-fit algorithm
 /// is about 4 times faster than the optimal-fit algorithm.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WrapAlgorithm {
     /// Use an advanced algorithm which considers the entire paragraph
     /// to find optimal line breaks. Implemented by
@@ -525,8 +2055,9 @@ pub enum WrapAlgorithm {
 ///                 "nicely."]);
 ///
 /// // We can avoid the short line if we look ahead:
+/// let penalties = textwrap::core::Penalties::new();
 /// #[cfg(feature = "smawk")]
-/// assert_eq!(lines_to_strings(textwrap::core::wrap_optimal_fit(&words, |_| 15)),
+/// assert_eq!(lines_to_strings(textwrap::core::wrap_optimal_fit(&words, |_| 15, &penalties)),
 ///            vec!["These few",
 ///                 "words will",
 ///                 "unfortunately",
@@ -627,6 +2158,46 @@ pub enum WrapAlgorithm {
 ///
 /// Apologies to anyone who actually knows how to build a house and
 /// knows how long each step takes :-)
+///
+/// The fragments do not have to be text at all. Here a toolbar of
+/// widgets, each with an icon and a label, is broken into rows which
+/// are at most 26 columns wide. The icon takes up two columns and a
+/// space separates it from the label, while the widgets are separated
+/// by a gap of two columns:
+///
+/// ```
+/// use textwrap::core::{display_width, wrap_first_fit, Fragment};
+///
+/// #[derive(Debug)]
+/// struct Widget<'a> {
+///     icon: char,
+///     label: &'a str,
+/// }
+///
+/// impl Fragment for Widget<'_> {
+///     fn width(&self) -> usize { 2 + 1 + display_width(self.label) }
+///     fn whitespace_width(&self) -> usize { 2 }
+///     fn penalty_width(&self) -> usize { 0 }
+/// }
+///
+/// let toolbar = vec![
+///     Widget { icon: '📂', label: "Open" },
+///     Widget { icon: '💾', label: "Save" },
+///     Widget { icon: '🖨', label: "Print" },
+///     Widget { icon: '🔍', label: "Search" },
+///     Widget { icon: '⚙', label: "Settings" },
+/// ];
+/// let rows = wrap_first_fit(&toolbar, |_| 26)
+///     .iter()
+///     .map(|row| row.iter().map(|widget| widget.label).collect::<Vec<_>>())
+///     .collect::<Vec<_>>();
+/// assert_eq!(rows, vec![vec!["Open", "Save", "Print"],
+///                       vec!["Search", "Settings"]]);
+/// ```
+///
+/// This is the same function which [`wrap`](crate::wrap) uses when
+/// [`WrapAlgorithm::FirstFit`] is selected, so text and other
+/// fragments are always broken into lines the same way.
 pub fn wrap_first_fit<T: Fragment, F: Fn(usize) -> usize>(
     fragments: &[T],
     line_widths: F,
@@ -651,6 +2222,7 @@ pub fn wrap_first_fit<T: Fragment, F: Fn(usize) -> usize>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[cfg(feature = "unicode-width")]
     use unicode_width::UnicodeWidthChar;
@@ -671,6 +2243,76 @@ mod tests {
         assert_eq!(chars.next(), Some('H'));
     }
 
+    #[test]
+    fn skip_ansi_escape_sequence_osc() {
+        let link = "\u{1b}]8;;https://example.com\u{7}Hi";
+        let mut chars = link.chars();
+        let ch = chars.next().unwrap();
+        assert!(skip_ansi_escape_sequence(ch, &mut chars));
+        assert_eq!(chars.next(), Some('H'));
+
+        let title = "\u{1b}]0;My title\u{1b}\\Hi";
+        let mut chars = title.chars();
+        let ch = chars.next().unwrap();
+        assert!(skip_ansi_escape_sequence(ch, &mut chars));
+        assert_eq!(chars.next(), Some('H'));
+    }
+
+    #[test]
+    fn display_width_osc() {
+        let link = "\u{1b}]8;;https://example.com\u{7}link\u{1b}]8;;\u{1b}\\";
+        assert_eq!(display_width(link), 4);
+    }
+
+    #[test]
+    fn ansi_escape_sequences_ranges() {
+        assert_eq!(ansi_escape_sequences("plain"), vec![]);
+        assert_eq!(
+            ansi_escape_sequences("\u{1b}[1mbold\u{1b}[0m \u{1b}x"),
+            vec![0..4, 8..12]
+        );
+    }
+
+    #[test]
+    fn find_words_osc_with_space() {
+        let text = "\u{1b}]0;My title\u{7}foo bar";
+        assert_iter_eq!(
+            find_words(text),
+            vec![Word::from("\u{1b}]0;My title\u{7}foo "), Word::from("bar")]
+        );
+    }
+
+    #[test]
+    fn find_words_colored_after_whitespace() {
+        assert_iter_eq!(
+            find_words("foo \u{1b}[31mbar"),
+            vec![Word::from("foo "), Word::from("\u{1b}[31mbar")]
+        );
+    }
+
+    #[test]
+    fn split_words_not_inside_escape_sequence() {
+        let link = "\u{1b}]8;;https://for-example.com\u{7}foo-bar\u{1b}]8;;\u{7}";
+        let options = Options::new(80);
+        assert_iter_eq!(
+            split_words(vec![Word::from(link)], &options),
+            vec![
+                Word::from("\u{1b}]8;;https://for-example.com\u{7}foo-"),
+                Word::from("bar\u{1b}]8;;\u{7}")
+            ]
+        );
+    }
+
+    #[test]
+    fn unicode_break_properties_not_inside_escape_sequence() {
+        let text = "\u{1b}]0;a b\u{7}foo bar";
+        let words = WordSeparator::UnicodeBreakProperties.find_words(text);
+        assert_iter_eq!(
+            words,
+            vec![Word::from("\u{1b}]0;a b\u{7}foo "), Word::from("bar")]
+        );
+    }
+
     #[test]
     fn emojis_have_correct_width() {
         use unic_emoji_char::is_emoji;
@@ -731,12 +2373,28 @@ mod tests {
 
     #[test]
     fn display_width_narrow_emojis_variant_selector() {
+        // The variant selector-16 asks for the wide emoji
+        // presentation.
+        assert_eq!(display_width("⁉\u{fe0f}"), 2);
+        assert_eq!(display_width("❤\u{fe0f}"), 2);
+        // The variant selector-15 asks for the text presentation.
         #[cfg(feature = "unicode-width")]
-        assert_eq!(display_width("⁉\u{fe0f}"), 1);
+        assert_eq!(display_width("❤\u{fe0e}"), 1);
+    }
 
-        // The variant selector-16 is also counted.
-        #[cfg(not(feature = "unicode-width"))]
-        assert_eq!(display_width("⁉\u{fe0f}"), 4);
+    #[test]
+    fn display_width_flags() {
+        assert_eq!(display_width("🇩🇰"), 2);
+        assert_eq!(display_width("🇩🇰🇸🇪"), 4);
+        // A lone regional indicator is a single cluster.
+        assert_eq!(display_width("🇩🇰🇸"), 2 + display_width("🇸"));
+    }
+
+    #[test]
+    fn display_width_zwj_sequences() {
+        assert_eq!(display_width("👨\u{200d}👩\u{200d}👧"), 2);
+        assert_eq!(display_width("👨\u{200d}👩\u{200d}👧\u{200d}👦"), 2);
+        assert_eq!(display_width("👍🏽"), 2);
     }
 
     #[test]
@@ -744,6 +2402,65 @@ mod tests {
         assert_eq!(display_width("😂😭🥺🤣✨😍🙏🥰😊🔥"), 20);
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1000))]
+
+        #[test]
+        fn ascii_fast_path_agrees_with_general_path(
+            text in prop::collection::vec(
+                prop_oneof![
+                    2 => Just(' '),
+                    1 => prop::sample::select(&['-', '.', '~', '!'][..]),
+                    7 => prop::char::range(' ', '~'),
+                ],
+                1..41,
+            )
+            .prop_map(|chars| chars.into_iter().collect::<String>()),
+        ) {
+            prop_assert!(is_printable_ascii(&text));
+            let slow = grapheme_clusters(&text, ch_width)
+                .map(|(_, width)| width)
+                .sum::<usize>();
+            prop_assert_eq!(display_width(&text), slow, "{:?}", text);
+            prop_assert_eq!(
+                find_words(&text).collect::<Vec<_>>(),
+                find_words_separated_by(&text, |ch| ch == ' ' || ch == ZWSP).collect::<Vec<_>>(),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn ascii_fast_path_skips_control_characters() {
+        assert!(!is_printable_ascii("foo\tbar"));
+        assert!(!is_printable_ascii("\u{1b}[31mred"));
+        assert_eq!(display_width("\u{1b}[31mred"), 3);
+        assert_eq!(
+            find_words("\u{1b}]0;a title\u{7}foo bar").collect::<Vec<_>>(),
+            vec![Word::from("\u{1b}]0;a title\u{7}foo "), Word::from("bar")]
+        );
+    }
+
+    #[test]
+    fn word_range_in_line() {
+        let line = "foo  bar\u{200b}baz ";
+        let words = find_words(line).collect::<Vec<_>>();
+        let ranges = words
+            .iter()
+            .map(|word| word.range_in(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![0..3, 5..8, 11..14]);
+        for (word, range) in words.iter().zip(ranges) {
+            assert_eq!(
+                &line[range.end..range.end + word.whitespace().len()],
+                word.whitespace()
+            );
+        }
+        assert_eq!(words[0].range_in(&line[1..]), None);
+        assert_eq!(words[1].range_in(&line[..6]), None);
+    }
+
     #[test]
     fn find_words_empty() {
         assert_iter_eq!(find_words(""), vec![]);
@@ -780,7 +2497,7 @@ mod tests {
         assert_iter_eq!(
             find_words("foo   bar"),
             vec![Word::from("foo   "), Word::from("bar")]
-        )
+        );
     }
 
     #[test]
@@ -823,13 +2540,13 @@ mod tests {
 
     #[test]
     fn split_words_no_words() {
-        assert_iter_eq!(split_words(vec![], 80), vec![]);
+        assert_iter_eq!(split_words(vec![], &Options::new(80)), vec![]);
     }
 
     #[test]
     fn split_words_empty_word() {
         assert_iter_eq!(
-            split_words(vec![Word::from("   ")], 80),
+            split_words(vec![Word::from("   ")], &Options::new(80)),
             vec![Word::from("   ")]
         );
     }
@@ -837,7 +2554,7 @@ mod tests {
     #[test]
     fn split_words_hyphen_splitter() {
         assert_iter_eq!(
-            split_words(vec![Word::from("foo-bar")], 80),
+            split_words(vec![Word::from("foo-bar")], &Options::new(80)),
             vec![Word::from("foo-"), Word::from("bar")]
         );
     }
@@ -847,7 +2564,7 @@ mod tests {
         // Note that `split_words` does not take the line width into
         // account, that is the job of `break_words`.
         assert_iter_eq!(
-            split_words(vec![Word::from("foobar")], 3),
+            split_words(vec![Word::from("foobar")], &Options::new(3)),
             vec![Word::from("foobar")]
         );
     }
@@ -870,13 +2587,21 @@ mod tests {
                     word: "foo",
                     width: 3,
                     whitespace: "",
-                    penalty: "-"
+                    whitespace_width: 0,
+                    penalty: "-",
+                    before_break: "",
+                    after_break: "",
+                    marker_width: 0
                 },
                 Word {
                     word: "bar",
                     width: 3,
                     whitespace: "",
-                    penalty: ""
+                    whitespace_width: 0,
+                    penalty: "",
+                    before_break: "",
+                    after_break: "",
+                    marker_width: 0
                 }
             ]
         );
@@ -888,15 +2613,129 @@ mod tests {
                     word: "fo-",
                     width: 3,
                     whitespace: "",
-                    penalty: ""
+                    whitespace_width: 0,
+                    penalty: "",
+                    before_break: "",
+                    after_break: "",
+                    marker_width: 0
                 },
                 Word {
                     word: "bar",
                     width: 3,
                     whitespace: "",
-                    penalty: ""
+                    whitespace_width: 0,
+                    penalty: "",
+                    before_break: "",
+                    after_break: "",
+                    marker_width: 0
                 }
             ]
         );
     }
+
+    #[test]
+    fn break_apart_keeps_combining_marks() {
+        let text = "e\u{301}e\u{301}e\u{301}";
+        let pieces = Word::from(text).break_apart(2).collect::<Vec<_>>();
+        assert!(pieces.iter().all(|piece| !piece.starts_with('\u{301}')));
+
+        #[cfg(feature = "unicode-width")]
+        assert_eq!(
+            pieces.iter().map(|piece| piece.word).collect::<Vec<_>>(),
+            vec!["e\u{301}e\u{301}", "e\u{301}"]
+        );
+    }
+
+    #[test]
+    fn break_apart_keeps_zwj_sequences() {
+        let family = "👨\u{200d}👩\u{200d}👧\u{200d}👦";
+        let text = family.repeat(3);
+        let pieces = Word::from(&text).break_apart(3).collect::<Vec<_>>();
+        assert_eq!(
+            pieces.iter().map(|piece| piece.word).collect::<Vec<_>>(),
+            vec![family, family, family]
+        );
+    }
+
+    #[test]
+    fn break_apart_keeps_flags_and_modifiers() {
+        let flags = "🇩🇰🇺🇸🇫🇷";
+        let pieces = Word::from(flags).break_apart(3).collect::<Vec<_>>();
+        assert_eq!(
+            pieces.iter().map(|piece| piece.word).collect::<Vec<_>>(),
+            vec!["🇩🇰", "🇺🇸", "🇫🇷"]
+        );
+
+        let thumbs = "👍🏽👍🏽";
+        let pieces = Word::from(thumbs).break_apart(3).collect::<Vec<_>>();
+        assert_eq!(
+            pieces.iter().map(|piece| piece.word).collect::<Vec<_>>(),
+            vec!["👍🏽", "👍🏽"]
+        );
+    }
+
+    #[test]
+    fn break_words_with_separator() {
+        // The word is broken where the separator finds words, not at
+        // every second column.
+        let words = break_words_with_marker(
+            vec![Word::from("世界。世界")],
+            |_| 5,
+            0,
+            WidthMeasure::UnicodeCells,
+            &WordSeparator::UnicodeBreakProperties,
+            BreakRules::none(),
+        );
+        assert_eq!(
+            words.iter().map(|word| word.word).collect::<Vec<_>>(),
+            vec!["世", "界。", "世", "界"]
+        );
+    }
+
+    #[test]
+    fn break_words_with_line_widths() {
+        // Each piece is as wide as its own line.
+        let words = break_words_with_marker(
+            vec![Word::from("abcdefghijkl")],
+            |piece| if piece == 0 { 2 } else { 5 },
+            1,
+            WidthMeasure::UnicodeCells,
+            &WordSeparator::AsciiSpace,
+            BreakRules::none(),
+        );
+        assert_eq!(
+            words.iter().map(|word| word.word).collect::<Vec<_>>(),
+            vec!["a", "bcde", "fghi", "jkl"]
+        );
+    }
+
+    #[test]
+    fn break_words_grapheme_clusters() {
+        let text = "ne\u{301}e\u{301}";
+        let words = break_words(vec![Word::from(text)], 1);
+        assert_eq!(
+            words.iter().map(|word| word.word).collect::<Vec<_>>(),
+            vec!["n", "e\u{301}", "e\u{301}"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-segmentation")]
+    fn break_words_hangul_and_indic_clusters() {
+        // Hangul syllables written with conjoining jamo.
+        let text = "\u{1112}\u{1161}\u{11ab}\u{1100}\u{1173}\u{11af}";
+        let words = break_words(vec![Word::from(text)], 1);
+        assert_eq!(
+            words.iter().map(|word| word.word).collect::<Vec<_>>(),
+            vec!["\u{1112}\u{1161}\u{11ab}", "\u{1100}\u{1173}\u{11af}"]
+        );
+
+        // Devanagari letters with vowel signs.
+        let text = "\u{915}\u{93f}\u{928}\u{940}";
+        let words = break_words(vec![Word::from(text)], 1);
+        assert_eq!(
+            words.iter().map(|word| word.word).collect::<Vec<_>>(),
+            vec!["\u{915}\u{93f}", "\u{928}\u{940}"]
+        );
+    }
 }
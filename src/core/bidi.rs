@@ -0,0 +1,460 @@
+//! Display order of bidirectional text.
+//!
+//! The embedding levels of the characters are resolved by the
+//! [unicode-bidi] crate, which implements the [UAX #9] algorithm,
+//! including explicit embeddings, overrides, and isolates. The crate
+//! leaves two steps to the caller: combining marks are kept after the
+//! character they belong to (rule L3), and characters such as
+//! brackets and `≤` are mirrored in right-to-left text (rule L4).
+//! These are done here. ANSI escape sequences do not influence the
+//! directions.
+//!
+//! **Note:** Only available when the `unicode-bidi` Cargo feature is
+//! enabled.
+//!
+//! [unicode-bidi]: https://docs.rs/unicode-bidi/
+//! [UAX #9]: https://www.unicode.org/reports/tr9/
+
+use super::skip_ansi_escape_sequence;
+use ::core::ops::Range;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use unicode_bidi::{bidi_class, BidiClass, Level, ParagraphBidiInfo};
+
+/// The direction of a paragraph of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Text which is read from left to right, such as English.
+    LeftToRight,
+    /// Text which is read from right to left, such as Hebrew or
+    /// Arabic.
+    RightToLeft,
+}
+
+impl Direction {
+    /// The embedding level of the direction.
+    fn level(self) -> Level {
+        match self {
+            Direction::LeftToRight => Level::ltr(),
+            Direction::RightToLeft => Level::rtl(),
+        }
+    }
+}
+
+/// Replace the ANSI escape sequences in `text` with escape
+/// characters, which are boundary neutrals and so do not influence
+/// the directions. The byte offsets of the text are unchanged.
+fn neutralize_escapes(text: &str) -> Cow<'_, str> {
+    let escapes = super::ansi_escape_sequences(text);
+    if escapes.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in escapes {
+        result.push_str(&text[last..range.start]);
+        result.extend(::core::iter::repeat_n('\x1b', range.len()));
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    Cow::Owned(result)
+}
+
+/// Find the base direction of the paragraph `text`. This is the
+/// direction of the first character with a strong direction, such
+/// as a letter. ANSI escape sequences are skipped. `None` is returned
+/// if there is no such character.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::{paragraph_direction, Direction};
+///
+/// assert_eq!(paragraph_direction("Hello"), Some(Direction::LeftToRight));
+/// assert_eq!(paragraph_direction("123 שלום world"), Some(Direction::RightToLeft));
+/// assert_eq!(paragraph_direction("123 !"), None);
+/// ```
+pub fn paragraph_direction(text: &str) -> Option<Direction> {
+    match unicode_bidi::get_base_direction(&*neutralize_escapes(text)) {
+        unicode_bidi::Direction::Ltr => Some(Direction::LeftToRight),
+        unicode_bidi::Direction::Rtl => Some(Direction::RightToLeft),
+        unicode_bidi::Direction::Mixed => None,
+    }
+}
+
+/// The characters which are mirrored in right-to-left text, with
+/// their mirrored form, sorted by the first character. These are the
+/// `Bidi_Mirroring_Glyph` mappings from [BidiMirroring.txt] of
+/// Unicode 17.0: each line `XXXX; YYYY` of the file gives one pair.
+///
+/// [BidiMirroring.txt]: https://www.unicode.org/Public/17.0.0/ucd/BidiMirroring.txt
+#[rustfmt::skip]
+const MIRRORED: &[(char, char)] = &[
+    ('(', ')'), (')', '('), ('<', '>'),
+    ('>', '<'), ('[', ']'), (']', '['),
+    ('{', '}'), ('}', '{'), ('\u{00ab}', '\u{00bb}'),
+    ('\u{00bb}', '\u{00ab}'), ('\u{0f3a}', '\u{0f3b}'), ('\u{0f3b}', '\u{0f3a}'),
+    ('\u{0f3c}', '\u{0f3d}'), ('\u{0f3d}', '\u{0f3c}'), ('\u{169b}', '\u{169c}'),
+    ('\u{169c}', '\u{169b}'), ('\u{2039}', '\u{203a}'), ('\u{203a}', '\u{2039}'),
+    ('\u{2045}', '\u{2046}'), ('\u{2046}', '\u{2045}'), ('\u{207d}', '\u{207e}'),
+    ('\u{207e}', '\u{207d}'), ('\u{208d}', '\u{208e}'), ('\u{208e}', '\u{208d}'),
+    ('\u{2208}', '\u{220b}'), ('\u{2209}', '\u{220c}'), ('\u{220a}', '\u{220d}'),
+    ('\u{220b}', '\u{2208}'), ('\u{220c}', '\u{2209}'), ('\u{220d}', '\u{220a}'),
+    ('\u{2215}', '\u{29f5}'), ('\u{221f}', '\u{2bfe}'), ('\u{2220}', '\u{29a3}'),
+    ('\u{2221}', '\u{299b}'), ('\u{2222}', '\u{29a0}'), ('\u{2224}', '\u{2aee}'),
+    ('\u{223c}', '\u{223d}'), ('\u{223d}', '\u{223c}'), ('\u{2243}', '\u{22cd}'),
+    ('\u{2245}', '\u{224c}'), ('\u{224c}', '\u{2245}'), ('\u{2252}', '\u{2253}'),
+    ('\u{2253}', '\u{2252}'), ('\u{2254}', '\u{2255}'), ('\u{2255}', '\u{2254}'),
+    ('\u{2264}', '\u{2265}'), ('\u{2265}', '\u{2264}'), ('\u{2266}', '\u{2267}'),
+    ('\u{2267}', '\u{2266}'), ('\u{2268}', '\u{2269}'), ('\u{2269}', '\u{2268}'),
+    ('\u{226a}', '\u{226b}'), ('\u{226b}', '\u{226a}'), ('\u{226e}', '\u{226f}'),
+    ('\u{226f}', '\u{226e}'), ('\u{2270}', '\u{2271}'), ('\u{2271}', '\u{2270}'),
+    ('\u{2272}', '\u{2273}'), ('\u{2273}', '\u{2272}'), ('\u{2274}', '\u{2275}'),
+    ('\u{2275}', '\u{2274}'), ('\u{2276}', '\u{2277}'), ('\u{2277}', '\u{2276}'),
+    ('\u{2278}', '\u{2279}'), ('\u{2279}', '\u{2278}'), ('\u{227a}', '\u{227b}'),
+    ('\u{227b}', '\u{227a}'), ('\u{227c}', '\u{227d}'), ('\u{227d}', '\u{227c}'),
+    ('\u{227e}', '\u{227f}'), ('\u{227f}', '\u{227e}'), ('\u{2280}', '\u{2281}'),
+    ('\u{2281}', '\u{2280}'), ('\u{2282}', '\u{2283}'), ('\u{2283}', '\u{2282}'),
+    ('\u{2284}', '\u{2285}'), ('\u{2285}', '\u{2284}'), ('\u{2286}', '\u{2287}'),
+    ('\u{2287}', '\u{2286}'), ('\u{2288}', '\u{2289}'), ('\u{2289}', '\u{2288}'),
+    ('\u{228a}', '\u{228b}'), ('\u{228b}', '\u{228a}'), ('\u{228f}', '\u{2290}'),
+    ('\u{2290}', '\u{228f}'), ('\u{2291}', '\u{2292}'), ('\u{2292}', '\u{2291}'),
+    ('\u{2298}', '\u{29b8}'), ('\u{22a2}', '\u{22a3}'), ('\u{22a3}', '\u{22a2}'),
+    ('\u{22a6}', '\u{2ade}'), ('\u{22a8}', '\u{2ae4}'), ('\u{22a9}', '\u{2ae3}'),
+    ('\u{22ab}', '\u{2ae5}'), ('\u{22b0}', '\u{22b1}'), ('\u{22b1}', '\u{22b0}'),
+    ('\u{22b2}', '\u{22b3}'), ('\u{22b3}', '\u{22b2}'), ('\u{22b4}', '\u{22b5}'),
+    ('\u{22b5}', '\u{22b4}'), ('\u{22b6}', '\u{22b7}'), ('\u{22b7}', '\u{22b6}'),
+    ('\u{22b8}', '\u{27dc}'), ('\u{22c9}', '\u{22ca}'), ('\u{22ca}', '\u{22c9}'),
+    ('\u{22cb}', '\u{22cc}'), ('\u{22cc}', '\u{22cb}'), ('\u{22cd}', '\u{2243}'),
+    ('\u{22d0}', '\u{22d1}'), ('\u{22d1}', '\u{22d0}'), ('\u{22d6}', '\u{22d7}'),
+    ('\u{22d7}', '\u{22d6}'), ('\u{22d8}', '\u{22d9}'), ('\u{22d9}', '\u{22d8}'),
+    ('\u{22da}', '\u{22db}'), ('\u{22db}', '\u{22da}'), ('\u{22dc}', '\u{22dd}'),
+    ('\u{22dd}', '\u{22dc}'), ('\u{22de}', '\u{22df}'), ('\u{22df}', '\u{22de}'),
+    ('\u{22e0}', '\u{22e1}'), ('\u{22e1}', '\u{22e0}'), ('\u{22e2}', '\u{22e3}'),
+    ('\u{22e3}', '\u{22e2}'), ('\u{22e4}', '\u{22e5}'), ('\u{22e5}', '\u{22e4}'),
+    ('\u{22e6}', '\u{22e7}'), ('\u{22e7}', '\u{22e6}'), ('\u{22e8}', '\u{22e9}'),
+    ('\u{22e9}', '\u{22e8}'), ('\u{22ea}', '\u{22eb}'), ('\u{22eb}', '\u{22ea}'),
+    ('\u{22ec}', '\u{22ed}'), ('\u{22ed}', '\u{22ec}'), ('\u{22f0}', '\u{22f1}'),
+    ('\u{22f1}', '\u{22f0}'), ('\u{22f2}', '\u{22fa}'), ('\u{22f3}', '\u{22fb}'),
+    ('\u{22f4}', '\u{22fc}'), ('\u{22f6}', '\u{22fd}'), ('\u{22f7}', '\u{22fe}'),
+    ('\u{22fa}', '\u{22f2}'), ('\u{22fb}', '\u{22f3}'), ('\u{22fc}', '\u{22f4}'),
+    ('\u{22fd}', '\u{22f6}'), ('\u{22fe}', '\u{22f7}'), ('\u{2308}', '\u{2309}'),
+    ('\u{2309}', '\u{2308}'), ('\u{230a}', '\u{230b}'), ('\u{230b}', '\u{230a}'),
+    ('\u{2329}', '\u{232a}'), ('\u{232a}', '\u{2329}'), ('\u{2768}', '\u{2769}'),
+    ('\u{2769}', '\u{2768}'), ('\u{276a}', '\u{276b}'), ('\u{276b}', '\u{276a}'),
+    ('\u{276c}', '\u{276d}'), ('\u{276d}', '\u{276c}'), ('\u{276e}', '\u{276f}'),
+    ('\u{276f}', '\u{276e}'), ('\u{2770}', '\u{2771}'), ('\u{2771}', '\u{2770}'),
+    ('\u{2772}', '\u{2773}'), ('\u{2773}', '\u{2772}'), ('\u{2774}', '\u{2775}'),
+    ('\u{2775}', '\u{2774}'), ('\u{27c3}', '\u{27c4}'), ('\u{27c4}', '\u{27c3}'),
+    ('\u{27c5}', '\u{27c6}'), ('\u{27c6}', '\u{27c5}'), ('\u{27c8}', '\u{27c9}'),
+    ('\u{27c9}', '\u{27c8}'), ('\u{27cb}', '\u{27cd}'), ('\u{27cd}', '\u{27cb}'),
+    ('\u{27d5}', '\u{27d6}'), ('\u{27d6}', '\u{27d5}'), ('\u{27dc}', '\u{22b8}'),
+    ('\u{27dd}', '\u{27de}'), ('\u{27de}', '\u{27dd}'), ('\u{27e2}', '\u{27e3}'),
+    ('\u{27e3}', '\u{27e2}'), ('\u{27e4}', '\u{27e5}'), ('\u{27e5}', '\u{27e4}'),
+    ('\u{27e6}', '\u{27e7}'), ('\u{27e7}', '\u{27e6}'), ('\u{27e8}', '\u{27e9}'),
+    ('\u{27e9}', '\u{27e8}'), ('\u{27ea}', '\u{27eb}'), ('\u{27eb}', '\u{27ea}'),
+    ('\u{27ec}', '\u{27ed}'), ('\u{27ed}', '\u{27ec}'), ('\u{27ee}', '\u{27ef}'),
+    ('\u{27ef}', '\u{27ee}'), ('\u{2983}', '\u{2984}'), ('\u{2984}', '\u{2983}'),
+    ('\u{2985}', '\u{2986}'), ('\u{2986}', '\u{2985}'), ('\u{2987}', '\u{2988}'),
+    ('\u{2988}', '\u{2987}'), ('\u{2989}', '\u{298a}'), ('\u{298a}', '\u{2989}'),
+    ('\u{298b}', '\u{298c}'), ('\u{298c}', '\u{298b}'), ('\u{298d}', '\u{2990}'),
+    ('\u{298e}', '\u{298f}'), ('\u{298f}', '\u{298e}'), ('\u{2990}', '\u{298d}'),
+    ('\u{2991}', '\u{2992}'), ('\u{2992}', '\u{2991}'), ('\u{2993}', '\u{2994}'),
+    ('\u{2994}', '\u{2993}'), ('\u{2995}', '\u{2996}'), ('\u{2996}', '\u{2995}'),
+    ('\u{2997}', '\u{2998}'), ('\u{2998}', '\u{2997}'), ('\u{299b}', '\u{2221}'),
+    ('\u{29a0}', '\u{2222}'), ('\u{29a3}', '\u{2220}'), ('\u{29a4}', '\u{29a5}'),
+    ('\u{29a5}', '\u{29a4}'), ('\u{29a8}', '\u{29a9}'), ('\u{29a9}', '\u{29a8}'),
+    ('\u{29aa}', '\u{29ab}'), ('\u{29ab}', '\u{29aa}'), ('\u{29ac}', '\u{29ad}'),
+    ('\u{29ad}', '\u{29ac}'), ('\u{29ae}', '\u{29af}'), ('\u{29af}', '\u{29ae}'),
+    ('\u{29b8}', '\u{2298}'), ('\u{29c0}', '\u{29c1}'), ('\u{29c1}', '\u{29c0}'),
+    ('\u{29c4}', '\u{29c5}'), ('\u{29c5}', '\u{29c4}'), ('\u{29cf}', '\u{29d0}'),
+    ('\u{29d0}', '\u{29cf}'), ('\u{29d1}', '\u{29d2}'), ('\u{29d2}', '\u{29d1}'),
+    ('\u{29d4}', '\u{29d5}'), ('\u{29d5}', '\u{29d4}'), ('\u{29d8}', '\u{29d9}'),
+    ('\u{29d9}', '\u{29d8}'), ('\u{29da}', '\u{29db}'), ('\u{29db}', '\u{29da}'),
+    ('\u{29e8}', '\u{29e9}'), ('\u{29e9}', '\u{29e8}'), ('\u{29f5}', '\u{2215}'),
+    ('\u{29f8}', '\u{29f9}'), ('\u{29f9}', '\u{29f8}'), ('\u{29fc}', '\u{29fd}'),
+    ('\u{29fd}', '\u{29fc}'), ('\u{2a2b}', '\u{2a2c}'), ('\u{2a2c}', '\u{2a2b}'),
+    ('\u{2a2d}', '\u{2a2e}'), ('\u{2a2e}', '\u{2a2d}'), ('\u{2a34}', '\u{2a35}'),
+    ('\u{2a35}', '\u{2a34}'), ('\u{2a3c}', '\u{2a3d}'), ('\u{2a3d}', '\u{2a3c}'),
+    ('\u{2a64}', '\u{2a65}'), ('\u{2a65}', '\u{2a64}'), ('\u{2a79}', '\u{2a7a}'),
+    ('\u{2a7a}', '\u{2a79}'), ('\u{2a7b}', '\u{2a7c}'), ('\u{2a7c}', '\u{2a7b}'),
+    ('\u{2a7d}', '\u{2a7e}'), ('\u{2a7e}', '\u{2a7d}'), ('\u{2a7f}', '\u{2a80}'),
+    ('\u{2a80}', '\u{2a7f}'), ('\u{2a81}', '\u{2a82}'), ('\u{2a82}', '\u{2a81}'),
+    ('\u{2a83}', '\u{2a84}'), ('\u{2a84}', '\u{2a83}'), ('\u{2a85}', '\u{2a86}'),
+    ('\u{2a86}', '\u{2a85}'), ('\u{2a87}', '\u{2a88}'), ('\u{2a88}', '\u{2a87}'),
+    ('\u{2a89}', '\u{2a8a}'), ('\u{2a8a}', '\u{2a89}'), ('\u{2a8b}', '\u{2a8c}'),
+    ('\u{2a8c}', '\u{2a8b}'), ('\u{2a8d}', '\u{2a8e}'), ('\u{2a8e}', '\u{2a8d}'),
+    ('\u{2a8f}', '\u{2a90}'), ('\u{2a90}', '\u{2a8f}'), ('\u{2a91}', '\u{2a92}'),
+    ('\u{2a92}', '\u{2a91}'), ('\u{2a93}', '\u{2a94}'), ('\u{2a94}', '\u{2a93}'),
+    ('\u{2a95}', '\u{2a96}'), ('\u{2a96}', '\u{2a95}'), ('\u{2a97}', '\u{2a98}'),
+    ('\u{2a98}', '\u{2a97}'), ('\u{2a99}', '\u{2a9a}'), ('\u{2a9a}', '\u{2a99}'),
+    ('\u{2a9b}', '\u{2a9c}'), ('\u{2a9c}', '\u{2a9b}'), ('\u{2a9d}', '\u{2a9e}'),
+    ('\u{2a9e}', '\u{2a9d}'), ('\u{2a9f}', '\u{2aa0}'), ('\u{2aa0}', '\u{2a9f}'),
+    ('\u{2aa1}', '\u{2aa2}'), ('\u{2aa2}', '\u{2aa1}'), ('\u{2aa6}', '\u{2aa7}'),
+    ('\u{2aa7}', '\u{2aa6}'), ('\u{2aa8}', '\u{2aa9}'), ('\u{2aa9}', '\u{2aa8}'),
+    ('\u{2aaa}', '\u{2aab}'), ('\u{2aab}', '\u{2aaa}'), ('\u{2aac}', '\u{2aad}'),
+    ('\u{2aad}', '\u{2aac}'), ('\u{2aaf}', '\u{2ab0}'), ('\u{2ab0}', '\u{2aaf}'),
+    ('\u{2ab1}', '\u{2ab2}'), ('\u{2ab2}', '\u{2ab1}'), ('\u{2ab3}', '\u{2ab4}'),
+    ('\u{2ab4}', '\u{2ab3}'), ('\u{2ab5}', '\u{2ab6}'), ('\u{2ab6}', '\u{2ab5}'),
+    ('\u{2ab7}', '\u{2ab8}'), ('\u{2ab8}', '\u{2ab7}'), ('\u{2ab9}', '\u{2aba}'),
+    ('\u{2aba}', '\u{2ab9}'), ('\u{2abb}', '\u{2abc}'), ('\u{2abc}', '\u{2abb}'),
+    ('\u{2abd}', '\u{2abe}'), ('\u{2abe}', '\u{2abd}'), ('\u{2abf}', '\u{2ac0}'),
+    ('\u{2ac0}', '\u{2abf}'), ('\u{2ac1}', '\u{2ac2}'), ('\u{2ac2}', '\u{2ac1}'),
+    ('\u{2ac3}', '\u{2ac4}'), ('\u{2ac4}', '\u{2ac3}'), ('\u{2ac5}', '\u{2ac6}'),
+    ('\u{2ac6}', '\u{2ac5}'), ('\u{2ac7}', '\u{2ac8}'), ('\u{2ac8}', '\u{2ac7}'),
+    ('\u{2ac9}', '\u{2aca}'), ('\u{2aca}', '\u{2ac9}'), ('\u{2acb}', '\u{2acc}'),
+    ('\u{2acc}', '\u{2acb}'), ('\u{2acd}', '\u{2ace}'), ('\u{2ace}', '\u{2acd}'),
+    ('\u{2acf}', '\u{2ad0}'), ('\u{2ad0}', '\u{2acf}'), ('\u{2ad1}', '\u{2ad2}'),
+    ('\u{2ad2}', '\u{2ad1}'), ('\u{2ad3}', '\u{2ad4}'), ('\u{2ad4}', '\u{2ad3}'),
+    ('\u{2ad5}', '\u{2ad6}'), ('\u{2ad6}', '\u{2ad5}'), ('\u{2ade}', '\u{22a6}'),
+    ('\u{2ae3}', '\u{22a9}'), ('\u{2ae4}', '\u{22a8}'), ('\u{2ae5}', '\u{22ab}'),
+    ('\u{2aec}', '\u{2aed}'), ('\u{2aed}', '\u{2aec}'), ('\u{2aee}', '\u{2224}'),
+    ('\u{2af7}', '\u{2af8}'), ('\u{2af8}', '\u{2af7}'), ('\u{2af9}', '\u{2afa}'),
+    ('\u{2afa}', '\u{2af9}'), ('\u{2bfe}', '\u{221f}'), ('\u{2e02}', '\u{2e03}'),
+    ('\u{2e03}', '\u{2e02}'), ('\u{2e04}', '\u{2e05}'), ('\u{2e05}', '\u{2e04}'),
+    ('\u{2e09}', '\u{2e0a}'), ('\u{2e0a}', '\u{2e09}'), ('\u{2e0c}', '\u{2e0d}'),
+    ('\u{2e0d}', '\u{2e0c}'), ('\u{2e1c}', '\u{2e1d}'), ('\u{2e1d}', '\u{2e1c}'),
+    ('\u{2e20}', '\u{2e21}'), ('\u{2e21}', '\u{2e20}'), ('\u{2e22}', '\u{2e23}'),
+    ('\u{2e23}', '\u{2e22}'), ('\u{2e24}', '\u{2e25}'), ('\u{2e25}', '\u{2e24}'),
+    ('\u{2e26}', '\u{2e27}'), ('\u{2e27}', '\u{2e26}'), ('\u{2e28}', '\u{2e29}'),
+    ('\u{2e29}', '\u{2e28}'), ('\u{2e55}', '\u{2e56}'), ('\u{2e56}', '\u{2e55}'),
+    ('\u{2e57}', '\u{2e58}'), ('\u{2e58}', '\u{2e57}'), ('\u{2e59}', '\u{2e5a}'),
+    ('\u{2e5a}', '\u{2e59}'), ('\u{2e5b}', '\u{2e5c}'), ('\u{2e5c}', '\u{2e5b}'),
+    ('\u{3008}', '\u{3009}'), ('\u{3009}', '\u{3008}'), ('\u{300a}', '\u{300b}'),
+    ('\u{300b}', '\u{300a}'), ('\u{300c}', '\u{300d}'), ('\u{300d}', '\u{300c}'),
+    ('\u{300e}', '\u{300f}'), ('\u{300f}', '\u{300e}'), ('\u{3010}', '\u{3011}'),
+    ('\u{3011}', '\u{3010}'), ('\u{3014}', '\u{3015}'), ('\u{3015}', '\u{3014}'),
+    ('\u{3016}', '\u{3017}'), ('\u{3017}', '\u{3016}'), ('\u{3018}', '\u{3019}'),
+    ('\u{3019}', '\u{3018}'), ('\u{301a}', '\u{301b}'), ('\u{301b}', '\u{301a}'),
+    ('\u{fe59}', '\u{fe5a}'), ('\u{fe5a}', '\u{fe59}'), ('\u{fe5b}', '\u{fe5c}'),
+    ('\u{fe5c}', '\u{fe5b}'), ('\u{fe5d}', '\u{fe5e}'), ('\u{fe5e}', '\u{fe5d}'),
+    ('\u{fe64}', '\u{fe65}'), ('\u{fe65}', '\u{fe64}'), ('\u{ff08}', '\u{ff09}'),
+    ('\u{ff09}', '\u{ff08}'), ('\u{ff1c}', '\u{ff1e}'), ('\u{ff1e}', '\u{ff1c}'),
+    ('\u{ff3b}', '\u{ff3d}'), ('\u{ff3d}', '\u{ff3b}'), ('\u{ff5b}', '\u{ff5d}'),
+    ('\u{ff5d}', '\u{ff5b}'), ('\u{ff5f}', '\u{ff60}'), ('\u{ff60}', '\u{ff5f}'),
+    ('\u{ff62}', '\u{ff63}'), ('\u{ff63}', '\u{ff62}'),
+];
+
+/// The mirrored form of `ch` in right-to-left text.
+fn mirrored(ch: char) -> Option<char> {
+    MIRRORED
+        .binary_search_by_key(&ch, |&(from, _)| from)
+        .ok()
+        .map(|idx| MIRRORED[idx].1)
+}
+
+/// Reorder a single line of text from logical order to display
+/// order. The `direction` is the base direction of the paragraph the
+/// line belongs to, see [`paragraph_direction`].
+///
+/// Right-to-left runs are reversed and the characters in them which
+/// have a mirrored form, such as brackets, are mirrored.
+/// Combining marks stay after the character they belong to. ANSI
+/// escape sequences at the start and at the end of the line stay
+/// there, so a style which covers the whole line is kept. Other
+/// escape sequences are moved as a unit, which means that they may
+/// apply to different characters after reordering.
+///
+/// The line is borrowed when no reordering is necessary.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::{reorder_line, Direction};
+///
+/// assert_eq!(reorder_line("Hello", Direction::LeftToRight), "Hello");
+/// assert_eq!(reorder_line("שלום world", Direction::RightToLeft), "world םולש");
+/// assert_eq!(reorder_line("say שלום (hi)", Direction::LeftToRight), "say םולש (hi)");
+/// ```
+pub fn reorder_line(line: &str, direction: Direction) -> Cow<'_, str> {
+    let escapes = super::ansi_escape_sequences(line);
+    let mut start = 0;
+    for range in &escapes {
+        if range.start != start {
+            break;
+        }
+        start = range.end;
+    }
+    let mut end = line.len();
+    for range in escapes.iter().rev() {
+        if range.end != end || range.start < start {
+            break;
+        }
+        end = range.start;
+    }
+
+    match reorder_units(&line[start..end], direction) {
+        Cow::Borrowed(_) => Cow::Borrowed(line),
+        Cow::Owned(body) => {
+            let mut result = String::with_capacity(line.len());
+            result.push_str(&line[..start]);
+            result.push_str(&body);
+            result.push_str(&line[end..]);
+            Cow::Owned(result)
+        }
+    }
+}
+
+/// Reorder `line` like [`reorder_line`], without treating the escape
+/// sequences at the ends of the line specially.
+fn reorder_units(line: &str, direction: Direction) -> Cow<'_, str> {
+    if line.is_empty() {
+        return Cow::Borrowed(line);
+    }
+    let neutral = neutralize_escapes(line);
+    let info = ParagraphBidiInfo::new(&neutral, Some(direction.level()));
+    // `has_rtl` only looks at the character classes, so it misses
+    // neutrals resolved to the right-to-left paragraph level.
+    let (levels, runs) = info.visual_runs(0..line.len());
+    if levels.iter().all(|level| level.is_ltr()) {
+        return Cow::Borrowed(line);
+    }
+
+    let mut result = String::with_capacity(line.len());
+    for run in runs {
+        if levels[run.start].is_ltr() {
+            result.push_str(&line[run]);
+            continue;
+        }
+        // Reverse the run by units: a character with its combining
+        // marks (L3), or an escape sequence.
+        let mut units: Vec<Range<usize>> = Vec::new();
+        let mut idx = run.start;
+        while let Some(ch) = line[idx..run.end].chars().next() {
+            let mut chars = line[idx + ch.len_utf8()..run.end].chars();
+            let escape = skip_ansi_escape_sequence(ch, &mut chars);
+            let end = run.end - chars.as_str().len();
+            match units.last_mut() {
+                Some(unit) if !escape && bidi_class(ch) == BidiClass::NSM => unit.end = end,
+                _ => units.push(idx..end),
+            }
+            idx = end;
+        }
+        for unit in units.into_iter().rev() {
+            push_unit(&mut result, &line[unit]);
+        }
+    }
+    if result == line {
+        return Cow::Borrowed(line);
+    }
+    Cow::Owned(result)
+}
+
+/// Add a `unit` of a right-to-left run to `result`, mirroring it if
+/// it is a bracket (L4).
+fn push_unit(result: &mut String, unit: &str) {
+    let mut chars = unit.chars();
+    match chars.next().and_then(mirrored) {
+        Some(ch) => {
+            result.push(ch);
+            result.push_str(chars.as_str());
+        }
+        None => result.push_str(unit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::*;
+
+    #[test]
+    fn paragraph_direction_skips_neutrals() {
+        assert_eq!(paragraph_direction(""), None);
+        assert_eq!(paragraph_direction("(1) foo"), Some(LeftToRight));
+        assert_eq!(paragraph_direction("(1) שלום"), Some(RightToLeft));
+        assert_eq!(paragraph_direction("\u{1b}[31mمرحبا"), Some(RightToLeft));
+        assert_eq!(paragraph_direction("\u{200f}foo"), Some(RightToLeft));
+    }
+
+    #[test]
+    fn left_to_right_is_borrowed() {
+        assert!(matches!(
+            reorder_line("foo (bar) 123", LeftToRight),
+            Cow::Borrowed("foo (bar) 123")
+        ));
+    }
+
+    #[test]
+    fn hebrew_in_english() {
+        assert_eq!(
+            reorder_line("The word שלום means peace", LeftToRight),
+            "The word םולש means peace"
+        );
+    }
+
+    #[test]
+    fn english_in_hebrew() {
+        assert_eq!(reorder_line("שלום world", RightToLeft), "world םולש");
+        assert_eq!(
+            reorder_line("אני אוהב Rust מאוד", RightToLeft),
+            "דואמ Rust בהוא ינא"
+        );
+    }
+
+    #[test]
+    fn numbers_keep_their_order() {
+        assert_eq!(reorder_line("מחיר 100 שקל", RightToLeft), "לקש 100 ריחמ");
+        assert_eq!(reorder_line("סך 1,000", RightToLeft), "1,000 ךס");
+        assert_eq!(reorder_line("عدد ١٢٣", RightToLeft), "١٢٣ ددع");
+    }
+
+    #[test]
+    fn brackets_are_mirrored() {
+        assert_eq!(reorder_line("(שלום)", RightToLeft), "(םולש)");
+        assert_eq!(reorder_line("「שלום」", RightToLeft), "「םולש」");
+    }
+
+    #[test]
+    fn mirrored_table() {
+        // The table is sorted for the binary search, and every pair
+        // is mirrored both ways.
+        for pair in MIRRORED.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{:?}", pair);
+        }
+        for &(from, to) in MIRRORED {
+            assert_eq!(mirrored(from), Some(to));
+            assert_eq!(mirrored(to), Some(from), "{:?}", from);
+        }
+        assert_eq!(mirrored('\u{2264}'), Some('\u{2265}'));
+        assert_eq!(mirrored('\u{2208}'), Some('\u{220b}'));
+        assert_eq!(mirrored('\u{ff08}'), Some('\u{ff09}'));
+        assert_eq!(mirrored('\u{1d6db}'), None);
+        assert_eq!(mirrored('a'), None);
+    }
+
+    #[test]
+    fn math_symbols_are_mirrored() {
+        assert_eq!(reorder_line("א ≤ ב", RightToLeft), "ב ≥ א");
+    }
+
+    #[test]
+    fn combining_marks_stay_attached() {
+        assert_eq!(reorder_line("ש\u{5b8}ל", RightToLeft), "לש\u{5b8}");
+    }
+
+    #[test]
+    fn trailing_whitespace_in_base_direction() {
+        assert_eq!(reorder_line("foo שלום ", LeftToRight), "foo םולש ");
+        assert_eq!(reorder_line("שלום ", RightToLeft), " םולש");
+    }
+
+    #[test]
+    fn escape_sequences_at_the_ends_stay() {
+        assert_eq!(
+            reorder_line("\u{1b}[1mשלום\u{1b}[0m", RightToLeft),
+            "\u{1b}[1mםולש\u{1b}[0m"
+        );
+        assert_eq!(
+            reorder_line("foo \u{1b}[1mשלום\u{1b}[0m", LeftToRight),
+            "foo \u{1b}[1mםולש\u{1b}[0m"
+        );
+    }
+
+    #[test]
+    fn escape_sequences_are_units() {
+        assert_eq!(reorder_line("של\u{1b}[1mום", RightToLeft), "םו\u{1b}[1mלש");
+    }
+}
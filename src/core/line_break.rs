@@ -0,0 +1,376 @@
+//! Line break opportunities from the Unicode line breaking algorithm.
+//!
+//! This is a compact implementation of [UAX #14]. The line breaking
+//! classes are approximated with a table which covers the characters
+//! found in typical Latin, Greek, Cyrillic, and CJK text, and the
+//! pair rules are implemented for the resolved classes. Complex
+//! context dependent scripts (class `SA`) are treated as letters,
+//! which means that they are only broken at spaces.
+//!
+//! [UAX #14]: https://www.unicode.org/reports/tr14/
+
+use super::{is_regional_indicator, skip_ansi_escape_sequence};
+use alloc::vec::Vec;
+
+/// Line breaking classes from [UAX #14]. Some classes are merged
+/// with the class they resolve to in the absence of tailoring.
+///
+/// [UAX #14]: https://www.unicode.org/reports/tr14/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+enum Class {
+    /// Mandatory break.
+    BK,
+    /// Carriage return.
+    CR,
+    /// Line feed.
+    LF,
+    /// Next line.
+    NL,
+    /// Space.
+    SP,
+    /// Zero width space.
+    ZW,
+    /// Word joiner.
+    WJ,
+    /// Non-breaking ("glue").
+    GL,
+    /// Combining mark, also used for control characters.
+    CM,
+    /// Zero width joiner.
+    ZWJ,
+    /// Break opportunity after.
+    BA,
+    /// Break opportunity before.
+    BB,
+    /// Break opportunity before and after.
+    B2,
+    /// Hyphen.
+    HY,
+    /// Close punctuation.
+    CL,
+    /// Close parenthesis.
+    CP,
+    /// Open punctuation.
+    OP,
+    /// Quotation.
+    QU,
+    /// Exclamation and interrogation.
+    EX,
+    /// Infix numeric separator.
+    IS,
+    /// Symbols allowing break after.
+    SY,
+    /// Nonstarter.
+    NS,
+    /// Inseparable.
+    IN,
+    /// Prefix numeric.
+    PR,
+    /// Postfix numeric.
+    PO,
+    /// Numeric.
+    NU,
+    /// Ideographic.
+    ID,
+    /// Emoji modifier.
+    EM,
+    /// Regional indicator.
+    RI,
+    /// Alphabetic, also used for Hebrew letters and unknown
+    /// characters.
+    AL,
+}
+
+use Class::*;
+
+/// Find the line breaking class of `ch`.
+#[rustfmt::skip]
+fn class(ch: char) -> Class {
+    match ch {
+        '\n' => LF,
+        '\r' => CR,
+        '\u{0b}' | '\u{0c}' | '\u{2028}' | '\u{2029}' => BK,
+        '\u{85}' => NL,
+        ' ' => SP,
+        '\t' => BA,
+        '\u{200b}' => ZW,
+        super::ZWJ => ZWJ,
+        '\u{2060}' | '\u{feff}' => WJ,
+        '\u{a0}' | '\u{202f}' | '\u{2007}' | '\u{2011}' | '\u{034f}' | '\u{180e}' => GL,
+        '\u{00}'..='\u{1f}' | '\u{7f}'..='\u{9f}' => CM,
+        '\u{0300}'..='\u{036f}' | '\u{0483}'..='\u{0489}' | '\u{0591}'..='\u{05bd}'
+        | '\u{064b}'..='\u{065f}' | '\u{0900}'..='\u{0903}' | '\u{093a}'..='\u{094f}'
+        | '\u{1ab0}'..='\u{1aff}' | '\u{1dc0}'..='\u{1dff}' | '\u{200c}'
+        | '\u{20d0}'..='\u{20ff}' | '\u{3099}' | '\u{309a}' | '\u{fe00}'..='\u{fe0f}'
+        | '\u{fe20}'..='\u{fe2f}' | '\u{e0020}'..='\u{e007f}'
+        | '\u{e0100}'..='\u{e01ef}' => CM,
+        '\u{1f3fb}'..='\u{1f3ff}' => EM,
+        '|' | '\u{ad}' | '\u{058a}' | '\u{0964}' | '\u{0965}' | '\u{1680}'
+        | '\u{2000}'..='\u{2006}' | '\u{2008}'..='\u{200a}' | '\u{2010}' | '\u{2012}'
+        | '\u{2013}' | '\u{2027}' | '\u{205f}' | '\u{3000}' => BA,
+        '\u{b4}' | '\u{02c8}' | '\u{02cc}' | '\u{02df}' | '\u{1ffd}' => BB,
+        '\u{2014}' | '\u{2e3a}' | '\u{2e3b}' => B2,
+        '-' => HY,
+        '}' | '\u{3001}' | '\u{3002}' | '\u{3009}' | '\u{300b}' | '\u{300d}'
+        | '\u{300f}' | '\u{3011}' | '\u{3015}' | '\u{3017}' | '\u{3019}' | '\u{301b}'
+        | '\u{301e}' | '\u{301f}' | '\u{fe11}' | '\u{fe12}' | '\u{ff09}' | '\u{ff0c}'
+        | '\u{ff0e}' | '\u{ff3d}' | '\u{ff5d}' | '\u{ff60}' | '\u{ff61}'
+        | '\u{ff63}' | '\u{ff64}' => CL,
+        ')' | ']' => CP,
+        '(' | '[' | '{' | '\u{a1}' | '\u{bf}' | '\u{201a}' | '\u{201e}' | '\u{3008}'
+        | '\u{300a}' | '\u{300c}' | '\u{300e}' | '\u{3010}' | '\u{3014}' | '\u{3016}'
+        | '\u{3018}' | '\u{301a}' | '\u{301d}' | '\u{ff08}' | '\u{ff3b}' | '\u{ff5b}'
+        | '\u{ff5f}' | '\u{ff62}' => OP,
+        '"' | '\'' | '\u{ab}' | '\u{bb}' | '\u{2018}' | '\u{2019}' | '\u{201b}'
+        | '\u{201c}' | '\u{201d}' | '\u{201f}' | '\u{2039}' | '\u{203a}' | '\u{275b}'..='\u{275e}'
+        | '\u{2e00}'..='\u{2e0d}' => QU,
+        '!' | '?' | '\u{05c6}' | '\u{061f}' | '\u{06d4}' | '\u{ff01}' | '\u{ff1f}' => EX,
+        ',' | '.' | ':' | ';' | '\u{037e}' | '\u{0589}' | '\u{060c}' | '\u{060d}'
+        | '\u{2044}' | '\u{fe10}' | '\u{fe13}' | '\u{fe14}' => IS,
+        '/' => SY,
+        '\u{17d6}' | '\u{203c}' | '\u{203d}' | '\u{2047}'..='\u{2049}' | '\u{3005}'
+        | '\u{301c}' | '\u{303b}' | '\u{303c}' | '\u{309b}'..='\u{309e}' | '\u{30a0}'
+        | '\u{30fb}' | '\u{30fd}' | '\u{30fe}' | '\u{a015}' | '\u{fe54}' | '\u{fe55}'
+        | '\u{ff1a}' | '\u{ff1b}' | '\u{ff65}' | '\u{ff9e}' | '\u{ff9f}' => NS,
+        '\u{2024}'..='\u{2026}' | '\u{22ef}' | '\u{fe19}' => IN,
+        '$' | '+' | '\\' | '\u{a3}' | '\u{a4}' | '\u{a5}' | '\u{b1}' | '\u{20a0}'..='\u{20a6}'
+        | '\u{20a8}'..='\u{20b5}' | '\u{20b7}'..='\u{20ba}' | '\u{20bc}'..='\u{20bd}'
+        | '\u{20bf}'..='\u{20cf}' | '\u{2116}' | '\u{2212}' | '\u{2213}' | '\u{ff04}'
+        | '\u{ffe1}' | '\u{ffe5}' | '\u{ffe6}' => PR,
+        '%' | '\u{a2}' | '\u{b0}' | '\u{0609}'..='\u{060b}' | '\u{2030}'..='\u{2037}'
+        | '\u{20a7}' | '\u{20b6}' | '\u{20bb}' | '\u{20be}' | '\u{2103}' | '\u{2109}'
+        | '\u{fe6a}' | '\u{ff05}' | '\u{ffe0}' => PO,
+        '0'..='9' | '\u{0660}'..='\u{0669}' | '\u{06f0}'..='\u{06f9}'
+        | '\u{0966}'..='\u{096f}' => NU,
+        ch if is_regional_indicator(ch) => RI,
+        '\u{1100}'..='\u{115f}' | '\u{231a}' | '\u{231b}' | '\u{2600}'..='\u{2767}'
+        | '\u{2e80}'..='\u{2fff}' | '\u{3003}'..='\u{303f}' | '\u{3040}'..='\u{30ff}'
+        | '\u{3100}'..='\u{31ff}' | '\u{3200}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}'
+        | '\u{a000}'..='\u{a4cf}' | '\u{ac00}'..='\u{d7a3}' | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}' | '\u{ff00}'..='\u{ff5e}' | '\u{ff66}'..='\u{ff9d}'
+        | '\u{ffe2}'..='\u{ffe4}' | '\u{1b000}'..='\u{1b16f}' | '\u{1f000}'..='\u{1faff}'
+        | '\u{20000}'..='\u{3fffd}' => ID,
+        _ => AL,
+    }
+}
+
+/// Can the line be broken between a character of class `before` and
+/// a character of class `after`? The `lead` is the class before any
+/// spaces preceding `after`, it is equal to `before` when there are
+/// no spaces. The `regional_indicators` counts the consecutive
+/// regional indicators before `after`.
+fn is_break(before: Class, lead: Class, after: Class, regional_indicators: usize) -> bool {
+    match (before, after) {
+        // LB4 and LB5: always break after hard line breaks.
+        (CR, LF) => false,
+        (BK, _) | (CR, _) | (LF, _) | (NL, _) => true,
+        // LB6: do not break before hard line breaks.
+        (_, BK) | (_, CR) | (_, LF) | (_, NL) => false,
+        // LB7: do not break before spaces or zero width space.
+        (_, SP) | (_, ZW) => false,
+        // LB8: break after zero width space, even with spaces.
+        _ if lead == ZW => true,
+        // LB11: do not break around word joiners.
+        (WJ, _) | (_, WJ) => false,
+        // LB12 and LB12a: do not break around glue.
+        (GL, _) => false,
+        (SP, GL) | (BA, GL) | (HY, GL) => true,
+        (_, GL) => false,
+        // LB13: do not break before closing punctuation.
+        (_, CL) | (_, CP) | (_, EX) | (_, IS) | (_, SY) => false,
+        // LB14 to LB17: these hold even with intervening spaces.
+        _ if lead == OP => false,
+        (_, OP) if lead == QU => false,
+        (_, NS) if lead == CL || lead == CP => false,
+        (_, B2) if lead == B2 => false,
+        // LB18: break after spaces.
+        (SP, _) => true,
+        // LB19: do not break around quotation marks.
+        (QU, _) | (_, QU) => false,
+        // LB21: do not break before hyphens and small kana.
+        (_, BA) | (_, HY) | (_, NS) | (BB, _) => false,
+        // LB22: do not break before ellipses.
+        (_, IN) => false,
+        // LB23 and LB23a: letters, numbers and ideographs.
+        (AL, NU) | (NU, AL) => false,
+        (PR, ID) | (PR, EM) | (ID, PO) | (EM, PO) => false,
+        // LB24: prefixes and postfixes next to letters.
+        (PR, AL) | (PO, AL) | (AL, PR) | (AL, PO) => false,
+        // LB25: numbers.
+        (CL, PO) | (CP, PO) | (CL, PR) | (CP, PR) | (NU, PO) | (NU, PR) => false,
+        (PO, OP) | (PO, NU) | (PR, OP) | (PR, NU) => false,
+        (HY, NU) | (IS, NU) | (NU, NU) | (SY, NU) => false,
+        // LB28 and LB29: letters.
+        (AL, AL) | (IS, AL) => false,
+        // LB30: letters and numbers next to parentheses.
+        (AL, OP) | (NU, OP) | (CP, AL) | (CP, NU) => false,
+        // LB30a: keep flags together.
+        (RI, RI) if regional_indicators % 2 == 1 => false,
+        // LB30b: emoji modifiers.
+        (ID, EM) => false,
+        // LB31: break everywhere else.
+        _ => true,
+    }
+}
+
+/// Find the line break opportunities in `line`. The byte indices of
+/// the opportunities are returned in increasing order. Index zero and
+/// the end of `line` are never returned.
+pub(crate) fn break_opportunities(line: &str) -> Vec<usize> {
+    let mut opportunities = Vec::new();
+    let mut char_indices = line.char_indices();
+    // The class of the previous character after applying LB9 and
+    // LB10, the class before any spaces, and whether the previous
+    // character was a zero width joiner (LB8a).
+    let mut before: Option<Class> = None;
+    let mut lead = AL;
+    let mut after_zwj = false;
+    let mut regional_indicators = 0;
+
+    while let Some((idx, ch)) = char_indices.next() {
+        if skip_ansi_escape_sequence(ch, &mut char_indices.by_ref().map(|(_, ch)| ch)) {
+            // Escape sequences attach to the previous character.
+            continue;
+        }
+
+        let after = class(ch);
+        let prev = match before {
+            None => {
+                // LB2: never break at the start of text. LB10: lone
+                // combining marks are treated as letters.
+                before = Some(if after == CM || after == ZWJ {
+                    AL
+                } else {
+                    after
+                });
+                lead = before.unwrap();
+                after_zwj = ch == super::ZWJ;
+                regional_indicators = usize::from(after == RI);
+                continue;
+            }
+            Some(prev) => prev,
+        };
+
+        // LB9: combining marks and joiners attach to the previous
+        // character, except after spaces and hard line breaks.
+        if (after == CM || after == ZWJ) && !matches!(prev, BK | CR | LF | NL | SP | ZW) {
+            after_zwj = ch == super::ZWJ;
+            continue;
+        }
+        let after = if after == CM || after == ZWJ {
+            AL
+        } else {
+            after
+        };
+
+        // LB8a: do not break after a zero width joiner.
+        if !after_zwj && is_break(prev, lead, after, regional_indicators) {
+            opportunities.push(idx);
+        }
+
+        regional_indicators = if after == RI {
+            regional_indicators + 1
+        } else {
+            0
+        };
+        if after != SP {
+            lead = after;
+        }
+        before = Some(after);
+        after_zwj = ch == super::ZWJ;
+    }
+
+    opportunities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(line: &str) -> Vec<&str> {
+        let mut start = 0;
+        let mut pieces = Vec::new();
+        for idx in break_opportunities(line) {
+            pieces.push(&line[start..idx]);
+            start = idx;
+        }
+        pieces.push(&line[start..]);
+        pieces
+    }
+
+    #[test]
+    fn break_after_spaces() {
+        assert_eq!(pieces("foo bar  baz"), vec!["foo ", "bar  ", "baz"]);
+    }
+
+    #[test]
+    fn break_between_ideographs() {
+        assert_eq!(pieces("你好世界"), vec!["你", "好", "世", "界"]);
+    }
+
+    #[test]
+    fn no_break_before_closing_punctuation() {
+        assert_eq!(pieces("你好。世界！"), vec!["你", "好。", "世", "界！"]);
+        assert_eq!(pieces("(foo) bar."), vec!["(foo) ", "bar."]);
+        assert_eq!(pieces("「你好」"), vec!["「你", "好」"]);
+    }
+
+    #[test]
+    fn break_between_kana() {
+        assert_eq!(pieces("ひらがな"), vec!["ひ", "ら", "が", "な"]);
+        assert_eq!(pieces("ｶﾀｶﾅ"), vec!["ｶ", "ﾀ", "ｶ", "ﾅ"]);
+    }
+
+    #[test]
+    fn ascii_words_in_cjk_text() {
+        assert_eq!(pieces("用Rust写"), vec!["用", "Rust", "写"]);
+        assert_eq!(pieces("版本1.0发布"), vec!["版", "本", "1.0", "发", "布"]);
+    }
+
+    #[test]
+    fn break_at_zero_width_space() {
+        assert_eq!(pieces("foo\u{200b}bar"), vec!["foo\u{200b}", "bar"]);
+    }
+
+    #[test]
+    fn break_after_hyphens_and_dashes() {
+        assert_eq!(pieces("foo-bar"), vec!["foo-", "bar"]);
+        assert_eq!(pieces("foo—bar"), vec!["foo", "—", "bar"]);
+        assert_eq!(pieces("foo —bar"), vec!["foo ", "—", "bar"]);
+    }
+
+    #[test]
+    fn no_break_in_numbers() {
+        assert_eq!(pieces("$1,000.50 -5"), vec!["$1,000.50 ", "-5"]);
+        assert_eq!(pieces("50% off"), vec!["50% ", "off"]);
+    }
+
+    #[test]
+    fn no_break_at_glue() {
+        assert_eq!(pieces("10\u{a0}kB is"), vec!["10\u{a0}kB ", "is"]);
+    }
+
+    #[test]
+    fn mandatory_breaks() {
+        assert_eq!(pieces("foo\nbar"), vec!["foo\n", "bar"]);
+        assert_eq!(pieces("foo\r\nbar"), vec!["foo\r\n", "bar"]);
+        assert_eq!(pieces("foo\u{85}bar"), vec!["foo\u{85}", "bar"]);
+    }
+
+    #[test]
+    fn combining_marks_and_emoji() {
+        assert_eq!(pieces("你\u{301}好"), vec!["你\u{301}", "好"]);
+        assert_eq!(pieces("👍🏽👍"), vec!["👍🏽", "👍"]);
+        assert_eq!(pieces("👩\u{200d}💻!"), vec!["👩\u{200d}💻!"]);
+        assert_eq!(pieces("🇩🇰🇸🇪"), vec!["🇩🇰", "🇸🇪"]);
+    }
+
+    #[test]
+    fn ansi_escape_sequences() {
+        assert_eq!(
+            pieces("你\u{1b}[31m好\u{1b}[0m"),
+            vec!["你\u{1b}[31m", "好\u{1b}[0m"]
+        );
+    }
+}
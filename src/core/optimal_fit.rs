@@ -1,12 +1,18 @@
-use crate::core::Fragment;
-use std::cell::RefCell;
+#[cfg(feature = "smawk")]
+use crate::core::{wrap_first_fit, Fragment};
+#[cfg(feature = "smawk")]
+use ::core::cell::RefCell;
+#[cfg(feature = "smawk")]
+use alloc::vec::Vec;
 
 /// Cache for line numbers. This is necessary to avoid a O(n**2)
 /// behavior when computing line numbers in [`wrap_optimal_fit`].
+#[cfg(feature = "smawk")]
 struct LineNumbers {
     line_numbers: RefCell<Vec<usize>>,
 }
 
+#[cfg(feature = "smawk")]
 impl LineNumbers {
     fn new(size: usize) -> Self {
         let mut line_numbers = Vec::with_capacity(size);
@@ -19,7 +25,7 @@ impl LineNumbers {
     fn get<T>(&self, i: usize, minima: &[(usize, T)]) -> usize {
         while self.line_numbers.borrow_mut().len() < i + 1 {
             let pos = self.line_numbers.borrow().len();
-            let line_number = 1 + self.get(minima[pos].0, &minima);
+            let line_number = 1 + self.get(minima[pos].0, minima);
             self.line_numbers.borrow_mut().push(line_number);
         }
 
@@ -27,62 +33,156 @@ impl LineNumbers {
     }
 }
 
-/// Per-line penalty. This is added for every line, which makes it
-/// expensive to output more lines than the minimum required.
-const NLINE_PENALTY: i32 = 1000;
-
-/// Per-character cost for lines that overflow the target line width.
+/// Penalties for [`wrap_optimal_fit`].
 ///
-/// With a value of 50², every single character costs as much as
-/// leaving a gap of 50 characters behind. This is becuase we assign
-/// as cost of `gap * gap` to a short line. This means that we can
-/// overflow the line by 1 character in extreme cases:
+/// The optimal-fit algorithm assigns a cost to every possible line
+/// break and picks the breaks with the lowest total cost. The values
+/// here let you tune how the different kinds of costs are weighted
+/// against each other. Use [`Penalties::new`] (or
+/// [`Penalties::default`]) to get the default values.
+///
+/// # Examples
+///
+/// Raising the `split_penalty` makes lines which end inside a word
+/// less attractive. Here we use the
+/// [`HyphenSplitter`](crate::HyphenSplitter) to wrap a word which
+/// contains a hyphen:
 ///
 /// ```
-/// use textwrap::core::{wrap_optimal_fit, Word};
-///
-/// let short = "foo ";
-/// let long = "x".repeat(50);
-/// let fragments = vec![Word::from(short), Word::from(&long)];
-///
-/// // Perfect fit, both words are on a single line with no overflow.
-/// let wrapped = wrap_optimal_fit(&fragments, |_| short.len() + long.len());
-/// assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
-///
-/// // The words no longer fit, yet we get a single line back. While
-/// // the cost of overflow (`1 * 2500`) is the same as the cost of the
-/// // gap (`50 * 50 = 2500`), the tie is broken by `NLINE_PENALTY`
-/// // which makes it cheaper to overflow than to use two lines.
-/// let wrapped = wrap_optimal_fit(&fragments, |_| short.len() + long.len() - 1);
-/// assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
-///
-/// // The cost of overflow would be 2 * 2500, whereas the cost of the
-/// // gap is only `49 * 49 + NLINE_PENALTY = 2401 + 1000 = 3401`. We
-/// // therefore get two lines.
-/// let wrapped = wrap_optimal_fit(&fragments, |_| short.len() + long.len() - 2);
-/// assert_eq!(wrapped, vec![&[Word::from(short)],
-///                          &[Word::from(&long)]]);
-/// ```
+/// # #[cfg(feature = "smawk")] {
+/// use textwrap::core::Penalties;
+/// use textwrap::{wrap, Options};
+///
+/// let text = "a well-considered idea";
+/// let options = Options::new(15);
+/// assert_eq!(wrap(text, &options), vec!["a well-", "considered idea"]);
 ///
-/// This only happens if the overflowing word is 50 characters long
-/// _and_ if it happens to overflow the line by exactly one character.
-/// If it overflows by more than one character, the overflow penalty
-/// will quickly outgrow the cost of the gap, as seen above.
-const OVERFLOW_PENALTY: i32 = 50 * 50;
+/// let penalties = Penalties {
+///     split_penalty: 3000,
+///     ..Penalties::new()
+/// };
+/// let options = Options::new(15).penalties(penalties);
+/// assert_eq!(wrap(text, &options), vec!["a", "well-considered", "idea"]);
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Penalties {
+    /// Per-line penalty. This is added for every line, which makes it
+    /// expensive to output more lines than the minimum required.
+    pub nline_penalty: i32,
+
+    /// Per-character cost for lines that overflow the target line
+    /// width.
+    ///
+    /// With a default value of 50², every single character costs as
+    /// much as leaving a gap of 50 characters behind. This is because
+    /// we assign as cost of `gap * gap` to a short line. When wrapping
+    /// text, this can make it cheaper to overflow a line by 1
+    /// character than to start a new line in extreme cases. A line
+    /// which overflows is then wrapped again, since only a single
+    /// fragment may ever overflow a line:
+    ///
+    /// ```
+    /// #[cfg(feature = "smawk")]
+    /// {
+    ///     use textwrap::core::{wrap_optimal_fit, Penalties, Word};
+    ///
+    ///     let short = "foo ";
+    ///     let long = "x".repeat(50);
+    ///     let fragments = vec![Word::from(short), Word::from(&long)];
+    ///     let penalties = Penalties::new();
+    ///
+    ///     // Perfect fit, both words are on a single line with no overflow.
+    ///     let wrapped = wrap_optimal_fit(&fragments, |_| short.len() + long.len(), &penalties);
+    ///     assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
+    ///
+    ///     // The words no longer fit. While the cost of overflow (`1 *
+    ///     // 2500`) is the same as the cost of the gap (`50 * 50 =
+    ///     // 2500`), the tie is broken by `nline_penalty` which makes it
+    ///     // cheaper to overflow than to use two lines. The overflowing
+    ///     // line is wrapped again and we get two lines.
+    ///     let wrapped = wrap_optimal_fit(&fragments, |_| short.len() + long.len() - 1, &penalties);
+    ///     assert_eq!(wrapped, vec![&[Word::from(short)],
+    ///                              &[Word::from(&long)]]);
+    ///
+    ///     // A single fragment which is too long still overflows.
+    ///     let wrapped = wrap_optimal_fit(&fragments[1..], |_| long.len() - 1, &penalties);
+    ///     assert_eq!(wrapped, vec![&[Word::from(&long)]]);
+    /// }
+    /// ```
+    ///
+    /// The penalty still matters when a line ends with a long word:
+    /// overflowing by more than one character quickly outgrows the
+    /// cost of a gap, which moves the long word to a line of its own.
+    pub overflow_penalty: i32,
+
+    /// When should a single word on the last line be considered
+    /// "too short"?
+    ///
+    /// If the last line of the text consist of a single word and if
+    /// this word is shorter than `1 / short_last_line_fraction` of
+    /// the line width, then the final line will be considered "short"
+    /// and `short_last_line_penalty` is added to the cost of the line
+    /// break.
+    pub short_last_line_fraction: usize,
+
+    /// Penalty for a last line with a single short word.
+    ///
+    /// Set this to zero if you do not want to penalize short last
+    /// lines.
+    pub short_last_line_penalty: i32,
+
+    /// Penalty for lines ending with a hyphen inserted by the
+    /// [`WordSplitter`](crate::WordSplitter).
+    pub hyphen_penalty: i32,
+
+    /// Penalty for lines ending inside a word where no hyphen is
+    /// inserted, such as after a hyphen which is already in the text.
+    ///
+    /// This is zero by default, so that words like “well-known” are
+    /// broken as readily as at a space. Raise it to keep such words
+    /// together.
+    pub split_penalty: i32,
 
-/// The last line is short if it is less than 1/4 of the target width.
-const SHORT_LINE_FRACTION: usize = 4;
+    /// Penalty for lines ending with a one- or two-character word.
+    ///
+    /// Short words such as “a” or “to” look lonely at the end of a
+    /// line and typographers often prefer to move them to the next
+    /// line instead. The penalty is not added to the last line.
+    pub short_word_penalty: i32,
+}
 
-/// Penalize a short last line.
-const SHORT_LAST_LINE_PENALTY: i32 = 25;
+impl Penalties {
+    /// Default penalties for the optimal-fit algorithm.
+    ///
+    /// These values have been chosen to give good line breaks for
+    /// typical English text.
+    pub const fn new() -> Self {
+        Penalties {
+            nline_penalty: 1000,
+            overflow_penalty: 50 * 50,
+            short_last_line_fraction: 4,
+            short_last_line_penalty: 25,
+            hyphen_penalty: 25,
+            split_penalty: 0,
+            short_word_penalty: 0,
+        }
+    }
+}
 
-/// Penalty for lines ending with a hyphen.
-const HYPHEN_PENALTY: i32 = 25;
+impl Default for Penalties {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// Wrap abstract fragments into lines with an optimal-fit algorithm.
 ///
 /// The `line_widths` map line numbers (starting from 0) to a target
-/// line width. This can be used to implement hanging indentation.
+/// line width. This can be used to implement hanging indentation. The
+/// `penalties` determine the cost of the different kinds of line
+/// breaks, see [`Penalties`] for details.
 ///
 /// The fragments must already have been split into the desired
 /// widths, this function will not (and cannot) attempt to split them
@@ -153,10 +253,12 @@ const HYPHEN_PENALTY: i32 = 25;
 ///
 /// **Note:** Only available when the `smawk` Cargo feature is
 /// enabled.
-pub fn wrap_optimal_fit<T: Fragment, F: Fn(usize) -> usize>(
-    fragments: &[T],
+#[cfg(feature = "smawk")]
+pub fn wrap_optimal_fit<'a, T: Fragment, F: Fn(usize) -> usize>(
+    fragments: &'a [T],
     line_widths: F,
-) -> Vec<&[T]> {
+    penalties: &Penalties,
+) -> Vec<&'a [T]> {
     let mut widths = Vec::with_capacity(fragments.len() + 1);
     let mut width = 0;
     widths.push(width);
@@ -169,8 +271,8 @@ pub fn wrap_optimal_fit<T: Fragment, F: Fn(usize) -> usize>(
 
     let minima = smawk::online_column_minima(0, widths.len(), |minima, i, j| {
         // Line number for fragment `i`.
-        let line_number = line_numbers.get(i, &minima);
-        let target_width = std::cmp::max(1, line_widths(line_number));
+        let line_number = line_numbers.get(i, minima);
+        let target_width = ::core::cmp::max(1, line_widths(line_number));
 
         // Compute the width of a line spanning fragments[i..j] in
         // constant time. We need to adjust widths[j] by subtracting
@@ -182,31 +284,38 @@ pub fn wrap_optimal_fit<T: Fragment, F: Fn(usize) -> usize>(
         // start with values[i].1, which is the optimal cost for
         // breaking before fragments[i].
         //
-        // First, every extra line cost NLINE_PENALTY.
-        let mut cost = minima[i].1 + NLINE_PENALTY;
+        // First, every extra line cost nline_penalty.
+        let mut cost = minima[i].1 + penalties.nline_penalty;
 
         // Next, we add a penalty depending on the line length.
         if line_width > target_width {
             // Lines that overflow get a hefty penalty.
             let overflow = (line_width - target_width) as i32;
-            cost += overflow * OVERFLOW_PENALTY;
+            cost += overflow * penalties.overflow_penalty;
         } else if j < fragments.len() {
             // Other lines (except for the last line) get a milder
             // penalty which depend on the size of the gap.
             let gap = (target_width - line_width) as i32;
             cost += gap * gap;
-        } else if i + 1 == j && line_width < target_width / SHORT_LINE_FRACTION {
+        } else if i + 1 == j && line_width < target_width / penalties.short_last_line_fraction {
             // The last line can have any size gap, but we do add a
             // penalty if the line is very short (typically because it
             // contains just a single word).
-            cost += SHORT_LAST_LINE_PENALTY;
+            cost += penalties.short_last_line_penalty;
         }
 
-        // Finally, we discourage hyphens.
-        if fragments[j - 1].penalty_width() > 0 {
+        // Finally, we discourage hyphens and short words at the end
+        // of lines. A fragment without trailing whitespace is part of
+        // a larger word, so breaking after it splits the word.
+        let last = &fragments[j - 1];
+        if last.penalty_width() > 0 {
             // TODO: this should use a penalty value from the fragment
             // instead.
-            cost += HYPHEN_PENALTY;
+            cost += penalties.hyphen_penalty;
+        } else if j < fragments.len() && last.whitespace_width() == 0 {
+            cost += penalties.split_penalty;
+        } else if j < fragments.len() && last.width() <= 2 {
+            cost += penalties.short_word_penalty;
         }
 
         cost
@@ -224,5 +333,32 @@ pub fn wrap_optimal_fit<T: Fragment, F: Fn(usize) -> usize>(
     }
 
     lines.reverse();
-    lines
+
+    // The overflow penalty can make it cheaper to overflow a line
+    // than to start a new one. Only a single fragment may overflow a
+    // line, so such lines are wrapped again with first fit.
+    let mut result = Vec::with_capacity(lines.len());
+    for line in lines {
+        let line_number = result.len();
+        if line.len() > 1 && line_overflows(line, line_widths(line_number)) {
+            result.extend(wrap_first_fit(line, |i| line_widths(line_number + i)));
+        } else {
+            result.push(line);
+        }
+    }
+    result
+}
+
+/// Is `line` wider than `line_width`? Like the costs computed by
+/// [`wrap_optimal_fit`], this treats a zero line width as one.
+#[cfg(feature = "smawk")]
+fn line_overflows<T: Fragment>(line: &[T], line_width: usize) -> bool {
+    let last = &line[line.len() - 1];
+    let width = line
+        .iter()
+        .map(|fragment| fragment.width() + fragment.whitespace_width())
+        .sum::<usize>()
+        - last.whitespace_width()
+        + last.penalty_width();
+    width > ::core::cmp::max(1, line_width)
 }
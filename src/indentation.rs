@@ -4,6 +4,11 @@
 //! The functions here can be used to uniformly indent or dedent
 //! (unindent) word wrapped lines of text.
 
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+use crate::core::WidthMeasure;
+
 /// Add prefix to each non-empty line.
 ///
 /// ```
@@ -38,6 +43,15 @@
 /// ");
 /// ```
 ///
+/// Lines can end with `'\n'` or `"\r\n"`. A line with only a `'\r'`
+/// is empty, so no prefix is added to it:
+///
+/// ```
+/// use textwrap::indent;
+///
+/// assert_eq!(indent("foo\r\n\r\nbar\r\n", "> "), "> foo\r\n\r\n> bar\r\n");
+/// ```
+///
 /// Leading and trailing whitespace on non-empty lines is kept
 /// unchanged:
 ///
@@ -46,22 +60,144 @@
 ///
 /// assert_eq!(indent(" \t  Foo   ", "->"), "-> \t  Foo   ");
 /// ```
+///
+/// The result ends with a newline if and only if `s` does. A final
+/// newline ends the last line, it does not start a new line, so no
+/// prefix is added after it.
+///
+/// Use [`indent_opts`] to add the prefix to blank lines too, and
+/// [`indent_into`] to append the result to an existing `String`.
 pub fn indent(s: &str, prefix: &str) -> String {
-    let mut result = String::new();
+    let mut result = String::with_capacity(s.len());
+    indent_into(s, prefix, &mut result);
+    result
+}
 
-    for (idx, line) in s.split('\n').enumerate() {
-        if idx > 0 {
-            result.push('\n');
-        }
-        if !line.trim().is_empty() {
-            result.push_str(prefix);
-        }
-        result.push_str(line);
-    }
+/// Add prefix to each line, optionally including blank lines.
+///
+/// This is like [`indent`] when `indent_blank_lines` is `false`. When
+/// it is `true`, empty lines and lines with only whitespace get the
+/// prefix too. This is useful for code generators which put blank
+/// lines inside indented blocks:
+///
+/// ```
+/// use textwrap::indent_opts;
+///
+/// let block = "fn main() {\n\n}\n";
+/// assert_eq!(indent_opts(block, "    ", true), "    fn main() {\n    \n    }\n");
+/// assert_eq!(indent_opts(block, "    ", false), "    fn main() {\n\n    }\n");
+/// ```
+///
+/// Like for [`indent`], no prefix is added after a final newline.
+pub fn indent_opts(s: &str, prefix: &str, indent_blank_lines: bool) -> String {
+    let mut result = String::with_capacity(s.len());
+    indent_with_into(
+        s,
+        |_, line| {
+            if indent_blank_lines || !line.trim().is_empty() {
+                Cow::Borrowed(prefix)
+            } else {
+                Cow::Borrowed("")
+            }
+        },
+        &mut result,
+    );
+    result
+}
 
+/// Add prefix to each non-empty line and append the result to `buf`.
+///
+/// This gives the same text as [`indent`], but reuses the allocation
+/// of `buf`:
+///
+/// ```
+/// use textwrap::{indent, indent_into};
+///
+/// let mut buf = String::from("Quote:\n");
+/// indent_into("foo\n\nbar", "> ", &mut buf);
+/// assert_eq!(buf, "Quote:\n> foo\n\n> bar");
+/// ```
+pub fn indent_into(s: &str, prefix: &str, buf: &mut String) {
+    indent_with_into(
+        s,
+        |_, line| {
+            if line.trim().is_empty() {
+                Cow::Borrowed("")
+            } else {
+                Cow::Borrowed(prefix)
+            }
+        },
+        buf,
+    )
+}
+
+/// Add a computed prefix to each line.
+///
+/// The closure is called with the line number (starting from zero)
+/// and the content of each line, without the `'\n'` or `"\r\n"` line
+/// ending. The returned string is added in front of the line. Unlike
+/// [`indent`], the closure is also called for empty lines, so it
+/// decides if they get a prefix. The closure is not called for the
+/// end of the text after a final newline, since there is no line
+/// there. An empty `s` therefore has no lines at all.
+///
+/// Email-style quoting where blank lines get a prefix without
+/// trailing whitespace:
+///
+/// ```
+/// use std::borrow::Cow;
+/// use textwrap::indent_with;
+///
+/// let quoted = indent_with("Hello,\n\nBye!", |_, line| {
+///     if line.trim().is_empty() {
+///         Cow::Borrowed(">")
+///     } else {
+///         Cow::Borrowed("> ")
+///     }
+/// });
+/// assert_eq!(quoted, "> Hello,\n>\n> Bye!");
+/// ```
+///
+/// Indenting all but the first line, e.g., when generating code:
+///
+/// ```
+/// use std::borrow::Cow;
+/// use textwrap::indent_with;
+///
+/// let code = indent_with("foo(\na,\nb)", |idx, _| match idx {
+///     0 => Cow::Borrowed(""),
+///     _ => Cow::Borrowed("    "),
+/// });
+/// assert_eq!(code, "foo(\n    a,\n    b)");
+/// ```
+///
+/// The prefix can also be computed from the line number:
+///
+/// ```
+/// use std::borrow::Cow;
+/// use textwrap::indent_with;
+///
+/// let numbered = indent_with("foo\nbar", |idx, _| Cow::Owned(format!("{}: ", idx + 1)));
+/// assert_eq!(numbered, "1: foo\n2: bar");
+/// ```
+pub fn indent_with<'a>(s: &str, f: impl FnMut(usize, &str) -> Cow<'a, str>) -> String {
+    let mut result = String::new();
+    indent_with_into(s, f, &mut result);
     result
 }
 
+/// Add a computed prefix to each line and append the result to `buf`.
+fn indent_with_into<'a>(s: &str, mut f: impl FnMut(usize, &str) -> Cow<'a, str>, buf: &mut String) {
+    // Unlike `split('\n')`, this gives no empty line after a final
+    // newline.
+    for (idx, line) in s.split_inclusive('\n').enumerate() {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        buf.push_str(&f(idx, content));
+        buf.push_str(line);
+    }
+}
+
 /// Removes common leading whitespace from each line.
 ///
 /// This function will look at each non-empty line and determine the
@@ -80,7 +216,89 @@ pub fn indent(s: &str, prefix: &str) -> String {
 /// 3rd line
 /// ");
 /// ```
+///
+/// Windows line endings (`"\r\n"`) are kept in the result:
+///
+/// ```
+/// use textwrap::dedent;
+///
+/// assert_eq!(dedent("  foo\r\n\r\n    bar\r\n"), "foo\r\n\r\n  bar\r\n");
+/// ```
+///
+/// The removed prefix is the longest run of leading whitespace
+/// characters which is shared by all non-blank lines, compared
+/// character by character. Blank lines and lines with only
+/// whitespace are ignored and become empty. A tab and a number of
+/// spaces are never seen as the same indentation, so mixing them
+/// can leave all or some of the indentation in place:
+///
+/// ```
+/// use textwrap::dedent;
+///
+/// assert_eq!(dedent("\tfoo\n    bar"), "\tfoo\n    bar");
+/// assert_eq!(dedent("  \tfoo\n    bar"), "\tfoo\n  bar");
+/// ```
+///
+/// Use [`dedent_tabsize`] to remove indentation by columns instead,
+/// and [`common_indent`] to find the prefix which is removed. Use
+/// [`dedent_into`] to append the result to an existing `String`.
 pub fn dedent(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    dedent_into(s, &mut result);
+    result
+}
+
+/// Removes common leading whitespace from each line and appends the
+/// result to `buf`.
+///
+/// This gives the same text as [`dedent`], but reuses the allocation
+/// of `buf`:
+///
+/// ```
+/// use textwrap::dedent_into;
+///
+/// let mut buf = String::new();
+/// for text in &["  foo\n    bar\n", "\tbaz\n"] {
+///     buf.clear();
+///     dedent_into(text, &mut buf);
+///     println!("{}", buf);
+/// }
+/// assert_eq!(buf, "baz\n");
+/// ```
+pub fn dedent_into(s: &str, buf: &mut String) {
+    let prefix = common_indent(s);
+
+    // We now go over the lines to build the result. The line endings
+    // are kept as they are.
+    let result = buf;
+    for line in s.split_inclusive('\n') {
+        let content = line
+            .strip_suffix('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .unwrap_or(line);
+        if content.starts_with(prefix) && content.chars().any(|c| !c.is_whitespace()) {
+            let (_, tail) = content.split_at(prefix.len());
+            result.push_str(tail);
+        }
+        result.push_str(&line[content.len()..]);
+    }
+}
+
+/// Find the common leading whitespace of the lines.
+///
+/// This is exactly the prefix removed by [`dedent`]. Blank lines and
+/// lines with only whitespace are ignored. This makes it possible to
+/// put the indentation back after processing the dedented text:
+///
+/// ```
+/// use textwrap::{common_indent, dedent, indent};
+///
+/// let text = "    foo\r\n\r\n      bar\r\n";
+/// let prefix = common_indent(text);
+/// assert_eq!(prefix, "    ");
+/// assert_eq!(indent(&dedent(text), prefix), text);
+/// ```
+pub fn common_indent(s: &str) -> &str {
     let mut prefix = "";
     let mut lines = s.lines();
 
@@ -119,21 +337,129 @@ pub fn dedent(s: &str) -> String {
         }
     }
 
-    // We now go over the lines a second time to build the result.
+    prefix
+}
+
+/// Removes common leading whitespace from each line, measured in
+/// columns.
+///
+/// This is like [`dedent`], except that the indentation of each line
+/// is measured in columns: a tab moves to the next tab stop, which
+/// are `tabstop` columns apart, and other whitespace characters are
+/// one column wide. The smallest indentation of the non-blank lines
+/// is then removed from all lines. This makes lines indented with
+/// tabs and lines indented with spaces line up:
+///
+/// ```
+/// use textwrap::dedent_tabsize;
+///
+/// assert_eq!(dedent_tabsize("\tfoo\n        bar", 4), "foo\n    bar");
+/// assert_eq!(dedent_tabsize("\tfoo\n        bar", 8), "foo\nbar");
+/// ```
+///
+/// When a tab covers both removed and kept columns, the kept columns
+/// are replaced with spaces:
+///
+/// ```
+/// use textwrap::dedent_tabsize;
+///
+/// assert_eq!(dedent_tabsize("  foo\n\tbar", 4), "foo\n  bar");
+/// ```
+///
+/// Blank lines and lines with only whitespace are ignored and become
+/// empty, and line endings are kept as they are, just like for
+/// [`dedent`]. A `tabstop` of zero makes tabs zero columns wide.
+pub fn dedent_tabsize(s: &str, tabstop: usize) -> String {
+    let advance = |column: usize, ch: char| match ch {
+        '\t' if tabstop == 0 => column,
+        '\t' => column + tabstop - column % tabstop,
+        _ => column + 1,
+    };
+    let margin = s
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.chars()
+                .take_while(|ch| ch.is_whitespace())
+                .fold(0, advance)
+        })
+        .min()
+        .unwrap_or(0);
+
     let mut result = String::new();
-    for line in s.lines() {
-        if line.starts_with(&prefix) && line.chars().any(|c| !c.is_whitespace()) {
-            let (_, tail) = line.split_at(prefix.len());
-            result.push_str(tail);
+    for line in s.split_inclusive('\n') {
+        let content = line
+            .strip_suffix('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .unwrap_or(line);
+        if !content.trim().is_empty() {
+            let mut column = 0;
+            let mut start = 0;
+            for (idx, ch) in content.char_indices() {
+                if column >= margin {
+                    break;
+                }
+                column = advance(column, ch);
+                start = idx + ch.len_utf8();
+            }
+            result.push_str(&" ".repeat(column - margin));
+            result.push_str(&content[start..]);
         }
-        result.push('\n');
+        result.push_str(&line[content.len()..]);
     }
 
-    if result.ends_with('\n') && !s.ends_with('\n') {
-        let new_len = result.len() - 1;
-        result.truncate(new_len);
-    }
+    result
+}
 
+/// Replace each tab with spaces up to the next tab stop.
+///
+/// The tab stops are `tabstop` columns apart, like Python's
+/// [`str.expandtabs`]. The column is counted from the start of each
+/// line using the display width, see
+/// [`core::display_width`](crate::core::display_width). A `tabstop`
+/// of zero removes the tabs.
+///
+/// ```
+/// use textwrap::expand_tabs;
+///
+/// assert_eq!(expand_tabs("a\tbc\tdef\tg", 4), "a   bc  def g");
+/// assert_eq!(expand_tabs("\tfoo\n\tbar", 2), "  foo\n  bar");
+/// assert_eq!(expand_tabs("a\tb", 0), "ab");
+/// ```
+///
+/// Wide characters take up two columns:
+///
+/// ```
+/// use textwrap::expand_tabs;
+///
+/// assert_eq!(expand_tabs("你\tx", 4), "你  x");
+/// ```
+///
+/// [`str.expandtabs`]: https://docs.python.org/3/library/stdtypes.html#str.expandtabs
+pub fn expand_tabs(text: &str, tabstop: usize) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut column = 0;
+    let mut clusters = WidthMeasure::UnicodeCells.clusters(text).peekable();
+    while let Some((start, width)) = clusters.next() {
+        let end = clusters.peek().map_or(text.len(), |&(idx, _)| idx);
+        match &text[start..end] {
+            "\t" => {
+                if tabstop > 0 {
+                    let spaces = tabstop - column % tabstop;
+                    result.push_str(&" ".repeat(spaces));
+                    column += spaces;
+                }
+            }
+            cluster @ ("\n" | "\r" | "\r\n") => {
+                result.push_str(cluster);
+                column = 0;
+            }
+            cluster => {
+                result.push_str(cluster);
+                column += width;
+            }
+        }
+    }
     result
 }
 
@@ -180,6 +506,126 @@ mod tests {
         assert_eq!(indent(&text, "//"), expected);
     }
 
+    #[test]
+    fn indent_with_line_numbers() {
+        let mut seen = alloc::vec::Vec::new();
+        let result = indent_with("foo\r\n\nbar", |idx, line| {
+            seen.push((idx, String::from(line)));
+            Cow::Borrowed("|")
+        });
+        assert_eq!(result, "|foo\r\n|\n|bar");
+        assert_eq!(seen, [(0, "foo".into()), (1, "".into()), (2, "bar".into())]);
+    }
+
+    #[test]
+    fn expand_tabs_empty() {
+        assert_eq!(expand_tabs("", 8), "");
+        assert_eq!(expand_tabs("\t", 8), "        ");
+    }
+
+    #[test]
+    fn expand_tabs_columns() {
+        assert_eq!(expand_tabs("12345678\tx", 8), "12345678        x");
+        assert_eq!(expand_tabs("1234567\tx", 8), "1234567 x");
+        assert_eq!(expand_tabs("\t\tx", 3), "      x");
+    }
+
+    #[test]
+    fn expand_tabs_resets_after_line_break() {
+        assert_eq!(expand_tabs("ab\ta\r\n\tb", 4), "ab  a\r\n    b");
+    }
+
+    #[test]
+    fn expand_tabs_skips_escape_sequences() {
+        assert_eq!(
+            expand_tabs("\u{1b}[31mab\u{1b}[0m\tc", 4),
+            "\u{1b}[31mab\u{1b}[0m  c"
+        );
+    }
+
+    #[test]
+    fn indent_crlf() {
+        assert_eq!(indent("foo\r\n  \r\nbar", "//"), "//foo\r\n  \r\n//bar");
+    }
+
+    #[test]
+    fn indent_into_and_dedent_into_append() {
+        let texts = ["", "foo", "  foo\n\n    bar\n", "\tfoo\r\n  \r\n\tbar"];
+        let mut buf = String::from("prefix\n");
+        let mut expected = buf.clone();
+        for text in &texts {
+            indent_into(text, "> ", &mut buf);
+            expected.push_str(&indent(text, "> "));
+            dedent_into(text, &mut buf);
+            expected.push_str(&dedent(text));
+            assert_eq!(buf, expected);
+        }
+    }
+
+    #[test]
+    fn indent_final_newline() {
+        assert_eq!(indent("", "  "), "");
+        assert_eq!(indent("foo", "  "), "  foo");
+        assert_eq!(indent("foo\n", "  "), "  foo\n");
+        assert_eq!(indent("foo\r\n", "  "), "  foo\r\n");
+        assert_eq!(indent("foo\n\n", "  "), "  foo\n\n");
+        assert_eq!(indent_opts("foo\n\n", "  ", true), "  foo\n  \n");
+        assert_eq!(indent_opts("", "  ", true), "");
+        assert_eq!(indent_opts("\n", "  ", true), "  \n");
+        assert_eq!(indent_opts(" \t\r\nfoo", "> ", true), ">  \t\r\n> foo");
+        assert_eq!(indent_opts(" \t\r\nfoo", "> ", false), " \t\r\n> foo");
+    }
+
+    #[test]
+    fn indent_with_skips_end_after_final_newline() {
+        let mut seen = alloc::vec::Vec::new();
+        let result = indent_with("foo\n\n", |idx, line| {
+            seen.push((idx, String::from(line)));
+            Cow::Borrowed(">")
+        });
+        assert_eq!(result, ">foo\n>\n");
+        assert_eq!(seen, [(0, "foo".into()), (1, "".into())]);
+        assert_eq!(indent_with("", |_, _| Cow::Borrowed(">")), "");
+    }
+
+    #[test]
+    fn dedent_crlf() {
+        let x = "    foo\r\n\r\n      bar\r\n  \r\n    baz";
+        assert_eq!(dedent(x), "foo\r\n\r\n  bar\r\n\r\nbaz");
+    }
+
+    #[test]
+    fn dedent_tabsize_mixed_indentation() {
+        let x = "\tfoo\n    bar\n  \t  baz";
+        assert_eq!(dedent_tabsize(x, 4), "foo\nbar\n  baz");
+        assert_eq!(dedent_tabsize(x, 2), "foo\n  bar\n\t  baz");
+    }
+
+    #[test]
+    fn dedent_tabsize_ignores_blank_lines() {
+        let x = "\t\tfoo\r\n\t\r\n\n\t\t\tbar\r\n";
+        assert_eq!(dedent_tabsize(x, 8), "foo\r\n\r\n\n\tbar\r\n");
+    }
+
+    #[test]
+    fn dedent_tabsize_zero() {
+        assert_eq!(dedent_tabsize("\t foo\n  bar", 0), "foo\n bar");
+    }
+
+    #[test]
+    fn dedent_tabsize_agrees_with_dedent() {
+        let x = "    foo\n      bar\n\n    baz";
+        assert_eq!(dedent_tabsize(x, 4), dedent(x));
+    }
+
+    #[test]
+    fn common_indent_ignores_blank_lines() {
+        assert_eq!(common_indent(""), "");
+        assert_eq!(common_indent("  \n    foo\n\n      bar"), "    ");
+        assert_eq!(common_indent("\t foo\r\n\t\tbar\r\n"), "\t");
+        assert_eq!(common_indent("foo\n  bar"), "");
+    }
+
     #[test]
     fn dedent_empty() {
         assert_eq!(dedent(""), "");
@@ -8,10 +8,8 @@
 //! terminal. A quick example:
 //!
 //! ```no_run
-//! fn main() {
-//!     let text = "textwrap: a small library for wrapping text.";
-//!     println!("{}", textwrap::fill(text, 18));
-//! }
+//! let text = "textwrap: a small library for wrapping text.";
+//! println!("{}", textwrap::fill(text, 18));
 //! ```
 //!
 //! When you run this program, it will display the following output:
@@ -51,7 +49,8 @@
 //! ```
 //!
 //! See also the [`unfill`] and [`refill`] functions which allow you to
-//! manipulate already wrapped text.
+//! manipulate already wrapped text. Use [`shorten`] if you need to
+//! squeeze text into a single line of limited width.
 //!
 //! ## Wrapping Strings at Compile Time
 //!
@@ -130,6 +129,13 @@
 //!
 //! These features are enabled by default:
 //!
+//! * `std`: links against the standard library. Without this
+//!   feature, textwrap is a `no_std` crate which only needs the
+//!   [`alloc`] crate. The [`CachedSplitter`] and the functions which
+//!   write to [`std::io::Write`] or read from [`std::io::BufRead`]
+//!   require this feature, as do the `hyphenation`, `serde`, and
+//!   `terminal_size` features.
+//!
 //! * `unicode-width`: enables correct width computation of non-ASCII
 //!   characters via the [unicode-width] crate. Without this feature,
 //!   every [`char`] is 1 column wide, except for emojis which are 2
@@ -141,6 +147,14 @@
 //!   [`core::Fragment`]s for which the widths have been computed in
 //!   other ways.
 //!
+//! * `unicode-segmentation`: finds the grapheme clusters of the text
+//!   via the [unicode-segmentation] crate. Long words are only broken
+//!   between clusters, so that accents and emoji sequences stay
+//!   together. Without this feature, the clusters are approximated:
+//!   combining marks and emoji sequences are handled, but scripts
+//!   such as Hangul written with conjoining jamo or Indic conjuncts
+//!   can be broken inside a cluster.
+//!
 //! * `smawk`: enables linear-time wrapping of the whole paragraph via
 //!   the [smawk] crate. See the [`core::wrap_optimal_fit`] function
 //!   for details on the optimal-fit algorithm.
@@ -159,47 +173,198 @@
 //! * `hyphenation`: enables language-sensitive hyphenation via the
 //!   [hyphenation] crate. See the [`WordSplitter`] trait for details.
 //!
+//! * `serde`: enables reading and writing wrapping settings with the
+//!   [serde] crate. See the [`config`] module for details.
+//!
+//! * `rayon`: enables [`fill_par`], which fills the paragraphs of
+//!   large documents in parallel via the [rayon] crate.
+//!
+//! * `unicode-bidi`: enables [`Options::bidi_reorder`], which shows
+//!   right-to-left text such as Hebrew and Arabic in display order.
+//!   The directions are resolved by the [unicode-bidi] crate.
+//!
+//! * `cli`: builds the `textwrap` command line tool, which re-flows
+//!   text from files or the standard input like `fmt`. This enables
+//!   the `terminal_size` feature. Enable `hyphenation` too for the
+//!   `--hyphenate` option.
+//!
 //! [unicode-width]: https://docs.rs/unicode-width/
+//! [unicode-segmentation]: https://docs.rs/unicode-segmentation/
 //! [smawk]: https://docs.rs/smawk/
 //! [textwrap-macros]: https://docs.rs/textwrap-macros/
 //! [terminal_size]: https://docs.rs/terminal_size/
 //! [hyphenation]: https://docs.rs/hyphenation/
+//! [serde]: https://docs.rs/serde/
+//! [rayon]: https://docs.rs/rayon/
+//! [unicode-bidi]: https://docs.rs/unicode-bidi/
 
 #![doc(html_root_url = "https://docs.rs/textwrap/0.13.4")]
 #![forbid(unsafe_code)] // See https://github.com/mgeisler/textwrap/issues/210
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 #![allow(clippy::redundant_field_names)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 
 mod indentation;
+pub use crate::indentation::common_indent;
 pub use crate::indentation::dedent;
+pub use crate::indentation::dedent_into;
+pub use crate::indentation::dedent_tabsize;
+pub use crate::indentation::expand_tabs;
 pub use crate::indentation::indent;
+pub use crate::indentation::indent_into;
+pub use crate::indentation::indent_opts;
+pub use crate::indentation::indent_with;
+
+mod markdown;
+pub use crate::markdown::fill_markdown;
 
 mod splitting;
-pub use crate::splitting::{HyphenSplitter, NoHyphenation, WordSplitter};
+#[cfg(feature = "hyphenation")]
+pub use crate::splitting::dictionary_for_locale;
+#[cfg(feature = "std")]
+pub use crate::splitting::CachedSplitter;
+pub use crate::splitting::{
+    ChainSplitter, FnSplitter, HyphenSplitter, IdentifierSplitter, LangChoice, LanguageSplitter,
+    MinFragment, NoHyphenation, SoftHyphenSplitter, SplitPoint, SplitPoints, SplitPointsIter,
+    Substitution, WordSplitter,
+};
 
 pub mod core;
 
+#[cfg(feature = "serde")]
+pub mod config;
+
 /// Holds settings for wrapping and filling text.
+///
+/// The `Debug` output includes all settings and the splitter, also
+/// when the splitter is a trait object. `Options` can be cloned when
+/// the splitter can be cloned. A `Box<dyn WordSplitter>` cannot be
+/// cloned, but an `Arc<dyn WordSplitter>` can, which lets you keep a
+/// copy of the settings per thread:
+///
+/// ```
+/// use std::sync::Arc;
+/// use textwrap::{wrap, HyphenSplitter, Options, WordSplitter};
+///
+/// let splitter: Arc<dyn WordSplitter + Send + Sync> = Arc::new(HyphenSplitter);
+/// let options = Options::new(5).splitter(splitter);
+/// let copy = options.clone();
+/// std::thread::spawn(move || {
+///     assert_eq!(wrap("foo-bar", &copy), vec!["foo-", "bar"]);
+/// })
+/// .join()
+/// .unwrap();
+/// assert!(format!("{:?}", options).contains("splitter: HyphenSplitter"));
+/// ```
 #[derive(Debug, Clone)]
 pub struct Options<'a, S: ?Sized = Box<dyn WordSplitter>> {
     /// The width in columns at which the text will be wrapped.
     pub width: usize,
     /// Indentation used for the first line of output. See the
     /// [`Options::initial_indent`] method.
-    pub initial_indent: &'a str,
+    pub initial_indent: Cow<'a, str>,
     /// Indentation used for subsequent lines of output. See the
     /// [`Options::subsequent_indent`] method.
-    pub subsequent_indent: &'a str,
-    /// Allow long words to be broken if they cannot fit on a line.
-    /// When set to `false`, some lines may be longer than
-    /// `self.width`. See the [`Options::break_words`] method.
-    pub break_words: bool,
+    pub subsequent_indent: Cow<'a, str>,
+    /// How to handle words which are too long to fit on a line. See
+    /// the [`Options::long_words`] method.
+    pub long_words: LongWords,
     /// Wraping algorithm to use, see [`core::WrapAlgorithm`] for
     /// details.
     pub wrap_algorithm: core::WrapAlgorithm,
+    /// Penalties used by the optimal-fit algorithm, see
+    /// [`core::Penalties`] for details.
+    pub penalties: core::Penalties,
+    /// Maximum number of lines of output. See the
+    /// [`Options::max_lines`] method.
+    pub max_lines: Option<usize>,
+    /// Placeholder put at the end of the last line when text is
+    /// omitted due to [`Options::max_lines`]. See the
+    /// [`Options::placeholder`] method.
+    pub placeholder: &'a str,
+    /// Alignment of the wrapped lines. See the
+    /// [`Options::alignment`] method.
+    pub alignment: Alignment,
+    /// Indent the continuation lines of list items. See the
+    /// [`Options::detect_list_markers`] method.
+    pub detect_list_markers: bool,
+    /// Marker inserted where a word is forcibly broken. See the
+    /// [`Options::word_break_marker`] method.
+    pub word_break_marker: &'a str,
+    /// Minimum width of the last line of a paragraph. See the
+    /// [`Options::min_last_line_width`] method.
+    pub min_last_line_width: usize,
+    /// Width of each line as a function of the line number. See the
+    /// [`Options::width_fn`] method.
+    pub width_fn: Option<WidthFn<'a>>,
+    /// The method for finding words and break opportunities. See the
+    /// [`Options::word_separator`] method.
+    pub word_separator: core::WordSeparator,
+    /// Characters which must not start or end a line. See the
+    /// [`Options::break_rules`] method.
+    pub break_rules: core::BreakRules<'a>,
+    /// Maximum number of consecutive lines ending in a word split by
+    /// the splitter. See the [`Options::max_consecutive_hyphens`]
+    /// method.
+    pub max_consecutive_hyphens: Option<usize>,
+    /// Re-apply the active ANSI styles on every line. See the
+    /// [`Options::reapply_ansi_styles`] method.
+    pub reapply_ansi_styles: bool,
+    /// The method for measuring the width of text. See the
+    /// [`Options::width_measure`] method.
+    pub width_measure: core::WidthMeasure,
+    /// Expand tabs to tab stops this many columns apart before
+    /// wrapping. See the [`Options::expand_tabs`] method.
+    pub expand_tabs: Option<usize>,
+    /// The line ending used between the lines by [`fill`] and
+    /// related functions. See the [`Options::line_ending`] method.
+    pub line_ending: &'a str,
+    /// Remove control characters before wrapping. See the
+    /// [`Options::sanitize_control_chars`] method.
+    pub sanitize_control_chars: bool,
+    /// Indent the continuation lines like the first line of the
+    /// paragraph. See the [`Options::hanging_indent_from_input`] method.
+    pub hanging_indent_from_input: bool,
+    /// How to break URLs and email addresses. See the
+    /// [`Options::url_breaks`] method.
+    pub url_breaks: UrlBreaks,
+    /// Balance the line lengths of short paragraphs. See the
+    /// [`Options::balance`] method.
+    pub balance: Option<usize>,
+    /// Start a new line at every line break in the text. See the
+    /// [`Options::respect_newlines`] method.
+    pub respect_newlines: bool,
+    /// Start a new line after each sentence. See the
+    /// [`Options::sentence_per_line`] method.
+    pub sentence_per_line: bool,
+    /// Abbreviations which do not end a sentence. See the
+    /// [`Options::abbreviations`] method.
+    pub abbreviations: &'a [&'a str],
+    /// Keep Markdown code spans and emphasis markers together. See
+    /// the [`Options::inline_markup`] method.
+    pub inline_markup: bool,
+    /// Reorder bidirectional text for display. See the
+    /// [`Options::bidi_reorder`] method.
+    #[cfg(feature = "unicode-bidi")]
+    pub bidi_reorder: bool,
+    /// Let opening quotes and dashes hang into the margin. See the
+    /// [`Options::hanging_punctuation`] method.
+    pub hanging_punctuation: bool,
+    /// The characters which hang into the margin. See the
+    /// [`Options::hanging_punctuation_chars`] method.
+    pub hanging_punctuation_chars: &'a str,
+    /// Marker put in front of the lines which continue a wrapped line.
+    /// See the [`Options::continuation_marker`] method.
+    pub continuation_marker: &'a str,
     /// The method for splitting words. This can be used to prohibit
     /// splitting words on hyphens, or it can be used to implement
     /// language-aware machine hyphenation. Please see the
@@ -211,10 +376,38 @@ impl<'a, S: ?Sized> From<&'a Options<'a, S>> for Options<'a, &'a S> {
     fn from(options: &'a Options<'a, S>) -> Self {
         Self {
             width: options.width,
-            initial_indent: options.initial_indent,
-            subsequent_indent: options.subsequent_indent,
-            break_words: options.break_words,
+            initial_indent: Cow::Borrowed(&options.initial_indent),
+            subsequent_indent: Cow::Borrowed(&options.subsequent_indent),
+            long_words: options.long_words,
             wrap_algorithm: options.wrap_algorithm,
+            penalties: options.penalties,
+            max_lines: options.max_lines,
+            placeholder: options.placeholder,
+            alignment: options.alignment,
+            detect_list_markers: options.detect_list_markers,
+            word_break_marker: options.word_break_marker,
+            min_last_line_width: options.min_last_line_width,
+            width_fn: options.width_fn,
+            word_separator: options.word_separator.clone(),
+            break_rules: options.break_rules,
+            max_consecutive_hyphens: options.max_consecutive_hyphens,
+            reapply_ansi_styles: options.reapply_ansi_styles,
+            width_measure: options.width_measure,
+            expand_tabs: options.expand_tabs,
+            line_ending: options.line_ending,
+            sanitize_control_chars: options.sanitize_control_chars,
+            hanging_indent_from_input: options.hanging_indent_from_input,
+            url_breaks: options.url_breaks,
+            balance: options.balance,
+            respect_newlines: options.respect_newlines,
+            sentence_per_line: options.sentence_per_line,
+            abbreviations: options.abbreviations,
+            inline_markup: options.inline_markup,
+            #[cfg(feature = "unicode-bidi")]
+            bidi_reorder: options.bidi_reorder,
+            hanging_punctuation: options.hanging_punctuation,
+            hanging_punctuation_chars: options.hanging_punctuation_chars,
+            continuation_marker: options.continuation_marker,
             splitter: &options.splitter,
         }
     }
@@ -238,21 +431,77 @@ impl<'a> Options<'a, HyphenSplitter> {
     /// # let expected =
     /// Options {
     ///     width: width,
-    ///     initial_indent: "",
-    ///     subsequent_indent: "",
-    ///     break_words: true,
+    ///     initial_indent: "".into(),
+    ///     subsequent_indent: "".into(),
+    ///     long_words: textwrap::LongWords::Break,
     ///     #[cfg(feature = "smawk")]
     ///     wrap_algorithm: textwrap::core::WrapAlgorithm::OptimalFit,
     ///     #[cfg(not(feature = "smawk"))]
     ///     wrap_algorithm: textwrap::core::WrapAlgorithm::FirstFit,
+    ///     penalties: textwrap::core::Penalties::new(),
+    ///     max_lines: None,
+    ///     placeholder: "…",
+    ///     alignment: textwrap::Alignment::Left,
+    ///     detect_list_markers: false,
+    ///     word_break_marker: "",
+    ///     min_last_line_width: 0,
+    ///     width_fn: None,
+    ///     word_separator: textwrap::core::WordSeparator::AsciiSpace,
+    ///     break_rules: textwrap::core::BreakRules::none(),
+    ///     max_consecutive_hyphens: None,
+    ///     reapply_ansi_styles: false,
+    ///     width_measure: textwrap::core::WidthMeasure::UnicodeCells,
+    ///     expand_tabs: None,
+    ///     line_ending: "\n",
+    ///     sanitize_control_chars: false,
+    ///     hanging_indent_from_input: false,
+    ///     url_breaks: textwrap::UrlBreaks::Anywhere,
+    ///     balance: None,
+    ///     respect_newlines: true,
+    ///     sentence_per_line: false,
+    ///     abbreviations: textwrap::DEFAULT_ABBREVIATIONS,
+    ///     inline_markup: false,
+    ///     #[cfg(feature = "unicode-bidi")]
+    ///     bidi_reorder: false,
+    ///     hanging_punctuation: false,
+    ///     hanging_punctuation_chars: textwrap::DEFAULT_HANGING_PUNCTUATION,
+    ///     continuation_marker: "",
     ///     splitter: HyphenSplitter,
     /// }
     /// # ;
     /// # assert_eq!(actual.width, expected.width);
     /// # assert_eq!(actual.initial_indent, expected.initial_indent);
     /// # assert_eq!(actual.subsequent_indent, expected.subsequent_indent);
-    /// # assert_eq!(actual.break_words, expected.break_words);
+    /// # assert_eq!(actual.long_words, expected.long_words);
     /// # assert_eq!(actual.wrap_algorithm, expected.wrap_algorithm);
+    /// # assert_eq!(actual.penalties, expected.penalties);
+    /// # assert_eq!(actual.max_lines, expected.max_lines);
+    /// # assert_eq!(actual.placeholder, expected.placeholder);
+    /// # assert_eq!(actual.alignment, expected.alignment);
+    /// # assert_eq!(actual.detect_list_markers, expected.detect_list_markers);
+    /// # assert_eq!(actual.word_break_marker, expected.word_break_marker);
+    /// # assert_eq!(actual.min_last_line_width, expected.min_last_line_width);
+    /// # assert!(actual.width_fn.is_none() && expected.width_fn.is_none());
+    /// # assert_eq!(actual.word_separator, expected.word_separator);
+    /// # assert_eq!(actual.break_rules, expected.break_rules);
+    /// # assert_eq!(actual.max_consecutive_hyphens, expected.max_consecutive_hyphens);
+    /// # assert_eq!(actual.reapply_ansi_styles, expected.reapply_ansi_styles);
+    /// # assert!(matches!(actual.width_measure, textwrap::core::WidthMeasure::UnicodeCells));
+    /// # assert_eq!(actual.expand_tabs, expected.expand_tabs);
+    /// # assert_eq!(actual.line_ending, expected.line_ending);
+    /// # assert_eq!(actual.sanitize_control_chars, expected.sanitize_control_chars);
+    /// # assert_eq!(actual.hanging_indent_from_input, expected.hanging_indent_from_input);
+    /// # assert_eq!(actual.url_breaks, expected.url_breaks);
+    /// # assert_eq!(actual.balance, expected.balance);
+    /// # assert_eq!(actual.respect_newlines, expected.respect_newlines);
+    /// # assert_eq!(actual.sentence_per_line, expected.sentence_per_line);
+    /// # assert_eq!(actual.abbreviations, expected.abbreviations);
+    /// # assert_eq!(actual.inline_markup, expected.inline_markup);
+    /// # #[cfg(feature = "unicode-bidi")]
+    /// # assert_eq!(actual.bidi_reorder, expected.bidi_reorder);
+    /// # assert_eq!(actual.hanging_punctuation, expected.hanging_punctuation);
+    /// # assert_eq!(actual.hanging_punctuation_chars, expected.hanging_punctuation_chars);
+    /// # assert_eq!(actual.continuation_marker, expected.continuation_marker);
     /// # let expected_coerced: Options<'static, HyphenSplitter> = expected;
     /// ```
     ///
@@ -308,16 +557,39 @@ impl<'a> Options<'a, HyphenSplitter> {
     /// the different `WordSplitter` in use. Thus dynamic dispatch
     /// allows to change the splitter at run-time without changing the
     /// variables type.
+    ///
+    /// No `width` is rejected: a width of zero works like a width of
+    /// one, and indentation which is as wide as the lines leaves a
+    /// single column for the text, see [`Options::subsequent_indent`].
+    /// Use [`Options::try_new`] or [`Options::validate`] to catch such
+    /// settings instead.
     pub const fn new(width: usize) -> Self {
         Options::with_splitter(width, HyphenSplitter)
     }
 
+    /// Creates a new [`Options`] like [`Options::new`], but returns
+    /// an error if `width` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{Options, OptionsError};
+    ///
+    /// assert_eq!(Options::try_new(0).unwrap_err(), OptionsError::ZeroWidth);
+    /// assert_eq!(Options::try_new(80).unwrap().width, 80);
+    /// ```
+    pub fn try_new(width: usize) -> Result<Self, OptionsError> {
+        let options = Options::new(width);
+        options.validate()?;
+        Ok(options)
+    }
+
     /// Creates a new [`Options`] with `width` set to the current
-    /// terminal width. If the terminal width cannot be determined
-    /// (typically because the standard input and output is not
-    /// connected to a terminal), a width of 80 characters will be
-    /// used. Other settings use the same defaults as
-    /// [`Options::new`].
+    /// terminal width. The `COLUMNS` environment variable is used
+    /// if it is set. If the terminal width cannot be determined
+    /// (typically because the standard output is not connected to a
+    /// terminal), a width of 80 characters will be used. Other
+    /// settings use the same defaults as [`Options::new`].
     ///
     /// Equivalent to:
     ///
@@ -333,6 +605,118 @@ impl<'a> Options<'a, HyphenSplitter> {
     pub fn with_termwidth() -> Self {
         Self::new(termwidth())
     }
+
+    /// Creates a new [`Options`] for wrapping documentation, such as
+    /// README files and comments in source code.
+    ///
+    /// The width is fixed at 79 columns and long words are not broken.
+    /// Words are only split at existing hyphens using the
+    /// [`HyphenSplitter`], which means that the result is the same
+    /// for every user, regardless of the locale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{LongWords, Options};
+    ///
+    /// let options = Options::for_documentation();
+    /// assert_eq!(options.width, 79);
+    /// assert_eq!(options.long_words, LongWords::Overflow);
+    /// ```
+    pub fn for_documentation() -> Self {
+        Options::new(79).long_words(LongWords::Overflow)
+    }
+}
+
+impl<'a> Options<'a, NoHyphenation> {
+    /// Creates a new [`Options`] for wrapping the description of a
+    /// command line option in `--help` output.
+    ///
+    /// The continuation lines are indented with `flag_column` spaces
+    /// so that they line up with the descriptions after the flags.
+    /// Words are neither broken nor hyphenated, which keeps flags such
+    /// as `--no-color` intact. The width is the terminal width, but
+    /// at most 100 columns since longer lines are hard to read. If
+    /// the terminal width cannot be determined, 79 columns are used.
+    ///
+    /// Use [`Options::for_help_with_width`] to choose the width
+    /// yourself.
+    ///
+    /// **Note:** Only available when the `terminal_size` feature is
+    /// enabled.
+    #[cfg(feature = "terminal_size")]
+    pub fn for_help(flag_column: usize) -> Self {
+        Self::for_help_with_width(termwidth_with_fallback(79).min(100), flag_column)
+    }
+
+    /// Creates a new [`Options`] for wrapping `--help` output at the
+    /// given width. This works like [`Options::for_help`], except
+    /// that the terminal width is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::for_help_with_width(50, 20);
+    /// let line = "    --no-color      Do not use colors, also when the output is a terminal";
+    /// assert_eq!(wrap(line, &options),
+    ///            vec!["    --no-color      Do not use colors, also when",
+    ///                 "                    the output is a terminal"]);
+    /// ```
+    pub fn for_help_with_width(width: usize, flag_column: usize) -> Self {
+        Options::with_splitter(width, NoHyphenation)
+            .subsequent_indent(" ".repeat(flag_column))
+            .long_words(LongWords::Overflow)
+    }
+}
+
+impl<'a> Options<'a, Box<dyn WordSplitter>> {
+    /// Creates a new [`Options`] for wrapping prose in a terminal.
+    ///
+    /// The width is the terminal width clamped to between 40 and 100
+    /// columns, minus a margin of 4 columns for breathing room. If
+    /// the terminal width cannot be determined, 80 columns are used
+    /// before the margin is subtracted. Long words are not broken.
+    ///
+    /// Words are hyphenated using the language of the user's locale
+    /// when the `hyphenation` feature is enabled and a dictionary is
+    /// available for the language. The locale is found in the
+    /// `LC_ALL`, `LC_MESSAGES`, and `LANG` environment variables.
+    /// Otherwise, the [`HyphenSplitter`] is used.
+    ///
+    /// **Note:** Only available when the `terminal_size` feature is
+    /// enabled.
+    #[cfg(feature = "terminal_size")]
+    pub fn for_terminal() -> Self {
+        let width = termwidth_with_fallback(80).clamp(40, 100) - 4;
+        Options::new(width)
+            .long_words(LongWords::Overflow)
+            .splitter(locale_splitter())
+    }
+}
+
+/// Find the user's locale. This is the first non-empty value of
+/// `LC_ALL`, `LC_MESSAGES`, and `LANG`.
+#[cfg(all(feature = "terminal_size", feature = "hyphenation"))]
+fn locale_env() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// The [`WordSplitter`] for the user's locale, see
+/// [`Options::for_terminal`].
+#[cfg(feature = "terminal_size")]
+fn locale_splitter() -> Box<dyn WordSplitter> {
+    #[cfg(feature = "hyphenation")]
+    {
+        if let Some(dictionary) = locale_env().and_then(|locale| dictionary_for_locale(&locale)) {
+            return Box::new(dictionary);
+        }
+    }
+    Box::new(HyphenSplitter)
 }
 
 impl<'a, S> Options<'a, S> {
@@ -347,21 +731,77 @@ impl<'a, S> Options<'a, S> {
     /// # let expected =
     /// Options {
     ///     width: width,
-    ///     initial_indent: "",
-    ///     subsequent_indent: "",
-    ///     break_words: true,
+    ///     initial_indent: "".into(),
+    ///     subsequent_indent: "".into(),
+    ///     long_words: textwrap::LongWords::Break,
     ///     #[cfg(feature = "smawk")]
     ///     wrap_algorithm: textwrap::core::WrapAlgorithm::OptimalFit,
     ///     #[cfg(not(feature = "smawk"))]
     ///     wrap_algorithm: textwrap::core::WrapAlgorithm::FirstFit,
+    ///     penalties: textwrap::core::Penalties::new(),
+    ///     max_lines: None,
+    ///     placeholder: "…",
+    ///     alignment: textwrap::Alignment::Left,
+    ///     detect_list_markers: false,
+    ///     word_break_marker: "",
+    ///     min_last_line_width: 0,
+    ///     width_fn: None,
+    ///     word_separator: textwrap::core::WordSeparator::AsciiSpace,
+    ///     break_rules: textwrap::core::BreakRules::none(),
+    ///     max_consecutive_hyphens: None,
+    ///     reapply_ansi_styles: false,
+    ///     width_measure: textwrap::core::WidthMeasure::UnicodeCells,
+    ///     expand_tabs: None,
+    ///     line_ending: "\n",
+    ///     sanitize_control_chars: false,
+    ///     hanging_indent_from_input: false,
+    ///     url_breaks: textwrap::UrlBreaks::Anywhere,
+    ///     balance: None,
+    ///     respect_newlines: true,
+    ///     sentence_per_line: false,
+    ///     abbreviations: textwrap::DEFAULT_ABBREVIATIONS,
+    ///     inline_markup: false,
+    ///     #[cfg(feature = "unicode-bidi")]
+    ///     bidi_reorder: false,
+    ///     hanging_punctuation: false,
+    ///     hanging_punctuation_chars: textwrap::DEFAULT_HANGING_PUNCTUATION,
+    ///     continuation_marker: "",
     ///     splitter: splitter,
     /// }
     /// # ;
     /// # assert_eq!(actual.width, expected.width);
     /// # assert_eq!(actual.initial_indent, expected.initial_indent);
     /// # assert_eq!(actual.subsequent_indent, expected.subsequent_indent);
-    /// # assert_eq!(actual.break_words, expected.break_words);
+    /// # assert_eq!(actual.long_words, expected.long_words);
     /// # assert_eq!(actual.wrap_algorithm, expected.wrap_algorithm);
+    /// # assert_eq!(actual.penalties, expected.penalties);
+    /// # assert_eq!(actual.max_lines, expected.max_lines);
+    /// # assert_eq!(actual.placeholder, expected.placeholder);
+    /// # assert_eq!(actual.alignment, expected.alignment);
+    /// # assert_eq!(actual.detect_list_markers, expected.detect_list_markers);
+    /// # assert_eq!(actual.word_break_marker, expected.word_break_marker);
+    /// # assert_eq!(actual.min_last_line_width, expected.min_last_line_width);
+    /// # assert!(actual.width_fn.is_none() && expected.width_fn.is_none());
+    /// # assert_eq!(actual.word_separator, expected.word_separator);
+    /// # assert_eq!(actual.break_rules, expected.break_rules);
+    /// # assert_eq!(actual.max_consecutive_hyphens, expected.max_consecutive_hyphens);
+    /// # assert_eq!(actual.reapply_ansi_styles, expected.reapply_ansi_styles);
+    /// # assert!(matches!(actual.width_measure, textwrap::core::WidthMeasure::UnicodeCells));
+    /// # assert_eq!(actual.expand_tabs, expected.expand_tabs);
+    /// # assert_eq!(actual.line_ending, expected.line_ending);
+    /// # assert_eq!(actual.sanitize_control_chars, expected.sanitize_control_chars);
+    /// # assert_eq!(actual.hanging_indent_from_input, expected.hanging_indent_from_input);
+    /// # assert_eq!(actual.url_breaks, expected.url_breaks);
+    /// # assert_eq!(actual.balance, expected.balance);
+    /// # assert_eq!(actual.respect_newlines, expected.respect_newlines);
+    /// # assert_eq!(actual.sentence_per_line, expected.sentence_per_line);
+    /// # assert_eq!(actual.abbreviations, expected.abbreviations);
+    /// # assert_eq!(actual.inline_markup, expected.inline_markup);
+    /// # #[cfg(feature = "unicode-bidi")]
+    /// # assert_eq!(actual.bidi_reorder, expected.bidi_reorder);
+    /// # assert_eq!(actual.hanging_punctuation, expected.hanging_punctuation);
+    /// # assert_eq!(actual.hanging_punctuation_chars, expected.hanging_punctuation_chars);
+    /// # assert_eq!(actual.continuation_marker, expected.continuation_marker);
     /// # let expected_coerced: Options<'static, NoHyphenation> = expected;
     /// ```
     ///
@@ -406,21 +846,99 @@ impl<'a, S> Options<'a, S> {
     pub const fn with_splitter(width: usize, splitter: S) -> Self {
         Options {
             width,
-            initial_indent: "",
-            subsequent_indent: "",
-            break_words: true,
+            initial_indent: Cow::Borrowed(""),
+            subsequent_indent: Cow::Borrowed(""),
+            long_words: LongWords::Break,
             #[cfg(feature = "smawk")]
             wrap_algorithm: core::WrapAlgorithm::OptimalFit,
             #[cfg(not(feature = "smawk"))]
             wrap_algorithm: core::WrapAlgorithm::FirstFit,
+            penalties: core::Penalties::new(),
+            max_lines: None,
+            placeholder: "…",
+            alignment: Alignment::Left,
+            detect_list_markers: false,
+            word_break_marker: "",
+            min_last_line_width: 0,
+            width_fn: None,
+            word_separator: core::WordSeparator::AsciiSpace,
+            break_rules: core::BreakRules::none(),
+            max_consecutive_hyphens: None,
+            reapply_ansi_styles: false,
+            width_measure: core::WidthMeasure::UnicodeCells,
+            expand_tabs: None,
+            line_ending: "\n",
+            sanitize_control_chars: false,
+            hanging_indent_from_input: false,
+            url_breaks: UrlBreaks::Anywhere,
+            balance: None,
+            respect_newlines: true,
+            sentence_per_line: false,
+            abbreviations: DEFAULT_ABBREVIATIONS,
+            inline_markup: false,
+            #[cfg(feature = "unicode-bidi")]
+            bidi_reorder: false,
+            hanging_punctuation: false,
+            hanging_punctuation_chars: DEFAULT_HANGING_PUNCTUATION,
+            continuation_marker: "",
             splitter: splitter,
         }
     }
+
+    /// Update [`self.width`] to the current terminal width.
+    ///
+    /// The width is found like in [`termwidth`], but if the terminal
+    /// width cannot be determined, the current width is kept instead
+    /// of falling back to 80 columns. This makes the method suitable
+    /// for long-running programs which call it regularly, e.g., when
+    /// the terminal is resized, to re-flow their output. Returns
+    /// `true` if the width changed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "Long-running programs re-flow their output.";
+    /// let mut options = Options::with_termwidth();
+    /// loop {
+    ///     // ... wait for the terminal to be resized ...
+    ///     if options.refresh_termwidth() {
+    ///         println!("{}", fill(text, &options));
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// **Note:** Only available when the `terminal_size` feature is
+    /// enabled.
+    ///
+    /// [`self.width`]: #structfield.width
+    #[cfg(feature = "terminal_size")]
+    pub fn refresh_termwidth(&mut self) -> bool {
+        self.refresh_width(columns_env().or_else(|| terminal_width(terminal_size::terminal_size())))
+    }
+
+    /// Update [`self.width`] to `width` if it is known. Returns `true`
+    /// if the width changed.
+    ///
+    /// [`self.width`]: #structfield.width
+    #[cfg(feature = "terminal_size")]
+    fn refresh_width(&mut self, width: Option<usize>) -> bool {
+        match width {
+            Some(width) if width != self.width => {
+                self.width = width;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl<'a, S: WordSplitter> Options<'a, S> {
     /// Change [`self.initial_indent`]. The initial indentation is
-    /// used on the very first line of output.
+    /// used on the very first line of output. See
+    /// [`Options::subsequent_indent`] for indentation which is as wide
+    /// as the line.
     ///
     /// # Examples
     ///
@@ -436,10 +954,21 @@ impl<'a, S: WordSplitter> Options<'a, S> {
     ///                 "little example."]);
     /// ```
     ///
+    /// The indentation can also be computed at runtime:
+    ///
+    /// ```
+    /// use textwrap::{Options, wrap};
+    ///
+    /// let options = Options::new(16).initial_indent(" ".repeat(4));
+    /// assert_eq!(wrap("This is a little example.", options),
+    ///            vec!["    This is a",
+    ///                 "little example."]);
+    /// ```
+    ///
     /// [`self.initial_indent`]: #structfield.initial_indent
-    pub fn initial_indent(self, indent: &'a str) -> Self {
+    pub fn initial_indent(self, indent: impl Into<Cow<'a, str>>) -> Self {
         Options {
-            initial_indent: indent,
+            initial_indent: indent.into(),
             ..self
         }
     }
@@ -472,39 +1001,132 @@ impl<'a, S: WordSplitter> Options<'a, S> {
     ///                 "  example."]);
     /// ```
     ///
+    /// # Wide Indentation
+    ///
+    /// When the indentation is as wide as the line, or wider, the
+    /// text is wrapped as if there was a single column left. The
+    /// lines overflow, but wrapping never fails. Use
+    /// [`Options::validate`] to reject such settings up front.
+    ///
+    /// Every line with a wide subsequent indentation holds at least
+    /// a single character:
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(4).subsequent_indent("世界");
+    /// assert_eq!(wrap("foo bar", &options), vec!["foo", "世界b", "世界a", "世界r"]);
+    /// ```
+    ///
+    /// The words are broken for the subsequent lines. This means that
+    /// with a wide [`Options::initial_indent`], a first word which
+    /// does not fit in the single column is moved to the second line,
+    /// as it is for any first line which is too narrow for the first
+    /// word. The first line then only holds the indentation, unless
+    /// long words overflow:
+    ///
+    /// ```
+    /// use textwrap::{wrap, LongWords, Options};
+    ///
+    /// let options = Options::new(3).initial_indent("    ");
+    /// assert_eq!(wrap("foo bar", &options), vec!["    ", "foo", "bar"]);
+    /// assert_eq!(wrap("a b", &options), vec!["    a", "b"]);
+    ///
+    /// let options = options.long_words(LongWords::Overflow);
+    /// assert_eq!(wrap("foo bar", &options), vec!["    foo", "bar"]);
+    /// ```
+    ///
     /// [`self.subsequent_indent`]: #structfield.subsequent_indent
-    pub fn subsequent_indent(self, indent: &'a str) -> Self {
+    pub fn subsequent_indent(self, indent: impl Into<Cow<'a, str>>) -> Self {
         Options {
-            subsequent_indent: indent,
+            subsequent_indent: indent.into(),
             ..self
         }
     }
 
-    /// Change [`self.break_words`]. This controls if words longer
-    /// than `self.width` can be broken, or if they will be left
-    /// sticking out into the right margin.
+    /// Change [`self.continuation_marker`]. The marker is put at the
+    /// start of every line which continues a wrapped input line,
+    /// i.e., on all lines except the first line produced from each
+    /// line of the input. This makes it possible to tell a wrapped
+    /// line from a line break in the input, as is often done when
+    /// showing source code or logs.
+    ///
+    /// The marker is put in front of [`self.subsequent_indent`],
+    /// which is still used on the continuation lines. The width of
+    /// the marker is counted against the line width, so that the
+    /// lines still fit within [`self.width`]. This also holds for
+    /// the last line when text is truncated by [`self.max_lines`].
+    ///
+    /// The default is an empty marker.
     ///
     /// # Examples
     ///
     /// ```
     /// use textwrap::{wrap, Options};
     ///
-    /// let options = Options::new(4).break_words(true);
-    /// assert_eq!(wrap("This is a little example.", options),
+    /// let options = Options::new(12).continuation_marker("↪ ");
+    /// assert_eq!(wrap("first line of text\nsecond line", &options),
+    ///            vec!["first line", "↪ of text", "second line"]);
+    ///
+    /// let options = options.subsequent_indent("  ");
+    /// assert_eq!(wrap("first line of text\nsecond line", &options),
+    ///            vec!["first line", "↪   of text", "  second", "↪   line"]);
+    /// ```
+    ///
+    /// [`self.continuation_marker`]: #structfield.continuation_marker
+    /// [`self.subsequent_indent`]: #structfield.subsequent_indent
+    /// [`self.width`]: #structfield.width
+    /// [`self.max_lines`]: #structfield.max_lines
+    pub fn continuation_marker(self, marker: &'a str) -> Self {
+        Options {
+            continuation_marker: marker,
+            ..self
+        }
+    }
+
+    /// Change [`self.long_words`]. This controls if words longer
+    /// than `self.width` are broken, if they are left sticking out
+    /// into the right margin, or if they are an error for
+    /// [`try_wrap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, LongWords, Options};
+    ///
+    /// let options = Options::new(4).long_words(LongWords::Break);
+    /// assert_eq!(wrap("This is a little example.", &options),
     ///            vec!["This",
     ///                 "is a",
     ///                 "litt",
     ///                 "le",
     ///                 "exam",
     ///                 "ple."]);
+    ///
+    /// let options = options.long_words(LongWords::Overflow);
+    /// assert_eq!(wrap("This is a little example.", &options),
+    ///            vec!["This",
+    ///                 "is a",
+    ///                 "little",
+    ///                 "example."]);
     /// ```
     ///
-    /// [`self.break_words`]: #structfield.break_words
+    /// [`self.long_words`]: #structfield.long_words
+    pub fn long_words(self, long_words: LongWords) -> Self {
+        Options { long_words, ..self }
+    }
+
+    /// Allow long words to be broken if they cannot fit on a line.
+    /// This sets [`self.long_words`] to [`LongWords::Break`] when
+    /// `setting` is `true` and to [`LongWords::Overflow`] otherwise.
+    ///
+    /// [`self.long_words`]: #structfield.long_words
+    #[deprecated(note = "use `Options::long_words` instead")]
     pub fn break_words(self, setting: bool) -> Self {
-        Options {
-            break_words: setting,
-            ..self
-        }
+        self.long_words(match setting {
+            true => LongWords::Break,
+            false => LongWords::Overflow,
+        })
     }
 
     /// Change [`self.wrap_algorithm`].
@@ -519,1186 +1141,9960 @@ impl<'a, S: WordSplitter> Options<'a, S> {
         }
     }
 
-    /// Change [`self.splitter`]. The [`WordSplitter`] is used to fit
-    /// part of a word into the current line when wrapping text.
+    /// Change [`self.penalties`].
     ///
-    /// This function may return a different type than `Self`. That is
-    /// the case when the given `splitter` is of a different type the
-    /// the currently stored one in the `splitter` field. Take for
-    /// example:
+    /// The penalties are only used by the optimal-fit algorithm. See
+    /// [`core::Penalties`] for details on the individual values.
+    ///
+    /// [`self.penalties`]: #structfield.penalties
+    pub fn penalties(self, penalties: core::Penalties) -> Self {
+        Options { penalties, ..self }
+    }
+
+    /// Change [`self.max_lines`]. At most `max_lines` lines of output
+    /// are produced. If text had to be omitted, the last line is
+    /// shortened so that [`self.placeholder`] fits after it within
+    /// `self.width`.
+    ///
+    /// # Examples
     ///
     /// ```
-    /// use textwrap::{HyphenSplitter, NoHyphenation, Options};
-    /// // The default type returned by `new` is `Options<HyphenSplitter>`
-    /// let opt: Options<HyphenSplitter> = Options::new(80);
-    /// // Setting a different splitter changes the type
-    /// let opt: Options<NoHyphenation> = opt.splitter(NoHyphenation);
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(15).max_lines(2);
+    /// assert_eq!(wrap("Memory safety without garbage collection.", &options),
+    ///            vec!["Memory safety",
+    ///                 "without…"]);
     /// ```
     ///
-    /// [`self.splitter`]: #structfield.splitter
-    pub fn splitter<T>(self, splitter: T) -> Options<'a, T> {
+    /// A single word which is too long for the last line is cut
+    /// short:
+    ///
+    /// ```
+    /// use textwrap::{wrap, LongWords, Options};
+    ///
+    /// let options = Options::new(8)
+    ///     .long_words(LongWords::Overflow)
+    ///     .max_lines(1)
+    ///     .placeholder("...");
+    /// assert_eq!(wrap("Supercalifragilistic words", &options),
+    ///            vec!["Super..."]);
+    /// ```
+    ///
+    /// [`self.max_lines`]: #structfield.max_lines
+    /// [`self.placeholder`]: #structfield.placeholder
+    pub fn max_lines(self, max_lines: usize) -> Self {
         Options {
-            width: self.width,
-            initial_indent: self.initial_indent,
-            subsequent_indent: self.subsequent_indent,
-            break_words: self.break_words,
-            wrap_algorithm: self.wrap_algorithm,
-            splitter: splitter,
+            max_lines: Some(max_lines),
+            ..self
         }
     }
-}
-
-/// Return the current terminal width.
-///
-/// If the terminal width cannot be determined (typically because the
-/// standard output is not connected to a terminal), a default width
-/// of 80 characters will be used.
-///
-/// # Examples
-///
-/// Create an [`Options`] for wrapping at the current terminal width
-/// with a two column margin to the left and the right:
-///
-/// ```no_run
-/// use textwrap::{termwidth, NoHyphenation, Options};
-///
-/// let width = termwidth() - 4; // Two columns on each side.
-/// let options = Options::new(width)
-///     .splitter(NoHyphenation)
-///     .initial_indent("  ")
-///     .subsequent_indent("  ");
-/// ```
-///
-/// **Note:** Only available when the `terminal_size` Cargo feature is
-/// enabled.
-#[cfg(feature = "terminal_size")]
-pub fn termwidth() -> usize {
-    terminal_size::terminal_size().map_or(80, |(terminal_size::Width(w), _)| w.into())
-}
-
-/// Fill a line of text at a given width.
-///
-/// The result is a [`String`], complete with newlines between each
-/// line. Use the [`wrap`] function if you need access to the
-/// individual lines.
-///
-/// The easiest way to use this function is to pass an integer for
-/// `width_or_options`:
-///
-/// ```
-/// use textwrap::fill;
-///
-/// assert_eq!(
-///     fill("Memory safety without garbage collection.", 15),
-///     "Memory safety\nwithout garbage\ncollection."
-/// );
-/// ```
-///
-/// If you need to customize the wrapping, you can pass an [`Options`]
-/// instead of an `usize`:
-///
-/// ```
-/// use textwrap::{fill, Options};
-///
-/// let options = Options::new(15)
-///     .initial_indent("- ")
-///     .subsequent_indent("  ");
-/// assert_eq!(
-///     fill("Memory safety without garbage collection.", &options),
-///     "- Memory safety\n  without\n  garbage\n  collection."
-/// );
-/// ```
-pub fn fill<'a, S, Opt>(text: &str, width_or_options: Opt) -> String
-where
-    S: WordSplitter,
-    Opt: Into<Options<'a, S>>,
-{
-    // This will avoid reallocation in simple cases (no
-    // indentation, no hyphenation).
-    let mut result = String::with_capacity(text.len());
 
-    for (i, line) in wrap(text, width_or_options).iter().enumerate() {
-        if i > 0 {
-            result.push('\n');
+    /// Change [`self.placeholder`]. The placeholder is put at the end
+    /// of the last line when text is omitted due to
+    /// [`self.max_lines`]. The default placeholder is `"…"`.
+    ///
+    /// The placeholder is measured in columns like the rest of the
+    /// text. If it is wider than the last line, it will itself be
+    /// cut short so that `self.width` is never exceeded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(15)
+    ///     .subsequent_indent("  ")
+    ///     .max_lines(2)
+    ///     .placeholder(" [...]");
+    /// assert_eq!(wrap("Memory safety without garbage collection.", &options),
+    ///            vec!["Memory safety",
+    ///                 "  without [...]"]);
+    /// ```
+    ///
+    /// [`self.placeholder`]: #structfield.placeholder
+    /// [`self.max_lines`]: #structfield.max_lines
+    pub fn placeholder(self, placeholder: &'a str) -> Self {
+        Options {
+            placeholder,
+            ..self
         }
-        result.push_str(&line);
     }
 
-    result
-}
-
-/// Unpack a paragraph of already-wrapped text.
-///
-/// This function attempts to recover the original text from a single
-/// paragraph of text produced by the [`fill`] function. This means
-/// that it turns
-///
-/// ```text
-/// textwrap: a small
-/// library for
-/// wrapping text.
-/// ```
-///
-/// back into
-///
-/// ```text
-/// textwrap: a small library for wrapping text.
-/// ```
-///
-/// In addition, it will recognize a common prefix among the lines.
-/// The prefix of the first line is returned in
-/// [`Options::initial_indent`] and the prefix (if any) of the the
-/// other lines is returned in [`Options::subsequent_indent`].
-///
-/// In addition to `' '`, the prefixes can consist of characters used
-/// for unordered lists (`'-'`, `'+'`, and `'*'`) and block quotes
-/// (`'>'`) in Markdown as well as characters often used for inline
-/// comments (`'#'` and `'/'`).
-///
-/// The text must come from a single wrapped paragraph. This means
-/// that there can be no `"\n\n"` within the text.
-///
-/// # Examples
-///
-/// ```
-/// use textwrap::unfill;
-///
-/// let (text, options) = unfill("\
-/// * This is an
-///   example of
-///   a list item.
-/// ");
-///
-/// assert_eq!(text, "This is an example of a list item.\n");
-/// assert_eq!(options.initial_indent, "* ");
-/// assert_eq!(options.subsequent_indent, "  ");
-/// ```
-pub fn unfill(text: &str) -> (String, Options<'_, HyphenSplitter>) {
-    let trimmed = text.trim_end_matches('\n');
-    let prefix_chars: &[_] = &[' ', '-', '+', '*', '>', '#', '/'];
+    /// Change [`self.alignment`]. See [`Alignment`] for the
+    /// available choices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Alignment, Options};
+    ///
+    /// let text = "Memory safety without garbage collection. \
+    ///             Concurrency without data races. \
+    ///             Zero-cost abstractions.";
+    /// let options = Options::new(30).alignment(Alignment::Justified);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Memory safety  without garbage",
+    ///                 "collection.        Concurrency",
+    ///                 "without data races.  Zero-cost",
+    ///                 "abstractions."]);
+    /// ```
+    ///
+    /// Centered lines are padded on both sides:
+    ///
+    /// ```
+    /// use textwrap::{fill, Alignment, Options};
+    ///
+    /// let options = Options::new(12).alignment(Alignment::Center);
+    /// assert_eq!(fill("Usage: foo [OPTIONS]", &options),
+    ///            " Usage: foo \n [OPTIONS]  ");
+    /// ```
+    ///
+    /// [`self.alignment`]: #structfield.alignment
+    pub fn alignment(self, alignment: Alignment) -> Self {
+        Options { alignment, ..self }
+    }
 
-    let mut options = Options::new(0);
-    for (idx, line) in trimmed.split('\n').enumerate() {
-        options.width = std::cmp::max(options.width, core::display_width(line));
-        let without_prefix = line.trim_start_matches(prefix_chars);
-        let prefix = &line[..line.len() - without_prefix.len()];
+    /// Change [`self.detect_list_markers`]. When enabled, a line
+    /// which starts with a list marker gets a hanging indentation:
+    /// the continuation lines are indented with spaces so that they
+    /// line up with the text after the marker. This replaces
+    /// [`self.subsequent_indent`] for such lines.
+    ///
+    /// The markers `-`, `*` and `+` are recognized, as well as
+    /// numbers and single letters followed by `.` or `)`, such as
+    /// `1.`, `1)`, and `a.`. The marker must be followed by a space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let options = Options::new(16).detect_list_markers(true);
+    /// assert_eq!(fill("- first item that is fairly long\n\
+    ///                  10. second item here", &options),
+    ///            "- first item\n  that is fairly\n  long\n10. second item\n    here");
+    /// ```
+    ///
+    /// [`self.detect_list_markers`]: #structfield.detect_list_markers
+    /// [`self.subsequent_indent`]: #structfield.subsequent_indent
+    pub fn detect_list_markers(self, setting: bool) -> Self {
+        Options {
+            detect_list_markers: setting,
+            ..self
+        }
+    }
 
-        if idx == 0 {
-            options.initial_indent = prefix;
-        } else if idx == 1 {
-            options.subsequent_indent = prefix;
-        } else if idx > 1 {
-            for ((idx, x), y) in prefix.char_indices().zip(options.subsequent_indent.chars()) {
-                if x != y {
-                    options.subsequent_indent = &prefix[..idx];
-                    break;
-                }
-            }
-            if prefix.len() < options.subsequent_indent.len() {
-                options.subsequent_indent = prefix;
-            }
+    /// Change [`self.word_break_marker`]. When [`self.long_words`]
+    /// forces a long word to be broken, the marker is inserted at the
+    /// end of every line where the word was broken. Room is made for
+    /// the width of the marker. The marker is not used when a word is
+    /// split by the [`self.splitter`] and it is dropped if the line is
+    /// too narrow to hold anything besides the marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(5).word_break_marker("\\");
+    /// assert_eq!(wrap("abcdefghij", &options), vec!["abcd\\", "efgh\\", "ij"]);
+    /// ```
+    ///
+    /// [`self.word_break_marker`]: #structfield.word_break_marker
+    /// [`self.long_words`]: #structfield.long_words
+    /// [`self.splitter`]: #structfield.splitter
+    pub fn word_break_marker(self, marker: &'a str) -> Self {
+        Options {
+            word_break_marker: marker,
+            ..self
         }
     }
 
-    let mut unfilled = String::with_capacity(text.len());
-    for (idx, line) in trimmed.split('\n').enumerate() {
-        if idx == 0 {
-            unfilled.push_str(&line[options.initial_indent.len()..]);
-        } else {
-            unfilled.push(' ');
-            unfilled.push_str(&line[options.subsequent_indent.len()..]);
+    /// Change [`self.min_last_line_width`]. When the last line of a
+    /// paragraph is narrower than this, the last word of the
+    /// previous line is moved down to the last line. This is only
+    /// done if the result still fits within [`self.width`] and if the
+    /// previous line keeps at least one word. Words split by the
+    /// [`self.splitter`] are moved as a whole, so a hyphenated
+    /// fragment is never left behind. The default value of `0`
+    /// disables this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::WrapAlgorithm::FirstFit;
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "The quick brown fox jumps over it";
+    /// let options = Options::new(30).wrap_algorithm(FirstFit);
+    /// assert_eq!(wrap(text, &options), vec!["The quick brown fox jumps over", "it"]);
+    ///
+    /// let options = options.min_last_line_width(5);
+    /// assert_eq!(wrap(text, &options), vec!["The quick brown fox jumps", "over it"]);
+    /// ```
+    ///
+    /// [`self.min_last_line_width`]: #structfield.min_last_line_width
+    /// [`self.width`]: #structfield.width
+    /// [`self.splitter`]: #structfield.splitter
+    pub fn min_last_line_width(self, width: usize) -> Self {
+        Options {
+            min_last_line_width: width,
+            ..self
         }
     }
 
-    unfilled.push_str(&text[trimmed.len()..]);
-    (unfilled, options)
-}
+    /// Change [`self.width_fn`]. The function is called with the
+    /// number of each line of output, starting from zero, and
+    /// returns the width of that line. This replaces
+    /// [`self.width`] and makes it possible to wrap text into
+    /// irregular shapes. The widths of the indentation are
+    /// subtracted from the returned width as usual.
+    ///
+    /// When [`self.long_words`] is [`LongWords::Break`], long words are broken so
+    /// that they fit on the narrowest line which may be needed for
+    /// the paragraph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::WrapAlgorithm::FirstFit;
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let shape = |line| if line < 2 { 10 } else { 15 };
+    /// let options = Options::new(0).wrap_algorithm(FirstFit).width_fn(&shape);
+    /// assert_eq!(
+    ///     wrap("The quick brown fox jumps over the lazy dog", &options),
+    ///     vec!["The quick", "brown fox", "jumps over the", "lazy dog"]
+    /// );
+    /// ```
+    ///
+    /// [`self.width_fn`]: #structfield.width_fn
+    /// [`self.width`]: #structfield.width
+    /// [`self.long_words`]: #structfield.long_words
+    pub fn width_fn<F: Fn(usize) -> usize + Sync>(self, width_fn: &'a F) -> Self {
+        Options {
+            width_fn: Some(WidthFn(width_fn)),
+            ..self
+        }
+    }
 
-/// Refill a paragraph of wrapped text with a new width.
-///
-/// This function will first use the [`unfill`] function to remove
-/// newlines from the text. Afterwards the text is filled again using
-/// the [`fill`] function.
-///
-/// The `new_width_or_options` argument specify the new width and can
-/// specify other options as well — except for
-/// [`Options::initial_indent`] and [`Options::subsequent_indent`],
-/// which are deduced from `filled_text`.
-///
-/// # Examples
-///
-/// ```
-/// use textwrap::refill;
-///
-/// // Some loosely wrapped text. The "> " prefix is recognized automatically.
-/// let text = "\
-/// > Memory
-/// > safety without garbage
-/// > collection.
-/// ";
-///
-/// assert_eq!(refill(text, 20), "\
-/// > Memory safety
-/// > without garbage
-/// > collection.
-/// ");
-///
-/// assert_eq!(refill(text, 40), "\
-/// > Memory safety without garbage
-/// > collection.
-/// ");
-///
-/// assert_eq!(refill(text, 60), "\
-/// > Memory safety without garbage collection.
-/// ");
-/// ```
-///
-/// You can also reshape bullet points:
-///
-/// ```
-/// use textwrap::refill;
-///
-/// let text = "\
-/// - This is my
-///   list item.
-/// ";
-///
-/// assert_eq!(refill(text, 20), "\
-/// - This is my list
-///   item.
-/// ");
-/// ```
-pub fn refill<'a, S, Opt>(filled_text: &str, new_width_or_options: Opt) -> String
-where
-    S: WordSplitter,
-    Opt: Into<Options<'a, S>>,
-{
-    let trimmed = filled_text.trim_end_matches('\n');
-    let (text, options) = unfill(trimmed);
-    let mut new_options = new_width_or_options.into();
-    new_options.initial_indent = options.initial_indent;
-    new_options.subsequent_indent = options.subsequent_indent;
-    let mut refilled = fill(&text, new_options);
-    refilled.push_str(&filled_text[trimmed.len()..]);
-    refilled
-}
+    /// Change [`self.word_separator`].
+    ///
+    /// See [`core::WordSeparator`] for details on the choices. Use
+    /// [`core::WordSeparator::Custom`] to plug in your own
+    /// [`core::WordFinder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::WordSeparator;
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "中文文本，没有空格。";
+    /// assert_eq!(wrap(text, 8), vec!["中文文本", "，没有空", "格。"]);
+    ///
+    /// let options = Options::new(8).word_separator(WordSeparator::UnicodeBreakProperties);
+    /// assert_eq!(wrap(text, &options), vec!["中文文", "本，没有", "空格。"]);
+    /// ```
+    ///
+    /// [`self.word_separator`]: #structfield.word_separator
+    pub fn word_separator(self, word_separator: core::WordSeparator) -> Self {
+        Options {
+            word_separator,
+            ..self
+        }
+    }
 
-/// Wrap a line of text at a given width.
-///
-/// The result is a vector of lines, each line is of type [`Cow<'_,
-/// str>`](Cow), which means that the line will borrow from the input
-/// `&str` if possible. The lines do not have trailing whitespace,
-/// including a final `'\n'`. Please use the [`fill`] function if you
-/// need a [`String`] instead.
-///
-/// The easiest way to use this function is to pass an integer for
-/// `width_or_options`:
-///
-/// ```
-/// use textwrap::wrap;
-///
-/// let lines = wrap("Memory safety without garbage collection.", 15);
-/// assert_eq!(lines, &[
-///     "Memory safety",
-///     "without garbage",
-///     "collection.",
-/// ]);
-/// ```
-///
-/// If you need to customize the wrapping, you can pass an [`Options`]
-/// instead of an `usize`:
-///
-/// ```
-/// use textwrap::{wrap, Options};
-///
-/// let options = Options::new(15)
-///     .initial_indent("- ")
-///     .subsequent_indent("  ");
-/// let lines = wrap("Memory safety without garbage collection.", &options);
-/// assert_eq!(lines, &[
-///     "- Memory safety",
-///     "  without",
-///     "  garbage",
-///     "  collection.",
-/// ]);
-/// ```
-///
-/// # Optimal-Fit Wrapping
-///
-/// By default, `wrap` will try to ensure an even right margin by
+    /// Change [`self.break_rules`]. The rules list characters which
+    /// must not start or end a line. See [`core::BreakRules`] for
+    /// details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::{BreakRules, WordSeparator};
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(8)
+    ///     .word_separator(WordSeparator::UnicodeBreakProperties)
+    ///     .break_rules(BreakRules::japanese());
+    /// assert_eq!(wrap("「こんにちは」と言った", &options),
+    ///            vec!["「こんに", "ちは」と", "言った"]);
+    /// ```
+    ///
+    /// [`self.break_rules`]: #structfield.break_rules
+    pub fn break_rules(self, break_rules: core::BreakRules<'a>) -> Self {
+        Options {
+            break_rules,
+            ..self
+        }
+    }
+
+    /// Change [`self.max_consecutive_hyphens`]. At most this many
+    /// lines in a row end with a word split by [`self.splitter`]. A
+    /// split that would exceed the limit is not used and the whole
+    /// word is moved to the next line instead. The count starts over
+    /// after a line which does not end in a split word and at the
+    /// start of every paragraph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    /// use textwrap::core::WrapAlgorithm::FirstFit;
+    ///
+    /// let text = "a well-known and long-lived self-made man";
+    /// let options = Options::new(13).wrap_algorithm(FirstFit);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["a well-known", "and long-", "lived self-", "made man"]);
+    ///
+    /// let options = options.max_consecutive_hyphens(1);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["a well-known", "and long-", "lived", "self-made man"]);
+    /// ```
+    ///
+    /// [`self.max_consecutive_hyphens`]: #structfield.max_consecutive_hyphens
+    /// [`self.splitter`]: #structfield.splitter
+    pub fn max_consecutive_hyphens(self, max: usize) -> Self {
+        Options {
+            max_consecutive_hyphens: Some(max),
+            ..self
+        }
+    }
+
+    /// Change [`self.reapply_ansi_styles`]. When enabled, the ANSI
+    /// SGR escape sequences (colors, bold, underline, etc.) which are
+    /// active at the end of a line are reset before the line ends, and
+    /// they are replayed after the indentation on the next line. This
+    /// way, each line is styled on its own: terminals which reset the
+    /// style at the end of a line show the continuation lines in the
+    /// right colors, and the indentation is never styled.
+    ///
+    /// The SGR sequences are replayed in the order they appeared since
+    /// the last reset (`ESC [ 0 m` or `ESC [ m`).
+    ///
+    /// OSC 8 hyperlinks are handled regardless of this setting: a
+    /// hyperlink which is open at the end of a line is always closed
+    /// there and opened again after the indentation on the next line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "\u{1b}[1mbold \u{1b}[31mand red\u{1b}[0m text";
+    /// let options = Options::new(8).subsequent_indent("> ").reapply_ansi_styles(true);
+    /// assert_eq!(
+    ///     wrap(text, &options),
+    ///     vec![
+    ///         "\u{1b}[1mbold \u{1b}[31mand\u{1b}[0m",
+    ///         "> \u{1b}[1m\u{1b}[31mred\u{1b}[0m",
+    ///         "> text",
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`self.reapply_ansi_styles`]: #structfield.reapply_ansi_styles
+    pub fn reapply_ansi_styles(self, reapply_ansi_styles: bool) -> Self {
+        Options {
+            reapply_ansi_styles,
+            ..self
+        }
+    }
+
+    /// Change [`self.width_measure`]. The measure is used for the
+    /// width of words, indentation, penalties, and placeholders, and
+    /// when words are broken apart. This means that
+    /// [`self.width`] is given in the unit of the measure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::WidthMeasure;
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(6)
+    ///     .width_measure(WidthMeasure::Chars)
+    ///     .subsequent_indent("» ");
+    /// assert_eq!(wrap("café au lait", &options), vec!["café", "» au", "» lait"]);
+    /// ```
+    ///
+    /// [`self.width_measure`]: #structfield.width_measure
+    /// [`self.width`]: #structfield.width
+    pub fn width_measure(self, width_measure: core::WidthMeasure) -> Self {
+        Options {
+            width_measure,
+            ..self
+        }
+    }
+
+    /// Measure characters with an ambiguous East Asian width as 2
+    /// columns wide. This changes [`self.width_measure`] between
+    /// [`WidthMeasure::UnicodeCells`] and
+    /// [`WidthMeasure::UnicodeCellsCjk`]. Other measures, such as
+    /// [`WidthMeasure::Bytes`], are kept unchanged.
+    ///
+    /// Characters such as `±`, `×`, `→`, and `②` have an ambiguous
+    /// width: they are 1 column wide in most Western terminals, but 2
+    /// columns wide in many terminals set up for Chinese, Japanese, or
+    /// Korean text. Turn this on if your output is shown in such a
+    /// terminal: otherwise, lines with these characters overflow. The
+    /// setting is used for the words, the indentation, and when words
+    /// are broken apart.
+    ///
+    /// This requires the `unicode-width` Cargo feature. Without it,
+    /// the setting has no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(6);
+    /// assert_eq!(wrap("±1 ±2", &options), vec!["±1 ±2"]);
+    ///
+    /// let options = options.cjk_ambiguous_wide(true);
+    /// # #[cfg(feature = "unicode-width")]
+    /// assert_eq!(wrap("±1 ±2", &options), vec!["±1", "±2"]);
+    /// ```
+    ///
+    /// [`self.width_measure`]: #structfield.width_measure
+    /// [`WidthMeasure::UnicodeCells`]: core::WidthMeasure::UnicodeCells
+    /// [`WidthMeasure::UnicodeCellsCjk`]: core::WidthMeasure::UnicodeCellsCjk
+    /// [`WidthMeasure::Bytes`]: core::WidthMeasure::Bytes
+    pub fn cjk_ambiguous_wide(self, wide: bool) -> Self {
+        let width_measure = match (self.width_measure, wide) {
+            (core::WidthMeasure::UnicodeCells, true) => core::WidthMeasure::UnicodeCellsCjk,
+            (core::WidthMeasure::UnicodeCellsCjk, false) => core::WidthMeasure::UnicodeCells,
+            (width_measure, _) => width_measure,
+        };
+        Options {
+            width_measure,
+            ..self
+        }
+    }
+
+    /// Change [`self.expand_tabs`]. The tabs in the text are replaced
+    /// by spaces up to the next tab stop before the text is wrapped,
+    /// see [`expand_tabs()`](crate::expand_tabs). The tab stops are
+    /// `tabstop` columns apart. Without this, tabs are kept and they
+    /// are not measured as moving to the next tab stop.
+    ///
+    /// Since the wrapped lines cannot borrow from the expanded text,
+    /// they are returned as owned strings when the text has tabs.
+    /// The ranges returned by [`wrap_ranges`] always refer to the
+    /// original text and do not use this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(12).expand_tabs(4);
+    /// assert_eq!(wrap("a\tb\tc\td", &options), vec!["a   b   c", "d"]);
+    /// ```
+    ///
+    /// [`self.expand_tabs`]: #structfield.expand_tabs
+    pub fn expand_tabs(self, tabstop: usize) -> Self {
+        Options {
+            expand_tabs: Some(tabstop),
+            ..self
+        }
+    }
+
+    /// Change [`self.line_ending`]. This is the line ending which
+    /// [`fill`], [`fill_paragraphs`], [`wrap_stream`], [`Wrapped`],
+    /// and [`fill_boxed`] put between the lines they produce,
+    /// including the empty lines kept between paragraphs. Use
+    /// `"\r\n"` to produce files for Windows, HTTP, or SMTP bodies.
+    /// The default is `"\n"`. Functions which return the lines
+    /// separately, such as [`wrap`], are not affected.
+    ///
+    /// The line endings in the input can be either `"\n"` or
+    /// `"\r\n"`, regardless of this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, fill_paragraphs, Options};
+    ///
+    /// let options = Options::new(10).line_ending("\r\n");
+    /// assert_eq!(fill("Memory safety\r\nmatters", &options),
+    ///            "Memory\r\nsafety\r\nmatters");
+    ///
+    /// let options = Options::new(10).line_ending("<br>\n");
+    /// assert_eq!(fill_paragraphs("Memory safety\n\nmatters", &options),
+    ///            "Memory<br>\nsafety<br>\n<br>\nmatters");
+    /// ```
+    ///
+    /// [`self.line_ending`]: #structfield.line_ending
+    pub fn line_ending(self, line_ending: &'a str) -> Self {
+        Options {
+            line_ending,
+            ..self
+        }
+    }
+
+    /// Change [`self.sanitize_control_chars`]. When enabled, the C0
+    /// and C1 control characters, such as BEL, backspace, and
+    /// vertical tab, are removed from the text before it is wrapped.
+    /// Line breaks (`'\n'` and `"\r\n"`), tabs, and the ANSI escape
+    /// sequences recognized by [`core::display_width`] are kept.
+    ///
+    /// Control characters have a width of zero regardless of this
+    /// setting, so they never make a line too wide. They can,
+    /// however, garble the output when it is shown in a terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Ding\u{7}!\u{8} \u{1b}[1mbold\u{1b}[0m";
+    /// let options = Options::new(80).sanitize_control_chars(true);
+    /// assert_eq!(wrap(text, &options), vec!["Ding! \u{1b}[1mbold\u{1b}[0m"]);
+    /// ```
+    ///
+    /// [`self.sanitize_control_chars`]: #structfield.sanitize_control_chars
+    pub fn sanitize_control_chars(self, sanitize_control_chars: bool) -> Self {
+        Options {
+            sanitize_control_chars,
+            ..self
+        }
+    }
+
+    /// Change [`self.hanging_indent_from_input`]. When enabled, the
+    /// leading whitespace of each paragraph is copied to the start of
+    /// its continuation lines. This replaces
+    /// [`self.subsequent_indent`] for such paragraphs. The whitespace
+    /// is copied verbatim, so a mix of tabs and spaces is kept, and
+    /// its width is subtracted from the width of the continuation
+    /// lines. Paragraphs without leading whitespace use
+    /// [`self.subsequent_indent`] as usual.
+    ///
+    /// Tabs have a width of zero when measured, see
+    /// [`core::display_width`]. Use [`Options::expand_tabs`] to turn
+    /// them into spaces first if they should take up room.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let options = Options::new(14).hanging_indent_from_input(true);
+    /// assert_eq!(fill("    Hello, wonderful world!\nA B C", &options),
+    ///            "    Hello,\n    wonderful\n    world!\nA B C");
+    /// ```
+    ///
+    /// [`self.hanging_indent_from_input`]: #structfield.hanging_indent_from_input
+    /// [`self.subsequent_indent`]: #structfield.subsequent_indent
+    pub fn hanging_indent_from_input(self, setting: bool) -> Self {
+        Options {
+            hanging_indent_from_input: setting,
+            ..self
+        }
+    }
+
+    /// Change [`self.url_breaks`]. This decides where URLs and
+    /// email addresses can be broken, see [`UrlBreaks`]. Breaking a
+    /// URL at an arbitrary character, or adding a hyphen to it,
+    /// corrupts the URL when it is copied from the output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options, UrlBreaks};
+    ///
+    /// let text = "See https://example.com/docs?page=2";
+    /// let options = Options::new(16);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["See", "https://example.", "com/docs?page=2"]);
+    ///
+    /// let options = options.url_breaks(UrlBreaks::Never);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["See", "https://example.com/docs?page=2"]);
+    ///
+    /// let options = options.url_breaks(UrlBreaks::AfterSeparators);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["See https://", "example.com/", "docs?page=2"]);
+    /// ```
+    ///
+    /// [`self.url_breaks`]: #structfield.url_breaks
+    pub fn url_breaks(self, url_breaks: UrlBreaks) -> Self {
+        Options { url_breaks, ..self }
+    }
+
+    /// Change [`self.balance`]. When set, a paragraph which is
+    /// wrapped into at most `max_lines` lines gets lines of roughly
+    /// equal length, like `text-wrap: balance` in CSS. The number of
+    /// lines is kept and the lines are never wider than allowed, but
+    /// the lines are broken so that the difference between the
+    /// longest and the shortest line becomes as small as possible.
+    ///
+    /// Balancing wraps the paragraph again for each narrower line
+    /// width, so `max_lines` should be small. Longer paragraphs are
+    /// wrapped as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "This is a fairly long heading that wraps";
+    /// let options = Options::new(36);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["This is a fairly long heading that", "wraps"]);
+    ///
+    /// let options = options.balance(6);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["This is a fairly long", "heading that wraps"]);
+    /// ```
+    ///
+    /// [`self.balance`]: #structfield.balance
+    pub fn balance(self, max_lines: usize) -> Self {
+        Options {
+            balance: Some(max_lines),
+            ..self
+        }
+    }
+
+    /// Change [`self.respect_newlines`]. By default, every `'\n'`
+    /// (or `"\r\n"`) in the text starts a new line and blank lines
+    /// are kept as empty lines. The lines after a line break use
+    /// the subsequent indentation.
+    ///
+    /// When set to `false`, the text is treated as a single paragraph
+    /// and each line break is replaced by a space before wrapping.
+    /// This is useful for text whose lines have been broken already,
+    /// but see [`refill`] and [`fill_paragraphs`] for more flexible
+    /// ways of re-wrapping such text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Memory\nsafety without\ngarbage collection.";
+    /// let options = Options::new(20).subsequent_indent("  ");
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Memory", "  safety without", "  garbage", "  collection."]);
+    ///
+    /// let options = options.respect_newlines(false);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Memory safety", "  without garbage", "  collection."]);
+    /// ```
+    ///
+    /// [`self.respect_newlines`]: #structfield.respect_newlines
+    pub fn respect_newlines(self, respect_newlines: bool) -> Self {
+        Options {
+            respect_newlines,
+            ..self
+        }
+    }
+
+    /// Change [`self.sentence_per_line`]. When enabled, every
+    /// sentence starts on a new line, regardless of the width. This
+    /// is known as semantic line breaks and it keeps the diffs of
+    /// prose in version control small. Sentences which are too long
+    /// for a line are wrapped as usual.
+    ///
+    /// A sentence ends with `.`, `!`, or `?`, possibly followed by
+    /// closing quotes or parentheses, when the next word starts with
+    /// an uppercase letter, possibly after opening quotes or
+    /// parentheses. Words in [`Options::abbreviations`], such
+    /// as `"e.g."` and `"Dr."`, and initials such as `"J."` do not end
+    /// a sentence. The lines after the first sentence use the
+    /// subsequent indentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "Dr. Smith is here. Is it true? Yes, it is: the \
+    ///             wrapping continues for long sentences.";
+    /// let options = Options::new(30).sentence_per_line(true);
+    /// assert_eq!(fill(text, &options),
+    ///            "Dr. Smith is here.\n\
+    ///             Is it true?\n\
+    ///             Yes, it is: the wrapping\n\
+    ///             continues for long sentences.");
+    /// ```
+    ///
+    /// [`self.sentence_per_line`]: #structfield.sentence_per_line
+    pub fn sentence_per_line(self, sentence_per_line: bool) -> Self {
+        Options {
+            sentence_per_line,
+            ..self
+        }
+    }
+
+    /// Change [`self.abbreviations`]. These are the words which do
+    /// not end a sentence when [`Options::sentence_per_line`] is
+    /// enabled. The default is [`DEFAULT_ABBREVIATIONS`], which can be
+    /// extended with more words:
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options, DEFAULT_ABBREVIATIONS};
+    ///
+    /// let text = "Ask Sgt. Pepper.";
+    /// let options = Options::new(80).sentence_per_line(true);
+    /// assert_eq!(wrap(text, &options), vec!["Ask Sgt.", "Pepper."]);
+    ///
+    /// let mut abbreviations = DEFAULT_ABBREVIATIONS.to_vec();
+    /// abbreviations.push("Sgt.");
+    /// let options = options.abbreviations(&abbreviations);
+    /// assert_eq!(wrap(text, &options), vec!["Ask Sgt. Pepper."]);
+    /// ```
+    ///
+    /// [`self.abbreviations`]: #structfield.abbreviations
+    pub fn abbreviations(self, abbreviations: &'a [&'a str]) -> Self {
+        Options {
+            abbreviations,
+            ..self
+        }
+    }
+
+    /// Find the first hard line break in `text`, see
+    /// [`core::WordSeparator::find_line_break`]. The end of a
+    /// sentence is a line break too with
+    /// [`Options::sentence_per_line`]. The start and end of the line
+    /// break are returned.
+    fn find_line_break(&self, text: &str) -> Option<(usize, usize)> {
+        let line_break = self.word_separator.find_line_break(text);
+        if !self.sentence_per_line {
+            return line_break;
+        }
+        let line_end = line_break.map_or(text.len(), |(idx, _)| idx);
+        find_sentence_end(&text[..line_end], self.abbreviations).or(line_break)
+    }
+
+    /// Change [`self.inline_markup`]. When enabled, inline Markdown
+    /// markup is kept intact when words are separated and broken:
+    ///
+    /// * A code span, such as `` `config_value` `` or `` `a b` ``, is
+    ///   a single word which is never broken, even by a hyphenating
+    ///   [`WordSplitter`] or by [`LongWords::Break`].
+    /// * The emphasis markers `*`, `**`, and `_` stay attached to the
+    ///   word next to them, so a marker never ends up alone at the
+    ///   start or end of a line. The word can still be split by the
+    ///   [`WordSplitter`], but it is not broken by
+    ///   [`LongWords::Break`].
+    ///
+    /// This works with any [`WordSplitter`] and
+    /// [`core::WordSeparator`]. The default is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Set `config_value` to **true**.";
+    /// let options = Options::new(10);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Set", "`config_va", "lue` to", "**true**."]);
+    ///
+    /// let options = options.inline_markup(true);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Set", "`config_value`", "to", "**true**."]);
+    /// ```
+    ///
+    /// [`self.inline_markup`]: #structfield.inline_markup
+    pub fn inline_markup(self, inline_markup: bool) -> Self {
+        Options {
+            inline_markup,
+            ..self
+        }
+    }
+
+    /// Change [`self.bidi_reorder`]. When enabled, the base direction
+    /// of each paragraph is found with [`core::paragraph_direction`]
+    /// and every wrapped line is reordered for display with
+    /// [`core::reorder_line`]. The line breaks are still found in the
+    /// logical order of the text, so only the output changes.
+    ///
+    /// The indentation is shown at the start of the paragraph, which
+    /// is the right side for right-to-left paragraphs. Use
+    /// [`Alignment::Right`] to line up right-to-left paragraphs at the
+    /// right edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(13)
+    ///     .initial_indent("* ")
+    ///     .subsequent_indent("  ")
+    ///     .bidi_reorder(true);
+    /// assert_eq!(
+    ///     wrap("שלום עולם hello world", &options),
+    ///     vec!["םלוע םולש *", "hello world  "]
+    /// );
+    /// ```
+    ///
+    /// **Note:** Only available when the `unicode-bidi` Cargo feature
+    /// is enabled.
+    ///
+    /// [`self.bidi_reorder`]: #structfield.bidi_reorder
+    #[cfg(feature = "unicode-bidi")]
+    pub fn bidi_reorder(self, bidi_reorder: bool) -> Self {
+        Options {
+            bidi_reorder,
+            ..self
+        }
+    }
+
+    /// Change [`self.hanging_punctuation`]. When enabled, a line
+    /// which starts with one of the
+    /// [`Options::hanging_punctuation_chars`] can be wider by the
+    /// width of that character. The character is meant to hang into
+    /// the margin, so that the text after it stays optically aligned
+    /// with the other lines. The width of the hanging character is
+    /// recorded in [`LineRange::hanging_punctuation`], so a renderer
+    /// can shift the line to the left. The default is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::WrapAlgorithm::FirstFit;
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "\"Memory safety\" without garbage collection.";
+    /// let options = Options::new(14).wrap_algorithm(FirstFit);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["\"Memory", "safety\"", "without", "garbage", "collection."]);
+    ///
+    /// let options = options.hanging_punctuation(true);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["\"Memory safety\"", "without", "garbage", "collection."]);
+    /// ```
+    ///
+    /// [`self.hanging_punctuation`]: #structfield.hanging_punctuation
+    pub fn hanging_punctuation(self, hanging_punctuation: bool) -> Self {
+        Options {
+            hanging_punctuation,
+            ..self
+        }
+    }
+
+    /// Change [`self.hanging_punctuation_chars`]. These are the
+    /// characters which hang into the margin when
+    /// [`Options::hanging_punctuation`] is enabled. The default is
+    /// [`DEFAULT_HANGING_PUNCTUATION`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(11).hanging_punctuation(true);
+    /// assert_eq!(wrap("«Bonjour» et merci", &options), vec!["«Bonjour»", "et merci"]);
+    ///
+    /// let options = options.hanging_punctuation_chars("«");
+    /// assert_eq!(wrap("«Bonjour» et merci", &options), vec!["«Bonjour» et", "merci"]);
+    /// ```
+    ///
+    /// [`self.hanging_punctuation_chars`]: #structfield.hanging_punctuation_chars
+    pub fn hanging_punctuation_chars(self, hanging_punctuation_chars: &'a str) -> Self {
+        Options {
+            hanging_punctuation_chars,
+            ..self
+        }
+    }
+
+    /// Change [`self.splitter`]. The [`WordSplitter`] is used to fit
+    /// part of a word into the current line when wrapping text.
+    ///
+    /// This function may return a different type than `Self`. That is
+    /// the case when the given `splitter` is of a different type the
+    /// the currently stored one in the `splitter` field. Take for
+    /// example:
+    ///
+    /// ```
+    /// use textwrap::{HyphenSplitter, NoHyphenation, Options};
+    /// // The default type returned by `new` is `Options<HyphenSplitter>`
+    /// let opt: Options<HyphenSplitter> = Options::new(80);
+    /// // Setting a different splitter changes the type
+    /// let opt: Options<NoHyphenation> = opt.splitter(NoHyphenation);
+    /// ```
+    ///
+    /// [`self.splitter`]: #structfield.splitter
+    pub fn splitter<T>(self, splitter: T) -> Options<'a, T> {
+        Options {
+            width: self.width,
+            initial_indent: self.initial_indent,
+            subsequent_indent: self.subsequent_indent,
+            long_words: self.long_words,
+            wrap_algorithm: self.wrap_algorithm,
+            penalties: self.penalties,
+            max_lines: self.max_lines,
+            placeholder: self.placeholder,
+            alignment: self.alignment,
+            detect_list_markers: self.detect_list_markers,
+            word_break_marker: self.word_break_marker,
+            min_last_line_width: self.min_last_line_width,
+            width_fn: self.width_fn,
+            word_separator: self.word_separator,
+            break_rules: self.break_rules,
+            max_consecutive_hyphens: self.max_consecutive_hyphens,
+            reapply_ansi_styles: self.reapply_ansi_styles,
+            width_measure: self.width_measure,
+            expand_tabs: self.expand_tabs,
+            line_ending: self.line_ending,
+            sanitize_control_chars: self.sanitize_control_chars,
+            hanging_indent_from_input: self.hanging_indent_from_input,
+            url_breaks: self.url_breaks,
+            balance: self.balance,
+            respect_newlines: self.respect_newlines,
+            sentence_per_line: self.sentence_per_line,
+            abbreviations: self.abbreviations,
+            inline_markup: self.inline_markup,
+            #[cfg(feature = "unicode-bidi")]
+            bidi_reorder: self.bidi_reorder,
+            hanging_punctuation: self.hanging_punctuation,
+            hanging_punctuation_chars: self.hanging_punctuation_chars,
+            continuation_marker: self.continuation_marker,
+            splitter: splitter,
+        }
+    }
+
+    /// Check that the settings make sense for wrapping text.
+    ///
+    /// The indentation and the placeholder (when [`self.max_lines`] is
+    /// set) must leave room for at least one column of text. The
+    /// [`Options::continuation_marker`] counts as part of the
+    /// subsequent indentation. The
+    /// widths are not checked when [`self.width_fn`] is used since
+    /// the line widths are then unknown.
+    ///
+    /// Options which fail the check can still be used for wrapping:
+    /// a single column is then used for the text, see
+    /// [`Options::subsequent_indent`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{Options, OptionsError};
+    ///
+    /// let options = Options::new(4).subsequent_indent("    ");
+    /// assert_eq!(options.validate(),
+    ///            Err(OptionsError::IndentWiderThanWidth { indent_width: 4, width: 4 }));
+    /// assert_eq!(Options::new(5).subsequent_indent("    ").validate(), Ok(()));
+    /// ```
+    ///
+    /// [`self.max_lines`]: #structfield.max_lines
+    /// [`self.width_fn`]: #structfield.width_fn
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        if self.width_fn.is_some() {
+            return Ok(());
+        }
+        if self.width == 0 {
+            return Err(OptionsError::ZeroWidth);
+        }
+        let indent_width = self.width_measure.width(&self.initial_indent).max(
+            self.width_measure.width(self.continuation_marker)
+                + self.width_measure.width(&self.subsequent_indent),
+        );
+        if indent_width >= self.width {
+            return Err(OptionsError::IndentWiderThanWidth {
+                indent_width,
+                width: self.width,
+            });
+        }
+        let placeholder_width = self.width_measure.width(self.placeholder);
+        if self.max_lines.is_some() && placeholder_width >= self.width {
+            return Err(OptionsError::PlaceholderWiderThanWidth {
+                placeholder_width,
+                width: self.width,
+            });
+        }
+        Ok(())
+    }
+
+    /// Count the lines produced when wrapping `text`.
+    ///
+    /// This gives the same result as `wrap(text, self).len()`, but
+    /// the lines themselves are never built. This makes it cheap to
+    /// find the height of a paragraph, e.g., when computing a layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(15).subsequent_indent("  ");
+    /// let text = "Memory safety without garbage collection.";
+    /// assert_eq!(options.line_count(text), 4);
+    /// assert_eq!(options.line_count(text), wrap(text, &options).len());
+    /// ```
+    pub fn line_count(&self, text: &str) -> usize {
+        let prepared = prepare_text(text, self);
+        let text = prepared.as_deref().unwrap_or(text);
+        let mut wrapper = LineWrapper::new(Options::from(self));
+        ::core::iter::from_fn(|| wrapper.next_line(text)).count()
+    }
+
+    /// Change [`self.splitter`] to a boxed [`WordSplitter`].
+    ///
+    /// This always returns the default `Options` type, which stores a
+    /// `Box<dyn WordSplitter>`. This makes it possible to change the
+    /// splitter at run time without changing the type:
+    ///
+    /// ```
+    /// use textwrap::{wrap, HyphenSplitter, NoHyphenation, Options};
+    ///
+    /// let mut options: Options = Options::new(5).splitter_boxed(HyphenSplitter);
+    /// assert_eq!(wrap("foo-bar", &options), vec!["foo-", "bar"]);
+    ///
+    /// options = options.splitter_boxed(NoHyphenation);
+    /// assert_eq!(wrap("foo-bar", &options), vec!["foo-b", "ar"]);
+    /// ```
+    ///
+    /// [`self.splitter`]: #structfield.splitter
+    pub fn splitter_boxed<T: WordSplitter + 'static>(
+        self,
+        splitter: T,
+    ) -> Options<'a, Box<dyn WordSplitter>> {
+        self.splitter(Box::new(splitter))
+    }
+
+    /// Change [`self.splitter`] to the hyphenation dictionary for the
+    /// language given by the locale `tag`, such as `"de-DE"` or
+    /// `"pt_BR.UTF-8"`.
+    ///
+    /// The dictionary is found with [`dictionary_for_locale`]. If no
+    /// dictionary is available for the locale, the
+    /// [`HyphenSplitter`] is used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(10).hyphenate_locale("en-US");
+    /// assert_eq!(wrap("Internationalization", &options),
+    ///            vec!["Interna-", "tionaliza-", "tion"]);
+    ///
+    /// let options = Options::new(10).hyphenate_locale("tlh");
+    /// assert_eq!(wrap("Internationalization", &options),
+    ///            vec!["Internatio", "nalization"]);
+    /// ```
+    ///
+    /// **Note:** Only available when the `hyphenation` Cargo feature is
+    /// enabled.
+    ///
+    /// [`self.splitter`]: #structfield.splitter
+    #[cfg(feature = "hyphenation")]
+    pub fn hyphenate_locale(self, tag: &str) -> Options<'a, Box<dyn WordSplitter>> {
+        match dictionary_for_locale(tag) {
+            Some(dictionary) => self.splitter_boxed(dictionary),
+            None => self.splitter_boxed(HyphenSplitter),
+        }
+    }
+}
+
+/// Error returned by [`Options::validate`] and [`Options::try_new`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum OptionsError {
+    /// The width is zero.
+    ZeroWidth,
+    /// The initial or subsequent indentation, including the
+    /// continuation marker, leaves no room for text.
+    IndentWiderThanWidth {
+        /// Width of the widest indentation.
+        indent_width: usize,
+        /// Width of the lines.
+        width: usize,
+    },
+    /// The placeholder used with [`Options::max_lines`] leaves no
+    /// room for text.
+    PlaceholderWiderThanWidth {
+        /// Width of the placeholder.
+        placeholder_width: usize,
+        /// Width of the lines.
+        width: usize,
+    },
+}
+
+impl ::core::fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        match *self {
+            OptionsError::ZeroWidth => write!(f, "width must be at least 1"),
+            OptionsError::IndentWiderThanWidth {
+                indent_width,
+                width,
+            } => write!(
+                f,
+                "indentation of width {} leaves no room in lines of width {}",
+                indent_width, width
+            ),
+            OptionsError::PlaceholderWiderThanWidth {
+                placeholder_width,
+                width,
+            } => write!(
+                f,
+                "placeholder of width {} leaves no room in lines of width {}",
+                placeholder_width, width
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OptionsError {}
+
+/// Errors returned by [`try_wrap`] and [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WrapError {
+    /// A word does not fit on a line and [`Options::long_words`] is
+    /// [`LongWords::Error`].
+    WordTooLong {
+        /// The first word which did not fit.
+        word: String,
+        /// Width of the word.
+        word_width: usize,
+        /// Width available for the word on its line, that is, the
+        /// line width minus the indentation.
+        available_width: usize,
+    },
+    /// A line is wider than the line width, but it holds more than a
+    /// single unbreakable word.
+    LineTooWide {
+        /// Index of the line.
+        line: usize,
+        /// Width of the line.
+        line_width: usize,
+        /// Width allowed for the line.
+        width: usize,
+    },
+    /// A line does not start with its indentation.
+    MissingIndent {
+        /// Index of the line.
+        line: usize,
+    },
+    /// A line has whitespace at the start or end which does not come
+    /// from the indentation, the text, or the alignment.
+    StrayWhitespace {
+        /// Index of the line.
+        line: usize,
+    },
+    /// The words of a line do not continue the words of the text. If
+    /// words are missing at the end, this is the number of lines.
+    WordsChanged {
+        /// Index of the line.
+        line: usize,
+    },
+}
+
+impl ::core::fmt::Display for WrapError {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            WrapError::WordTooLong {
+                word,
+                word_width,
+                available_width,
+            } => write!(
+                f,
+                "word {:?} of width {} does not fit in {} columns",
+                word, word_width, available_width
+            ),
+            WrapError::LineTooWide {
+                line,
+                line_width,
+                width,
+            } => write!(
+                f,
+                "line {} of width {} is wider than {} columns",
+                line, line_width, width
+            ),
+            WrapError::MissingIndent { line } => {
+                write!(f, "line {} does not start with its indentation", line)
+            }
+            WrapError::StrayWhitespace { line } => {
+                write!(f, "line {} has leading or trailing whitespace", line)
+            }
+            WrapError::WordsChanged { line } => {
+                write!(f, "line {} does not continue the words of the text", line)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WrapError {}
+
+/// Function which computes the width of each line of output.
+///
+/// This is used by [`Options::width_fn`] to wrap text into lines of
+/// varying width.
+#[derive(Copy, Clone)]
+pub struct WidthFn<'a>(&'a (dyn Fn(usize) -> usize + Sync));
+
+impl WidthFn<'_> {
+    /// Compute the width of line number `line`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::Options;
+    ///
+    /// let shape = |line| 10 + line;
+    /// let options = Options::new(80).width_fn(&shape);
+    /// assert_eq!(options.width_fn.unwrap().width(5), 15);
+    /// ```
+    pub fn width(&self, line: usize) -> usize {
+        (self.0)(line)
+    }
+}
+
+impl ::core::fmt::Debug for WidthFn<'_> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.write_str("WidthFn(..)")
+    }
+}
+
+/// Width of line number `line`, taking [`Options::width_fn`] into
+/// account.
+fn line_width<S: ?Sized>(options: &Options<'_, S>, line: usize) -> usize {
+    match options.width_fn {
+        Some(width_fn) => width_fn.width(line),
+        None => options.width,
+    }
+}
+
+/// Alignment of wrapped lines.
+///
+/// The alignment is applied after the text has been broken into
+/// lines, so it does not influence where the lines are broken. The
+/// width of the lines is measured with [`core::display_width`].
+/// Empty lines and lines which are already `width` columns wide (or
+/// wider) are left untouched.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alignment {
+    /// Lines are left-aligned. This is the default.
+    Left,
+    /// Lines are centered by padding them with spaces on both sides
+    /// up to the full width. If the padding cannot be split evenly,
+    /// the extra space goes to the right.
+    Center,
+    /// Lines are right-aligned by padding them with spaces on the
+    /// left.
+    Right,
+    /// Lines are stretched to fill the full width by widening the
+    /// gaps between words. Gaps after punctuation are widened first
+    /// and the remaining extra spaces are spread out evenly over the
+    /// line.
+    ///
+    /// The last line of each paragraph is left-aligned, as are lines
+    /// consisting of a single word and lines which are already too
+    /// long.
+    Justified,
+}
+
+/// Pad `line` with spaces to `width` columns.
+///
+/// The width of `line` is measured with [`core::display_width`], so
+/// wide characters such as CJK and emoji are counted correctly. The
+/// line is padded on the right for [`Alignment::Left`], on the left
+/// for [`Alignment::Right`], and on both sides for
+/// [`Alignment::Center`], where the odd space goes to the right. A
+/// single line cannot be justified, so [`Alignment::Justified`]
+/// pads like [`Alignment::Left`].
+///
+/// Lines which are already `width` columns wide (or wider) are
+/// returned unchanged and without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use textwrap::{pad, Alignment};
+///
+/// assert_eq!(format!("│{}│", pad("日本", 6, Alignment::Left)), "│日本  │");
+/// assert_eq!(pad("foo", 6, Alignment::Right), "   foo");
+/// assert!(matches!(pad("foo bar", 6, Alignment::Center), Cow::Borrowed("foo bar")));
+/// ```
+pub fn pad(line: &str, width: usize, alignment: Alignment) -> Cow<'_, str> {
+    pad_line(
+        Cow::Borrowed(line),
+        width,
+        alignment,
+        core::WidthMeasure::default(),
+    )
+}
+
+/// Center every line of `text` within `width` columns.
+///
+/// The lines are padded on both sides with [`pad`], and lines which
+/// are already too wide are kept as they are. The lines are separated
+/// by `'\n'` in the result.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::center;
+///
+/// assert_eq!(center("Memory\nsafety", 8), " Memory \n safety ");
+/// assert_eq!(center("😊", 5), " 😊  ");
+/// ```
+pub fn center(text: &str, width: usize) -> String {
+    let mut result = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        result.push_str(&pad(line, width, Alignment::Center));
+    }
+    result
+}
+
+/// Pad `line` to `width` columns according to `alignment`, see
+/// [`pad`].
+fn pad_line(
+    line: Cow<'_, str>,
+    width: usize,
+    alignment: Alignment,
+    measure: core::WidthMeasure,
+) -> Cow<'_, str> {
+    let extra = width.saturating_sub(measure.width(&line));
+    if extra == 0 {
+        return line;
+    }
+    let left = match alignment {
+        Alignment::Left | Alignment::Justified => 0,
+        Alignment::Center => extra / 2,
+        Alignment::Right => extra,
+    };
+    let mut padded = String::with_capacity(line.len() + extra);
+    padded.push_str(&" ".repeat(left));
+    padded.push_str(&line);
+    padded.push_str(&" ".repeat(extra - left));
+    Cow::Owned(padded)
+}
+
+/// How words which are too long to fit on a line are handled. See
+/// [`Options::long_words`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LongWords {
+    /// Long words are broken to fit on the lines. This is the
+    /// default.
+    Break,
+    /// Long words are put on lines of their own, which are then
+    /// longer than the width.
+    Overflow,
+    /// Long words overflow the lines like for [`LongWords::Overflow`],
+    /// but [`try_wrap`] returns an error. Use this when text which
+    /// does not fit is a mistake, e.g., in fixed-width formats.
+    Error,
+}
+
+/// The default [`Options::hanging_punctuation_chars`]: straight and
+/// curly opening quotes and the hyphen used for bullets.
+pub const DEFAULT_HANGING_PUNCTUATION: &str = "\"'“‘-";
+
+/// The default [`Options::abbreviations`]: common English
+/// abbreviations which do not end a sentence. These are also the
+/// abbreviations kept together with the following word by
+/// [`core::GlueRule::ABBREVIATION`].
+pub const DEFAULT_ABBREVIATIONS: &[&str] = &[
+    "e.g.", "i.e.", "etc.", "cf.", "vs.", "approx.", "Dr.", "Mr.", "Mrs.", "Ms.", "Prof.", "Sr.",
+    "Jr.", "St.", "No.", "no.", "Fig.", "fig.", "Vol.", "vol.", "p.", "pp.", "Ch.", "ch.", "Sec.",
+    "sec.", "Eq.", "eq.", "Inc.", "Ltd.",
+];
+
+/// Find the first sentence end in `line`, see
+/// [`Options::sentence_per_line`]. The end of the sentence and the
+/// start of the next sentence are returned.
+fn find_sentence_end(line: &str, abbreviations: &[&str]) -> Option<(usize, usize)> {
+    let mut search = 0;
+    while let Some(idx) = line[search..].find(['.', '!', '?']) {
+        let punctuation = search + idx;
+        search = punctuation + 1;
+        let end = punctuation
+            + 1
+            + line[punctuation + 1..]
+                .find(|ch| !matches!(ch, '.' | '!' | '?' | '"' | '\'' | '”' | '’' | ')' | ']'))
+                .unwrap_or(line.len() - punctuation - 1);
+        let rest = &line[end..];
+        let next = rest.trim_start_matches(' ');
+        let starts_sentence = next
+            .trim_start_matches(['"', '\'', '“', '‘', '(', '['])
+            .starts_with(char::is_uppercase);
+        if next.len() == rest.len() || !starts_sentence {
+            continue;
+        }
+        let word_start = line[..punctuation].rfind(' ').map_or(0, |idx| idx + 1);
+        let word = &line[word_start..end];
+        if core::is_initial(word) || abbreviations.contains(&word) {
+            continue;
+        }
+        return Some((end, line.len() - next.len()));
+    }
+    None
+}
+
+/// How URLs and email addresses are broken.
+///
+/// A word is seen as a URL if it contains `://` after a scheme such
+/// as `https`, and as an email address if it contains a `@` followed
+/// by a domain with a `.`. See [`Options::url_breaks`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UrlBreaks {
+    /// URLs are treated like other words: they are split by the
+    /// [`WordSplitter`] and broken by [`Options::long_words`]. This
+    /// is the default.
+    Anywhere,
+    /// URLs are never broken. A URL which is too long for a line
+    /// overflows the line instead.
+    Never,
+    /// URLs are only broken after `/`, `?`, `&`, and `#`, and no
+    /// hyphen is inserted. A part of a URL which is too long for a
+    /// line overflows the line.
+    AfterSeparators,
+}
+
+/// Return the current terminal width.
+///
+/// This is [`termwidth_with_fallback`] with a default width of 80
+/// characters, which is used if the terminal width cannot be
+/// determined (typically because the standard output is not
+/// connected to a terminal).
+///
+/// # Examples
+///
+/// Create an [`Options`] for wrapping at the current terminal width
+/// with a two column margin to the left and the right:
+///
+/// ```no_run
+/// use textwrap::{termwidth, NoHyphenation, Options};
+///
+/// let width = termwidth() - 4; // Two columns on each side.
+/// let options = Options::new(width)
+///     .splitter(NoHyphenation)
+///     .initial_indent("  ")
+///     .subsequent_indent("  ");
+/// ```
+///
+/// **Note:** Only available when the `terminal_size` Cargo feature is
+/// enabled.
+#[cfg(feature = "terminal_size")]
+pub fn termwidth() -> usize {
+    termwidth_with_fallback(80)
+}
+
+/// Return the current terminal width, or `default` if it cannot be
+/// determined.
+///
+/// The width is taken from the `COLUMNS` environment variable if it
+/// holds a sensible width. This lets users force a width, e.g., in
+/// CI or when the output is piped. Otherwise the width of the
+/// terminal connected to the standard output is used.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textwrap::termwidth_with_fallback;
+///
+/// // Prints 42 when run with COLUMNS=42 in the environment.
+/// println!("{}", termwidth_with_fallback(100));
+/// ```
+///
+/// **Note:** Only available when the `terminal_size` Cargo feature is
+/// enabled.
+#[cfg(feature = "terminal_size")]
+pub fn termwidth_with_fallback(default: usize) -> usize {
+    columns_env()
+        .or_else(|| terminal_width(terminal_size::terminal_size()))
+        .unwrap_or(default)
+}
+
+/// Return the width of the terminal connected to the standard error,
+/// or `default` if it cannot be determined.
+///
+/// This works like [`termwidth_with_fallback`], including the use of
+/// the `COLUMNS` environment variable, but it measures the standard
+/// error instead. Use this when wrapping diagnostics written to the
+/// standard error, which is often still a terminal when the standard
+/// output is redirected.
+///
+/// **Note:** Only available when the `terminal_size` Cargo feature is
+/// enabled.
+#[cfg(feature = "terminal_size")]
+pub fn stderr_termwidth_with_fallback(default: usize) -> usize {
+    #[cfg(unix)]
+    let size = {
+        use std::os::unix::io::AsRawFd;
+        terminal_size::terminal_size_using_fd(std::io::stderr().as_raw_fd())
+    };
+    #[cfg(windows)]
+    let size = {
+        use std::os::windows::io::AsRawHandle;
+        terminal_size::terminal_size_using_handle(std::io::stderr().as_raw_handle())
+    };
+    #[cfg(not(any(unix, windows)))]
+    let size = None;
+
+    columns_env()
+        .or_else(|| terminal_width(size))
+        .unwrap_or(default)
+}
+
+/// Parse the `COLUMNS` environment variable. Values which cannot be
+/// a terminal width are ignored.
+#[cfg(feature = "terminal_size")]
+fn columns_env() -> Option<usize> {
+    parse_columns(&std::env::var("COLUMNS").ok()?)
+}
+
+/// Parse a value of the `COLUMNS` environment variable.
+#[cfg(feature = "terminal_size")]
+fn parse_columns(columns: &str) -> Option<usize> {
+    columns
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&width| width > 0 && width <= usize::from(u16::MAX))
+}
+
+#[cfg(feature = "terminal_size")]
+fn terminal_width(size: Option<(terminal_size::Width, terminal_size::Height)>) -> Option<usize> {
+    size.map(|(terminal_size::Width(w), _)| w.into())
+        .filter(|&width| width > 0)
+}
+
+/// Fill a line of text at a given width.
+///
+/// The result is a [`String`], complete with newlines between each
+/// line. Use the [`wrap`] function if you need access to the
+/// individual lines.
+///
+/// The easiest way to use this function is to pass an integer for
+/// `width_or_options`:
+///
+/// ```
+/// use textwrap::fill;
+///
+/// assert_eq!(
+///     fill("Memory safety without garbage collection.", 15),
+///     "Memory safety\nwithout garbage\ncollection."
+/// );
+/// ```
+///
+/// If you need to customize the wrapping, you can pass an [`Options`]
+/// instead of an `usize`:
+///
+/// ```
+/// use textwrap::{fill, Options};
+///
+/// let options = Options::new(15)
+///     .initial_indent("- ")
+///     .subsequent_indent("  ");
+/// assert_eq!(
+///     fill("Memory safety without garbage collection.", &options),
+///     "- Memory safety\n  without\n  garbage\n  collection."
+/// );
+/// ```
+///
+/// Every `'\n'` or `"\r\n"` in `text` is kept as a line break. The
+/// lines are separated by [`Options::line_ending`]. Use
+/// [`fill_paragraphs`] if the text consists of paragraphs which have
+/// been wrapped already. Use [`fill_into`] to append the result to
+/// an existing `String`.
+pub fn fill<'a, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    // This will avoid reallocation in simple cases (no
+    // indentation, no hyphenation).
+    let mut result = String::with_capacity(text.len());
+    fill_into(text, width_or_options, &mut result);
+    result
+}
+
+/// Fill a line of text at a given width and append the result to
+/// `buf`.
+///
+/// This gives the same text as [`fill`], but the lines are appended
+/// to `buf` instead of a new `String`. Clearing and reusing one
+/// buffer avoids an allocation for each call, e.g., when many small
+/// pieces of text are wrapped for every frame drawn:
+///
+/// ```
+/// use textwrap::{fill_into, Options};
+///
+/// let options = Options::new(12);
+/// let mut buf = String::new();
+/// for text in &["Memory safety", "without garbage collection."] {
+///     buf.clear();
+///     fill_into(text, &options, &mut buf);
+///     println!("{}", buf);
+/// }
+/// assert_eq!(buf, "without\ngarbage\ncollection.");
+/// ```
+pub fn fill_into<'a, S, Opt>(text: &str, width_or_options: Opt, buf: &mut String)
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let line_ending = options.line_ending;
+
+    for (i, line) in wrap(text, options).iter().enumerate() {
+        if i > 0 {
+            buf.push_str(line_ending);
+        }
+        buf.push_str(line);
+    }
+}
+
+/// Text which is wrapped when it is displayed.
+///
+/// Formatting a `Wrapped` with `{}` gives the same text as [`fill`],
+/// but the lines are written straight to the formatter as they are
+/// computed by [`wrap_iter`]. No `String` is built for the filled
+/// text, which makes this useful in log messages and similar places:
+///
+/// ```
+/// use textwrap::{fill, Options, Wrapped};
+///
+/// let options = Options::new(15).subsequent_indent("  ");
+/// let text = "Memory safety without garbage collection.";
+/// assert_eq!(format!("{}", Wrapped::new(text, &options)),
+///            "Memory safety\n  without\n  garbage\n  collection.");
+/// assert_eq!(Wrapped::new(text, &options).to_string(), fill(text, &options));
+/// ```
+///
+/// The lines are separated by [`Options::line_ending`]. Formatter
+/// flags such as the width, fill, and alignment in `{:>30}` are
+/// ignored: use [`Options::alignment`] to align the lines.
+#[derive(Debug)]
+pub struct Wrapped<'a, 't, S: ?Sized = Box<dyn WordSplitter>> {
+    text: &'t str,
+    options: &'a Options<'a, S>,
+}
+
+impl<'a, 't, S: ?Sized> Wrapped<'a, 't, S> {
+    /// Wrap `text` with `options` when displayed.
+    pub fn new(text: &'t str, options: &'a Options<'a, S>) -> Self {
+        Wrapped { text, options }
+    }
+}
+
+impl<S: ?Sized + WordSplitter> ::core::fmt::Display for Wrapped<'_, '_, S> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        for (i, line) in wrap_iter(self.text, self.options).enumerate() {
+            if i > 0 {
+                f.write_str(self.options.line_ending)?;
+            }
+            f.write_str(&line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Wrapped lines which are updated incrementally when the text
+/// changes.
+///
+/// Every input line (that is, every `'\n'`-terminated paragraph) is
+/// wrapped by itself. When the text is changed with
+/// [`WrappedText::update`], the paragraphs at the start and at the
+/// end of the text which are unchanged keep their lines, and only
+/// the paragraphs between them are wrapped again. This is useful for
+/// editors which re-wrap a large buffer on every keystroke. The lines are
+/// always the same as those returned by [`wrap`] for the whole text:
+///
+/// ```
+/// use textwrap::{wrap, Options, WrappedText};
+///
+/// let mut wrapped = WrappedText::new(Options::new(15));
+/// wrapped.update("Memory safety without garbage collection.\nNo data races.");
+/// assert_eq!(wrapped.lines(), ["Memory safety", "without garbage",
+///                              "collection.", "No data races."]);
+///
+/// // Only the second paragraph is wrapped again.
+/// let text = "Memory safety without garbage collection.\nNo data races!";
+/// assert_eq!(wrapped.update(text), 3..4);
+/// assert_eq!(wrapped.lines(), wrap(text, 15));
+/// ```
+///
+/// Settings which link the paragraphs together, such as
+/// [`Options::max_lines`], [`Options::width_fn`],
+/// [`Options::reapply_ansi_styles`], and turning off
+/// [`Options::respect_newlines`], make every update wrap the whole
+/// text again. The same happens when the text contains OSC 8
+/// hyperlinks, since a hyperlink can span several paragraphs.
+#[derive(Debug)]
+pub struct WrappedText<'a, S = Box<dyn WordSplitter>> {
+    options: Options<'a, S>,
+    /// The prepared text of the last update.
+    text: String,
+    /// The byte range of each paragraph in `text` and its number of
+    /// lines. This is empty if the lines cannot be reused.
+    paragraphs: Vec<(::core::ops::Range<usize>, usize)>,
+    lines: Vec<String>,
+}
+
+impl<'a, S: WordSplitter> WrappedText<'a, S> {
+    /// Create an empty `WrappedText` which wraps text with the given
+    /// options. Call [`WrappedText::update`] to set the text.
+    pub fn new(options: Options<'a, S>) -> Self {
+        WrappedText {
+            options,
+            text: String::new(),
+            paragraphs: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// The options used for wrapping.
+    pub fn options(&self) -> &Options<'a, S> {
+        &self.options
+    }
+
+    /// The wrapped lines of the text given to the last
+    /// [`WrappedText::update`].
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Change the width used for wrapping. All paragraphs are wrapped
+    /// again on the next [`WrappedText::update`] if the width changes.
+    pub fn set_width(&mut self, width: usize) {
+        if self.options.width != width {
+            self.options.width = width;
+            self.paragraphs.clear();
+        }
+    }
+
+    /// Change the options used for wrapping. All paragraphs are
+    /// wrapped again on the next [`WrappedText::update`].
+    pub fn set_options(&mut self, options: Options<'a, S>) {
+        self.options = options;
+        self.paragraphs.clear();
+    }
+
+    /// Wrap `text`, reusing the wrapped paragraphs from earlier
+    /// updates. The range of lines which differ from the lines of the
+    /// previous update is returned. The range is empty if the lines
+    /// are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{Options, WrappedText};
+    ///
+    /// let mut wrapped = WrappedText::new(Options::new(10));
+    /// assert_eq!(wrapped.update("foo\nbar\nbaz"), 0..3);
+    /// assert_eq!(wrapped.update("foo\nbar\nbaz"), 3..3);
+    /// assert_eq!(wrapped.update("foo\nbar bar bar\nbaz"), 1..2);
+    /// assert_eq!(wrapped.lines(), ["foo", "bar bar", "bar", "baz"]);
+    /// ```
+    pub fn update(&mut self, text: &str) -> ::core::ops::Range<usize> {
+        if self.links_paragraphs() || text.contains("\x1b]8;") {
+            self.paragraphs.clear();
+            let lines = wrap(text, &self.options)
+                .into_iter()
+                .map(Cow::into_owned)
+                .collect();
+            let old = ::core::mem::replace(&mut self.lines, lines);
+            return changed_lines(&old, &self.lines, 0);
+        }
+        self.wrap_paragraphs(text)
+    }
+
+    /// Do the options make the wrapping of a paragraph depend on
+    /// the paragraphs before it?
+    fn links_paragraphs(&self) -> bool {
+        self.options.max_lines.is_some()
+            || self.options.width_fn.is_some()
+            || self.options.reapply_ansi_styles
+            || !self.options.respect_newlines
+    }
+
+    /// Wrap each paragraph of `text` by itself, keeping the lines of
+    /// the unchanged paragraphs at the start and end of the text.
+    fn wrap_paragraphs(&mut self, text: &str) -> ::core::ops::Range<usize> {
+        let prepared = prepare_text(text, &self.options);
+        let text = prepared.as_deref().unwrap_or(text);
+        let paragraphs = self.paragraph_ranges(text);
+
+        // A paragraph can only keep its lines if it is still the
+        // first paragraph, or still not the first paragraph, since
+        // the first line of the text gets the initial indent.
+        let old = &self.paragraphs;
+        let same = |(old, new): (
+            &(::core::ops::Range<usize>, usize),
+            &::core::ops::Range<usize>,
+        )| {
+            (old.0.start == 0) == (new.start == 0) && self.text[old.0.clone()] == text[new.clone()]
+        };
+        let prefix = old.iter().zip(&paragraphs).take_while(|&p| same(p)).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(paragraphs[prefix..].iter().rev())
+            .take_while(|&p| same(p))
+            .count();
+
+        let line_count = |paragraphs: &[(_, usize)]| paragraphs.iter().map(|(_, n)| n).sum();
+        let prefix_lines = line_count(&old[..prefix]);
+        let mut lines = ::core::mem::take(&mut self.lines);
+        let suffix_lines = lines.split_off(lines.len() - line_count(&old[old.len() - suffix..]));
+        let removed = lines.split_off(prefix_lines);
+
+        let mut counts = old.iter().map(|(_, n)| *n).collect::<Vec<_>>();
+        let kept_suffix = counts.split_off(counts.len() - suffix);
+        counts.truncate(prefix);
+        for range in &paragraphs[prefix..paragraphs.len() - suffix] {
+            // The line count only decides the indentation here, since
+            // the options with line numbers are handled by
+            // `links_paragraphs`.
+            let mut wrapper = LineWrapper::new(Options::from(&self.options));
+            wrapper.line_count = usize::from(range.start > 0);
+            let before = lines.len();
+            lines.extend(
+                ::core::iter::from_fn(|| wrapper.next_cow(&text[range.clone()]))
+                    .map(Cow::into_owned),
+            );
+            counts.push(lines.len() - before);
+        }
+        counts.extend(kept_suffix);
+        let changed = changed_lines(&removed, &lines[prefix_lines..], prefix_lines);
+        lines.extend(suffix_lines);
+
+        self.paragraphs = paragraphs.into_iter().zip(counts).collect();
+        self.text.clear();
+        self.text.push_str(text);
+        self.lines = lines;
+        changed
+    }
+
+    /// Find the byte ranges of the paragraphs of `text`.
+    fn paragraph_ranges(&self, text: &str) -> Vec<::core::ops::Range<usize>> {
+        let mut paragraphs = Vec::new();
+        let mut start = Some(0);
+        while let Some(offset) = start {
+            let end = match self.options.find_line_break(&text[offset..]) {
+                Some((idx, next)) => {
+                    start = Some(offset + next);
+                    offset + idx
+                }
+                None => {
+                    start = None;
+                    text.len()
+                }
+            };
+            paragraphs.push(offset..end);
+        }
+        paragraphs
+    }
+}
+
+/// Find the range of the `new` lines which differ from the `old`
+/// lines they replace. The range is shifted by `offset`.
+fn changed_lines(old: &[String], new: &[String], offset: usize) -> ::core::ops::Range<usize> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    offset + prefix..offset + new.len() - suffix
+}
+
+/// Fill a line of text at a given width and write it to `out`.
+///
+/// This produces the same text as [`fill`]: the lines are separated
+/// by [`Options::line_ending`]. The lines are computed one at a time
+/// with [`wrap_iter`] and written as soon as they are known, so the
+/// filled text is never held in memory.
+///
+/// # Errors
+///
+/// Returns the first error from writing to `out`. Nothing more is
+/// wrapped or written after the error.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fill_to_writer, Options};
+///
+/// let mut out = Vec::new();
+/// let options = Options::new(15).line_ending("\r\n");
+/// fill_to_writer("Memory safety without garbage collection.", &options, &mut out)?;
+/// assert_eq!(out, b"Memory safety\r\nwithout garbage\r\ncollection.");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn fill_to_writer<'a, S, Opt, W>(
+    text: &str,
+    width_or_options: Opt,
+    out: &mut W,
+) -> std::io::Result<()>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+    W: std::io::Write + ?Sized,
+{
+    let options = width_or_options.into();
+    let line_ending = options.line_ending;
+
+    for (i, line) in wrap_iter(text, options).enumerate() {
+        if i > 0 {
+            out.write_all(line_ending.as_bytes())?;
+        }
+        out.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Wrap a line of text at a given width and write the lines to
+/// `out`.
+///
+/// Every line produced by [`wrap`] is written followed by the
+/// [`Options::line_ending`], including the last line. The lines are written as soon as they
+/// are computed and lines which are plain slices of `text` are
+/// written without allocating.
+///
+/// # Errors
+///
+/// Returns the first error from writing to `out`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap_to_writer, Options};
+///
+/// let mut out = Vec::new();
+/// wrap_to_writer("Memory safety without garbage collection.",
+///                Options::new(15).initial_indent("> "), &mut out)?;
+/// assert_eq!(String::from_utf8(out).unwrap(),
+///            "> Memory safety\nwithout garbage\ncollection.\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn wrap_to_writer<'a, S, Opt, W>(
+    text: &str,
+    width_or_options: Opt,
+    out: &mut W,
+) -> std::io::Result<()>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+    W: std::io::Write + ?Sized,
+{
+    let options = width_or_options.into();
+    let line_ending = options.line_ending;
+
+    for line in wrap_iter(text, options) {
+        out.write_all(line.as_bytes())?;
+        out.write_all(line_ending.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Fill each paragraph of `text` at a given width.
+///
+/// Paragraphs are separated by one or more blank lines. The lines of
+/// each paragraph are joined and the paragraph is then filled by
+/// itself using [`fill`]. This means that the
+/// [`Options::initial_indent`] is used for the first line of every
+/// paragraph. The blank lines between the paragraphs are kept, but
+/// lines consisting only of whitespace become empty. The lines of the
+/// input can end with `'\n'` or `"\r\n"`, and the lines of the result
+/// end with [`Options::line_ending`].
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fill_paragraphs, Options};
+///
+/// let text = "Memory safety without\ngarbage collection.\n\n\
+///             Concurrency without\ndata races.";
+/// let options = Options::new(25).initial_indent("  ");
+/// assert_eq!(fill_paragraphs(text, &options),
+///            "  Memory safety without\ngarbage collection.\n\n  \
+///             Concurrency without\ndata races.");
+/// ```
+pub fn fill_paragraphs<'a, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let mut result = String::with_capacity(text.len());
+
+    for piece in paragraph_pieces(text) {
+        match piece {
+            ParagraphPiece::Paragraph(paragraph, _) => {
+                fill_into(&paragraph, &options, &mut result);
+            }
+            ParagraphPiece::LineEnding => result.push_str(options.line_ending),
+        }
+    }
+
+    result
+}
+
+/// What [`fill_paragraphs_with`] does with a paragraph.
+#[derive(Debug)]
+pub enum ParagraphAction<'o, S = Box<dyn WordSplitter>> {
+    /// Join the lines of the paragraph and fill it with the given
+    /// options, like [`fill_paragraphs`] does.
+    Wrap(&'o Options<'o, S>),
+    /// Keep the lines of the paragraph as they are. Only the line
+    /// endings are changed.
+    PassThrough,
+    /// Leave the paragraph out of the output. The blank lines around
+    /// it are kept.
+    Skip,
+}
+
+/// Fill each paragraph of `text`, with a `policy` deciding what to do
+/// with each paragraph.
+///
+/// The paragraphs are found like in [`fill_paragraphs`] and the blank
+/// lines between them are kept. The `policy` is called with the
+/// index of each paragraph, starting from zero, and the lines of the
+/// paragraph as they appear in `text`. The [`ParagraphAction`]
+/// returned by the `policy` decides if the paragraph is filled, kept
+/// as it is, or left out. This way, a document can use different
+/// indentation for quotes and list items and keep code blocks
+/// untouched. The [`Options::line_ending`] of `width_or_options` is
+/// used between the paragraphs.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fill_paragraphs_with, Options, ParagraphAction};
+///
+/// let text = "Some text which\nis wrapped.\n\n    let code = 1;\n\n- A list item which is long.";
+/// let options = Options::new(16);
+/// let item = Options::new(16).subsequent_indent("  ");
+/// let filled = fill_paragraphs_with(text, &options, |_, paragraph| {
+///     if paragraph.starts_with("    ") {
+///         ParagraphAction::PassThrough
+///     } else if paragraph.starts_with("- ") {
+///         ParagraphAction::Wrap(&item)
+///     } else {
+///         ParagraphAction::Wrap(&options)
+///     }
+/// });
+/// assert_eq!(
+///     filled,
+///     "Some text which\nis wrapped.\n\n    let code = 1;\n\n- A list item\n  which is long."
+/// );
+/// ```
+pub fn fill_paragraphs_with<'a, 'o, S, T, Opt, F>(
+    text: &str,
+    width_or_options: Opt,
+    policy: F,
+) -> String
+where
+    S: WordSplitter,
+    T: WordSplitter + 'o,
+    Opt: Into<Options<'a, S>>,
+    F: Fn(usize, &str) -> ParagraphAction<'o, T>,
+{
+    let options = width_or_options.into();
+    let mut result = String::with_capacity(text.len());
+
+    let mut index = 0;
+    let mut skipped = false;
+    for piece in paragraph_pieces(text) {
+        match piece {
+            ParagraphPiece::Paragraph(paragraph, range) => {
+                skipped = false;
+                match policy(index, &text[range.clone()]) {
+                    ParagraphAction::Wrap(options) => fill_into(&paragraph, options, &mut result),
+                    ParagraphAction::PassThrough => {
+                        for (i, line) in text[range].split('\n').enumerate() {
+                            if i > 0 {
+                                result.push_str(options.line_ending);
+                            }
+                            result.push_str(line.strip_suffix('\r').unwrap_or(line));
+                        }
+                    }
+                    ParagraphAction::Skip => skipped = true,
+                }
+                index += 1;
+            }
+            // The line ending after a skipped paragraph is skipped too.
+            ParagraphPiece::LineEnding if ::core::mem::take(&mut skipped) => {}
+            ParagraphPiece::LineEnding => result.push_str(options.line_ending),
+        }
+    }
+
+    result
+}
+
+/// Fill each paragraph of `text` at a given width, using several
+/// threads.
+///
+/// This gives the same text as [`fill_paragraphs`], but the
+/// paragraphs are filled in parallel by the [rayon] thread pool. The
+/// filled paragraphs are put back together in their original order,
+/// with the same blank lines between them. This is useful for large
+/// documents, such as a whole book, with many paragraphs.
+///
+/// The [`WordSplitter`] is shared between the threads, which is
+/// possible since all splitters are `Sync`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fill_paragraphs, fill_par, Options};
+///
+/// let text = "Memory safety without\ngarbage collection.\n\n\
+///             Concurrency without\ndata races.";
+/// let options = Options::new(25);
+/// assert_eq!(fill_par(text, &options), fill_paragraphs(text, &options));
+/// assert_eq!(fill_par(text, 20),
+///            "Memory safety\nwithout garbage\ncollection.\n\n\
+///             Concurrency without\ndata races.");
+/// ```
+///
+/// **Note:** Only available when the `rayon` Cargo feature is
+/// enabled.
+///
+/// [rayon]: https://docs.rs/rayon/
+#[cfg(feature = "rayon")]
+pub fn fill_par<'a, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    use rayon::prelude::*;
+
+    let options = width_or_options.into();
+    let pieces = paragraph_pieces(text)
+        .into_par_iter()
+        .map(|piece| match piece {
+            ParagraphPiece::Paragraph(paragraph, _) => Cow::Owned(fill(&paragraph, &options)),
+            ParagraphPiece::LineEnding => Cow::Borrowed(options.line_ending),
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = String::with_capacity(text.len());
+    for piece in pieces {
+        result.push_str(&piece);
+    }
+    result
+}
+
+/// Part of the output of [`fill_paragraphs`].
+#[derive(Debug, PartialEq)]
+enum ParagraphPiece {
+    /// The lines of a paragraph, joined by spaces, and the byte range
+    /// of the lines in the text.
+    Paragraph(String, ::core::ops::Range<usize>),
+    /// A line ending, either after a paragraph or for a blank line.
+    LineEnding,
+}
+
+/// Split `text` into the paragraphs and line endings which make up
+/// the output of [`fill_paragraphs`].
+fn paragraph_pieces(text: &str) -> Vec<ParagraphPiece> {
+    let mut pieces = Vec::new();
+    let mut paragraph = String::new();
+    let mut range = 0..0;
+    let mut offset = 0;
+
+    for (i, line) in text.split('\n').enumerate() {
+        let start = offset;
+        offset += line.len() + 1;
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                pieces.push(ParagraphPiece::Paragraph(
+                    ::core::mem::take(&mut paragraph),
+                    range.clone(),
+                ));
+            }
+            if i > 0 {
+                pieces.push(ParagraphPiece::LineEnding);
+            }
+            continue;
+        }
+
+        if paragraph.is_empty() {
+            if i > 0 {
+                pieces.push(ParagraphPiece::LineEnding);
+            }
+            paragraph.push_str(line.trim_end());
+            range.start = start;
+        } else {
+            paragraph.push(' ');
+            paragraph.push_str(line.trim());
+        }
+        range.end = start + line.len();
+    }
+    if !paragraph.is_empty() {
+        pieces.push(ParagraphPiece::Paragraph(paragraph, range));
+    }
+
+    pieces
+}
+
+/// Fill the paragraphs read from `reader` and write them to `writer`.
+///
+/// This produces the same output as [`fill_paragraphs`] applied to
+/// the whole input, but only one paragraph is held in memory at a
+/// time. This makes it possible to re-flow files which are too large
+/// to read into a single string. Both `'\n'` and `"\r\n"` are
+/// recognized in the input, and the output uses
+/// [`Options::line_ending`]. A final line without a trailing newline
+/// is wrapped like any other line.
+///
+/// # Errors
+///
+/// Returns the first error from reading or writing. The input must
+/// be valid UTF-8, otherwise an error of kind
+/// [`std::io::ErrorKind::InvalidData`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_stream;
+///
+/// let input = b"Memory safety without\r\ngarbage collection.\r\n\r\nNo data races.";
+/// let mut output = Vec::new();
+/// wrap_stream(&input[..], &mut output, 15)?;
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "Memory safety\nwithout garbage\ncollection.\n\nNo data races.");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn wrap_stream<'a, S, Opt, R, W>(
+    mut reader: R,
+    mut writer: W,
+    width_or_options: Opt,
+) -> std::io::Result<()>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+    R: std::io::BufRead,
+    W: std::io::Write,
+{
+    let options = width_or_options.into();
+    let mut paragraph = String::new();
+    let mut line = String::new();
+
+    // This follows fill_paragraphs with `i` counting the lines.
+    let mut i = 0;
+    let mut more = true;
+    while more {
+        line.clear();
+        more = reader.read_line(&mut line)? > 0 && line.ends_with('\n');
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                fill_to_writer(&paragraph, &options, &mut writer)?;
+                paragraph.clear();
+            }
+            if i > 0 {
+                writer.write_all(options.line_ending.as_bytes())?;
+            }
+        } else if paragraph.is_empty() {
+            if i > 0 {
+                writer.write_all(options.line_ending.as_bytes())?;
+            }
+            paragraph.push_str(line.trim_end());
+        } else {
+            paragraph.push(' ');
+            paragraph.push_str(line.trim());
+        }
+        i += 1;
+    }
+    if !paragraph.is_empty() {
+        fill_to_writer(&paragraph, &options, &mut writer)?;
+    }
+
+    Ok(())
+}
+
+/// Unpack a paragraph of already-wrapped text.
+///
+/// This function attempts to recover the original text from a single
+/// paragraph of text produced by the [`fill`] function. This means
+/// that it turns
+///
+/// ```text
+/// textwrap: a small
+/// library for
+/// wrapping text.
+/// ```
+///
+/// back into
+///
+/// ```text
+/// textwrap: a small library for wrapping text.
+/// ```
+///
+/// In addition, it will recognize a common prefix among the lines.
+/// The prefix of the first line is returned in
+/// [`Options::initial_indent`] and the prefix (if any) of the the
+/// other lines is returned in [`Options::subsequent_indent`].
+///
+/// In addition to `' '`, the prefixes can consist of characters used
+/// for unordered lists (`'-'`, `'+'`, and `'*'`) and block quotes
+/// (`'>'`) in Markdown as well as characters often used for inline
+/// comments (`'#'` and `'/'`).
+///
+/// The text must come from a single wrapped paragraph. This means
+/// that there can be no `"\n\n"` within the text.
+///
+/// # Hyphens
+///
+/// A line which ends with a hyphen after a letter or digit is joined
+/// with the following line without a space. This keeps words such as
+/// "well-known" intact when they were split at the hyphen. There is
+/// no way to tell such a hyphen apart from a hyphen inserted by a
+/// [`WordSplitter`] doing machine hyphenation, so the latter is kept
+/// as well:
+///
+/// ```
+/// use textwrap::unfill;
+///
+/// assert_eq!(unfill("a well-\nknown fact").0, "a well-known fact");
+/// assert_eq!(unfill("wrap-\nping text").0, "wrap-ping text");
+/// assert_eq!(unfill("foo -\nbar").0, "foo - bar");
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::unfill;
+///
+/// let (text, options) = unfill("\
+/// * This is an
+///   example of
+///   a list item.
+/// ");
+///
+/// assert_eq!(text, "This is an example of a list item.\n");
+/// assert_eq!(options.initial_indent, "* ");
+/// assert_eq!(options.subsequent_indent, "  ");
+/// ```
+pub fn unfill(text: &str) -> (String, Options<'_, HyphenSplitter>) {
+    let trimmed = text.trim_end_matches('\n');
+    let prefix_chars: &[_] = &[' ', '-', '+', '*', '>', '#', '/'];
+
+    let mut options = Options::new(0);
+    let mut initial_indent = "";
+    let mut subsequent_indent = "";
+    for (idx, line) in trimmed.split('\n').enumerate() {
+        options.width = ::core::cmp::max(options.width, core::display_width(line));
+        let without_prefix = line.trim_start_matches(prefix_chars);
+        let prefix = &line[..line.len() - without_prefix.len()];
+
+        if idx == 0 {
+            initial_indent = prefix;
+        } else if idx == 1 {
+            subsequent_indent = prefix;
+        } else if idx > 1 {
+            for ((idx, x), y) in prefix.char_indices().zip(subsequent_indent.chars()) {
+                if x != y {
+                    subsequent_indent = &prefix[..idx];
+                    break;
+                }
+            }
+            if prefix.len() < subsequent_indent.len() {
+                subsequent_indent = prefix;
+            }
+        }
+    }
+    options.initial_indent = Cow::Borrowed(initial_indent);
+    options.subsequent_indent = Cow::Borrowed(subsequent_indent);
+
+    let mut unfilled = String::with_capacity(text.len());
+    for (idx, line) in trimmed.split('\n').enumerate() {
+        if idx == 0 {
+            unfilled.push_str(&line[options.initial_indent.len()..]);
+        } else {
+            // A line ending with a hyphen is joined with the next
+            // line without a space. This keeps hyphenated words such
+            // as "well-known" intact.
+            let mut chars = unfilled.chars().rev();
+            let hyphenated = chars.next() == Some('-')
+                && chars.next().filter(|ch| ch.is_alphanumeric()).is_some();
+            if !hyphenated {
+                unfilled.push(' ');
+            }
+            unfilled.push_str(&line[options.subsequent_indent.len()..]);
+        }
+    }
+
+    unfilled.push_str(&text[trimmed.len()..]);
+    (unfilled, options)
+}
+
+/// Refill a paragraph of wrapped text with a new width.
+///
+/// This function will first use the [`unfill`] function to remove
+/// newlines from the text. Afterwards the text is filled again using
+/// the [`fill`] function.
+///
+/// The `new_width_or_options` argument specify the new width and can
+/// specify other options as well — except for
+/// [`Options::initial_indent`] and [`Options::subsequent_indent`],
+/// which are deduced from `filled_text`.
+///
+/// Blank lines separate paragraphs. Each paragraph is refilled by
+/// itself and the blank lines are kept unchanged. This means that you
+/// can refill an entire document in one go.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::refill;
+///
+/// // Some loosely wrapped text. The "> " prefix is recognized automatically.
+/// let text = "\
+/// > Memory
+/// > safety without garbage
+/// > collection.
+/// ";
+///
+/// assert_eq!(refill(text, 20), "\
+/// > Memory safety
+/// > without garbage
+/// > collection.
+/// ");
+///
+/// assert_eq!(refill(text, 40), "\
+/// > Memory safety without garbage
+/// > collection.
+/// ");
+///
+/// assert_eq!(refill(text, 60), "\
+/// > Memory safety without garbage collection.
+/// ");
+/// ```
+///
+/// You can also reshape bullet points:
+///
+/// ```
+/// use textwrap::refill;
+///
+/// let text = "\
+/// - This is my
+///   list item.
+/// ";
+///
+/// assert_eq!(refill(text, 20), "\
+/// - This is my list
+///   item.
+/// ");
+/// ```
+///
+/// Paragraphs are refilled one by one:
+///
+/// ```
+/// use textwrap::refill;
+///
+/// let text = "\
+/// // A paragraph
+/// // in a comment.
+///
+///   An indented
+///   paragraph.
+/// ";
+///
+/// assert_eq!(refill(text, 40), "\
+/// // A paragraph in a comment.
+///
+///   An indented paragraph.
+/// ");
+/// ```
+pub fn refill<'a, S, Opt>(filled_text: &str, new_width_or_options: Opt) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let new_options = new_width_or_options.into();
+    let mut refilled = String::with_capacity(filled_text.len());
+
+    // Start of the current paragraph and of the current line.
+    let mut start = 0;
+    let mut pos = 0;
+    for line in filled_text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if start < pos {
+                refilled.push_str(&refill_paragraph(&filled_text[start..pos], &new_options));
+            }
+            refilled.push_str(line);
+            start = pos + line.len();
+        }
+        pos += line.len();
+    }
+    if start < pos {
+        refilled.push_str(&refill_paragraph(&filled_text[start..], &new_options));
+    }
+
+    refilled
+}
+
+/// Refill a single paragraph, i.e., text without any blank lines.
+fn refill_paragraph<S: WordSplitter>(filled_text: &str, new_options: &Options<'_, S>) -> String {
+    let trimmed = filled_text.trim_end_matches('\n');
+    let (text, options) = unfill(trimmed);
+    let mut new_options = Options::from(new_options);
+    new_options.initial_indent = options.initial_indent;
+    new_options.subsequent_indent = options.subsequent_indent;
+    let mut refilled = fill(&text, new_options);
+    refilled.push_str(&filled_text[trimmed.len()..]);
+    refilled
+}
+
+/// Refill comment blocks while keeping their comment prefixes.
+///
+/// Each line is matched against the `prefixes`, such as `"//"` or
+/// `"#"`, after any leading whitespace. The longest matching prefix
+/// wins, so doc comments with `"///"` or `"//!"` are kept apart from
+/// plain `"//"` comments when all three are given. Consecutive lines
+/// with the same indentation and prefix form a block. The prefix, the
+/// indentation in front of it, and a single space after it are
+/// removed, the content is refilled to fit in the width minus the
+/// prefix, and the prefix is put back on every line.
+///
+/// A line with only the bare prefix separates paragraphs within a
+/// block. Lines without one of the `prefixes` are kept unchanged.
+///
+/// The `width_or_options` argument works like for [`fill`], but the
+/// indentation is replaced by the comment prefix.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::refill_prefixed;
+///
+/// let text = "\
+/// /// Returns the
+/// /// answer.
+/// ///
+/// /// Computed
+/// /// slowly.
+/// fn answer() -> u32 {
+///     // Deep thought
+///     // takes a while.
+///     42
+/// }
+/// ";
+///
+/// assert_eq!(refill_prefixed(text, 40, &["//", "///"]), "\
+/// /// Returns the answer.
+/// ///
+/// /// Computed slowly.
+/// fn answer() -> u32 {
+///     // Deep thought takes a while.
+///     42
+/// }
+/// ");
+/// ```
+pub fn refill_prefixed<'a, S, Opt>(text: &str, width_or_options: Opt, prefixes: &[&str]) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let mut refilled = String::with_capacity(text.len());
+
+    // The indentation and prefix of the current block, the prefix
+    // used when filling, and the content of the current paragraph.
+    let mut block = None;
+    let mut paragraph_prefix = String::new();
+    let mut paragraph = String::new();
+    let mut ending = "";
+    let flush = |paragraph: &mut String, prefix: &str, ending: &str, refilled: &mut String| {
+        if paragraph.is_empty() {
+            return;
+        }
+        let mut options = Options::from(&options);
+        options.initial_indent = Cow::Borrowed(prefix);
+        options.subsequent_indent = Cow::Borrowed(prefix);
+        refilled.push_str(&fill(paragraph, options));
+        refilled.push_str(ending);
+        paragraph.clear();
+    };
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let trimmed = content.trim_start();
+        let marker = prefixes
+            .iter()
+            .filter(|prefix| !prefix.is_empty() && trimmed.starts_with(**prefix))
+            .max_by_key(|prefix| prefix.len());
+        let lead = marker.map(|marker| &content[..content.len() - trimmed.len() + marker.len()]);
+
+        if lead != block {
+            flush(&mut paragraph, &paragraph_prefix, ending, &mut refilled);
+            block = lead;
+        }
+        let lead = match lead {
+            Some(lead) => lead,
+            None => {
+                refilled.push_str(line);
+                continue;
+            }
+        };
+
+        let rest = &content[lead.len()..];
+        if rest.trim().is_empty() {
+            flush(&mut paragraph, &paragraph_prefix, ending, &mut refilled);
+            refilled.push_str(lead);
+            refilled.push_str(&line[content.len()..]);
+            continue;
+        }
+        if paragraph.is_empty() {
+            paragraph_prefix.clear();
+            paragraph_prefix.push_str(lead);
+            if rest.starts_with(' ') {
+                paragraph_prefix.push(' ');
+            }
+        } else {
+            paragraph.push(' ');
+        }
+        paragraph.push_str(rest.strip_prefix(' ').unwrap_or(rest));
+        ending = &line[content.len()..];
+    }
+    flush(&mut paragraph, &paragraph_prefix, ending, &mut refilled);
+
+    refilled
+}
+
+/// Reflow quoted email text.
+///
+/// Lines are grouped by their quoting depth, i.e., the number of `>`
+/// characters at the start of the line. Quotes such as `>`, `> >`,
+/// and `>>>` are recognized. Each group of consecutive lines with the
+/// same depth is unwrapped and filled again, after which the quote
+/// prefix is written back as `"> "` per level. The width of the
+/// prefix is subtracted from the width. A line with only a prefix
+/// separates paragraphs, it is written without trailing whitespace.
+///
+/// A signature block, which starts with a line consisting of `"-- "`,
+/// is kept unchanged until the quoting depth changes.
+///
+/// The `width_or_options` argument works like for [`fill`], but the
+/// indentation is replaced by the quote prefix.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::reflow_quoted;
+///
+/// let text = ">> Can we meet\n>> tomorrow?\n\
+///             > Yes, at noon in the usual\n> place.\n>\n\
+///             > -- \n> Alice\n\
+///             Great!\n";
+///
+/// assert_eq!(reflow_quoted(text, 20),
+///            "> > Can we meet\n> > tomorrow?\n\
+///             > Yes, at noon in\n> the usual place.\n>\n\
+///             > -- \n> Alice\n\
+///             Great!\n");
+/// ```
+pub fn reflow_quoted<'a, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let mut reflowed = String::with_capacity(text.len());
+
+    // The depth of the current group, the content of the current
+    // paragraph, and the line ending of its last line.
+    let mut group = None;
+    let mut paragraph = String::new();
+    let mut ending = "";
+    let mut signature = false;
+    let flush = |paragraph: &mut String, depth: usize, ending: &str, reflowed: &mut String| {
+        if paragraph.is_empty() {
+            return;
+        }
+        let prefix = "> ".repeat(depth);
+        let mut options = Options::from(&options);
+        options.initial_indent = Cow::Borrowed(&prefix);
+        options.subsequent_indent = Cow::Borrowed(&prefix);
+        reflowed.push_str(&fill(paragraph, options));
+        reflowed.push_str(ending);
+        paragraph.clear();
+    };
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let (depth, rest) = quote_depth(content);
+
+        if group != Some(depth) {
+            if let Some(depth) = group {
+                flush(&mut paragraph, depth, ending, &mut reflowed);
+            }
+            group = Some(depth);
+            signature = false;
+        }
+        if rest == "-- " {
+            flush(&mut paragraph, depth, ending, &mut reflowed);
+            signature = true;
+        }
+        if signature {
+            reflowed.push_str(line);
+            continue;
+        }
+
+        if rest.trim().is_empty() {
+            flush(&mut paragraph, depth, ending, &mut reflowed);
+            reflowed.push_str("> ".repeat(depth).trim_end());
+            reflowed.push_str(&line[content.len()..]);
+            continue;
+        }
+        if !paragraph.is_empty() {
+            paragraph.push(' ');
+        }
+        paragraph.push_str(rest);
+        ending = &line[content.len()..];
+    }
+    if let Some(depth) = group {
+        flush(&mut paragraph, depth, ending, &mut reflowed);
+    }
+
+    reflowed
+}
+
+/// Return the quoting depth of `line` and the text after the quote
+/// prefix, see [`reflow_quoted`].
+fn quote_depth(line: &str) -> (usize, &str) {
+    let mut depth = 0;
+    let mut end = 0;
+    for (idx, ch) in line.char_indices() {
+        match ch {
+            '>' => {
+                depth += 1;
+                end = idx + 1;
+            }
+            ' ' => continue,
+            _ => break,
+        }
+    }
+    match depth {
+        0 => (0, line),
+        _ => (depth, line[end..].trim_start_matches(' ')),
+    }
+}
+
+/// Collapse and truncate `text` to fit in `width` columns.
+///
+/// All runs of whitespace are first collapsed into a single space
+/// and leading and trailing whitespace is removed. If the result
+/// fits in `width`, it is returned. Otherwise, as many whole words
+/// as possible are kept and the `placeholder` is appended so that
+/// the result still fits in `width`. This works like
+/// [`textwrap.shorten`] in Python.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::shorten;
+///
+/// assert_eq!(shorten("Hello  world!", 12, "..."), "Hello world!");
+/// assert_eq!(shorten("Hello  world!", 11, "..."), "Hello...");
+/// ```
+///
+/// The width is measured in columns, see [`core::display_width`].
+/// If not even the first word fits before the placeholder, the word
+/// is cut at a character boundary:
+///
+/// ```
+/// use textwrap::shorten;
+///
+/// assert_eq!(shorten("Supercalifragilistic", 8, "..."), "Super...");
+/// assert_eq!(shorten("😂😭🥺🤣", 7, "..."), "😂😭...");
+/// ```
+///
+/// If the placeholder itself is wider than `width`, it is cut as
+/// well so that the result never exceeds `width`.
+///
+/// Non-breaking spaces (U+00A0, U+2007, and U+202F) are not
+/// collapsed, so words joined by them are kept or dropped together:
+///
+/// ```
+/// use textwrap::shorten;
+///
+/// assert_eq!(shorten("See  Fig.\u{a0}7", 9, "..."), "See...");
+/// ```
+///
+/// The result borrows from `text` when there is no whitespace to
+/// collapse and no truncation was needed.
+///
+/// [`textwrap.shorten`]: https://docs.python.org/3/library/textwrap.html#textwrap.shorten
+pub fn shorten<'a>(text: &'a str, width: usize, placeholder: &str) -> Cow<'a, str> {
+    let trimmed = text.trim_matches(is_breaking_whitespace);
+    let collapsed = if trimmed.contains("  ")
+        || trimmed.contains(|ch| ch != ' ' && is_breaking_whitespace(ch))
+    {
+        Cow::Owned(
+            trimmed
+                .split(is_breaking_whitespace)
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    } else {
+        Cow::Borrowed(trimmed)
+    };
+
+    if core::display_width(&collapsed) <= width {
+        return collapsed;
+    }
+
+    let placeholder = truncate_to_width(placeholder, width, core::WidthMeasure::UnicodeCells);
+    let available = width - core::display_width(placeholder);
+
+    // Find the end of the last whole word which fits.
+    let mut end = 0;
+    let mut line_width = 0;
+    for (idx, word) in collapsed.split(' ').enumerate() {
+        let word_width = core::display_width(word) + if idx > 0 { 1 } else { 0 };
+        if line_width + word_width > available {
+            break;
+        }
+        line_width += word_width;
+        end += word.len() + if idx > 0 { 1 } else { 0 };
+    }
+
+    let content = if end == 0 {
+        truncate_to_width(&collapsed, available, core::WidthMeasure::UnicodeCells)
+    } else {
+        &collapsed[..end]
+    };
+
+    let mut result = String::with_capacity(content.len() + placeholder.len());
+    result.push_str(content);
+    result.push_str(placeholder);
+    Cow::Owned(result)
+}
+
+/// Is `ch` whitespace which allows a line break? This excludes the
+/// non-breaking spaces.
+fn is_breaking_whitespace(ch: char) -> bool {
+    ch.is_whitespace() && !matches!(ch, '\u{a0}' | '\u{2007}' | '\u{202f}')
+}
+
+/// Wrap a line of text at a given width.
+///
+/// The result is a vector of lines, each line is of type [`Cow<'_,
+/// str>`](Cow), which means that the line will borrow from the input
+/// `&str` if possible. The lines do not have trailing whitespace,
+/// including a final `'\n'`. Please use the [`fill`] function if you
+/// need a [`String`] instead.
+///
+/// The easiest way to use this function is to pass an integer for
+/// `width_or_options`:
+///
+/// ```
+/// use textwrap::wrap;
+///
+/// let lines = wrap("Memory safety without garbage collection.", 15);
+/// assert_eq!(lines, &[
+///     "Memory safety",
+///     "without garbage",
+///     "collection.",
+/// ]);
+/// ```
+///
+/// If you need to customize the wrapping, you can pass an [`Options`]
+/// instead of an `usize`:
+///
+/// ```
+/// use textwrap::{wrap, Options};
+///
+/// let options = Options::new(15)
+///     .initial_indent("- ")
+///     .subsequent_indent("  ");
+/// let lines = wrap("Memory safety without garbage collection.", &options);
+/// assert_eq!(lines, &[
+///     "- Memory safety",
+///     "  without",
+///     "  garbage",
+///     "  collection.",
+/// ]);
+/// ```
+///
+/// # Optimal-Fit Wrapping
+///
+/// By default, `wrap` will try to ensure an even right margin by
 /// finding breaks which avoid short lines. We call this an
 /// “optimal-fit algorithm” since the line breaks are computed by
 /// considering all possible line breaks. The alternative is a
 /// “first-fit algorithm” which simply accumulates words until they no
 /// longer fit on the line.
 ///
-/// As an example, using the first-fit algorithm to wrap the famous
-/// Hamlet quote “To be, or not to be: that is the question” in a
-/// narrow column with room for only 10 characters looks like this:
+/// As an example, using the first-fit algorithm to wrap the famous
+/// Hamlet quote “To be, or not to be: that is the question” in a
+/// narrow column with room for only 10 characters looks like this:
+///
+/// ```
+/// # use textwrap::{Options, wrap};
+/// # use textwrap::core::WrapAlgorithm::FirstFit;
+/// #
+/// # let lines = wrap("To be, or not to be: that is the question",
+/// #                  Options::new(10).wrap_algorithm(FirstFit));
+/// # assert_eq!(lines.join("\n") + "\n", "\
+/// To be, or
+/// not to be:
+/// that is
+/// the
+/// question
+/// # ");
+/// ```
+///
+/// Notice how the second to last line is quite narrow because
+/// “question” was too large to fit? The greedy first-fit algorithm
+/// doesn’t look ahead, so it has no other option than to put
+/// “question” onto its own line.
+///
+/// With the optimal-fit wrapping algorithm, the previous lines are
+/// shortened slightly in order to make the word “is” go into the
+/// second last line:
+///
+/// ```
+/// # #[cfg(feature = "smawk")] {
+/// # use textwrap::{Options, wrap};
+/// # use textwrap::core::WrapAlgorithm::OptimalFit;
+/// #
+/// # let lines = wrap("To be, or not to be: that is the question",
+/// #                  Options::new(10).wrap_algorithm(OptimalFit));
+/// # assert_eq!(lines.join("\n") + "\n", "\
+/// To be,
+/// or not to
+/// be: that
+/// is the
+/// question
+/// # "); }
+/// ```
+///
+/// Please see [`core::WrapAlgorithm`] for details.
+///
+/// # Examples
+///
+/// The returned iterator yields lines of type `Cow<'_, str>`. If
+/// possible, the wrapped lines will borrow from the input string. As
+/// an example, a hanging indentation, the first line can borrow from
+/// the input, but the subsequent lines become owned strings:
+///
+/// ```
+/// use std::borrow::Cow::{Borrowed, Owned};
+/// use textwrap::{wrap, Options};
+///
+/// let options = Options::new(15).subsequent_indent("....");
+/// let lines = wrap("Wrapping text all day long.", &options);
+/// let annotated = lines
+///     .iter()
+///     .map(|line| match line {
+///         Borrowed(text) => format!("[Borrowed] {}", text),
+///         Owned(text) => format!("[Owned]    {}", text),
+///     })
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     annotated,
+///     &[
+///         "[Borrowed] Wrapping text",
+///         "[Owned]    ....all day",
+///         "[Owned]    ....long.",
+///     ]
+/// );
+/// ```
+///
+/// ## Leading and Trailing Whitespace
+///
+/// As a rule, leading whitespace (indentation) is preserved and
+/// trailing whitespace is discarded.
+///
+/// In more details, when wrapping words into lines, words are found
+/// by splitting the input text on space characters. One or more
+/// spaces (shown here as “␣”) are attached to the end of each word:
+///
+/// ```text
+/// "Foo␣␣␣bar␣baz" -> ["Foo␣␣␣", "bar␣", "baz"]
+/// ```
+///
+/// These words are then put into lines. The interword whitespace is
+/// preserved, unless the lines are wrapped so that the `"Foo␣␣␣"`
+/// word falls at the end of a line:
+///
+/// ```
+/// use textwrap::wrap;
+///
+/// assert_eq!(wrap("Foo   bar baz", 10), vec!["Foo   bar", "baz"]);
+/// assert_eq!(wrap("Foo   bar baz", 8), vec!["Foo", "bar baz"]);
+/// ```
+///
+/// Notice how the trailing whitespace is removed in both case: in the
+/// first example, `"bar␣"` becomes `"bar"` and in the second case
+/// `"Foo␣␣␣"` becomes `"Foo"`. All the interword whitespace counts
+/// towards the width of a line, so preserved runs of spaces (such as
+/// two spaces after a sentence) never push a line past the margin.
+///
+/// Leading whitespace is preserved when the following word fits on
+/// the first line. To understand this, consider how words are found
+/// in a text with leading spaces:
+///
+/// ```text
+/// "␣␣foo␣bar" -> ["␣␣", "foo␣", "bar"]
+/// ```
+///
+/// When put into lines, the indentation is preserved if `"foo"` fits
+/// on the first line, otherwise you end up with an empty line:
+///
+/// ```
+/// use textwrap::wrap;
+///
+/// assert_eq!(wrap("  foo bar", 8), vec!["  foo", "bar"]);
+/// assert_eq!(wrap("  foo bar", 4), vec!["", "foo", "bar"]);
+/// ```
+pub fn wrap<'a, S, Opt>(text: &str, width_or_options: Opt) -> Vec<Cow<'_, str>>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    wrap_iter(text, width_or_options).collect()
+}
+
+/// Find the smallest width at which `text` wraps into at most
+/// `max_lines` lines.
+///
+/// This is useful when the available height is known and the text
+/// should take up as little horizontal space as possible. The width
+/// is found by a binary search between the width of the longest
+/// unbreakable word and the width needed to keep every input line on
+/// a single output line. All other settings of `width_or_options`
+/// are used when wrapping, except for [`Options::width_fn`] and
+/// [`Options::max_lines`] which are ignored.
+///
+/// `None` is returned when the text cannot fit in `max_lines` lines
+/// at any width, such as when it contains more than `max_lines`
+/// hard line breaks.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fit_width_for_lines, wrap};
+///
+/// let text = "Memory safety without garbage collection.";
+/// assert_eq!(fit_width_for_lines(text, 2, 80), Some(21));
+/// assert_eq!(wrap(text, 21), vec!["Memory safety without", "garbage collection."]);
+///
+/// assert_eq!(fit_width_for_lines("foo\nbar\nbaz", 2, 80), None);
+/// ```
+pub fn fit_width_for_lines<'a, S, Opt>(
+    text: &str,
+    max_lines: usize,
+    width_or_options: Opt,
+) -> Option<usize>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let mut options = width_or_options.into();
+    options.width_fn = None;
+    options.max_lines = None;
+    let measure = options.width_measure;
+
+    let indent_width = measure.width(&options.initial_indent).max(
+        measure.width(options.continuation_marker) + measure.width(&options.subsequent_indent),
+    );
+    let longest_line = text.lines().map(|line| measure.width(line)).max();
+    let mut high = indent_width + longest_line.unwrap_or(0).max(1);
+    let mut low = if options.long_words == LongWords::Break {
+        1
+    } else {
+        let longest_word = text.split_whitespace().map(|word| measure.width(word));
+        indent_width + longest_word.max().unwrap_or(0).max(1)
+    };
+
+    let mut fits = |width| {
+        options.width = width;
+        options.line_count(text) <= max_lines
+    };
+    if !fits(high) {
+        return None;
+    }
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if fits(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(high)
+}
+
+/// Wrap a line of text, failing if a word does not fit.
+///
+/// This works like [`wrap`], except when [`Options::long_words`] is
+/// [`LongWords::Error`]: an error is then returned for the first word
+/// which does not fit on its line. The word is the one which crosses
+/// the end of the first line that is too wide. This is useful for
+/// generating fixed-width output, where a line which is too long is
+/// a mistake rather than a cosmetic problem. With other settings of
+/// [`Options::long_words`], the lines are always returned.
+///
+/// # Errors
+///
+/// Returns [`WrapError::WordTooLong`] for the word which crosses the
+/// end of the first line that is too wide when [`Options::long_words`] is [`LongWords::Error`].
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{try_wrap, LongWords, Options, WrapError};
+///
+/// let options = Options::new(10).long_words(LongWords::Error);
+/// assert_eq!(try_wrap("Memory safety", &options).unwrap(), vec!["Memory", "safety"]);
+/// assert_eq!(
+///     try_wrap("No garbage collection.", &options),
+///     Err(WrapError::WordTooLong {
+///         word: String::from("collection."),
+///         word_width: 11,
+///         available_width: 10,
+///     })
+/// );
+/// ```
+pub fn try_wrap<'a, S, Opt>(
+    text: &str,
+    width_or_options: Opt,
+) -> Result<Vec<Cow<'_, str>>, WrapError>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    if options.long_words != LongWords::Error {
+        return Ok(wrap(text, options));
+    }
+    let prepared = prepare_text(text, &options);
+    let mut wrapper = LineWrapper::new(options);
+    match &prepared {
+        Some(prepared) => Ok(try_wrap_lines(&mut wrapper, prepared)?
+            .into_iter()
+            .map(|line| Cow::Owned(line.into_owned()))
+            .collect()),
+        None => try_wrap_lines(&mut wrapper, text),
+    }
+}
+
+/// Build the lines of `wrapper` for [`try_wrap`], failing on the
+/// first line which is too wide.
+fn try_wrap_lines<'t, S: WordSplitter>(
+    wrapper: &mut LineWrapper<'_, S>,
+    text: &'t str,
+) -> Result<Vec<Cow<'t, str>>, WrapError> {
+    let mut lines = Vec::new();
+    while let Some(line) = wrapper.next_line(text) {
+        check_line_width(text, &line, &wrapper.options, wrapper.line_count - 1)?;
+        lines.push(wrapper.build_cow(text, line));
+    }
+    Ok(lines)
+}
+
+/// Check that `line`, the `line_no`th line of `text`, fits in its
+/// line width. Otherwise the word which crosses the end of the line
+/// is returned in the error.
+fn check_line_width<S: ?Sized>(
+    text: &str,
+    line: &LineRange<'_>,
+    options: &Options<'_, S>,
+    line_no: usize,
+) -> Result<(), WrapError> {
+    let measure = options.width_measure;
+    let indent_width = measure.width(&line.indent)
+        + measure.width(&text[line.hanging_indent.clone()])
+        + measure.width(&line.prefix);
+    let available_width = line_width(options, line_no).saturating_sub(indent_width);
+    let content = &text[line.range.clone()];
+    let content_width = measure.width(content) + measure.width(&line.penalty);
+    if content_width <= available_width {
+        return Ok(());
+    }
+
+    let mut words = options.word_separator.find_words(content).peekable();
+    let mut end = 0;
+    while let Some(word) = words.next() {
+        let mut word_width = measure.width(&word);
+        if words.peek().is_none() {
+            // The hyphen belongs to the last word.
+            word_width += measure.width(&line.penalty);
+        }
+        if end + word_width > available_width {
+            return Err(WrapError::WordTooLong {
+                word: String::from(&*word),
+                word_width,
+                available_width,
+            });
+        }
+        end += word_width + measure.width(word.whitespace);
+    }
+    Ok(())
+}
+
+/// Check that `lines` is a valid wrapping of `text` with `options`.
+///
+/// This checks the guarantees made by [`wrap`], which is useful when
+/// testing code which produces or post-processes wrapped lines:
+///
+/// * No line is wider than its line width, unless it holds a single
+///   word which cannot be broken any further.
+/// * No line starts or ends with whitespace, except for the
+///   indentation, the leading whitespace of an input line, and the
+///   padding added by [`Options::alignment`].
+/// * The words on the lines are the words of `text` in order, apart
+///   from the hyphens and [`Options::word_break_marker`] added where
+///   words are broken, and the spaces collapsed at line breaks. Only
+///   a line cut short by [`Options::max_lines`] may end early.
+///
+/// The text is prepared like [`wrap`] prepares it, so options such
+/// as [`Options::expand_tabs`] are taken into account. Options which
+/// add, remove, or move text on the lines, such as
+/// [`Options::hanging_indent_from_input`],
+/// [`Options::hanging_punctuation`], [`Options::reapply_ansi_styles`],
+/// and splitters with a [`Substitution`], are not.
+///
+/// # Errors
+///
+/// Returns an error for the first line which breaks a guarantee.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{verify, wrap, Options, WrapError};
+///
+/// let text = "Memory safety without garbage collection.";
+/// let options = Options::new(15);
+/// assert_eq!(verify(text, &wrap(text, &options), &options), Ok(()));
+///
+/// let lines = vec!["Memory safety without".into(), "garbage collection.".into()];
+/// assert_eq!(
+///     verify(text, &lines, &options),
+///     Err(WrapError::LineTooWide { line: 0, line_width: 21, width: 15 })
+/// );
+/// ```
+pub fn verify<S: ?Sized>(
+    text: &str,
+    lines: &[Cow<'_, str>],
+    options: &Options<'_, S>,
+) -> Result<(), WrapError> {
+    // The text of a line without whitespace and without the
+    // characters which are removed by `build_line`.
+    fn squeeze(text: &str) -> String {
+        text.chars()
+            .filter(|&ch| ![' ', core::ZWSP, core::WJ, core::SHY].contains(&ch))
+            .collect()
+    }
+
+    let prepared = prepare_text(text, options);
+    let prepared = prepared.as_deref().unwrap_or(text);
+    let paragraphs = prepared
+        .split_inclusive('\n')
+        .chain(Some("").filter(|_| prepared.is_empty() || prepared.ends_with('\n')))
+        .map(|paragraph| {
+            let paragraph = paragraph
+                .strip_suffix('\n')
+                .map_or(paragraph, |line| line.strip_suffix('\r').unwrap_or(line));
+            let visible = paragraph.replace([core::WJ, core::SHY], "");
+            let words = squeeze(&visible);
+            (visible, words)
+        })
+        .collect::<Vec<_>>();
+    let measure = options.width_measure;
+    let markers = [String::from("-"), squeeze(options.word_break_marker)];
+    let placeholder = squeeze(options.placeholder);
+    let truncated = options.max_lines == Some(lines.len());
+    if lines.is_empty() {
+        return match truncated {
+            true => Ok(()),
+            false => Err(WrapError::WordsChanged { line: 0 }),
+        };
+    }
+
+    // The possible positions in the text after each line. A position
+    // is ambiguous when a line ends with a hyphen which may have been
+    // added or may come from the text.
+    let mut positions = vec![(0, 0, true)];
+    let mut continued = String::new();
+    for (line_no, line) in lines.iter().enumerate() {
+        let width = line_width(options, line_no);
+        let line_width = measure.width(line);
+
+        let mut body: &str = line;
+        let padded = matches!(options.alignment, Alignment::Center | Alignment::Right);
+        if padded {
+            body = body.trim_start_matches(' ');
+        }
+        if options.alignment == Alignment::Center {
+            body = body.trim_end_matches(' ');
+        }
+        let mut indent: &str = match line_no {
+            0 => &options.initial_indent,
+            // Lines which continue a wrapped line start with the
+            // marker, lines which start an input line do not.
+            _ if !options.continuation_marker.is_empty() => {
+                continued.clear();
+                continued.push_str(options.continuation_marker);
+                continued.push_str(&options.subsequent_indent);
+                let mut expected: &str = &continued;
+                if padded {
+                    expected = expected.trim_start_matches(' ');
+                }
+                if options.alignment == Alignment::Center {
+                    expected = expected.trim_end_matches(' ');
+                }
+                match body.starts_with(expected) {
+                    true => &continued,
+                    false => &options.subsequent_indent,
+                }
+            }
+            _ => &options.subsequent_indent,
+        };
+        if padded {
+            indent = indent.trim_start_matches(' ');
+        }
+        let last = line_no + 1 == lines.len();
+        // Empty lines have no indentation, or only part of it.
+        let content = match body.strip_prefix(indent) {
+            Some(content) => content,
+            None if indent.starts_with(body) => "",
+            None if truncated && last => body,
+            None => return Err(WrapError::MissingIndent { line: line_no }),
+        };
+        let trimmed = content.trim_matches(' ');
+
+        if line_width > width {
+            let word = trimmed.trim_matches([' ', core::ZWSP]);
+            let unbreakable = match options.long_words {
+                // All of the width comes from a single cluster, plus
+                // the marker for the break.
+                LongWords::Break => {
+                    let piece = [options.word_break_marker, "-"]
+                        .iter()
+                        .filter(|marker| !marker.is_empty())
+                        .find_map(|marker| word.strip_suffix(marker))
+                        .unwrap_or(word);
+                    measure
+                        .clusters(piece)
+                        .map(|(_, cluster_width)| cluster_width)
+                        .max()
+                        .is_none_or(|widest| widest == measure.width(piece))
+                }
+                // A single word, or words which the rules join.
+                LongWords::Overflow | LongWords::Error => {
+                    let rules = options.break_rules;
+                    word.match_indices(' ').all(|(idx, _)| {
+                        let before = word[..idx].trim_end_matches([' ', core::ZWSP]);
+                        let after = word[idx..].trim_start_matches([' ', core::ZWSP]);
+                        after.is_empty()
+                            || !rules.allows_break(before, after)
+                            || rules.glued(before, after)
+                    })
+                }
+            };
+            if !unbreakable {
+                return Err(WrapError::LineTooWide {
+                    line: line_no,
+                    line_width,
+                    width,
+                });
+            }
+        }
+
+        // A used up paragraph can be followed by the next paragraph
+        // or by lines which only held invisible characters.
+        positions = positions
+            .into_iter()
+            .flat_map(|(paragraph, pos, first)| {
+                let used_up = !first && pos == paragraphs[paragraph].1.len();
+                let next = Some((paragraph + 1, 0, true))
+                    .filter(|&(next, ..)| used_up && next < paragraphs.len());
+                ::core::iter::once((paragraph, pos, first)).chain(next)
+            })
+            .collect();
+
+        let leading = &content[..content.len() - content.trim_start_matches(' ').len()];
+        let leading_allowed = leading.is_empty()
+            || positions.iter().any(|&(paragraph, _, first)| {
+                first && paragraphs[paragraph].0.starts_with(leading)
+            });
+        if !leading_allowed || (!trimmed.is_empty() && content.ends_with(' ')) {
+            return Err(WrapError::StrayWhitespace { line: line_no });
+        }
+
+        let piece = squeeze(content);
+        let mut next = Vec::new();
+        for &(paragraph, pos, _) in &positions {
+            let rest = &paragraphs[paragraph].1[pos..];
+            if truncated && last {
+                // The line may end with a cut-off word and a cut-off
+                // placeholder, or with a broken word and its marker.
+                let fits = (0..=placeholder.len())
+                    .filter(|&end| placeholder.is_char_boundary(end))
+                    .filter_map(|end| piece.strip_suffix(&placeholder[..end]))
+                    .chain(markers.iter().filter_map(|marker| {
+                        piece
+                            .strip_suffix(marker.as_str())
+                            .filter(|_| !marker.is_empty())
+                    }))
+                    .any(|head| rest.starts_with(head));
+                if fits {
+                    return Ok(());
+                }
+                continue;
+            }
+            if rest.starts_with(&piece) {
+                next.push((paragraph, pos + piece.len(), false));
+            }
+            for marker in markers.iter().filter(|marker| !marker.is_empty()) {
+                if let Some(head) = piece.strip_suffix(marker.as_str()) {
+                    if !head.is_empty() && rest.starts_with(head) {
+                        next.push((paragraph, pos + head.len(), false));
+                    }
+                }
+            }
+        }
+        // The non-breaking spaces are removed where a long word is
+        // broken at them.
+        let skipped = next.iter().flat_map(|&(paragraph, pos, first)| {
+            let rest = &paragraphs[paragraph].1[pos..];
+            rest.char_indices()
+                .take_while(|&(_, ch)| core::NO_BREAK_SPACES.contains(&ch))
+                .map(move |(i, ch)| (paragraph, pos + i + ch.len_utf8(), first))
+        });
+        next.extend(skipped.collect::<Vec<_>>());
+        next.sort_unstable();
+        next.dedup();
+        if next.is_empty() {
+            return Err(WrapError::WordsChanged { line: line_no });
+        }
+        positions = next;
+    }
+
+    let complete = positions.iter().any(|&(paragraph, pos, _)| {
+        paragraph + 1 == paragraphs.len() && pos == paragraphs[paragraph].1.len()
+    });
+    match complete || truncated {
+        true => Ok(()),
+        false => Err(WrapError::WordsChanged { line: lines.len() }),
+    }
+}
+
+/// Lazily wrap a line of text at a given width.
+///
+/// This works like [`wrap`], but instead of returning a vector of
+/// all lines, the lines are computed on demand by the returned
+/// [`WrapIter`]. The iterator produces exactly the same lines as
+/// [`wrap`] and the lines borrow from `text` whenever possible.
+///
+/// The text is wrapped one input line (that is, one `'\n'`-terminated
+/// paragraph) at a time. This makes it cheap to only look at the
+/// first few lines of a long text:
+///
+/// ```
+/// use textwrap::wrap_iter;
+///
+/// let text = "Memory safety without garbage collection.\n\
+///             Concurrency without data races.\n\
+///             Zero-cost abstractions.";
+/// let mut lines = wrap_iter(text, 15);
+/// assert_eq!(lines.next().as_deref(), Some("Memory safety"));
+/// assert_eq!(lines.next().as_deref(), Some("without garbage"));
+/// // The remaining text is never wrapped.
+/// ```
+pub fn wrap_iter<'a, 't, S, Opt>(text: &'t str, width_or_options: Opt) -> WrapIter<'a, 't, S>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let prepared = prepare_text(text, &options);
+    WrapIter {
+        wrapper: LineWrapper::new(options),
+        text,
+        prepared,
+    }
+}
+
+/// Remove control characters, replace line breaks, and expand tabs
+/// according to `options`.
+/// Returns `None` if the text is unchanged.
+fn prepare_text<S: ?Sized>(text: &str, options: &Options<'_, S>) -> Option<String> {
+    let mut prepared = None;
+    if options.sanitize_control_chars && text.contains(is_unsafe_control) {
+        prepared = Some(remove_control_chars(text));
+    }
+    if !options.respect_newlines {
+        let text = prepared.as_deref().unwrap_or(text);
+        if text.contains('\n') {
+            prepared = Some(text.replace("\r\n", " ").replace('\n', " "));
+        }
+    }
+    if let Some(tabstop) = options.expand_tabs {
+        let text = prepared.as_deref().unwrap_or(text);
+        if text.contains('\t') {
+            prepared = Some(expand_tabs(text, tabstop));
+        }
+    }
+    prepared
+}
+
+/// Is `ch` removed by [`Options::sanitize_control_chars`]? A `'\r'`
+/// is only kept when it is part of a `"\r\n"` line break, which is
+/// checked by [`remove_control_chars`].
+fn is_unsafe_control(ch: char) -> bool {
+    ch.is_control() && ch != '\n' && ch != '\t'
+}
+
+/// Remove the control characters from `text`, but keep line breaks,
+/// tabs, and ANSI escape sequences.
+fn remove_control_chars(text: &str) -> String {
+    let escapes = core::ansi_escape_sequences(text);
+    let mut escapes = escapes.iter().peekable();
+    let mut result = String::with_capacity(text.len());
+    for (idx, ch) in text.char_indices() {
+        while escapes.next_if(|range| range.end <= idx).is_some() {}
+        let in_escape = escapes.peek().is_some_and(|range| range.contains(&idx));
+        let keep = in_escape
+            || !is_unsafe_control(ch)
+            || (ch == '\r' && text[idx + 1..].starts_with('\n'));
+        if keep {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// An iterator over wrapped lines.
+///
+/// This struct is created by the [`wrap_iter`] function. See its
+/// documentation for details.
+#[derive(Debug)]
+pub struct WrapIter<'a, 't, S> {
+    wrapper: LineWrapper<'a, S>,
+    text: &'t str,
+    /// The text prepared by [`prepare_text`], if it was changed.
+    prepared: Option<String>,
+}
+
+impl<'a, 't, S: WordSplitter> Iterator for WrapIter<'a, 't, S> {
+    type Item = Cow<'t, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &self.prepared {
+            Some(prepared) => self
+                .wrapper
+                .next_cow(prepared)
+                .map(|line| Cow::Owned(line.into_owned())),
+            None => self.wrapper.next_cow(self.text),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.wrapper.size_hint()
+    }
+}
+
+impl<S: WordSplitter> ::core::iter::FusedIterator for WrapIter<'_, '_, S> {}
+
+/// Wrap an owned string, yielding owned lines.
+///
+/// This works like [`wrap_iter`], except that the returned
+/// [`IntoWrapIter`] takes ownership of both the text and the options.
+/// The lines are returned as [`String`]s. This is useful if the text
+/// is built on the fly and you would otherwise have to keep it alive
+/// next to the wrapped lines.
+///
+/// When the options have a `'static` lifetime and the splitter is
+/// `'static` too, the iterator is also `'static`. It can then be
+/// boxed and stored, or sent to another thread if the splitter
+/// is [`Send`]:
+///
+/// ```
+/// use textwrap::into_wrap_iter;
+///
+/// let message = format!("{} is {} years old", "Ferris", 11);
+/// let lines: Box<dyn Iterator<Item = String> + Send> =
+///     Box::new(into_wrap_iter(message, 10));
+///
+/// let handle = std::thread::spawn(move || lines.collect::<Vec<_>>());
+/// assert_eq!(handle.join().unwrap(), vec!["Ferris is", "11 years", "old"]);
+/// ```
+pub fn into_wrap_iter<'a, S, Opt>(text: String, width_or_options: Opt) -> IntoWrapIter<'a, S>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let text = prepare_text(&text, &options).unwrap_or(text);
+    IntoWrapIter {
+        wrapper: LineWrapper::new(options),
+        text,
+    }
+}
+
+/// An owning iterator over wrapped lines.
+///
+/// This struct is created by the [`into_wrap_iter`] function. See its
+/// documentation for details.
+#[derive(Debug)]
+pub struct IntoWrapIter<'a, S> {
+    wrapper: LineWrapper<'a, S>,
+    text: String,
+}
+
+impl<'a, S: WordSplitter> Iterator for IntoWrapIter<'a, S> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.wrapper.next_cow(&self.text).map(Cow::into_owned)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.wrapper.size_hint()
+    }
+}
+
+impl<S: WordSplitter> ::core::iter::FusedIterator for IntoWrapIter<'_, S> {}
+
+/// The location of a wrapped line in the original text.
+///
+/// This is returned by [`wrap_ranges`]. The wrapped line consists of
+/// `indent`, followed by `text[hanging_indent]`, `text[range]`, and
+/// `penalty`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineRange<'a> {
+    /// Indentation added in front of the line. This is either
+    /// [`Options::initial_indent`], [`Options::subsequent_indent`],
+    /// or empty. On lines which continue a wrapped line, it starts
+    /// with the [`Options::continuation_marker`].
+    pub indent: Cow<'a, str>,
+    /// Byte range of the indentation copied from the start of the
+    /// paragraph in the original text, see
+    /// [`Options::hanging_indent_from_input`]. This is `0..0` unless
+    /// the option is enabled.
+    pub hanging_indent: ::core::ops::Range<usize>,
+    /// Text added in front of the line content. This is the
+    /// respelled start of a word which was split with a
+    /// [`Substitution`] at the end of the previous line. Otherwise it
+    /// is empty.
+    pub prefix: Cow<'a, str>,
+    /// Byte range of the line content in the original text.
+    pub range: ::core::ops::Range<usize>,
+    /// Penalty added after the line content. This is `"-"` if a
+    /// hyphen was inserted by the [`WordSplitter`], together with the
+    /// respelled end of the word if it was split with a
+    /// [`Substitution`]. It is [`Options::placeholder`] on a last
+    /// line which was truncated due to [`Options::max_lines`].
+    /// Otherwise it is empty.
+    pub penalty: Cow<'a, str>,
+    /// Width of the character at the start of the content which
+    /// hangs into the margin, see [`Options::hanging_punctuation`].
+    /// The line is this much wider than otherwise allowed. This is
+    /// zero unless the option is enabled.
+    pub hanging_punctuation: usize,
+}
+
+/// Wrap `text` and return the location of each line in the text.
+///
+/// This works like [`wrap`], but instead of building the lines, the
+/// positions of the lines in `text` are returned. Each [`LineRange`]
+/// holds the byte range of the content taken from `text` along with
+/// the indentation, the prefix, and the penalty (typically a `"-"`
+/// inserted by the [`WordSplitter`]) which surround the content. This is useful if you
+/// need to map the wrapped lines back to the input, e.g., to re-apply
+/// syntax highlighting.
+///
+/// The ranges do not reflect the [`Options::alignment`] since the
+/// spaces inserted to align the lines are not part of `text`. Similarly,
+/// word joiners (U+2060) and unused soft hyphens (U+00AD) are part of
+/// the ranges even though they are removed by [`wrap`].
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, wrap_ranges, LineRange, Options};
+///
+/// let text = "Memory safety without garbage collection.";
+/// let options = Options::new(15).subsequent_indent("  ");
+/// let ranges = wrap_ranges(text, &options);
+/// assert_eq!(
+///     ranges[1],
+///     LineRange {
+///         indent: "  ".into(),
+///         hanging_indent: 0..0,
+///         prefix: "".into(),
+///         range: 14..21,
+///         penalty: "".into(),
+///         hanging_punctuation: 0,
+///     }
+/// );
+///
+/// // The ranges can be used to reconstruct the lines:
+/// let lines = ranges
+///     .iter()
+///     .map(|line| {
+///         let hanging_indent = &text[line.hanging_indent.clone()];
+///         let content = &text[line.range.clone()];
+///         let (indent, prefix, penalty) = (&line.indent, &line.prefix, &line.penalty);
+///         format!("{}{}{}{}{}", indent, hanging_indent, prefix, content, penalty)
+///     })
+///     .collect::<Vec<_>>();
+/// assert_eq!(lines, wrap(text, &options));
+/// ```
+pub fn wrap_ranges<'a, S, Opt>(text: &str, width_or_options: Opt) -> Vec<LineRange<'a>>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let mut wrapper = LineWrapper::new(width_or_options.into());
+    let mut ranges = Vec::new();
+    while let Some(line) = wrapper.next_line(text) {
+        ranges.push(line);
+    }
+    ranges
+}
+
+/// Wrap `text` without breaking inside the `protected` byte ranges.
+///
+/// This works like [`wrap`], but no line break is put strictly
+/// inside one of the `protected` ranges. This holds for breaks
+/// between words, for breaks found by the [`WordSplitter`], and for
+/// breaks forced by [`Options::long_words`]. This can be used to
+/// keep inline code spans or placeholders such as `{user_name}`
+/// together. A range which covers whitespace joins the surrounding
+/// words into a single unbreakable word. A word which is wider than
+/// the line overflows instead of being broken.
+///
+/// Hard line breaks (`'\n'`) in the text are kept, even inside a
+/// protected range. Like for [`wrap_ranges`], the
+/// [`Options::expand_tabs`] and [`Options::sanitize_control_chars`]
+/// settings are ignored, since they would move the ranges.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, wrap_with_protection};
+///
+/// let text = "Hello {user name}, welcome!";
+/// assert_eq!(wrap(text, 12), vec!["Hello {user", "name},", "welcome!"]);
+/// assert_eq!(wrap_with_protection(text, 12, &[6..17]),
+///            vec!["Hello", "{user name},", "welcome!"]);
+/// ```
+///
+/// A protected word is not broken, even if it is too long:
+///
+/// ```
+/// use textwrap::wrap_with_protection;
+///
+/// assert_eq!(wrap_with_protection("a `very_long_name` b", 8, &[2..18]),
+///            vec!["a", "`very_long_name`", "b"]);
+/// ```
+pub fn wrap_with_protection<'a, 't, S, Opt>(
+    text: &'t str,
+    width_or_options: Opt,
+    protected: &[::core::ops::Range<usize>],
+) -> Vec<Cow<'t, str>>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let mut wrapper = LineWrapper::new(width_or_options.into());
+    wrapper.protected = protected.to_vec();
+    let mut lines = Vec::new();
+    while let Some(line) = wrapper.next_cow(text) {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Build a wrapped line from its location in `text`.
+fn build_line<'t>(text: &'t str, line: LineRange<'_>) -> Cow<'t, str> {
+    let LineRange {
+        indent,
+        hanging_indent,
+        prefix,
+        range,
+        penalty,
+        ..
+    } = line;
+
+    // The result is owned if we have indentation, otherwise we can
+    // simply borrow an empty string.
+    let mut result = if indent.is_empty() && hanging_indent.is_empty() && prefix.is_empty() {
+        // We can use an empty string here since string concatenation
+        // for `Cow` preserves a borrowed value when either side is
+        // empty.
+        Cow::from("")
+    } else {
+        Cow::Owned([&indent, &text[hanging_indent], &prefix].concat())
+    };
+
+    result += &text[range];
+
+    if !penalty.is_empty() {
+        result.to_mut().push_str(&penalty);
+    }
+
+    // Word joiners and soft hyphens have done their job once the
+    // line is wrapped. A soft hyphen used for the break has already
+    // been replaced by the penalty.
+    if result.contains([core::WJ, core::SHY]) {
+        result = Cow::Owned(result.replace([core::WJ, core::SHY], ""));
+    }
+
+    result
+}
+
+/// Build a wrapped line from its location in `text`, widening the
+/// gaps between words so that the line becomes `width` columns wide.
+///
+/// Returns `None` if the line cannot be justified, i.e., if it has no
+/// gaps or if it is already too long.
+fn justify_line(
+    text: &str,
+    line: &LineRange<'_>,
+    width: usize,
+    measure: core::WidthMeasure,
+) -> Option<String> {
+    let content = &text[line.range.clone()];
+    let hanging_indent = &text[line.hanging_indent.clone()];
+    let line_width = measure.width(&line.indent)
+        + measure.width(hanging_indent)
+        + measure.width(&line.prefix)
+        + measure.width(content)
+        + measure.width(&line.penalty);
+    let extra = width.checked_sub(line_width).filter(|&extra| extra > 0)?;
+
+    // All words except the last are followed by a gap. Leading
+    // whitespace is kept as-is since it is not a gap between words,
+    // even when it follows a word which is removed, such as a lone
+    // soft hyphen.
+    let words = core::find_words_separated_by(content, |ch| ch == ' ').collect::<Vec<_>>();
+    let first_visible = words
+        .iter()
+        .position(|word| !word.trim_matches([core::WJ, core::SHY]).is_empty())
+        .unwrap_or(words.len());
+    let gaps = (first_visible..words.len().saturating_sub(1)).collect::<Vec<_>>();
+    if gaps.is_empty() {
+        return None;
+    }
+
+    // Every gap gets the same number of extra spaces. The remaining
+    // spaces go to gaps after punctuation first, and are then spread
+    // evenly over the other gaps.
+    let mut spaces = vec![0; words.len()];
+    for &idx in &gaps {
+        spaces[idx] = extra / gaps.len();
+    }
+    let mut remaining = extra % gaps.len();
+    let (punctuated, plain): (Vec<usize>, Vec<usize>) = gaps
+        .iter()
+        .partition(|&&idx| words[idx].ends_with(|ch| ".,;:!?".contains(ch)));
+    for &idx in punctuated.iter().take(remaining) {
+        spaces[idx] += 1;
+    }
+    remaining = remaining.saturating_sub(punctuated.len());
+    for i in 0..remaining {
+        spaces[plain[(2 * i + 1) * plain.len() / (2 * remaining)]] += 1;
+    }
+
+    let mut result = String::with_capacity(
+        line.indent.len() + hanging_indent.len() + line.prefix.len() + content.len() + extra,
+    );
+    result.push_str(&line.indent);
+    result.push_str(hanging_indent);
+    result.push_str(&line.prefix);
+    for (word, spaces) in words.iter().zip(spaces) {
+        result.push_str(word);
+        result.push_str(word.whitespace);
+        result.push_str(&" ".repeat(spaces));
+    }
+    result.push_str(&line.penalty);
+    Some(result.replace([core::WJ, core::SHY], ""))
+}
+
+/// The ANSI SGR escape sequences and the OSC 8 hyperlink which are
+/// active at some point in the wrapped text.
+#[derive(Debug, Default)]
+struct AnsiStyles {
+    /// SGR sequences seen since the last reset, in order.
+    active: String,
+    /// The OSC 8 sequence which opened the current hyperlink, empty
+    /// when no hyperlink is open.
+    hyperlink: String,
+    /// End of the part of the text which has been scanned.
+    offset: usize,
+}
+
+impl AnsiStyles {
+    /// Scan `text` up to the end of `line` and return the escape
+    /// sequences to replay at the start of the line and the escape
+    /// sequences which close the line. SGR sequences are only tracked
+    /// when `sgr` is set, hyperlinks are always tracked.
+    fn track(&mut self, text: &str, line: &LineRange<'_>, sgr: bool) -> (String, String) {
+        // The text between the lines, such as the whitespace at a
+        // break or the `'\n'` between paragraphs, may change the style
+        // too.
+        self.scan(
+            &text[self.offset.min(line.range.start)..line.range.start],
+            sgr,
+        );
+        let mut before = self.active.clone();
+        before.push_str(&self.hyperlink);
+        self.scan(&text[line.range.clone()], sgr);
+        self.offset = line.range.end;
+
+        let mut after = String::new();
+        if !self.hyperlink.is_empty() {
+            // Close the hyperlink with the same terminator which was
+            // used to open it.
+            after.push_str("\x1b]8;;");
+            after.push_str(match self.hyperlink.ends_with('\x07') {
+                true => "\x07",
+                false => "\x1b\\",
+            });
+        }
+        if !self.active.is_empty() {
+            after.push_str("\x1b[0m");
+        }
+        (before, after)
+    }
+
+    fn scan(&mut self, text: &str, sgr: bool) {
+        for range in core::ansi_escape_sequences(text) {
+            let sequence = &text[range];
+            if let Some(rest) = sequence.strip_prefix("\x1b]8;") {
+                // The parameters come before the second ';' and the
+                // target comes after it. An empty target closes the
+                // hyperlink.
+                let target = rest.split_once(';').map_or("", |(_, target)| target);
+                let target = target
+                    .strip_suffix('\x07')
+                    .or_else(|| target.strip_suffix("\x1b\\"))
+                    .unwrap_or(target);
+                self.hyperlink.clear();
+                if !target.is_empty() {
+                    self.hyperlink.push_str(sequence);
+                }
+                continue;
+            }
+            if !sgr {
+                continue;
+            }
+            let params = match sequence.strip_prefix("\x1b[") {
+                Some(rest) => match rest.strip_suffix('m') {
+                    Some(params) => params,
+                    None => continue,
+                },
+                None => continue,
+            };
+            if params.is_empty() || params == "0" {
+                self.active.clear();
+                continue;
+            }
+            if params.starts_with("0;") {
+                self.active.clear();
+            }
+            self.active.push_str(sequence);
+        }
+    }
+}
+
+/// Replay the `before` escape sequences after the indentation of the
+/// line and add the `after` escape sequences at the end of the line.
+fn restyle_line<'t>(
+    result: Cow<'t, str>,
+    indent_len: usize,
+    before: &str,
+    after: &str,
+) -> Cow<'t, str> {
+    if before.is_empty() && after.is_empty() {
+        return result;
+    }
+    let (indent, rest) = result.split_at(indent_len);
+    let mut restyled = String::with_capacity(result.len() + before.len() + after.len());
+    restyled.push_str(indent);
+    restyled.push_str(before);
+    restyled.push_str(rest);
+    restyled.push_str(after);
+    Cow::Owned(restyled)
+}
+
+/// Reorder a wrapped line for display. The indentation is kept at the
+/// start of the line in the base `direction` of the paragraph.
+#[cfg(feature = "unicode-bidi")]
+fn reorder_for_display(
+    result: Cow<'_, str>,
+    indent_len: usize,
+    direction: core::Direction,
+) -> Cow<'_, str> {
+    let (indent, rest) = result.split_at(indent_len);
+    let body = match core::reorder_line(rest, direction) {
+        Cow::Borrowed(_) if direction == core::Direction::LeftToRight => None,
+        body => Some(body.into_owned()),
+    };
+    match body {
+        None => result,
+        Some(mut body) => match direction {
+            core::Direction::LeftToRight => Cow::Owned(String::from(indent) + &body),
+            core::Direction::RightToLeft => {
+                body.push_str(&core::reorder_line(indent, direction));
+                Cow::Owned(body)
+            }
+        },
+    }
+}
+
+/// Shared state for the functions which wrap text line by line.
+///
+/// The text itself is not stored here, instead it is passed to
+/// [`LineWrapper::next_line`]. This allows the same logic to be used
+/// for both borrowed and owned text.
+#[derive(Debug)]
+struct LineWrapper<'a, S> {
+    options: Options<'a, S>,
+    /// Start of the part of the text which has not yet been wrapped.
+    offset: Option<usize>,
+    /// Wrapped lines of the current input line.
+    lines: alloc::vec::IntoIter<LineRange<'a>>,
+    /// Number of lines returned so far.
+    line_count: usize,
+    /// Active ANSI styles and hyperlink, see
+    /// [`Options::reapply_ansi_styles`].
+    styles: AnsiStyles,
+    /// Byte ranges which must not be broken, see
+    /// [`wrap_with_protection`].
+    protected: Vec<::core::ops::Range<usize>>,
+    /// Base direction of the current paragraph, see
+    /// [`Options::bidi_reorder`].
+    #[cfg(feature = "unicode-bidi")]
+    direction: core::Direction,
+}
+
+impl<'a, S: WordSplitter> LineWrapper<'a, S> {
+    fn new(options: Options<'a, S>) -> Self {
+        LineWrapper {
+            options,
+            offset: Some(0),
+            lines: Vec::new().into_iter(),
+            line_count: 0,
+            styles: AnsiStyles::default(),
+            protected: Vec::new(),
+            #[cfg(feature = "unicode-bidi")]
+            direction: core::Direction::LeftToRight,
+        }
+    }
+
+    /// Return the location of the next line in `text`. The same
+    /// `text` must be passed on every call.
+    fn next_line(&mut self, text: &str) -> Option<LineRange<'a>> {
+        loop {
+            if self
+                .options
+                .max_lines
+                .filter(|&n| self.line_count >= n)
+                .is_some()
+            {
+                return None;
+            }
+
+            if let Some(line) = self.lines.next() {
+                self.line_count += 1;
+                if self.options.max_lines == Some(self.line_count)
+                    && !text[line.range.end..].trim().is_empty()
+                {
+                    // The truncated line is the last line of output.
+                    self.offset = None;
+                    self.lines = Vec::new().into_iter();
+                    return Some(self.truncate(text, line));
+                }
+                return Some(line);
+            }
+
+            let start = self.offset?;
+            let end = match self.options.find_line_break(&text[start..]) {
+                Some((idx, next)) => {
+                    self.offset = Some(start + next);
+                    start + idx
+                }
+                None => {
+                    self.offset = None;
+                    text.len()
+                }
+            };
+
+            let line = &text[start..end];
+            #[cfg(feature = "unicode-bidi")]
+            if self.options.bidi_reorder {
+                self.direction =
+                    core::paragraph_direction(line).unwrap_or(core::Direction::LeftToRight);
+            }
+            let initial_indent = if self.line_count == 0 {
+                self.options.initial_indent.clone()
+            } else {
+                self.options.subsequent_indent.clone()
+            };
+            let leading_whitespace = line.len() - line.trim_start_matches([' ', '\t']).len();
+            let (subsequent_indent, hanging_len) = match list_marker_len(line) {
+                Some(len) if self.options.detect_list_markers => (
+                    Cow::Borrowed(spaces(
+                        self.options.width_measure.width(&initial_indent) + len,
+                    )),
+                    0,
+                ),
+                _ if self.options.hanging_indent_from_input
+                    && (1..line.len()).contains(&leading_whitespace) =>
+                {
+                    (Cow::Borrowed(""), leading_whitespace)
+                }
+                _ => (self.options.subsequent_indent.clone(), 0),
+            };
+            let subsequent_indent = match self.options.continuation_marker {
+                "" => subsequent_indent,
+                marker => Cow::Owned([marker, &subsequent_indent].concat()),
+            };
+
+            let mut protected = Cow::Borrowed(&self.protected[..]);
+            if self.options.inline_markup {
+                let markup = inline_markup_ranges(line);
+                protected.to_mut().extend(
+                    markup
+                        .into_iter()
+                        .map(|range| start + range.start..start + range.end),
+                );
+            }
+
+            let mut lines = Vec::new();
+            wrap_line_ranges(
+                line,
+                start,
+                self.line_count,
+                &self.options,
+                initial_indent,
+                subsequent_indent,
+                hanging_len,
+                &protected,
+                &mut lines,
+            );
+            self.lines = lines.into_iter();
+        }
+    }
+
+    /// Return the next line of `text` as a string, aligned according
+    /// to the options.
+    fn next_cow<'t>(&mut self, text: &'t str) -> Option<Cow<'t, str>> {
+        let line = self.next_line(text)?;
+        Some(self.build_cow(text, line))
+    }
+
+    /// Build the `line` just returned by [`LineWrapper::next_line`]
+    /// as a string, aligned according to the options.
+    fn build_cow<'t>(&mut self, text: &'t str, line: LineRange<'a>) -> Cow<'t, str> {
+        let width = line_width(&self.options, self.line_count - 1) + line.hanging_punctuation;
+        let (before, after) = self
+            .styles
+            .track(text, &line, self.options.reapply_ansi_styles);
+        let indent_len = line.indent.len() + line.hanging_indent.len();
+        #[cfg(feature = "unicode-bidi")]
+        let direction = self.options.bidi_reorder.then_some(self.direction);
+        let restyle = |result| {
+            let result = restyle_line(result, indent_len, &before, &after);
+            #[cfg(feature = "unicode-bidi")]
+            if let Some(direction) = direction {
+                return reorder_for_display(result, indent_len, direction);
+            }
+            result
+        };
+        match self.options.alignment {
+            Alignment::Left => restyle(build_line(text, line.clone())),
+            Alignment::Center | Alignment::Right => {
+                let result = restyle(build_line(text, line.clone()));
+                if result.is_empty() {
+                    return result;
+                }
+                pad_line(
+                    result,
+                    width,
+                    self.options.alignment,
+                    self.options.width_measure,
+                )
+            }
+            Alignment::Justified => {
+                // Only the last line of a paragraph has no pending lines.
+                let last_in_paragraph = self.lines.len() == 0;
+                if !last_in_paragraph {
+                    if let Some(justified) =
+                        justify_line(text, &line, width, self.options.width_measure)
+                    {
+                        return restyle(Cow::Owned(justified));
+                    }
+                }
+                restyle(build_line(text, line.clone()))
+            }
+        }
+    }
+
+    /// Shorten the last line so that the placeholder fits after it.
+    ///
+    /// Trailing words are removed until the placeholder fits. If a
+    /// single word remains which is still too long, the word is cut
+    /// at a character boundary. If the placeholder alone is wider
+    /// than the line, the placeholder is cut as well.
+    fn truncate(&self, text: &str, line: LineRange<'a>) -> LineRange<'a> {
+        let measure = self.options.width_measure;
+        let available = line_width(&self.options, self.line_count - 1)
+            .saturating_sub(measure.width(&line.indent))
+            .saturating_sub(measure.width(&text[line.hanging_indent.clone()]))
+            .saturating_sub(measure.width(&line.prefix))
+            .max(1);
+        let placeholder = truncate_to_width(self.options.placeholder, available, measure);
+        let available = available - measure.width(placeholder);
+
+        let mut content = &text[line.range.clone()];
+        while measure.width(content) > available {
+            content = match content.rfind(' ') {
+                Some(idx) => content[..idx].trim_end_matches(' '),
+                None => truncate_to_width(content, available, measure),
+            };
+        }
+
+        LineRange {
+            indent: line.indent,
+            hanging_indent: line.hanging_indent,
+            prefix: line.prefix,
+            range: line.range.start..line.range.start + content.len(),
+            penalty: Cow::Borrowed(placeholder),
+            hanging_punctuation: line.hanging_punctuation,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every input line produces at least one line of output.
+        let pending = self.lines.len();
+        let (lower, upper) = match self.offset {
+            Some(_) => (pending + 1, None),
+            None => (pending, Some(pending)),
+        };
+        match self.options.max_lines {
+            Some(n) => {
+                let left = n.saturating_sub(self.line_count);
+                (
+                    lower.min(left),
+                    Some(upper.map_or(left, |upper| upper.min(left))),
+                )
+            }
+            None => (lower, upper),
+        }
+    }
+}
+
+/// Return the length of the list marker at the start of `line`,
+/// including the surrounding spaces.
+///
+/// The markers `-`, `*` and `+` are recognized, as well as numbers
+/// and single letters followed by `.` or `)`. The marker must be
+/// followed by a space and more text. This ensures that a negative
+/// number such as `-5` is not mistaken for a marker.
+fn list_marker_len(line: &str) -> Option<usize> {
+    let rest = line.trim_start_matches(' ');
+    let bytes = rest.as_bytes();
+    let marker_len = match bytes.first()? {
+        b'-' | b'*' | b'+' => 1,
+        b'0'..=b'9' => {
+            let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            match bytes.get(digits)? {
+                b'.' | b')' => digits + 1,
+                _ => return None,
+            }
+        }
+        b if b.is_ascii_alphabetic() => match bytes.get(1)? {
+            b'.' | b')' => 2,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let after = &rest[marker_len..];
+    let text = after.trim_start_matches(' ');
+    if text.len() == after.len() || text.is_empty() {
+        return None;
+    }
+    Some(line.len() - text.len())
+}
+
+/// Find the byte ranges of `line` which must not be broken with
+/// [`Options::inline_markup`]: the code spans and the emphasis
+/// markers together with the character next to them.
+fn inline_markup_ranges(line: &str) -> Vec<::core::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let run_len =
+        |idx: usize, marker: char| line[idx..].len() - line[idx..].trim_start_matches(marker).len();
+    let mut idx = 0;
+    while let Some((start, marker)) = line[idx..]
+        .char_indices()
+        .find(|&(_, ch)| matches!(ch, '`' | '*' | '_'))
+        .map(|(i, ch)| (idx + i, ch))
+    {
+        let len = run_len(start, marker);
+        let end = start + len;
+        idx = end;
+        if marker == '`' {
+            // A code span ends with a backtick run of the same
+            // length. Without one, the backticks are literal text.
+            let mut search = end;
+            while let Some(found) = line[search..].find('`') {
+                let close = search + found;
+                let close_len = run_len(close, '`');
+                if close_len == len {
+                    ranges.push(start..close + close_len);
+                    idx = close + close_len;
+                    break;
+                }
+                search = close + close_len;
+            }
+            continue;
+        }
+
+        let before = line[..start].chars().next_back();
+        let after = line[end..].chars().next();
+        let is_space = |ch: Option<char>| ch.is_none_or(char::is_whitespace);
+        // An underscore inside a word is not an emphasis marker.
+        let intraword = !is_space(before) && !is_space(after);
+        if marker == '_' && intraword {
+            continue;
+        }
+        if let Some(ch) = after.filter(|ch| !ch.is_whitespace()) {
+            ranges.push(start..end + ch.len_utf8());
+        }
+        if let Some(ch) = before.filter(|ch| !ch.is_whitespace()) {
+            ranges.push(start - ch.len_utf8()..end);
+        }
+    }
+    ranges
+}
+
+/// Return a string of `n` spaces. At most 128 spaces are returned.
+fn spaces(n: usize) -> &'static str {
+    const SPACES: &str = concat!(
+        "                                                                ",
+        "                                                                ",
+    );
+    &SPACES[..n.min(SPACES.len())]
+}
+
+/// Return the longest prefix of `text` which is at most `width`
+/// columns wide according to `measure`. The text is only cut between
+/// grapheme clusters.
+fn truncate_to_width(text: &str, width: usize, measure: core::WidthMeasure) -> &str {
+    let mut total = 0;
+    for (idx, cluster_width) in measure.clusters(text) {
+        total += cluster_width;
+        if total > width {
+            return &text[..idx];
+        }
+    }
+    text
+}
+
+/// Wrap a single line of text (without any `'\n'`) and push the
+/// location of the resulting lines onto `ranges`. The ranges are
+/// shifted by `offset`. The first resulting line is indented with
+/// `initial_indent`, the remaining lines with `subsequent_indent`
+/// followed by the first `hanging_len` bytes of `line`. The
+/// `protected` ranges are never broken, see [`wrap_with_protection`].
+#[allow(clippy::too_many_arguments)]
+fn wrap_line_ranges<'a, S: WordSplitter>(
+    line: &str,
+    offset: usize,
+    first_line: usize,
+    options: &Options<'a, S>,
+    initial_indent: Cow<'a, str>,
+    subsequent_indent: Cow<'a, str>,
+    hanging_len: usize,
+    protected: &[::core::ops::Range<usize>],
+    ranges: &mut Vec<LineRange<'a>>,
+) {
+    let initial_indent_width = options.width_measure.width(&initial_indent);
+    let subsequent_indent_width = options.width_measure.width(&subsequent_indent)
+        + options.width_measure.width(&line[..hanging_len]);
+    let indent_width = |i| {
+        if i == 0 {
+            initial_indent_width
+        } else {
+            subsequent_indent_width
+        }
+    };
+    // An indentation which is too wide leaves a single column for
+    // the text.
+    let line_lengths = |i| {
+        line_width(options, first_line + i)
+            .saturating_sub(indent_width(i))
+            .max(1)
+    };
+    let break_width = match options.width_fn {
+        // A paragraph never needs more lines than it has columns, so
+        // this is the narrowest line which can be used.
+        Some(_) => (0..options.width_measure.width(line).max(1))
+            .map(line_lengths)
+            .min()
+            .unwrap_or(0),
+        None => line_lengths(1),
+    };
+
+    let start = ranges.len();
+    let words = options
+        .break_rules
+        .join_words(line, options.word_separator.find_words(line));
+
+    let mut split_words = split_words_flagged(&words, options);
+    let mut joined = vec![false; split_words.len()];
+    protect_ranges(&mut split_words, &mut joined, offset, protected);
+    let mut pieces = BrokenWords::default();
+    let mut line_sizes = break_and_wrap(
+        line,
+        &split_words,
+        &mut joined.clone(),
+        options,
+        &|_, _| break_width,
+        line_lengths,
+        &mut pieces,
+    );
+    if options.width_fn.is_some() {
+        // The words broken for the narrowest line are broken again
+        // for the lines they land on, which are found by wrapping
+        // the previous pieces. A few attempts are made before the
+        // pieces for the narrowest line are kept.
+        let mut landing = broken_word_lines(&pieces, &line_sizes, split_words.len());
+        for _ in 0..3 {
+            if landing.iter().all(Option::is_none) {
+                break;
+            }
+            let width = |group: usize, piece: usize| match landing[group] {
+                Some((line, used)) if piece == 0 => line_lengths(line).saturating_sub(used).max(1),
+                Some((line, _)) => line_lengths(line + piece),
+                None => break_width,
+            };
+            let mut refit = BrokenWords::default();
+            let refit_sizes = break_and_wrap(
+                line,
+                &split_words,
+                &mut joined.clone(),
+                options,
+                &width,
+                line_lengths,
+                &mut refit,
+            );
+            let refit_landing = broken_word_lines(&refit, &refit_sizes, split_words.len());
+            let broken = |group: usize| landing[group].is_some() || refit_landing[group].is_some();
+            if broken_lines_fit(&refit, &refit_sizes, broken, line_lengths) {
+                pieces = refit;
+                line_sizes = refit_sizes;
+                break;
+            }
+            landing = refit_landing;
+        }
+    }
+    let broken_words = &pieces.words;
+    let mut rest = &broken_words[..];
+    let mut wrapped_words = line_sizes
+        .into_iter()
+        .map(|size| {
+            let (words, tail) = rest.split_at(size);
+            rest = tail;
+            words
+        })
+        .collect::<Vec<_>>();
+    if let Some(max_lines) = options.balance {
+        if (2..=max_lines).contains(&wrapped_words.len()) {
+            wrapped_words = balance_lines(
+                broken_words,
+                wrapped_words,
+                options,
+                line_lengths,
+                indent_width,
+            );
+        }
+    }
+
+    let mut idx = 0;
+    let mut prefix = Cow::Borrowed("");
+    for words in wrapped_words {
+        let is_first = ranges.len() == start;
+        let last_word = match words.last() {
+            None => {
+                ranges.push(LineRange {
+                    indent: Cow::Borrowed(""),
+                    hanging_indent: 0..0,
+                    prefix: Cow::Borrowed(""),
+                    range: offset + idx..offset + idx,
+                    penalty: Cow::Borrowed(""),
+                    hanging_punctuation: 0,
+                });
+                continue;
+            }
+            Some(word) => word,
+        };
+
+        // We assume here that all words are contiguous in `line`.
+        // That is, the sum of their lengths should add up to the
+        // length of `line`.
+        let len = words
+            .iter()
+            .map(|word| word.len() + word.whitespace.len())
+            .sum::<usize>()
+            - last_word.whitespace.len();
+
+        // A word which only holds characters removed by `build_line`,
+        // such as a lone soft hyphen, would leave its whitespace at
+        // the start or end of the line.
+        // An empty word with whitespace holds the leading whitespace
+        // of the line, which is kept.
+        let invisible = |word: &&core::Word<'_>| {
+            (!word.is_empty() || word.whitespace.is_empty())
+                && word.chars().all(|ch| ch == core::WJ || ch == core::SHY)
+        };
+        let visible_len = |words: &[core::Word<'_>]| {
+            words
+                .iter()
+                .map(|word| word.len() + word.whitespace.len())
+                .sum::<usize>()
+        };
+        let skip = words.iter().take_while(invisible).count();
+        let keep = words.len() - words.iter().rev().take_while(invisible).count();
+        let visible = match keep > skip {
+            true => {
+                visible_len(&words[..skip])
+                    ..visible_len(&words[..keep]) - words[keep - 1].whitespace.len()
+            }
+            false => 0..0,
+        };
+
+        ranges.push(LineRange {
+            indent: if is_first {
+                initial_indent.clone()
+            } else {
+                subsequent_indent.clone()
+            },
+            hanging_indent: if is_first || hanging_len == 0 {
+                0..0
+            } else {
+                offset..offset + hanging_len
+            },
+            // The respelled letters of a word split with a
+            // Substitution are borrowed from the splitter, so they
+            // are copied. This is only needed for the rare lines
+            // which end in such a split.
+            prefix: ::core::mem::replace(
+                &mut prefix,
+                match last_word.after_break {
+                    "" => Cow::Borrowed(""),
+                    after_break => Cow::Owned(String::from(after_break)),
+                },
+            ),
+            range: offset + idx + visible.start..offset + idx + visible.end,
+            penalty: if last_word.marker_width > 0 {
+                Cow::Borrowed(options.word_break_marker)
+            } else if last_word.before_break.is_empty() {
+                Cow::Borrowed(last_word.penalty)
+            } else {
+                Cow::Owned([last_word.before_break, last_word.penalty].concat())
+            },
+            hanging_punctuation: words
+                .iter()
+                .find(|word| !word.is_empty() || !word.whitespace.is_empty())
+                .map_or(0, |word| hanging_width(options, word)),
+        });
+
+        // Advance by the length of the line, plus the length of
+        // `last_word.whitespace` -- even if we had a penalty, we
+        // need to skip over the whitespace.
+        idx += len + last_word.whitespace.len();
+    }
+}
+
+/// Split `words` according to `options`. Each fragment is returned
+/// together with a flag telling if it ends where the splitter split
+/// the word, and a flag telling if it must not be broken.
+fn split_words_flagged<'a, S: WordSplitter>(
+    words: &[core::Word<'a>],
+    options: &'a Options<'_, S>,
+) -> Vec<(core::Word<'a>, bool, bool)> {
+    let mut split_words = Vec::with_capacity(words.len());
+    for &word in words {
+        let start = split_words.len();
+        // No hyphens are inserted into URLs, so their fragments do
+        // not count as hyphenated and they are never force-broken.
+        let url = options.url_breaks != UrlBreaks::Anywhere && core::is_url_like(&word);
+        split_words
+            .extend(core::split_words(::core::iter::once(word), options).map(|w| (w, !url, url)));
+        if let Some((_, split, _)) = split_words[start..].last_mut() {
+            *split = false;
+        }
+    }
+    split_words
+}
+
+/// Mark the `split_words` which overlap a `protected` range as
+/// unbreakable and join the fragments which end inside such a range
+/// with the following fragment. The `split_words` start at `offset`
+/// in the text which the ranges refer to.
+fn protect_ranges(
+    split_words: &mut [(core::Word<'_>, bool, bool)],
+    joined: &mut [bool],
+    offset: usize,
+    protected: &[::core::ops::Range<usize>],
+) {
+    let last = split_words.len().saturating_sub(1);
+    let mut end = offset;
+    for (idx, (word, _, unbreakable)) in split_words.iter_mut().enumerate() {
+        let start = end;
+        let word_end = start + word.len();
+        end = word_end + word.whitespace.len();
+        for range in protected {
+            if range.start < end && start < range.end {
+                *unbreakable = true;
+            }
+            // A break after the fragment would fall somewhere between
+            // `word_end` and `end`.
+            if idx < last && range.start < end && word_end < range.end {
+                joined[idx] = true;
+            }
+        }
+    }
+}
+
+/// Words broken by [`join_and_break_words`].
+#[derive(Default)]
+struct BrokenWords<'a> {
+    /// The broken words.
+    words: Vec<core::Word<'a>>,
+    /// For each word, the index of the fragment in `split_words` it
+    /// ends with, if the word ends where the splitter split a word.
+    hyphenated: Vec<Option<usize>>,
+    /// For each word, the index of the first fragment of the joined
+    /// fragments it comes from.
+    groups: Vec<usize>,
+}
+
+impl BrokenWords<'_> {
+    fn truncate(&mut self, len: usize) {
+        self.words.truncate(len);
+        self.hyphenated.truncate(len);
+        self.groups.truncate(len);
+    }
+}
+
+/// Break the `split_words` of `line` according to `options`, starting
+/// with the fragment at `from`, and append the result to `pieces`.
+/// The fragments marked in `joined` are first joined with the
+/// following fragment. Words with an unbreakable fragment, such as a
+/// part of a URL (see [`UrlBreaks`]), are not broken.
+///
+/// The words must be passed to [`finish_broken_words`] before they
+/// are wrapped.
+fn join_and_break_words<'a, S: ?Sized>(
+    line: &'a str,
+    split_words: &[(core::Word<'a>, bool, bool)],
+    joined: &[bool],
+    options: &Options<'_, S>,
+    break_width: &dyn Fn(usize, usize) -> usize,
+    from: usize,
+    pieces: &mut BrokenWords<'a>,
+) {
+    let marker_width = options.width_measure.width(options.word_break_marker);
+    let first = pieces.words.len();
+    if from == 0 && options.long_words == LongWords::Break && !options.initial_indent.is_empty() {
+        // Without this, the first word will always go into the first
+        // line. However, since we break words based on the _second_
+        // line width, it can be wrong to unconditionally put the
+        // first word onto the first line. An empty zero-width word
+        // fixed this.
+        pieces.words.push(core::Word::from(""));
+        pieces.hyphenated.push(None);
+        pieces.groups.push(0);
+    }
+
+    // We assume here that all words are contiguous in `line`.
+    let (mut start, mut end) = match split_words.get(from) {
+        Some((word, ..)) => {
+            let offset = word.as_ptr() as usize - line.as_ptr() as usize;
+            (offset, offset)
+        }
+        None => (line.len(), line.len()),
+    };
+    let mut group = from;
+    let mut unbreakable = false;
+    for (idx, (word, split, fragment_unbreakable)) in split_words.iter().enumerate().skip(from) {
+        let word_end = end + word.len();
+        end = word_end + word.whitespace.len();
+        unbreakable |= fragment_unbreakable;
+        if joined[idx] {
+            continue;
+        }
+        let word = word.with_word(&line[start..word_end], options.width_measure);
+        start = end;
+
+        let breakable =
+            options.long_words == LongWords::Break && !::core::mem::take(&mut unbreakable);
+        let before = pieces.words.len();
+        // Most words fit and are pushed as they are, which saves an
+        // allocation per word.
+        if breakable && core::Fragment::width(&word) > break_width(group, 0) {
+            pieces.words.extend(core::break_words_with_marker(
+                ::core::iter::once(word),
+                |piece| break_width(group, piece),
+                marker_width,
+                options.width_measure,
+                &options.word_separator,
+                options.break_rules,
+            ));
+        } else {
+            pieces.words.push(word);
+        }
+        let n = pieces.words.len() - before;
+        pieces
+            .hyphenated
+            .extend((0..n).map(|i| Some(idx).filter(|_| *split && i + 1 == n)));
+        pieces.groups.extend((0..n).map(|_| group));
+        group = idx + 1;
+    }
+
+    // The empty word goes after the leading whitespace of the line,
+    // so that the whitespace is not moved to the start of the second
+    // line when the first line is left empty.
+    if from == 0
+        && options.long_words == LongWords::Break
+        && !options.initial_indent.is_empty()
+        && pieces
+            .words
+            .get(first + 1)
+            .is_some_and(|word| word.is_empty() && !word.whitespace.is_empty())
+    {
+        pieces.words.swap(first, first + 1);
+        pieces.hyphenated.swap(first, first + 1);
+    }
+}
+
+/// Adjust the widths of the `words` from `from` onwards for respelled
+/// split words and for hanging punctuation. The word before `from`
+/// must already have been adjusted.
+fn finish_broken_words<S: ?Sized>(
+    words: &mut [core::Word<'_>],
+    from: usize,
+    options: &Options<'_, S>,
+) {
+    core::respell_split_words(&mut words[from.saturating_sub(1)..], options.width_measure);
+    if options.hanging_punctuation {
+        core::hang_punctuation(&mut words[from..], |word| hanging_width(options, word));
+    }
+}
+
+/// Wrap the `pieces` so that at most `max` lines in a row end in a
+/// word which was split by the splitter. Returns the number of words
+/// on each line.
+///
+/// When a line ends in a split word after `max` such lines, the split
+/// is joined in `joined` and the lines from the one holding the start
+/// of the word onwards are wrapped again. The lines before it are
+/// kept, so every line is only involved in a few wrappings.
+#[allow(clippy::too_many_arguments)]
+fn limit_hyphenated_lines<'a, S: ?Sized>(
+    line: &'a str,
+    split_words: &[(core::Word<'a>, bool, bool)],
+    joined: &mut [bool],
+    options: &Options<'_, S>,
+    break_width: &dyn Fn(usize, usize) -> usize,
+    max: usize,
+    line_lengths: impl Fn(usize) -> usize + Copy,
+    pieces: &mut BrokenWords<'a>,
+) -> Vec<usize> {
+    // Number of words on each kept line, the number of words on the
+    // kept lines, and for each kept line the number of lines ending
+    // in a split word in a row before it.
+    let mut line_sizes = Vec::new();
+    let mut kept = 0;
+    let mut counts = Vec::new();
+    loop {
+        // Split points which must not be used since they would give
+        // too many hyphenated lines in a row are marked in `joined`.
+        //
+        // The word before the remaining words is included so that
+        // the remaining words can be adjusted like the kept words.
+        let skip = usize::from(kept > 0);
+        let mut rest = pieces.words[kept - skip..].to_vec();
+        finish_broken_words(&mut rest, skip, options);
+        let first_line = line_sizes.len();
+        let sizes = wrap_words(&rest[skip..], options, |i| line_lengths(first_line + i))
+            .iter()
+            .map(|words| words.len())
+            .collect::<Vec<_>>();
+
+        let mut count = match (line_sizes.last(), counts.last()) {
+            (Some(&size), Some(&count)) if size > 0 && pieces.hyphenated[kept - 1].is_some() => {
+                count + 1
+            }
+            _ => 0,
+        };
+        let mut excess = None;
+        for &size in &sizes {
+            if size > 0 {
+                if let Some(idx) = pieces.hyphenated[kept + size - 1] {
+                    if count == max {
+                        excess = Some((idx, kept + size - 1));
+                        break;
+                    }
+                }
+            }
+            line_sizes.push(size);
+            counts.push(count);
+            kept += size;
+            count = match pieces.hyphenated[..kept].last() {
+                Some(Some(_)) if size > 0 => count + 1,
+                _ => 0,
+            };
+        }
+
+        let (idx, last) = match excess {
+            Some(excess) => excess,
+            None => return line_sizes,
+        };
+
+        // Join the split word and wrap it again together with the
+        // lines after the line where the word starts.
+        joined[idx] = true;
+        // A long word broken by `break_words` can span several lines,
+        // so the kept lines must also end between two words.
+        let mut target = last;
+        while kept > 0 {
+            let group = pieces.groups[target];
+            let group_start = pieces.groups[..target].partition_point(|&g| g < group);
+            while kept > group_start {
+                kept -= line_sizes.pop().unwrap_or_default();
+                counts.pop();
+            }
+            if kept == 0 || pieces.groups[kept - 1] != pieces.groups[kept] {
+                break;
+            }
+            target = kept;
+        }
+        let from = pieces.groups.get(kept).copied().unwrap_or(0);
+        pieces.truncate(kept);
+        join_and_break_words(
+            line,
+            split_words,
+            joined,
+            options,
+            break_width,
+            from,
+            pieces,
+        );
+    }
+}
+
+/// Break the `split_words` of `line` with [`join_and_break_words`]
+/// and wrap the pieces in `line_lengths`. The `break_width` of each
+/// piece is given by the group of the word and the index of the
+/// piece. Returns the number of pieces on each line.
+fn break_and_wrap<'a, S: ?Sized>(
+    line: &'a str,
+    split_words: &[(core::Word<'a>, bool, bool)],
+    joined: &mut [bool],
+    options: &Options<'_, S>,
+    break_width: &dyn Fn(usize, usize) -> usize,
+    line_lengths: impl Fn(usize) -> usize + Copy,
+    pieces: &mut BrokenWords<'a>,
+) -> Vec<usize> {
+    join_and_break_words(line, split_words, joined, options, break_width, 0, pieces);
+    let line_sizes = match options.max_consecutive_hyphens {
+        Some(max) => limit_hyphenated_lines(
+            line,
+            split_words,
+            joined,
+            options,
+            break_width,
+            max,
+            line_lengths,
+            pieces,
+        ),
+        None => Vec::new(),
+    };
+    finish_broken_words(&mut pieces.words, 0, options);
+    match options.max_consecutive_hyphens {
+        Some(_) => line_sizes,
+        None => wrap_words(&pieces.words, options, line_lengths)
+            .iter()
+            .map(|words| words.len())
+            .collect(),
+    }
+}
+
+/// Find where each word which is broken into several `pieces` lands
+/// when they are wrapped into lines with `line_sizes` pieces. The
+/// result is indexed by the group of the word, and gives the line of
+/// its first piece and the columns used before it on that line.
+fn broken_word_lines(
+    pieces: &BrokenWords<'_>,
+    line_sizes: &[usize],
+    groups: usize,
+) -> Vec<Option<(usize, usize)>> {
+    // The empty word before the first word is not a piece.
+    let mut counts = vec![0; groups + 1];
+    for (word, &group) in pieces.words.iter().zip(&pieces.groups) {
+        counts[group] += usize::from(!word.is_empty());
+    }
+
+    let mut landing = vec![None; groups + 1];
+    let mut idx = 0;
+    for (line, &size) in line_sizes.iter().enumerate() {
+        let mut used = 0;
+        for (word, &group) in pieces.words[idx..idx + size]
+            .iter()
+            .zip(&pieces.groups[idx..idx + size])
+        {
+            if counts[group] > 1 && !word.is_empty() && landing[group].is_none() {
+                landing[group] = Some((line, used));
+            }
+            used += core::Fragment::width(word) + core::Fragment::whitespace_width(word);
+        }
+        idx += size;
+    }
+    landing
+}
+
+/// Check that the lines with a piece of a `broken` word, given by
+/// its group, fit in their `line_lengths`.
+fn broken_lines_fit(
+    pieces: &BrokenWords<'_>,
+    line_sizes: &[usize],
+    broken: impl Fn(usize) -> bool,
+    line_lengths: impl Fn(usize) -> usize,
+) -> bool {
+    let mut idx = 0;
+    line_sizes.iter().enumerate().all(|(line, &size)| {
+        let words = &pieces.words[idx..idx + size];
+        let broken = pieces.groups[idx..idx + size]
+            .iter()
+            .any(|&group| broken(group));
+        idx += size;
+        let width = match words.split_last() {
+            Some((last, words)) => {
+                words
+                    .iter()
+                    .map(|word| {
+                        core::Fragment::width(word) + core::Fragment::whitespace_width(word)
+                    })
+                    .sum::<usize>()
+                    + core::Fragment::width(last)
+                    + core::Fragment::penalty_width(last)
+            }
+            None => 0,
+        };
+        !broken || width <= line_lengths(line)
+    })
+}
+
+/// Find the width of the character at the start of `word` which
+/// hangs into the margin, see [`Options::hanging_punctuation`].
+fn hanging_width<S: ?Sized>(options: &Options<'_, S>, word: &str) -> usize {
+    if !options.hanging_punctuation {
+        return 0;
+    }
+    match word.chars().next() {
+        Some(ch) if options.hanging_punctuation_chars.contains(ch) => {
+            options.width_measure.width(&word[..ch.len_utf8()])
+        }
+        _ => 0,
+    }
+}
+
+/// Wrap `words` into lines using the algorithm of `options`.
+fn wrap_words<'w, 'a, S: ?Sized>(
+    words: &'w [core::Word<'a>],
+    options: &Options<'_, S>,
+    line_lengths: impl Fn(usize) -> usize + Copy,
+) -> Vec<&'w [core::Word<'a>]> {
+    let mut wrapped_words = match options.wrap_algorithm {
+        #[cfg(feature = "smawk")]
+        core::WrapAlgorithm::OptimalFit => {
+            core::wrap_optimal_fit(words, line_lengths, &options.penalties)
+        }
+        core::WrapAlgorithm::FirstFit => core::wrap_first_fit(words, line_lengths),
+    };
+    avoid_short_last_line(
+        words,
+        &mut wrapped_words,
+        options.min_last_line_width,
+        line_lengths,
+    );
+    wrapped_words
+}
+
+/// Wrap `words` again with narrower lines to make the lines of
+/// `wrapped_words` as even as possible, see [`Options::balance`].
+///
+/// The lines are made narrower one column at a time. Of the results
+/// with the same number of lines as `wrapped_words`, the one with the
+/// smallest difference between the longest and the shortest line
+/// (including the indentation given by `indent_width`) is returned.
+fn balance_lines<'w, 'a, S: ?Sized>(
+    words: &'w [core::Word<'a>],
+    wrapped_words: Vec<&'w [core::Word<'a>]>,
+    options: &Options<'_, S>,
+    line_lengths: impl Fn(usize) -> usize + Copy,
+    indent_width: impl Fn(usize) -> usize,
+) -> Vec<&'w [core::Word<'a>]> {
+    let spread = |lines: &[&[core::Word<'_>]]| {
+        let widths = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| indent_width(i) + words_width(line));
+        widths.clone().max().unwrap_or(0) - widths.min().unwrap_or(0)
+    };
+
+    let line_count = wrapped_words.len();
+    let mut best_spread = spread(&wrapped_words);
+    let mut best = wrapped_words;
+    let narrowest = (0..line_count).map(line_lengths).min().unwrap_or(0);
+    for shrink in 1..narrowest {
+        // Lines after the last line can be narrower than `shrink`
+        // with an `Options::width_fn`. They are only used by wrappings
+        // with too many lines, which are rejected below.
+        let shrunk_lengths = move |i| line_lengths(i).saturating_sub(shrink);
+        let candidate = wrap_words(words, options, shrunk_lengths);
+        if candidate.len() == line_count && spread(&candidate) < best_spread {
+            best_spread = spread(&candidate);
+            best = candidate;
+        }
+    }
+    best
+}
+
+/// Compute the width of a line consisting of `words`.
+fn words_width(words: &[core::Word<'_>]) -> usize {
+    use core::Fragment;
+    match words.split_last() {
+        None => 0,
+        Some((last, rest)) => {
+            rest.iter()
+                .map(|word| word.width() + word.whitespace_width())
+                .sum::<usize>()
+                + last.width()
+                + last.penalty_width()
+        }
+    }
+}
+
+/// Move the last word of the second-last line down to the last line
+/// if the last line is narrower than `min_width`. The `lines` are
+/// consecutive slices of `words`.
+///
+/// Only whole words are moved: the new end of the second-last line
+/// must be followed by whitespace. Nothing is changed if the
+/// second-last line would become empty or if the last line would
+/// become wider than allowed by `line_widths`.
+fn avoid_short_last_line<'w, 'a>(
+    words: &'w [core::Word<'a>],
+    lines: &mut [&'w [core::Word<'a>]],
+    min_width: usize,
+    line_widths: impl Fn(usize) -> usize,
+) {
+    let n = lines.len();
+    if n < 2 || words_width(lines[n - 1]) >= min_width {
+        return;
+    }
+
+    let start = lines[..n - 2].iter().map(|line| line.len()).sum::<usize>();
+    let end = start + lines[n - 2].len() + lines[n - 1].len();
+    let prev = lines[n - 2];
+    if prev.is_empty() {
+        return;
+    }
+    // Find the last whitespace boundary in the second-last line,
+    // excluding the end of the line itself.
+    let split = match prev[..prev.len() - 1]
+        .iter()
+        .rposition(|word| !word.whitespace.is_empty() && !word.whitespace.starts_with(core::SHY))
+    {
+        Some(idx) => start + idx + 1,
+        None => return,
+    };
+
+    if words_width(&words[split..end]) <= line_widths(n - 1) {
+        lines[n - 2] = &words[start..split];
+        lines[n - 1] = &words[split..end];
+    }
+}
+
+/// Wrap text into columns with a given total width.
+///
+/// The `left_gap`, `middle_gap` and `right_gap` arguments specify the
+/// strings to insert before, between, and after the columns. The
+/// total width of all columns and all gaps is specified using the
+/// `total_width_or_options` argument. This argument can simply be an
+/// integer if you want to use default settings when wrapping, or it
+/// can be a [`Options`] value if you want to customize the wrapping.
+///
+/// If the columns are narrow, it is recommended to set
+/// [`Options::long_words`] to [`LongWords::Break`] to prevent words from
+/// protruding into the margins.
+///
+/// The per-column width is computed like this:
 ///
 /// ```
-/// # use textwrap::{Options, wrap};
-/// # use textwrap::core::WrapAlgorithm::FirstFit;
-/// #
-/// # let lines = wrap("To be, or not to be: that is the question",
-/// #                  Options::new(10).wrap_algorithm(FirstFit));
-/// # assert_eq!(lines.join("\n") + "\n", "\
-/// To be, or
-/// not to be:
-/// that is
-/// the
-/// question
-/// # ");
+/// # let (left_gap, middle_gap, right_gap) = ("", "", "");
+/// # let columns = 2;
+/// # let options = textwrap::Options::new(80);
+/// let inner_width = options.width
+///     - textwrap::core::display_width(left_gap)
+///     - textwrap::core::display_width(right_gap)
+///     - textwrap::core::display_width(middle_gap) * (columns - 1);
+/// let column_width = inner_width / columns;
 /// ```
 ///
-/// Notice how the second to last line is quite narrow because
-/// “question” was too large to fit? The greedy first-fit algorithm
-/// doesn’t look ahead, so it has no other option than to put
-/// “question” onto its own line.
+/// The `text` is wrapped using [`wrap`] and the given `options`
+/// argument, but the width is overwritten to the computed
+/// `column_width`.
 ///
-/// With the optimal-fit wrapping algorithm, the previous lines are
-/// shortened slightly in order to make the word “is” go into the
-/// second last line:
+/// The lines are distributed top-to-bottom over the columns. Columns
+/// which would be completely empty are left out, together with their
+/// gaps. This means that a text which fits in a single column is
+/// returned as a single column:
 ///
 /// ```
-/// # #[cfg(feature = "smawk")] {
-/// # use textwrap::{Options, wrap};
-/// # use textwrap::core::WrapAlgorithm::OptimalFit;
-/// #
-/// # let lines = wrap("To be, or not to be: that is the question",
-/// #                  Options::new(10).wrap_algorithm(OptimalFit));
-/// # assert_eq!(lines.join("\n") + "\n", "\
-/// To be,
-/// or not to
-/// be: that
-/// is the
-/// question
-/// # "); }
+/// use textwrap::wrap_columns;
+///
+/// assert_eq!(wrap_columns("Foo", 3, 30, "| ", " | ", " |"),
+///            vec!["| Foo    |"]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `columns` is zero.
+///
+/// # Examples
+///
 /// ```
+/// use textwrap::wrap_columns;
+///
+/// let text = "\
+/// This is an example text, which is wrapped into three columns. \
+/// Notice how the final column can be shorter than the others.";
+///
+/// #[cfg(feature = "smawk")]
+/// assert_eq!(wrap_columns(text, 3, 50, "| ", " | ", " |"),
+///            vec!["| This is       | into three    | column can be  |",
+///                 "| an example    | columns.      | shorter than   |",
+///                 "| text, which   | Notice how    | the others.    |",
+///                 "| is wrapped    | the final     |                |"]);
+///
+/// // Without the `smawk` feature, the middle column is a little more uneven:
+/// #[cfg(not(feature = "smawk"))]
+/// assert_eq!(wrap_columns(text, 3, 50, "| ", " | ", " |"),
+///            vec!["| This is an    | three         | column can be  |",
+///                 "| example text, | columns.      | shorter than   |",
+///                 "| which is      | Notice how    | the others.    |",
+///                 "| wrapped into  | the final     |                |"]);
+pub fn wrap_columns<'a, S, Opt>(
+    text: &str,
+    columns: usize,
+    total_width_or_options: Opt,
+    left_gap: &str,
+    middle_gap: &str,
+    right_gap: &str,
+) -> Vec<String>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    assert!(columns > 0);
+
+    let mut options = total_width_or_options.into();
+
+    let measure = options.width_measure;
+    let inner_width = options
+        .width
+        .saturating_sub(measure.width(left_gap))
+        .saturating_sub(measure.width(right_gap))
+        .saturating_sub(measure.width(middle_gap) * (columns - 1));
+
+    let column_width = ::core::cmp::max(inner_width / columns, 1);
+    options.width = column_width;
+    let last_column_padding = " ".repeat(inner_width % column_width);
+    let wrapped_lines = wrap(text, options);
+    let lines_per_column =
+        wrapped_lines.len() / columns + usize::from(wrapped_lines.len() % columns > 0);
+    // Skip trailing columns which would be completely empty.
+    let used_columns = wrapped_lines.len() / lines_per_column
+        + usize::from(wrapped_lines.len() % lines_per_column > 0);
+    let mut lines = Vec::new();
+    for line_no in 0..lines_per_column {
+        let mut line = String::from(left_gap);
+        for column_no in 0..used_columns {
+            match wrapped_lines.get(line_no + column_no * lines_per_column) {
+                Some(column_line) => {
+                    line.push_str(column_line);
+                    line.push_str(&" ".repeat(column_width - measure.width(column_line)));
+                }
+                None => {
+                    line.push_str(&" ".repeat(column_width));
+                }
+            }
+            if column_no == columns - 1 {
+                line.push_str(&last_column_padding);
+            } else if column_no < used_columns - 1 {
+                line.push_str(middle_gap);
+            }
+        }
+        line.push_str(right_gap);
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Wrap the cells of a table row so the cells can be put side by
+/// side.
+///
+/// Each cell in `cells` is wrapped using [`wrap`] and the given
+/// `options`, but with the width overwritten to the corresponding
+/// width in `widths`. The result has one entry per line of the row,
+/// and each entry has one string per cell. Cells which need fewer
+/// lines than the tallest cell are padded with blank lines, and every
+/// line is padded with spaces to the width of its column, as measured
+/// by [`Options::width_measure`]. This keeps the columns aligned even
+/// when a cell contains wide characters, so the lines of the row can
+/// be built by joining the cells with a separator.
+///
+/// A line can only be wider than its column if the column is too
+/// narrow for a single character or if [`Options::long_words`] is
+/// not [`LongWords::Break`]. The following columns are shifted in that case.
+///
+/// # Panics
 ///
-/// Please see [`core::WrapAlgorithm`] for details.
+/// Panics if `cells` and `widths` have different lengths.
 ///
 /// # Examples
 ///
-/// The returned iterator yields lines of type `Cow<'_, str>`. If
-/// possible, the wrapped lines will borrow from the input string. As
-/// an example, a hanging indentation, the first line can borrow from
-/// the input, but the subsequent lines become owned strings:
-///
 /// ```
-/// use std::borrow::Cow::{Borrowed, Owned};
-/// use textwrap::{wrap, Options};
+/// use textwrap::wrap_table_row;
 ///
-/// let options = Options::new(15).subsequent_indent("....");
-/// let lines = wrap("Wrapping text all day long.", &options);
-/// let annotated = lines
-///     .iter()
-///     .map(|line| match line {
-///         Borrowed(text) => format!("[Borrowed] {}", text),
-///         Owned(text) => format!("[Owned]    {}", text),
-///     })
-///     .collect::<Vec<_>>();
-/// assert_eq!(
-///     annotated,
-///     &[
-///         "[Borrowed] Wrapping text",
-///         "[Owned]    ....all day",
-///         "[Owned]    ....long.",
-///     ]
-/// );
+/// let lines = wrap_table_row(&["Name", "A short description", "日本語"], &[6, 11, 4], 80);
+/// let lines = lines.iter().map(|cells| cells.join("|")).collect::<Vec<_>>();
+/// assert_eq!(lines, vec!["Name  |A short    |日本",
+///                        "      |description|語  "]);
 /// ```
+pub fn wrap_table_row<'a, S, Opt>(
+    cells: &[&str],
+    widths: &[usize],
+    width_or_options: Opt,
+) -> Vec<Vec<String>>
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    assert_eq!(cells.len(), widths.len());
+
+    let mut options = width_or_options.into();
+    let measure = options.width_measure;
+    let wrapped_cells = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, &width)| {
+            options.width = width;
+            wrap(cell, &options)
+        })
+        .collect::<Vec<_>>();
+    let line_count = wrapped_cells.iter().map(Vec::len).max().unwrap_or(0);
+
+    (0..line_count)
+        .map(|line_no| {
+            wrapped_cells
+                .iter()
+                .zip(widths)
+                .map(|(lines, &width)| {
+                    let line = lines.get(line_no).map_or("", |line| line);
+                    let mut padded = String::from(line);
+                    padded.push_str(&" ".repeat(width.saturating_sub(measure.width(line))));
+                    padded
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The characters used to draw the border in [`fill_boxed`].
 ///
-/// ## Leading and Trailing Whitespace
+/// Use [`BoxStyle::UNICODE`] or [`BoxStyle::ASCII`], or set the
+/// fields to draw the border with other characters. An optional title
+/// can be added with [`BoxStyle::title`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxStyle<'a> {
+    /// Character in the top left corner.
+    pub top_left: char,
+    /// Character in the top right corner.
+    pub top_right: char,
+    /// Character in the bottom left corner.
+    pub bottom_left: char,
+    /// Character in the bottom right corner.
+    pub bottom_right: char,
+    /// Character used for the top and bottom borders.
+    pub horizontal: char,
+    /// Character used for the left and right borders.
+    pub vertical: char,
+    /// Title shown in the top border.
+    pub title: Option<&'a str>,
+}
+
+impl BoxStyle<'static> {
+    /// Border drawn with Unicode box-drawing characters:
+    ///
+    /// ```text
+    /// ┌───┐
+    /// │foo│
+    /// └───┘
+    /// ```
+    pub const UNICODE: BoxStyle<'static> = BoxStyle {
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+        horizontal: '─',
+        vertical: '│',
+        title: None,
+    };
+
+    /// Border drawn with ASCII characters:
+    ///
+    /// ```text
+    /// +---+
+    /// |foo|
+    /// +---+
+    /// ```
+    pub const ASCII: BoxStyle<'static> = BoxStyle {
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+        horizontal: '-',
+        vertical: '|',
+        title: None,
+    };
+}
+
+impl<'a> BoxStyle<'a> {
+    /// Show `title` in the top border. A title which does not fit is
+    /// truncated, see [`fill_boxed`].
+    pub const fn title(self, title: &'a str) -> Self {
+        BoxStyle {
+            title: Some(title),
+            ..self
+        }
+    }
+}
+
+impl Default for BoxStyle<'_> {
+    fn default() -> Self {
+        BoxStyle::UNICODE
+    }
+}
+
+/// Fill `text` and draw a border around it.
 ///
-/// As a rule, leading whitespace (indentation) is preserved and
-/// trailing whitespace is discarded.
+/// The text is wrapped using [`wrap`] and the given `options`. Every
+/// line is padded with spaces to the full width, as measured by
+/// [`Options::width_measure`], and put between the left and right
+/// borders of `style`. The inside of the box is as wide as the
+/// width of the options, but it is widened if a line is longer (e.g.,
+/// because long words are not broken, see [`Options::long_words`]).
+/// An empty text gives an empty box with only the top and bottom
+/// borders.
 ///
-/// In more details, when wrapping words into lines, words are found
-/// by splitting the input text on space characters. One or more
-/// spaces (shown here as “␣”) are attached to the end of each word:
+/// The title of the `style` never widens the box. A title which does
+/// not fit is truncated and ends with the [`Options::placeholder`],
+/// and it is left out if the box is too narrow for any of it.
 ///
-/// ```text
-/// "Foo␣␣␣bar␣baz" -> ["Foo␣␣␣", "bar␣", "baz"]
-/// ```
+/// The lines are separated by [`Options::line_ending`].
 ///
-/// These words are then put into lines. The interword whitespace is
-/// preserved, unless the lines are wrapped so that the `"Foo␣␣␣"`
-/// word falls at the end of a line:
+/// # Examples
 ///
 /// ```
-/// use textwrap::wrap;
+/// use textwrap::{fill_boxed, BoxStyle};
 ///
-/// assert_eq!(wrap("Foo   bar baz", 10), vec!["Foo   bar", "baz"]);
-/// assert_eq!(wrap("Foo   bar baz", 8), vec!["Foo", "bar baz"]);
+/// let text = "Memory safety without garbage collection.";
+/// assert_eq!(fill_boxed(text, 15, BoxStyle::UNICODE.title("Rust")),
+///            "┌─ Rust ────────┐\n\
+///             │Memory safety  │\n\
+///             │without garbage│\n\
+///             │collection.    │\n\
+///             └───────────────┘");
+///
+/// assert_eq!(fill_boxed("", 3, BoxStyle::ASCII), "+---+\n+---+");
 /// ```
+pub fn fill_boxed<'a, S, Opt>(text: &str, width_or_options: Opt, style: BoxStyle<'_>) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let measure = options.width_measure;
+    let lines = match text.is_empty() {
+        true => Vec::new(),
+        false => wrap(text, &options),
+    };
+
+    let inner_width = lines
+        .iter()
+        .map(|line| measure.width(line))
+        .chain([options.width])
+        .max()
+        .unwrap_or(0);
+
+    let mut result = String::new();
+    result.push(style.top_left);
+    let mut border_width = inner_width;
+    // The title is put between a border character and a space on the
+    // left, and a space on the right.
+    let title_room = inner_width.saturating_sub(3);
+    if let Some(title) = style.title.filter(|_| title_room > 0) {
+        result.push(style.horizontal);
+        result.push(' ');
+        let placeholder_width = measure.width(options.placeholder);
+        let title_width = if measure.width(title) <= title_room {
+            result.push_str(title);
+            measure.width(title)
+        } else if placeholder_width <= title_room {
+            let title = truncate_to_width(title, title_room - placeholder_width, measure);
+            result.push_str(title);
+            result.push_str(options.placeholder);
+            measure.width(title) + placeholder_width
+        } else {
+            let title = truncate_to_width(title, title_room, measure);
+            result.push_str(title);
+            measure.width(title)
+        };
+        result.push(' ');
+        border_width -= title_width + 3;
+    }
+    result.extend(::core::iter::repeat_n(style.horizontal, border_width));
+    result.push(style.top_right);
+
+    for line in &lines {
+        result.push_str(options.line_ending);
+        result.push(style.vertical);
+        result.push_str(&pad_line(
+            Cow::Borrowed(line),
+            inner_width,
+            Alignment::Left,
+            measure,
+        ));
+        result.push(style.vertical);
+    }
+
+    result.push_str(options.line_ending);
+    result.push(style.bottom_left);
+    result.extend(::core::iter::repeat_n(style.horizontal, inner_width));
+    result.push(style.bottom_right);
+    result
+}
+
+/// Fill `text` in-place without reallocating the input string.
 ///
-/// Notice how the trailing whitespace is removed in both case: in the
-/// first example, `"bar␣"` becomes `"bar"` and in the second case
-/// `"Foo␣␣␣"` becomes `"Foo"`.
+/// This function works by modifying the input string: some `' '`
+/// characters will be replaced by `'\n'` characters. The rest of the
+/// text remains untouched.
 ///
-/// Leading whitespace is preserved when the following word fits on
-/// the first line. To understand this, consider how words are found
-/// in a text with leading spaces:
+/// Since we can only replace existing whitespace in the input with
+/// `'\n'`, we cannot do hyphenation nor can we split words longer
+/// than the line width. Indentation is also ruled out. In other
+/// words, `fill_inplace(width)` behaves as if you had called [`fill`]
+/// with these options:
 ///
-/// ```text
-/// "␣␣foo␣bar" -> ["␣␣", "foo␣", "bar"]
+/// ```
+/// # use textwrap::{Options, NoHyphenation};
+/// # let width = 80;
+/// Options {
+///     width: width,
+///     initial_indent: "".into(),
+///     subsequent_indent: "".into(),
+///     long_words: textwrap::LongWords::Overflow,
+///     wrap_algorithm: textwrap::core::WrapAlgorithm::FirstFit,
+///     penalties: textwrap::core::Penalties::new(),
+///     max_lines: None,
+///     placeholder: "…",
+///     alignment: textwrap::Alignment::Left,
+///     detect_list_markers: false,
+///     word_break_marker: "",
+///     min_last_line_width: 0,
+///     width_fn: None,
+///     word_separator: textwrap::core::WordSeparator::AsciiSpace,
+///     break_rules: textwrap::core::BreakRules::none(),
+///     max_consecutive_hyphens: None,
+///     reapply_ansi_styles: false,
+///     width_measure: textwrap::core::WidthMeasure::UnicodeCells,
+///     expand_tabs: None,
+///     line_ending: "\n",
+///     sanitize_control_chars: false,
+///     hanging_indent_from_input: false,
+///     url_breaks: textwrap::UrlBreaks::Anywhere,
+///     balance: None,
+///     respect_newlines: true,
+///     sentence_per_line: false,
+///     abbreviations: textwrap::DEFAULT_ABBREVIATIONS,
+///     inline_markup: false,
+///     #[cfg(feature = "unicode-bidi")]
+///     bidi_reorder: false,
+///     hanging_punctuation: false,
+///     hanging_punctuation_chars: textwrap::DEFAULT_HANGING_PUNCTUATION,
+///     continuation_marker: "",
+///     splitter: NoHyphenation,
+/// };
 /// ```
 ///
-/// When put into lines, the indentation is preserved if `"foo"` fits
-/// on the first line, otherwise you end up with an empty line:
+/// The wrap algorithm is [`core::WrapAlgorithm::FirstFit`] since this
+/// is the fastest algorithm — and the main reason to use
+/// `fill_inplace` is to get the string broken into newlines as fast
+/// as possible.
 ///
-/// ```
-/// use textwrap::wrap;
+/// A last difference is that (unlike [`fill`]) `fill_inplace` can
+/// leave trailing whitespace on lines. This is because we wrap by
+/// inserting a `'\n'` at the final whitespace in the input string:
 ///
-/// assert_eq!(wrap("  foo bar", 8), vec!["  foo", "bar"]);
-/// assert_eq!(wrap("  foo bar", 4), vec!["", "foo", "bar"]);
 /// ```
-pub fn wrap<'a, S, Opt>(text: &str, width_or_options: Opt) -> Vec<Cow<'_, str>>
-where
-    S: WordSplitter,
-    Opt: Into<Options<'a, S>>,
-{
-    let options = width_or_options.into();
+/// let mut text = String::from("Hello   World!");
+/// textwrap::fill_inplace(&mut text, 10);
+/// assert_eq!(text, "Hello  \nWorld!");
+/// ```
+///
+/// If we didn't do this, the word `World!` would end up being
+/// indented. You can avoid this if you make sure that your input text
+/// has no double spaces.
+///
+/// # Performance
+///
+/// The bytes of `text` are modified in place, which means that the
+/// length and the capacity of the string stay the same. No memory is
+/// allocated for the wrapped lines, only a small buffer with the
+/// positions of the line breaks is needed.
+///
+/// In benchmarks, `fill_inplace` is about twice as fast as [`fill`].
+/// Please see the [`linear`
+/// benchmark](https://github.com/mgeisler/textwrap/blob/master/benches/linear.rs)
+/// for details.
+pub fn fill_inplace(text: &mut String, width: usize) {
+    let mut indices = Vec::new();
+
+    let mut offset = 0;
+    for line in text.split('\n') {
+        // Only ' ' can be replaced by '\n', so other separators such
+        // as zero width spaces are not considered here.
+        let words = core::find_words_separated_by(line, |ch| ch == ' ').collect::<Vec<_>>();
+        let wrapped_words = core::wrap_first_fit(&words, |_| width);
+
+        let mut line_offset = offset;
+        for words in &wrapped_words[..wrapped_words.len() - 1] {
+            let line_len = words
+                .iter()
+                .map(|word| word.len() + word.whitespace.len())
+                .sum::<usize>();
+
+            line_offset += line_len;
+            // We've advanced past all ' ' characters -- want to move
+            // one ' ' backwards and insert our '\n' there.
+            indices.push(line_offset - 1);
+        }
+
+        // Advance past entire line, plus the '\n' which was removed
+        // by the split call above.
+        offset += line.len() + 1;
+    }
+
+    let mut bytes = ::core::mem::take(text).into_bytes();
+    for idx in indices {
+        bytes[idx] = b'\n';
+    }
+    *text = String::from_utf8(bytes).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "hyphenation")]
+    use hyphenation::{extended::Extended, Language, Load, Standard};
+    use proptest::prelude::*;
+
+    #[test]
+    fn options_agree_with_usize() {
+        let opt_usize = Options::from(42_usize);
+        let opt_options = Options::new(42);
+
+        assert_eq!(opt_usize.width, opt_options.width);
+        assert_eq!(opt_usize.initial_indent, opt_options.initial_indent);
+        assert_eq!(opt_usize.subsequent_indent, opt_options.subsequent_indent);
+        assert_eq!(opt_usize.long_words, opt_options.long_words);
+        assert_eq!(
+            opt_usize.splitter.split_points("hello-world"),
+            opt_options.splitter.split_points("hello-world")
+        );
+    }
+
+    #[test]
+    fn no_wrap() {
+        assert_eq!(wrap("foo", 10), vec!["foo"]);
+    }
+
+    #[test]
+    fn wrap_simple() {
+        assert_eq!(wrap("foo bar baz", 5), vec!["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn to_be_or_not() {
+        assert_eq!(
+            wrap(
+                "To be, or not to be, that is the question.",
+                Options::new(10).wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            ),
+            vec!["To be, or", "not to be,", "that is", "the", "question."]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "smawk")]
+    fn short_word_penalty() {
+        let text = "I saw a cat on a mat in the hat";
+        assert_eq!(
+            wrap(text, 12),
+            vec!["I saw a cat", "on a mat in", "the hat"]
+        );
+
+        let penalties = core::Penalties {
+            short_word_penalty: 100,
+            ..core::Penalties::new()
+        };
+        assert_eq!(
+            wrap(text, Options::new(12).penalties(penalties)),
+            vec!["I saw a cat", "on a mat", "in the hat"]
+        );
+    }
+
+    #[test]
+    fn multiple_words_on_first_line() {
+        assert_eq!(wrap("foo bar baz", 10), vec!["foo bar", "baz"]);
+    }
+
+    #[test]
+    fn long_word() {
+        assert_eq!(wrap("foo", 0), vec!["f", "o", "o"]);
+    }
+
+    #[test]
+    fn long_words() {
+        assert_eq!(wrap("foo bar", 0), vec!["f", "o", "o", "b", "a", "r"]);
+    }
+
+    #[test]
+    fn max_width() {
+        assert_eq!(wrap("foo bar", usize::MAX), vec!["foo bar"]);
+    }
+
+    #[test]
+    fn leading_whitespace() {
+        assert_eq!(wrap("  foo bar", 6), vec!["  foo", "bar"]);
+    }
+
+    #[test]
+    fn leading_whitespace_empty_first_line() {
+        // If there is no space for the first word, the first line
+        // will be empty. This is because the string is split into
+        // words like [" ", "foobar ", "baz"], which puts "foobar " on
+        // the second line. We never output trailing whitespace
+        assert_eq!(wrap(" foobar baz", 6), vec!["", "foobar", "baz"]);
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        // Whitespace is only significant inside a line. After a line
+        // gets too long and is broken, the first word starts in
+        // column zero and is not indented.
+        assert_eq!(wrap("foo     bar     baz  ", 5), vec!["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn issue_99() {
+        // We did not reset the in_whitespace flag correctly and did
+        // not handle single-character words after a line break.
+        assert_eq!(
+            wrap("aaabbbccc x yyyzzzwww", 9),
+            vec!["aaabbbccc", "x", "yyyzzzwww"]
+        );
+    }
+
+    #[test]
+    fn issue_129() {
+        // The dash is an em-dash which takes up four bytes. We used
+        // to panic since we tried to index into the character.
+        assert_eq!(wrap("x – x", 1), vec!["x", "–", "x"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn wide_character_handling() {
+        assert_eq!(wrap("Hello, World!", 15), vec!["Hello, World!"]);
+        assert_eq!(
+            wrap("Ｈｅｌｌｏ, Ｗｏｒｌｄ!", 15),
+            vec!["Ｈｅｌｌｏ,", "Ｗｏｒｌｄ!"]
+        );
+    }
+
+    #[test]
+    fn empty_line_is_indented() {
+        // Previously, indentation was not applied to empty lines.
+        // However, this is somewhat inconsistent and undesirable if
+        // the indentation is something like a border ("| ") which you
+        // want to apply to all lines, empty or not.
+        let options = Options::new(10).initial_indent("!!!");
+        assert_eq!(fill("", &options), "!!!");
+    }
+
+    #[test]
+    fn indent_single_line() {
+        let options = Options::new(10).initial_indent(">>>"); // No trailing space
+        assert_eq!(fill("foo", &options), ">>>foo");
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn indent_first_emoji() {
+        let options = Options::new(10).initial_indent("👉👉");
+        assert_eq!(
+            wrap("x x x x x x x x x x x x x", &options),
+            vec!["👉👉x x x", "x x x x x", "x x x x x"]
+        );
+    }
+
+    #[test]
+    fn indent_multiple_lines() {
+        let options = Options::new(6).initial_indent("* ").subsequent_indent("  ");
+        assert_eq!(
+            wrap("foo bar baz", &options),
+            vec!["* foo", "  bar", "  baz"]
+        );
+    }
+
+    #[test]
+    fn indent_break_words() {
+        let options = Options::new(5).initial_indent("* ").subsequent_indent("  ");
+        assert_eq!(wrap("foobarbaz", &options), vec!["* foo", "  bar", "  baz"]);
+    }
+
+    #[test]
+    fn initial_indent_break_words() {
+        // This is a corner-case showing how the long word is broken
+        // according to the width of the subsequent lines. The first
+        // fragment of the word no longer fits on the first line,
+        // which ends up being pure indentation.
+        let options = Options::new(5).initial_indent("-->");
+        assert_eq!(wrap("foobarbaz", &options), vec!["-->", "fooba", "rbaz"]);
+    }
+
+    #[test]
+    fn indent_wider_than_width() {
+        let options = Options::new(3).subsequent_indent("    ");
+        assert_eq!(
+            wrap("foo bar", &options),
+            vec!["foo", "    b", "    a", "    r"]
+        );
+        let options = Options::new(3).initial_indent("    ");
+        assert_eq!(wrap("foo bar", &options), vec!["    ", "foo", "bar"]);
+        assert_eq!(
+            wrap("foo bar", options.long_words(LongWords::Overflow)),
+            vec!["    foo", "bar"]
+        );
+        let options = Options::new(0).initial_indent("> ");
+        assert_eq!(wrap("ab", &options), vec!["> a", "b"]);
+        let options = Options::new(2)
+            .initial_indent("> ")
+            .max_lines(1)
+            .placeholder("~");
+        assert_eq!(wrap("foo bar", &options), vec!["> ~"]);
+    }
+
+    #[test]
+    fn continuation_marker() {
+        let options = Options::new(10).continuation_marker("+ ");
+        assert_eq!(
+            wrap("one two three four\nfive", &options),
+            vec!["one two", "+ three", "+ four", "five"]
+        );
+        assert_eq!(
+            wrap(
+                "one two three four five six",
+                &options.clone().max_lines(2).placeholder("~")
+            ),
+            vec!["one two", "+ three~"]
+        );
+        assert_eq!(
+            wrap(
+                "- one two three four",
+                &options.clone().detect_list_markers(true)
+            ),
+            vec!["- one two", "+   three", "+   four"]
+        );
+        assert_eq!(
+            wrap(
+                "  one two three",
+                &options.clone().hanging_indent_from_input(true)
+            ),
+            vec!["  one two", "+   three"]
+        );
+        assert_eq!(
+            Options::new(4)
+                .continuation_marker("+ ")
+                .subsequent_indent("  ")
+                .validate(),
+            Err(OptionsError::IndentWiderThanWidth {
+                indent_width: 4,
+                width: 4
+            })
+        );
+    }
+
+    /// Random text of `len` characters or strings from `parts`.
+    fn random_text<S>(parts: S, len: ::core::ops::Range<usize>) -> impl Strategy<Value = String>
+    where
+        S: Strategy,
+        String: ::core::iter::FromIterator<S::Value>,
+    {
+        prop::collection::vec(parts, len).prop_map(|parts| parts.into_iter().collect())
+    }
+
+    const PATHOLOGICAL_INDENTS: &[&str] = &["", " ", "    ", "世界", "世界世界", "> > > "];
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn pathological_indent_and_width(
+            text in random_text(
+                prop::sample::select(
+                    &["a", "b", "c", " ", " ", "\n", "-", "世", "😀", "\u{ad}"][..],
+                ),
+                0..30,
+            ),
+            width in 0..6usize,
+            initial_indent in prop::sample::select(PATHOLOGICAL_INDENTS),
+            subsequent_indent in prop::sample::select(PATHOLOGICAL_INDENTS),
+            long_words in prop::sample::select(
+                &[LongWords::Break, LongWords::Overflow, LongWords::Error][..],
+            ),
+            alignment in prop::sample::select(
+                &[Alignment::Left, Alignment::Right, Alignment::Justified][..],
+            ),
+            first_fit in any::<bool>(),
+            max_lines in prop::option::weighted(0.25, 0..4usize),
+            balance in prop::bool::weighted(0.25),
+        ) {
+            let mut options = Options::new(width)
+                .initial_indent(initial_indent)
+                .subsequent_indent(subsequent_indent)
+                .long_words(long_words)
+                .alignment(alignment);
+            if first_fit {
+                options = options.wrap_algorithm(core::WrapAlgorithm::FirstFit);
+            }
+            if let Some(max_lines) = max_lines {
+                options = options.max_lines(max_lines);
+            }
+            if balance {
+                options = options.balance(3);
+            }
+            let lines = wrap(&text, &options);
+            prop_assert_eq!(options.line_count(&text), lines.len(), "{:?}", text);
+            prop_assert_eq!(verify(&text, &lines, &options), Ok(()), "{:?}", text);
+            let _ = try_wrap(&text, &options);
+            let _ = fill(&text, &options);
+        }
+    }
+
+    #[test]
+    fn hyphens() {
+        assert_eq!(wrap("foo-bar", 5), vec!["foo-", "bar"]);
+    }
+
+    #[test]
+    fn trailing_hyphen() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(wrap("foobar-", &options), vec!["foobar-"]);
+    }
+
+    #[test]
+    fn multiple_hyphens() {
+        assert_eq!(wrap("foo-bar-baz", 5), vec!["foo-", "bar-", "baz"]);
+    }
+
+    #[test]
+    fn hyphens_flag() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(
+            wrap("The --foo-bar flag.", &options),
+            vec!["The", "--foo-", "bar", "flag."]
+        );
+    }
+
+    #[test]
+    fn repeated_hyphens() {
+        let options = Options::new(4).long_words(LongWords::Overflow);
+        assert_eq!(wrap("foo--bar", &options), vec!["foo--bar"]);
+    }
+
+    #[test]
+    fn hyphens_alphanumeric() {
+        assert_eq!(wrap("Na2-CH4", 5), vec!["Na2-", "CH4"]);
+    }
+
+    #[test]
+    fn hyphens_before_digits() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(wrap("It is -15°C", &options), vec!["It is", "-15°C"]);
+        assert_eq!(wrap("pages 10-20", &options), vec!["pages", "10-20"]);
+        assert_eq!(wrap("pre-1990", &options), vec!["pre-1990"]);
+    }
+
+    #[test]
+    fn hyphens_cli_flags() {
+        let options = Options::new(4).long_words(LongWords::Overflow);
+        assert_eq!(wrap("-v --verbose", &options), vec!["-v", "--verbose"]);
+        assert_eq!(wrap("--width=80", &options), vec!["--width=80"]);
+        assert_eq!(wrap("---", &options), vec!["---"]);
+        assert_eq!(HyphenSplitter.split_points("--max-width"), vec![6]);
+        assert_eq!(HyphenSplitter.split_points("-j-4"), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn hyphens_compound_words() {
+        assert_eq!(HyphenSplitter.split_points("co-operate"), vec![3]);
+        assert_eq!(wrap("co-operate", 8), vec!["co-", "operate"]);
+    }
+
+    #[test]
+    fn hyphens_min_fragment() {
+        let splitter = HyphenSplitter::new().min_fragment(2);
+        assert_eq!(splitter.split_points("e-mail"), Vec::<usize>::new());
+        assert_eq!(splitter.split_points("co-op"), vec![3]);
+        assert_eq!(splitter.split_points("co-o"), Vec::<usize>::new());
+
+        let options = Options::new(5)
+            .long_words(LongWords::Overflow)
+            .splitter(splitter);
+        assert_eq!(wrap("e-mail co-op", &options), vec!["e-mail", "co-op"]);
+    }
+
+    #[test]
+    fn hyphens_min_fragment_default() {
+        let splitter = HyphenSplitter::new().min_fragment(1);
+        assert_eq!(splitter.split_points("e-mail"), vec![2]);
+        assert_eq!(HyphenSplitter::new().split_points("e-mail"), vec![2]);
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn hyphenation_min_fragment() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        assert_eq!(
+            dictionary.split_points("Internationalization"),
+            vec![2, 5, 7, 11, 13, 16]
+        );
+        let splitter = MinFragment::new(dictionary, 5);
+        assert_eq!(
+            splitter.split_points("Internationalization"),
+            vec![5, 7, 11, 13]
+        );
+    }
+
+    #[test]
+    fn fn_splitter_static() {
+        let splitter =
+            FnSplitter::new(|word: &str| word.match_indices('/').map(|(idx, _)| idx + 1).collect())
+                .without_hyphens();
+        assert_eq!(splitter.split_points("/usr/bin"), vec![1, 5]);
+        let options = Options::new(6)
+            .long_words(LongWords::Overflow)
+            .splitter(splitter);
+        assert_eq!(wrap("/usr/bin/env", &options), vec!["/usr/", "bin/", "env"]);
+    }
+
+    #[test]
+    fn fn_splitter_dynamic() {
+        let splitter = FnSplitter::new(|word: &str| (1..word.len()).collect());
+        let options: Options = Options::with_splitter(3, Box::new(splitter));
+        assert_eq!(wrap("abcde", &options), vec!["ab-", "cde"]);
+        assert_eq!(format!("{:?}", options.splitter), "FnSplitter(..)");
+    }
+
+    #[test]
+    fn chain_splitter_merges_split_points() {
+        let splitter = ChainSplitter::new(vec![
+            MinFragment::new(HyphenSplitter, 1),
+            MinFragment::new(HyphenSplitter, 3),
+        ]);
+        assert_eq!(splitter.split_points("e-mail-box"), vec![2, 7]);
+    }
+
+    #[test]
+    fn chain_splitter_boxed() {
+        let concrete = ChainSplitter::new(vec![HyphenSplitter, HyphenSplitter]);
+        let boxed: ChainSplitter = ChainSplitter::new(vec![
+            Box::new(HyphenSplitter) as Box<dyn WordSplitter>,
+            Box::new(NoHyphenation),
+        ]);
+        assert_eq!(concrete.split_points("foo-bar"), vec![4]);
+        assert_eq!(boxed.split_points("foo-bar"), vec![4]);
+        assert_eq!(
+            ChainSplitter::<NoHyphenation>::new(vec![]).split_points("foo-bar"),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn language_splitter_picks_splitter_per_word() {
+        let splitter = LanguageSplitter::new(
+            vec![
+                Box::new(HyphenSplitter) as Box<dyn WordSplitter>,
+                Box::new(FnSplitter::new(|word: &str| vec![word.len() / 2])),
+            ],
+            |word: &str| match word.len() {
+                0..=3 => LangChoice::Decline,
+                4..=7 => LangChoice::Use(0),
+                8..=9 => LangChoice::Use(1),
+                _ => LangChoice::Use(2),
+            },
+        );
+        assert_eq!(splitter.split_points("a-b"), Vec::<usize>::new());
+        assert_eq!(splitter.split_points("foo-bar"), vec![4]);
+        assert_eq!(splitter.split_points("foobarbaz"), vec![4]);
+        assert_eq!(splitter.split_points("foo-barbaz"), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn language_splitter_in_options() {
+        let splitter = LanguageSplitter::new(vec![HyphenSplitter], |word: &str| {
+            if word.starts_with("--") {
+                LangChoice::Decline
+            } else {
+                LangChoice::Use(0)
+            }
+        });
+        let options = Options::new(6).splitter(splitter);
+        assert_eq!(
+            wrap("see-also --no-color", &options),
+            vec!["see-", "also", "--no-c", "olor"]
+        );
+        assert!(format!("{:?}", options.splitter).starts_with("LanguageSplitter"));
+    }
+
+    #[test]
+    fn split_points_inline_and_spilled() {
+        let few: SplitPoints = vec![1, 2, 3].into();
+        assert_eq!(few.indices().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(few.len(), 3);
+
+        let many: SplitPoints = (1..10).collect();
+        assert_eq!(many.len(), 9);
+        assert_eq!(
+            many.indices().collect::<Vec<_>>(),
+            (1..10).collect::<Vec<_>>()
+        );
+        assert_eq!(many.into_iter().len(), 9);
+        assert!(SplitPoints::new().is_empty());
+    }
+
+    #[test]
+    fn split_matches_split_points() {
+        for word in &[
+            "foo",
+            "foo-bar",
+            "a-b-c-d-e-f-g",
+            "--foo",
+            "foo\u{ad}bar-baz",
+            "fooBar_baz2",
+        ] {
+            for splitter in &[
+                Box::new(HyphenSplitter) as Box<dyn WordSplitter>,
+                Box::new(SoftHyphenSplitter::with_fallback(HyphenSplitter)),
+                Box::new(MinFragment::new(HyphenSplitter, 2)),
+                Box::new(IdentifierSplitter::new(2)),
+                Box::new(NoHyphenation),
+            ] {
+                assert_eq!(
+                    splitter.split(word).indices().collect::<Vec<_>>(),
+                    splitter.split_points(word),
+                    "{:?} on {:?}",
+                    splitter,
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn identifier_splitter() {
+        let splitter = IdentifierSplitter::new(2);
+        assert_eq!(
+            splitter.split_points("max_http_request_body_size_bytes"),
+            vec![4, 9, 17, 22, 27]
+        );
+        assert_eq!(
+            splitter.split_points("ReallyLongConfigurationManagerFactoryBuilder"),
+            vec![6, 10, 23, 30, 37]
+        );
+        assert_eq!(splitter.split_points("sha256Sum"), vec![3, 6]);
+        assert_eq!(splitter.split_points("__init__"), Vec::<usize>::new());
+        assert_eq!(splitter.split_points("Hello"), Vec::<usize>::new());
+        assert_eq!(
+            IdentifierSplitter::new(1).split_points("getXValue"),
+            vec![3, 4]
+        );
+
+        let options = Options::new(16).splitter(splitter);
+        assert_eq!(
+            wrap("ReallyLongConfigurationManagerFactoryBuilder", &options),
+            vec!["ReallyLong", "Configuration", "ManagerFactory", "Builder"]
+        );
+        let options = Options::new(14).splitter(ChainSplitter::new(vec![
+            Box::new(splitter) as Box<dyn WordSplitter>,
+            Box::new(HyphenSplitter),
+        ]));
+        assert_eq!(
+            wrap("Call max_http_request_body_size_bytes now", &options),
+            vec!["Call max_http_", "request_body_", "size_bytes now"]
+        );
+    }
+
+    #[test]
+    fn chain_splitter_dynamic_options() {
+        let splitter: ChainSplitter = ChainSplitter::new(vec![Box::new(HyphenSplitter)]);
+        let options: Options = Options::with_splitter(5, Box::new(splitter));
+        assert_eq!(wrap("foo-bar", &options), vec!["foo-", "bar"]);
+    }
+
+    #[test]
+    fn max_consecutive_hyphens_zero() {
+        let options = Options::new(8)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .max_consecutive_hyphens(0);
+        assert_eq!(
+            wrap("foo-bar-baz-qux", &options),
+            vec!["foo-bar-", "baz-qux"]
+        );
+        assert_eq!(wrap("ab de-fg-hi", &options), vec!["ab", "de-fg-hi"]);
+    }
+
+    #[test]
+    fn max_consecutive_hyphens_reset() {
+        let options = Options::new(4)
+            .long_words(LongWords::Overflow)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .max_consecutive_hyphens(1);
+        assert_eq!(
+            wrap("ab-cd ef ab-cd", &options),
+            vec!["ab-", "cd", "ef", "ab-", "cd"]
+        );
+        assert_eq!(wrap("ab-cd-ef", &options), vec!["ab-", "cd-ef"]);
+    }
+
+    #[test]
+    fn max_consecutive_hyphens_paragraphs() {
+        let options = Options::new(2)
+            .long_words(LongWords::Overflow)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .max_consecutive_hyphens(1);
+        assert_eq!(fill("a-b-c\na-b", &options), "a-\nb-c\na-\nb");
+    }
+
+    #[test]
+    fn max_consecutive_hyphens_long_text() {
+        let options = Options::new(6)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .max_consecutive_hyphens(2);
+        let text = "ab-cd-ef ".repeat(20);
+        let lines = wrap(text.trim_end(), &options);
+        let mut in_a_row = 0;
+        for line in &lines {
+            in_a_row = if line.ends_with('-') { in_a_row + 1 } else { 0 };
+            assert!(in_a_row <= 2, "{:?}", lines);
+        }
+        assert_eq!(lines.concat().replace(' ', ""), text.replace(' ', ""));
+        assert_eq!(verify(text.trim_end(), &lines, &options), Ok(()));
+    }
+
+    #[test]
+    fn max_consecutive_hyphens_long_word() {
+        // The word is still broken since it cannot fit otherwise.
+        let options = Options::new(4)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .max_consecutive_hyphens(0);
+        assert_eq!(wrap("abc-defgh", &options), vec!["abc-", "defg", "h"]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cached_splitter_hit() {
+        let splitter = CachedSplitter::new(HyphenSplitter);
+        assert!(splitter.is_empty());
+        assert_eq!(splitter.split_points("foo-bar"), vec![4]);
+        assert_eq!(splitter.split_points("foo-bar"), vec![4]);
+        assert_eq!(splitter.split_points("baz"), Vec::<usize>::new());
+        assert_eq!(splitter.len(), 2);
+        splitter.clear();
+        assert!(splitter.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cached_splitter_capacity() {
+        let splitter = CachedSplitter::with_capacity(HyphenSplitter, 2);
+        splitter.split_points("a");
+        splitter.split_points("b");
+        assert_eq!(splitter.len(), 2);
+        splitter.split_points("c");
+        assert_eq!(splitter.len(), 1);
+
+        let splitter = CachedSplitter::with_capacity(HyphenSplitter, 0);
+        assert_eq!(splitter.split_points("foo-bar"), vec![4]);
+        assert!(splitter.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cached_splitter_shared() {
+        let splitter = CachedSplitter::new(HyphenSplitter);
+        let options = Options::new(5).splitter(splitter.clone());
+        let shared = std::sync::Arc::new(Options::new(5).splitter(splitter.clone()));
+        assert_eq!(wrap("foo-bar", &options), vec!["foo-", "bar"]);
+        assert_eq!(wrap("foo-bar", &*shared), vec!["foo-", "bar"]);
+        assert_eq!(splitter.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn cached_splitter_dictionary() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(10).splitter(CachedSplitter::new(dictionary));
+        for _ in 0..2 {
+            assert_eq!(
+                wrap("Internationalization", &options),
+                vec!["Interna-", "tionaliza-", "tion"]
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cached_splitter_substitutions() {
+        fn check<S: WordSplitter + Clone>(splitter: S, width: usize, text: &str) {
+            let cached = CachedSplitter::new(splitter.clone());
+            let expected = wrap(text, Options::new(width).splitter(splitter));
+            // The second wrap uses the cached split points.
+            for _ in 0..2 {
+                let options = Options::new(width).splitter(cached.clone());
+                assert_eq!(wrap(text, &options), expected);
+            }
+        }
+
+        check(IdentifierSplitter::new(3), 12, "A DatabaseConnection");
+        let slashes =
+            FnSplitter::new(|word: &str| word.match_indices('/').map(|(idx, _)| idx + 1).collect())
+                .without_hyphens();
+        check(slashes, 6, "/usr/bin/env");
+        check(std::sync::Arc::new(Respelling), 4, "foo cabdd");
+    }
+
+    #[test]
+    fn splitter_boxed() {
+        let options = Options::new(5).splitter_boxed(NoHyphenation);
+        let mut options = options.splitter_boxed(HyphenSplitter);
+        assert_eq!(wrap("foo-bar", &options), vec!["foo-", "bar"]);
+        options.splitter = Box::new(NoHyphenation);
+        assert_eq!(wrap("foo-bar", &options), vec!["foo-b", "ar"]);
+    }
+
+    #[test]
+    fn options_debug_dyn_splitter() {
+        let options: Options = Options::new(10)
+            .initial_indent("> ")
+            .splitter_boxed(NoHyphenation);
+        let debug = format!("{:?}", options);
+        assert!(debug.contains("width: 10"));
+        assert!(debug.contains("initial_indent: \"> \""));
+        assert!(debug.contains("long_words: Break"));
+        assert!(debug.contains("splitter: NoHyphenation"));
+
+        let dyn_options: &Options<dyn WordSplitter> = &Options::new(10);
+        assert!(format!("{:?}", dyn_options).contains("splitter: HyphenSplitter"));
+    }
+
+    #[test]
+    fn options_clone_shared_splitter() {
+        let splitter: std::sync::Arc<dyn WordSplitter> = std::sync::Arc::new(HyphenSplitter);
+        let options = Options::new(5).splitter(splitter);
+        let copy = options.clone().long_words(LongWords::Overflow);
+        assert_eq!(wrap("foo-barbaz", &options), vec!["foo-", "barba", "z"]);
+        assert_eq!(wrap("foo-barbaz", &copy), vec!["foo-", "barbaz"]);
+    }
+
+    #[test]
+    fn validate_zero_width() {
+        assert_eq!(Options::new(0).validate(), Err(OptionsError::ZeroWidth));
+        assert_eq!(
+            Options::try_new(0).unwrap_err().to_string(),
+            "width must be at least 1"
+        );
+    }
+
+    #[test]
+    fn validate_indent() {
+        let options = Options::new(3).initial_indent("---");
+        assert_eq!(
+            options.validate(),
+            Err(OptionsError::IndentWiderThanWidth {
+                indent_width: 3,
+                width: 3
+            })
+        );
+        assert_eq!(Options::new(4).initial_indent("---").validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_placeholder() {
+        let options = Options::new(3).placeholder("...");
+        assert_eq!(options.validate(), Ok(()));
+        assert_eq!(
+            options.max_lines(2).validate(),
+            Err(OptionsError::PlaceholderWiderThanWidth {
+                placeholder_width: 3,
+                width: 3
+            })
+        );
+    }
+
+    #[test]
+    fn validate_width_fn() {
+        let shape = |_| 0;
+        assert_eq!(Options::new(0).width_fn(&shape).validate(), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "terminal_size")]
+    fn termwidth_columns() {
+        // The environment is shared by the tests running in
+        // parallel, so the value of COLUMNS is passed in.
+        assert_eq!(parse_columns("33"), Some(33));
+        assert_eq!(parse_columns(" 50 "), Some(50));
+        for invalid in &["", "0", "-5", "wide", "100000"] {
+            assert_eq!(parse_columns(invalid), None);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "terminal_size")]
+    fn refresh_width() {
+        let mut options = Options::new(10);
+        assert!(options.refresh_width(Some(33)));
+        assert_eq!(options.width, 33);
+        assert!(!options.refresh_width(Some(33)));
+        assert!(!options.refresh_width(None));
+        assert_eq!(options.width, 33);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn fill_to_writer_matches_fill() {
+        let text = "Memory safety without garbage collection.\n\nConcurrency without data races.";
+        let mut out = Vec::new();
+        fill_to_writer(text, 12, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), fill(text, 12));
+
+        let options = Options::new(12).line_ending("\r\n");
+        let mut out = Vec::new();
+        fill_to_writer(text, &options, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), fill(text, &options));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_to_writer_line_ending() {
+        let options = Options::new(12).line_ending("\r\n");
+        let mut out = Vec::new();
+        wrap_to_writer("Memory safety without", &options, &mut out).unwrap();
+        assert_eq!(out, b"Memory\r\nsafety\r\nwithout\r\n");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_to_writer_empty() {
+        let mut out = Vec::new();
+        wrap_to_writer("", 10, &mut out).unwrap();
+        assert_eq!(out, b"\n");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_to_writer_stops_on_error() {
+        struct Failing(usize);
+
+        impl std::io::Write for Failing {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(std::io::Error::other("full"));
+                }
+                self.0 -= 1;
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut out = Failing(3);
+        let err = wrap_to_writer("foo bar baz", 3, &mut out).unwrap_err();
+        assert_eq!(err.to_string(), "full");
+        assert_eq!(out.0, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_stream_matches_fill_paragraphs() {
+        let texts = [
+            "",
+            "\n",
+            "foo",
+            "foo\n",
+            "foo bar baz\nqux\n\n\nquux corge\n",
+            "\n\nfoo bar\n   \nbaz",
+            "Memory safety without garbage collection.\n\nConcurrency without data races.",
+        ];
+        let options = Options::new(10).initial_indent("> ");
+        for text in &texts {
+            let mut output = Vec::new();
+            wrap_stream(text.as_bytes(), &mut output, &options).unwrap();
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                fill_paragraphs(text, &options),
+                "text: {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_stream_crlf() {
+        let mut output = Vec::new();
+        wrap_stream(&b"foo\r\nbar\r\n\r\nbaz"[..], &mut output, 80).unwrap();
+        assert_eq!(output, b"foo bar\n\nbaz");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_stream_invalid_utf8() {
+        let mut output = Vec::new();
+        let err = wrap_stream(&b"foo \xff"[..], &mut output, 80).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn hyphens_non_alphanumeric() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(wrap("foo(-)bar", &options), vec!["foo(-)bar"]);
+    }
+
+    #[test]
+    fn multiple_splits() {
+        assert_eq!(wrap("foo-bar-baz", 9), vec!["foo-bar-", "baz"]);
+    }
+
+    #[test]
+    fn forced_split() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(wrap("foobar-baz", &options), vec!["foobar-", "baz"]);
+    }
+
+    #[test]
+    fn multiple_unbroken_words_issue_193() {
+        let options = Options::new(3).long_words(LongWords::Overflow);
+        assert_eq!(
+            wrap("small large tiny", &options),
+            vec!["small", "large", "tiny"]
+        );
+        assert_eq!(
+            wrap("small  large   tiny", &options),
+            vec!["small", "large", "tiny"]
+        );
+    }
+
+    #[test]
+    fn very_narrow_lines_issue_193() {
+        let options = Options::new(1).long_words(LongWords::Overflow);
+        assert_eq!(wrap("fooo x y", &options), vec!["fooo", "x", "y"]);
+        assert_eq!(wrap("fooo   x     y", &options), vec!["fooo", "x", "y"]);
+    }
+
+    #[test]
+    fn simple_hyphens_static() {
+        let options = Options::new(8).splitter(HyphenSplitter);
+        assert_eq!(wrap("foo bar-baz", &options), vec!["foo bar-", "baz"]);
+    }
+
+    #[test]
+    fn simple_hyphens_dynamic() {
+        let options: Options = Options::new(8).splitter(Box::new(HyphenSplitter));
+        assert_eq!(wrap("foo bar-baz", &options), vec!["foo bar-", "baz"]);
+    }
+
+    #[test]
+    fn no_hyphenation_static() {
+        let options = Options::new(8).splitter(NoHyphenation);
+        assert_eq!(wrap("foo bar-baz", &options), vec!["foo", "bar-baz"]);
+    }
+
+    #[test]
+    fn no_hyphenation_dynamic() {
+        let options: Options = Options::new(8).splitter(Box::new(NoHyphenation));
+        assert_eq!(wrap("foo bar-baz", &options), vec!["foo", "bar-baz"]);
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn auto_hyphenation_double_hyphenation_static() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(10);
+        assert_eq!(
+            wrap("Internationalization", &options),
+            vec!["Internatio", "nalization"]
+        );
+
+        let options = Options::new(10).splitter(dictionary);
+        assert_eq!(
+            wrap("Internationalization", &options),
+            vec!["Interna-", "tionaliza-", "tion"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn auto_hyphenation_double_hyphenation_dynamic() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let mut options: Options = Options::new(10).splitter(Box::new(HyphenSplitter));
+        assert_eq!(
+            wrap("Internationalization", &options),
+            vec!["Internatio", "nalization"]
+        );
+
+        options = Options::new(10).splitter(Box::new(dictionary));
+        assert_eq!(
+            wrap("Internationalization", &options),
+            vec!["Interna-", "tionaliza-", "tion"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn auto_hyphenation_issue_158() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(10);
+        assert_eq!(
+            wrap("participation is the key to success", &options),
+            vec!["participat", "ion is", "the key to", "success"]
+        );
+
+        let options = Options::new(10).splitter(dictionary);
+        assert_eq!(
+            wrap("participation is the key to success", &options),
+            vec!["partici-", "pation is", "the key to", "success"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn hyphenation_split_matches_hyphenate() {
+        // The split points of ASCII words are found without the
+        // hyphenation crate, so they are compared with it for every
+        // bundled dictionary.
+        use hyphenation::Hyphenator;
+        const CODES: &[&str] = &[
+            "af",
+            "sq",
+            "hy",
+            "as",
+            "eu",
+            "be",
+            "bn",
+            "bg",
+            "ca",
+            "zh-latn-pinyin",
+            "cop",
+            "hr",
+            "cs",
+            "da",
+            "nl",
+            "en-gb",
+            "en-us",
+            "eo",
+            "et",
+            "mul-ethi",
+            "fi",
+            "fi-x-school",
+            "fr",
+            "fur",
+            "gl",
+            "ka",
+            "de-1901",
+            "de-1996",
+            "de-ch-1901",
+            "grc",
+            "el-monoton",
+            "el-polyton",
+            "gu",
+            "hi",
+            "hu",
+            "is",
+            "id",
+            "ia",
+            "ga",
+            "it",
+            "kn",
+            "kmr",
+            "la",
+            "la-x-classic",
+            "la-x-liturgic",
+            "lv",
+            "lt",
+            "mk",
+            "ml",
+            "mr",
+            "mn-cyrl",
+            "nb",
+            "nn",
+            "oc",
+            "or",
+            "pi",
+            "pa",
+            "pms",
+            "pl",
+            "pt",
+            "ro",
+            "rm",
+            "ru",
+            "sa",
+            "sr-cyrl",
+            "sh-cyrl",
+            "sh-latn",
+            "cu",
+            "sk",
+            "sl",
+            "es",
+            "sv",
+            "ta",
+            "te",
+            "th",
+            "tr",
+            "tk",
+            "uk",
+            "hsb",
+            "cy",
+        ];
+        let text = "Memory safety without garbage collection. Concurrency without \
+                    data races. Zero-cost abstractions, participation, hyphenation, \
+                    Bewegungsfreiheit, constitution, anticonstitutionnellement, \
+                    otorrinolaringologia, Donaudampfschifffahrt, coopération";
+        let long = "pneumonoultramicroscopicsilicovolcanoconiosis".repeat(2);
+        let mut words = vec![
+            String::new(),
+            String::from("co\u{ad}operation"),
+            String::from("naïveté"),
+            long[..64].to_string(),
+            long[..65].to_string(),
+            long,
+        ];
+        // Words shorter than the minima of all dictionaries.
+        for len in 1..=6 {
+            words.push("abcdef"[..len].to_string());
+        }
+        for word in text.split(&[' ', '-'][..]) {
+            words.push(word.to_string());
+            words.push(word.to_uppercase());
+        }
+
+        for code in CODES {
+            let language = Language::try_from_code(code).unwrap();
+            let dictionary = Standard::from_embedded(language).unwrap();
+            let exceptions = dictionary.exceptions.0.keys();
+            let exceptions = exceptions.flat_map(|word| vec![word.clone(), word.to_uppercase()]);
+            for word in words.iter().cloned().chain(exceptions) {
+                assert_eq!(
+                    dictionary.split(&word).indices().collect::<Vec<_>>(),
+                    dictionary.hyphenate(&word).breaks,
+                    "{} {:?}",
+                    code,
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn split_len_hyphenation() {
+        // Test that hyphenation takes the width of the wihtespace
+        // into account.
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(15).splitter(dictionary);
+        assert_eq!(
+            wrap("garbage   collection", &options),
+            vec!["garbage   col-", "lection"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn borrowed_lines() {
+        // Lines that end with an extra hyphen are owned, the final
+        // line is borrowed.
+        use alloc::borrow::Cow::{Borrowed, Owned};
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(10).splitter(dictionary);
+        let lines = wrap("Internationalization", &options);
+        if let Borrowed(s) = lines[0] {
+            panic!("should not have been borrowed: {:?}", s);
+        }
+        if let Borrowed(s) = lines[1] {
+            panic!("should not have been borrowed: {:?}", s);
+        }
+        if let Owned(ref s) = lines[2] {
+            panic!("should not have been owned: {:?}", s);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn auto_hyphenation_with_hyphen() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(8).long_words(LongWords::Overflow);
+        assert_eq!(
+            wrap("over-caffinated", &options),
+            vec!["over-", "caffinated"]
+        );
+
+        let options = options.splitter(dictionary);
+        assert_eq!(
+            wrap("over-caffinated", &options),
+            vec!["over-", "caffi-", "nated"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn auto_hyphenation_extended() {
+        let dictionary = Extended::from_embedded(Language::Hungarian).unwrap();
+        let options = Options::new(8).splitter(&dictionary);
+        assert_eq!(
+            wrap("egy asszonnyal ment", &options),
+            vec!["egy asz-", "szonnyal", "ment"]
+        );
+        let options = Options::new(12).splitter(&dictionary);
+        assert_eq!(
+            wrap("egy asszonnyal ment", &options),
+            vec!["egy asszony-", "nyal ment"]
+        );
+        let options = Options::new(20).splitter(&dictionary);
+        assert_eq!(
+            wrap("egy asszonnyal ment", &options),
+            vec!["egy asszonnyal ment"]
+        );
+    }
+
+    /// Respells "ab" as "xx-" and "yy" when split between the letters.
+    #[derive(Debug)]
+    struct Respelling;
+
+    impl WordSplitter for Respelling {
+        fn split_points(&self, word: &str) -> Vec<usize> {
+            self.split(word).indices().collect()
+        }
+
+        fn split(&self, word: &str) -> SplitPoints<'_> {
+            const AB: Substitution = Substitution {
+                left: 1,
+                right: 1,
+                before_break: "xx",
+                hyphen: "-",
+                after_break: "yy",
+            };
+            word.match_indices("ab")
+                .map(|(idx, _)| (idx + 1, Some(AB)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn substitution_applied_when_split() {
+        let options = Options::new(4).splitter(Respelling);
+        assert_eq!(wrap("foo cabdd", &options), vec!["foo", "cxx-", "yydd"]);
+        assert_eq!(wrap("cabd", &options), vec!["cabd"]);
+    }
+
+    #[test]
+    fn substitution_width_of_next_line() {
+        // The "e" would fit after "dd", but not after "yydd".
+        let options = Options::new(4)
+            .splitter(Respelling)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("cabdd e", &options), vec!["cxx-", "yydd", "e"]);
+    }
+
+    #[test]
+    fn substitution_wrap_ranges() {
+        let options = Options::new(8)
+            .splitter(Respelling)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        let ranges = wrap_ranges("e f cabdd", &options);
+        assert_eq!(ranges[0].range, 0..5);
+        assert_eq!(ranges[0].penalty, "xx-");
+        assert_eq!(ranges[1].prefix, "yy");
+        assert_eq!(ranges[1].range, 7..9);
+        assert_eq!(ranges[1].penalty, "");
+    }
+
+    #[test]
+    fn break_words() {
+        assert_eq!(wrap("foobarbaz", 3), vec!["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn break_words_wide_characters() {
+        // Even the poor man's version of `ch_width` counts these
+        // characters as wide.
+        assert_eq!(wrap("Ｈｅｌｌｏ", 5), vec!["Ｈｅ", "ｌｌ", "ｏ"]);
+    }
+
+    #[test]
+    fn break_words_zero_width() {
+        assert_eq!(wrap("foobar", 0), vec!["f", "o", "o", "b", "a", "r"]);
+    }
+
+    #[test]
+    fn break_long_first_word() {
+        assert_eq!(wrap("testx y", 4), vec!["test", "x y"]);
+    }
+
+    #[test]
+    fn break_words_line_breaks() {
+        assert_eq!(fill("ab\ncdefghijkl", 5), "ab\ncdefg\nhijkl");
+        assert_eq!(fill("abcdefgh\nijkl", 5), "abcde\nfgh\nijkl");
+    }
+
+    #[test]
+    fn break_words_empty_lines() {
+        assert_eq!(
+            fill("foo\nbar", &Options::new(2).long_words(LongWords::Overflow)),
+            "foo\nbar"
+        );
+    }
+
+    #[test]
+    fn preserve_line_breaks() {
+        assert_eq!(fill("", 80), "");
+        assert_eq!(fill("\n", 80), "\n");
+        assert_eq!(fill("\n\n\n", 80), "\n\n\n");
+        assert_eq!(fill("test\n", 80), "test\n");
+        assert_eq!(fill("test\n\na\n\n", 80), "test\n\na\n\n");
+        assert_eq!(
+            fill(
+                "1 3 5 7\n1 3 5 7",
+                Options::new(7).wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            ),
+            "1 3 5 7\n1 3 5 7"
+        );
+        assert_eq!(
+            fill(
+                "1 3 5 7\n1 3 5 7",
+                Options::new(5).wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            ),
+            "1 3 5\n7\n1 3 5\n7"
+        );
+    }
+
+    #[test]
+    fn preserve_line_breaks_with_whitespace() {
+        assert_eq!(fill("  ", 80), "");
+        assert_eq!(fill("  \n  ", 80), "\n");
+        assert_eq!(fill("  \n \n  \n ", 80), "\n\n\n");
+    }
+
+    #[test]
+    fn non_breaking_space() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(fill("foo bar baz", &options), "foo bar baz");
+    }
+
+    #[test]
+    fn non_breaking_hyphen() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(fill("foo‑bar‑baz", &options), "foo‑bar‑baz");
+    }
+
+    #[test]
+    fn fill_simple() {
+        assert_eq!(fill("foo bar baz", 10), "foo bar\nbaz");
+    }
+
+    #[test]
+    fn fill_colored_text() {
+        // The words are much longer than 6 bytes, but they remain
+        // intact after filling the text.
+        let green_hello = "\u{1b}[0m\u{1b}[32mHello\u{1b}[0m";
+        let blue_world = "\u{1b}[0m\u{1b}[34mWorld!\u{1b}[0m";
+        assert_eq!(
+            fill(&(String::from(green_hello) + " " + blue_world), 6),
+            String::from(green_hello) + "\n" + blue_world
+        );
+    }
+
+    #[test]
+    fn wrap_colored_text_width() {
+        // The escape sequences take up no room, so the colored words
+        // fit on the same lines as their plain counterparts.
+        let red = |word| format!("\u{1b}[31m{}\u{1b}[0m", word);
+        let text = format!("{} {} {}", red("foo"), red("bar"), red("baz"));
+        let options = Options::new(7).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap(&text, &options),
+            vec![format!("{} {}", red("foo"), red("bar")), red("baz")]
+        );
+    }
+
+    #[test]
+    fn break_words_skips_escape_sequences() {
+        let text = "\u{1b}[31mabcdef\u{1b}[0m";
+        assert_eq!(wrap(text, 3), vec!["\u{1b}[31mabc", "def\u{1b}[0m"]);
+    }
+
+    #[test]
+    fn wrap_hyperlink() {
+        let link = "\u{1b}]8;;https://x.org/a-b\u{1b}\\link\u{1b}]8;;\u{1b}\\";
+        let text = format!("see {} here", link);
+        assert_eq!(
+            wrap(&text, 9),
+            vec![format!("see {}", link), "here".to_string()]
+        );
+    }
+
+    #[test]
+    fn wrap_hyperlink_across_lines() {
+        let open = "\u{1b}]8;;https://x.org\u{1b}\\";
+        let close = "\u{1b}]8;;\u{1b}\\";
+        let text = format!("see {}foo bar baz{} ok", open, close);
+        let options = Options::new(7).subsequent_indent("> ");
+        assert_eq!(
+            wrap(&text, &options),
+            vec![
+                format!("see {}foo{}", open, close),
+                format!("> {}bar{}", open, close),
+                format!("> {}baz{}", open, close),
+                String::from("> ok"),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_hyperlink_bel_terminator() {
+        let open = "\u{1b}]8;id=1;https://x.org\u{7}";
+        let close = "\u{1b}]8;;\u{7}";
+        let text = format!("{}foo bar{}", open, close);
+        assert_eq!(
+            wrap(&text, 3),
+            vec![
+                format!("{}foo{}", open, close),
+                format!("{}bar{}", open, close)
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_hyperlink_break_words() {
+        let open = "\u{1b}]8;;https://x.org/a-b\u{7}";
+        let close = "\u{1b}]8;;\u{7}";
+        let text = format!("{}abcdef{}", open, close);
+        assert_eq!(
+            wrap(&text, 3),
+            vec![
+                format!("{}abc{}", open, close),
+                format!("{}def{}", open, close)
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_hyperlink_across_paragraphs() {
+        let open = "\u{1b}]8;;https://x.org\u{7}";
+        let close = "\u{1b}]8;;\u{7}";
+        let text = format!("{}foo\nbar{} baz", open, close);
+        assert_eq!(
+            wrap(&text, 80),
+            vec![
+                format!("{}foo{}", open, close),
+                format!("{}bar{} baz", open, close)
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_hyperlink_with_styles() {
+        let open = "\u{1b}]8;;https://x.org\u{7}";
+        let close = "\u{1b}]8;;\u{7}";
+        let text = format!("\u{1b}[4m{}foo bar{}\u{1b}[0m", open, close);
+        let options = Options::new(3).reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(&text, &options),
+            vec![
+                format!("\u{1b}[4m{}foo{}\u{1b}[0m", open, close),
+                format!("\u{1b}[4m{}bar{}\u{1b}[0m", open, close),
+            ]
+        );
+    }
+
+    #[test]
+    fn reapply_ansi_styles_disabled() {
+        let text = "\u{1b}[31mfoo bar\u{1b}[0m";
+        assert_eq!(wrap(text, 3), vec!["\u{1b}[31mfoo", "bar\u{1b}[0m"]);
+    }
+
+    #[test]
+    fn reapply_ansi_styles_simple() {
+        let text = "\u{1b}[31mfoo bar baz\u{1b}[0m";
+        let options = Options::new(3).reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                "\u{1b}[31mfoo\u{1b}[0m",
+                "\u{1b}[31mbar\u{1b}[0m",
+                "\u{1b}[31mbaz\u{1b}[0m"
+            ]
+        );
+    }
+
+    #[test]
+    fn reapply_ansi_styles_after_indent() {
+        let text = "\u{1b}[4mfoo bar\u{1b}[0m";
+        let options = Options::new(6)
+            .initial_indent("* ")
+            .subsequent_indent("  ")
+            .reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(text, &options),
+            vec!["* \u{1b}[4mfoo\u{1b}[0m", "  \u{1b}[4mbar\u{1b}[0m"]
+        );
+    }
+
+    #[test]
+    fn reapply_ansi_styles_reset_with_params() {
+        let text = "\u{1b}[1mfoo \u{1b}[0;32mbar baz";
+        let options = Options::new(3).reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                "\u{1b}[1mfoo\u{1b}[0m",
+                "\u{1b}[1m\u{1b}[0;32mbar\u{1b}[0m",
+                "\u{1b}[0;32mbaz\u{1b}[0m"
+            ]
+        );
+    }
+
+    #[test]
+    fn reapply_ansi_styles_across_paragraphs() {
+        let text = "\u{1b}[33mfoo\nbar\u{1b}[m baz";
+        let options = Options::new(3).reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(text, &options),
+            vec!["\u{1b}[33mfoo\u{1b}[0m", "\u{1b}[33mbar\u{1b}[m", "baz"]
+        );
+    }
+
+    #[test]
+    fn reapply_ansi_styles_ignores_other_sequences() {
+        let title = "\u{1b}]2;Window title\u{7}";
+        let text = format!("{}foo bar", title);
+        let options = Options::new(3).reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(&text, &options),
+            vec![format!("{}foo", title), "bar".to_string()]
+        );
+    }
+
+    #[test]
+    fn reapply_ansi_styles_centered() {
+        let text = "\u{1b}[31mfoo barbaz\u{1b}[0m";
+        let options = Options::new(6)
+            .alignment(Alignment::Center)
+            .reapply_ansi_styles(true);
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                " \u{1b}[31mfoo\u{1b}[0m  ".to_string(),
+                "\u{1b}[31mbarbaz\u{1b}[0m".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn width_measure_bytes_line_limit() {
+        // Lines in an email may be at most 998 bytes long.
+        let text = "Grüße aus Köln! ".repeat(200);
+        let options = Options::new(998)
+            .width_measure(core::WidthMeasure::Bytes)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        let lines = wrap(&text, &options);
+        assert!(lines.len() > 1);
+        for line in &lines {
+            assert!(line.len() <= 998, "{} bytes", line.len());
+        }
+        assert!(lines[0].len() > 998 - "Grüße ".len());
+        assert_eq!(lines.join(" "), text.trim_end());
+    }
+
+    #[test]
+    fn width_measure_bytes_break_words() {
+        let options = Options::new(5).width_measure(core::WidthMeasure::Bytes);
+        assert_eq!(wrap("ääääää", &options), vec!["ää", "ää", "ää"]);
+    }
+
+    #[test]
+    fn width_measure_bytes_indent() {
+        let options = Options::new(7)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .initial_indent("» ");
+        assert_eq!(wrap("ab cd ef", &options), vec!["» ab cd", "ef"]);
+        let options = options.width_measure(core::WidthMeasure::Bytes);
+        assert_eq!(wrap("ab cd ef", &options), vec!["» ab", "cd ef"]);
+    }
+
+    #[test]
+    fn width_measure_chars() {
+        let options = Options::new(4)
+            .width_measure(core::WidthMeasure::Chars)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("你好 世界", &options), vec!["你好", "世界"]);
+        assert_eq!(wrap("你好世界你", &options), vec!["你好世界", "你"]);
+    }
+
+    #[test]
+    fn width_measure_custom() {
+        // Every grapheme cluster is 3 columns.
+        fn wide(text: &str) -> usize {
+            3 * core::WidthMeasure::UnicodeCells.clusters(text).count()
+        }
+        let options = Options::new(9)
+            .width_measure(core::WidthMeasure::Custom(wide))
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("a b cd", &options), vec!["a b", "cd"]);
+        assert_eq!(wrap("abcdefg", &options), vec!["abc", "def", "g"]);
+    }
+
+    #[test]
+    fn width_measure_validate() {
+        let options = Options::new(4)
+            .width_measure(core::WidthMeasure::Bytes)
+            .initial_indent("é é");
+        assert_eq!(
+            options.validate(),
+            Err(OptionsError::IndentWiderThanWidth {
+                indent_width: 5,
+                width: 4
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn cjk_ambiguous_wide_indent() {
+        let options = Options::new(7)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .initial_indent("→ ");
+        assert_eq!(wrap("ab cd", &options), vec!["→ ab cd"]);
+        let options = options.cjk_ambiguous_wide(true);
+        assert_eq!(wrap("ab cd", &options), vec!["→ ab", "cd"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn cjk_ambiguous_wide_break_words() {
+        let options = Options::new(4).cjk_ambiguous_wide(true);
+        assert_eq!(wrap("±±±±±", &options), vec!["±±", "±±", "±"]);
+        let options = options.cjk_ambiguous_wide(false);
+        assert_eq!(wrap("±±±±±", &options), vec!["±±±±", "±"]);
+    }
+
+    #[test]
+    fn cjk_ambiguous_wide_keeps_other_measures() {
+        let options = Options::new(4)
+            .width_measure(core::WidthMeasure::Bytes)
+            .cjk_ambiguous_wide(true);
+        assert!(matches!(options.width_measure, core::WidthMeasure::Bytes));
+    }
+
+    #[test]
+    fn wrap_emoji_sequences() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let text = format!("{} 🇩🇰 ❤\u{fe0f} ok", family);
+        assert_eq!(
+            wrap(&text, 8),
+            vec![format!("{} 🇩🇰 ❤\u{fe0f}", family), "ok".to_string()]
+        );
+    }
+
+    #[test]
+    fn expand_tabs_option() {
+        let options = Options::new(10).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("a\tb c d", &options), vec!["a\tb c d"]);
+        let options = options.expand_tabs(8);
+        assert_eq!(wrap("a\tb c d", &options), vec!["a       b", "c d"]);
+        assert_eq!(
+            into_wrap_iter(String::from("a\tb c d"), &options).collect::<Vec<_>>(),
+            vec!["a       b", "c d"]
+        );
+    }
+
+    #[test]
+    fn expand_tabs_option_borrows_without_tabs() {
+        let options = Options::new(10).expand_tabs(8);
+        assert!(matches!(wrap("foo bar", &options)[0], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn expand_tabs_option_fill() {
+        let options = Options::new(8).expand_tabs(0);
+        assert_eq!(fill("a\tb\tc", &options), "abc");
+        let options = Options::new(8).expand_tabs(2);
+        assert_eq!(fill("\tfoo\n\tbar", &options), "  foo\n  bar");
+    }
+
+    #[test]
+    fn wrap_crlf() {
+        assert_eq!(wrap("foo\r\nbar baz\r\n", 3), vec!["foo", "bar", "baz", ""]);
+        // A lone '\r' is not a line break.
+        assert_eq!(wrap("foo\rbar", 80), vec!["foo\rbar"]);
+    }
+
+    #[test]
+    fn wrap_crlf_unicode_break_properties() {
+        let options = Options::new(3).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(wrap("foo\r\nbar", &options), vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn fill_crlf_round_trip() {
+        let text = "Memory safety\r\nwithout garbage collection.\r\n";
+        let options = Options::new(15).line_ending("\r\n");
+        let filled = fill(text, &options);
+        assert_eq!(
+            filled,
+            "Memory safety\r\nwithout garbage\r\ncollection.\r\n"
+        );
+        for line in filled.split("\r\n") {
+            assert!(!line.contains('\r'), "{:?}", line);
+        }
+        assert_eq!(fill(&filled, &options), filled);
+    }
+
+    #[test]
+    fn fill_paragraphs_crlf() {
+        let text = "foo\r\nbar\r\n\r\nbaz";
+        assert_eq!(fill_paragraphs(text, 80), "foo bar\n\nbaz");
+        let options = Options::new(80).line_ending("\r\n");
+        assert_eq!(fill_paragraphs(text, &options), "foo bar\r\n\r\nbaz");
+    }
+
+    #[test]
+    fn fill_into_appends_like_fill() {
+        let options = Options::new(10)
+            .initial_indent("* ")
+            .subsequent_indent("  ")
+            .line_ending("\r\n");
+        let texts = [
+            "",
+            "foo",
+            "Memory safety without garbage collection.",
+            "foo\n\nbar baz\r\n",
+            "  leading and trailing  ",
+        ];
+        let mut buf = String::from("prefix");
+        let mut expected = buf.clone();
+        for text in &texts {
+            fill_into(text, &options, &mut buf);
+            expected.push_str(&fill(text, &options));
+            assert_eq!(buf, expected);
+        }
+
+        for text in &texts {
+            buf.clear();
+            fill_into(text, 5, &mut buf);
+            assert_eq!(buf, fill(text, 5));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(200))]
+
+        #[test]
+        #[cfg(feature = "rayon")]
+        fn fill_par_agrees_with_fill_paragraphs(
+            text in random_text(
+                prop_oneof![
+                    2 => Just('\n'),
+                    1 => Just('\r'),
+                    3 => Just(' '),
+                    1 => Just('-'),
+                    7 => prop::char::range('a', 'z'),
+                ],
+                0..300,
+            ),
+            width in 1..21usize,
+            initial_indent in prop::sample::select(&["", "* "][..]),
+            line_ending in prop::sample::select(&["\n", "\r\n"][..]),
+        ) {
+            let options = Options::new(width)
+                .initial_indent(initial_indent)
+                .line_ending(line_ending);
+            prop_assert_eq!(
+                fill_par(&text, &options),
+                fill_paragraphs(&text, &options),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn options_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Options<'static>>();
+        assert_send_sync::<Options<'static, &dyn WordSplitter>>();
+        assert_send_sync::<Options<'static, NoHyphenation>>();
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+
+        #[test]
+        fn wrapped_text_agrees_with_wrap(
+            width in 1..16usize,
+            initial_indent in prop::sample::select(&["", "* "][..]),
+            subsequent_indent in prop::sample::select(&["", "  "][..]),
+            detect_list_markers in any::<bool>(),
+            hanging_indent_from_input in any::<bool>(),
+            alignment in prop::sample::select(
+                &[Alignment::Left, Alignment::Right, Alignment::Justified][..],
+            ),
+            max_lines in prop::option::weighted(0.25, 0..6usize),
+            edits in prop::collection::vec(
+                (
+                    any::<prop::sample::Index>(),
+                    any::<prop::sample::Index>(),
+                    random_text(
+                        prop_oneof![
+                            2 => Just('\n'),
+                            1 => Just('\t'),
+                            2 => Just(' '),
+                            1 => Just('-'),
+                            6 => prop::char::range('a', 'z'),
+                        ],
+                        0..10,
+                    ),
+                    prop::option::weighted(0.2, 1..16usize),
+                ),
+                20,
+            ),
+        ) {
+            let mut options = Options::new(width)
+                .initial_indent(initial_indent)
+                .subsequent_indent(subsequent_indent)
+                .detect_list_markers(detect_list_markers)
+                .hanging_indent_from_input(hanging_indent_from_input)
+                .alignment(alignment)
+                .expand_tabs(4);
+            if let Some(max_lines) = max_lines {
+                options = options.max_lines(max_lines);
+            }
+            let mut wrapped = WrappedText::new(options.clone());
+            let mut text = String::new();
+            for (start, end, replacement, width) in edits {
+                // Replace a random part of the text, like an edit.
+                let start = start.index(text.len() + 1);
+                let end = start + end.index(text.len() - start + 1);
+                let old_lines = wrapped.lines().to_vec();
+                text.replace_range(start..end, &replacement);
+                if let Some(width) = width {
+                    wrapped.set_width(width);
+                    options.width = wrapped.options().width;
+                }
+                let dirty = wrapped.update(&text);
+                let expected = wrap(&text, &options);
+                prop_assert_eq!(wrapped.lines(), &expected[..], "{:?} with {:?}", text, options);
+                prop_assert_eq!(
+                    &wrapped.lines()[..dirty.start],
+                    &old_lines[..dirty.start],
+                    "{:?}",
+                    text
+                );
+                let suffix = expected.len() - dirty.end;
+                prop_assert_eq!(
+                    &wrapped.lines()[dirty.end..],
+                    &old_lines[old_lines.len() - suffix..],
+                    "{:?}",
+                    text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sentence_per_line_breaks() {
+        let options = Options::new(80).sentence_per_line(true);
+        assert_eq!(
+            wrap("One. Two! Three? four. \"Five.\" (Six.) Seven", &options),
+            vec![
+                "One.",
+                "Two!",
+                "Three? four.",
+                "\"Five.\"",
+                "(Six.)",
+                "Seven"
+            ]
+        );
+        assert_eq!(
+            wrap(
+                "Read e.g. Chapter 2 by J. R. Hacker. Then stop...  Now.",
+                &options
+            ),
+            vec![
+                "Read e.g. Chapter 2 by J. R. Hacker.",
+                "Then stop...",
+                "Now."
+            ]
+        );
+        assert_eq!(
+            wrap(
+                "One. Two.\nThree.",
+                &options.clone().subsequent_indent("  ")
+            ),
+            vec!["One.", "  Two.", "  Three."]
+        );
+        assert_eq!(wrap("Ende. Über", &options), vec!["Ende.", "Über"]);
+    }
+
+    #[test]
+    fn sentence_per_line_wraps_long_sentences() {
+        let options = Options::new(10)
+            .sentence_per_line(true)
+            .initial_indent("* ")
+            .subsequent_indent("  ");
+        assert_eq!(
+            fill_paragraphs("Short.\nA longer\nsentence here. End.\n\nNext.", &options),
+            "* Short.\n  A longer\n  sentence\n  here.\n  End.\n\n* Next."
+        );
+        let mut wrapped = WrappedText::new(options.clone());
+        let text = "A longer sentence here. End.\nMore.";
+        wrapped.update(text);
+        assert_eq!(wrapped.lines(), wrap(text, &options));
+    }
+
+    #[test]
+    fn inline_markup_code_spans() {
+        let options = Options::new(6).inline_markup(true);
+        assert_eq!(wrap("a `b c d e` f", &options), vec!["a", "`b c d e`", "f"]);
+        assert_eq!(
+            wrap("``a`b c`` ``` x", &options),
+            vec!["``a`b c``", "``` x"]
+        );
+        assert_eq!(wrap("`a b c d", &options), vec!["`a b c", "d"]);
+        assert_eq!(
+            wrap("use `foo-bar-baz`", &options),
+            vec!["use", "`foo-bar-baz`"]
+        );
+    }
+
+    #[test]
+    fn inline_markup_emphasis() {
+        assert_eq!(
+            inline_markup_ranges("**bold** _it_ snake_case *x*"),
+            vec![0..3, 5..8, 9..11, 11..13, 25..27, 26..28]
+        );
+        let options = Options::new(3)
+            .inline_markup(true)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("**ab**", &options), vec!["**ab**"]);
+        assert_eq!(wrap("**abcdef**", &options), vec!["**abcdef**"]);
+        assert_eq!(
+            wrap("**foo-bar**", Options::new(6).inline_markup(true)),
+            vec!["**foo-", "bar**"]
+        );
+        assert_eq!(
+            wrap("**abcdef**", Options::new(3)),
+            vec!["**a", "bcd", "ef*", "*"]
+        );
+    }
+
+    #[test]
+    fn bidi_reorder_disabled() {
+        assert_eq!(wrap("שלום עולם", 80), vec!["שלום עולם"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-bidi")]
+    fn bidi_reorder_hebrew_with_english() {
+        let options = Options::new(10).bidi_reorder(true);
+        assert_eq!(
+            wrap("אני כותב קוד ב-Rust כל יום", &options),
+            vec!["בתוכ ינא", "Rust-ב דוק", "םוי לכ"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-bidi")]
+    fn bidi_reorder_english_with_hebrew() {
+        let options = Options::new(12).bidi_reorder(true);
+        assert_eq!(
+            wrap("The word שלום עולם means hello world", &options),
+            vec!["The word", "םלוע םולש", "means hello", "world"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-bidi")]
+    fn bidi_reorder_per_paragraph() {
+        let options = Options::new(80).bidi_reorder(true);
+        assert_eq!(
+            wrap("hello שלום\nשלום hello\n123", &options),
+            vec!["hello םולש", "hello םולש", "123"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-bidi")]
+    fn bidi_reorder_indent_on_right() {
+        let options = Options::new(8)
+            .initial_indent("1. ")
+            .subsequent_indent("   ")
+            .alignment(Alignment::Right)
+            .bidi_reorder(true);
+        assert_eq!(
+            wrap("אחת שתיים שלוש", &options),
+            vec!["  תחא .1", "םייתש   ", " שולש   "]
+        );
+    }
+
+    #[derive(Debug)]
+    struct CharFinder(char);
+
+    impl core::WordFinder for CharFinder {
+        fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = core::Word<'a>> + 'a> {
+            let breaks = line.match_indices(self.0).map(|(idx, _)| idx + 1);
+            Box::new(core::words_at_breaks(line, breaks.collect::<Vec<_>>()))
+        }
+    }
+
+    fn slash_finder() -> core::WordSeparator {
+        core::WordSeparator::Custom(std::sync::Arc::new(CharFinder('/')))
+    }
+
+    #[test]
+    fn word_separator_custom() {
+        let options = Options::new(8).word_separator(slash_finder());
+        assert_eq!(
+            wrap("foo/bar/baz quux", &options),
+            vec!["foo/bar/", "baz quux"]
+        );
+        assert_eq!(wrap("a/b\nc/d", &options), vec!["a/b", "c/d"]);
+    }
+
+    #[test]
+    fn word_separator_custom_with_splitter() {
+        // The separator finds the words and the splitter splits them.
+        let options = Options::new(6).word_separator(slash_finder());
+        assert_eq!(wrap("ab/cdef-ghi", &options), vec!["ab/", "cdef-", "ghi"]);
+        let options = options.splitter(NoHyphenation);
+        assert_eq!(wrap("ab/cdef-ghi", &options), vec!["ab/", "cdef-g", "hi"]);
+    }
+
+    #[test]
+    fn word_separator_custom_eq() {
+        // Separators are equal if they share the same finder.
+        let custom = slash_finder();
+        assert_eq!(custom, custom.clone());
+        assert_ne!(custom, slash_finder());
+        assert_ne!(custom, core::WordSeparator::AsciiSpace);
+    }
+
+    #[test]
+    fn break_rules_french() {
+        let options = Options::new(10)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .break_rules(core::BreakRules::french());
+        assert_eq!(
+            wrap("Quelle heure est-il ? Midi : l'heure du repas !", &options),
+            vec![
+                "Quelle",
+                "heure est-",
+                "il ?",
+                "Midi :",
+                "l'heure du",
+                "repas !"
+            ]
+        );
+    }
+
+    #[test]
+    fn break_rules_french_no_break_space() {
+        // Text with no-break spaces is kept together without the rules.
+        let options = Options::new(8)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .width_measure(core::WidthMeasure::Chars);
+        assert_eq!(
+            wrap("Il dit\u{a0}: «\u{202f}oui\u{202f}»", &options),
+            vec!["Il dit\u{a0}:", "«\u{202f}oui\u{202f}»"]
+        );
+        let options = options.break_rules(core::BreakRules::french());
+        assert_eq!(
+            wrap("Il dit\u{a0}: «\u{202f}oui\u{202f}»", &options),
+            vec!["Il dit\u{a0}:", "«\u{202f}oui\u{202f}»"]
+        );
+    }
+
+    #[test]
+    fn break_rules_joined_words_broken_by_the_rules() {
+        // A joined word which is too long for a line is split at its
+        // spaces, and only the word which is still too long is broken.
+        let options = Options::new(5).break_rules(core::BreakRules::french());
+        assert_eq!(
+            wrap("« bonjour » !", &options),
+            vec!["«", "bonjo", "ur »", "!"]
+        );
+        let options = Options {
+            width: 9,
+            ..options
+        };
+        assert_eq!(wrap("« bonjour » !", &options), vec!["« bonjour", "» !"]);
+    }
+
+    #[test]
+    fn break_rules_french_narrow_width() {
+        // The word inside the guillemets is only broken when it is
+        // too long for a line on its own.
+        let text = "Il dit «\u{202f}bonjour\u{202f}» !";
+        let options = Options::new(7)
+            .break_rules(core::BreakRules::french())
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        let lines = wrap(text, &options);
+        assert_eq!(lines, vec!["Il dit", "«", "bonjour", "» !"]);
+        assert_eq!(verify(text, &lines, &options), Ok(()));
+
+        let options = Options {
+            width: 4,
+            ..options
+        };
+        let lines = wrap(text, &options);
+        assert_eq!(lines, vec!["Il", "dit", "«", "bonj", "our", "» !"]);
+        assert_eq!(verify(text, &lines, &options), Ok(()));
+    }
+
+    #[test]
+    fn hanging_punctuation_disabled() {
+        let ranges = wrap_ranges("\"foo\" bar", 5);
+        assert!(ranges.iter().all(|line| line.hanging_punctuation == 0));
+        assert_eq!(wrap("\"foo\" bar", 5), vec!["\"foo\"", "bar"]);
+    }
+
+    #[test]
+    fn hanging_punctuation_bullets() {
+        let options = Options::new(8).hanging_punctuation(true);
+        assert_eq!(
+            wrap("- one two\n- three", &options),
+            vec!["- one two", "- three"]
+        );
+        let ranges = wrap_ranges("- one two\n- three", &options);
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|line| line.hanging_punctuation)
+                .collect::<Vec<_>>(),
+            vec![1, 1]
+        );
+    }
+
+    #[test]
+    fn hanging_punctuation_after_initial_indent() {
+        let options = Options::new(6)
+            .initial_indent("> ")
+            .hanging_punctuation(true);
+        assert_eq!(wrap("\"abc\" d", &options), vec!["> \"abc\"", "d"]);
+    }
+
+    #[test]
+    fn hanging_punctuation_only_at_line_start() {
+        // The quotes in the middle of a line take up room as usual.
+        let options = Options::new(7)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .hanging_punctuation(true);
+        assert_eq!(
+            wrap("ab 'cd' 'efg' hi", &options),
+            vec!["ab 'cd'", "'efg' hi"]
+        );
+        let ranges = wrap_ranges("ab 'cd' 'efg' hi", &options);
+        assert_eq!(ranges[0].hanging_punctuation, 0);
+        assert_eq!(ranges[1].hanging_punctuation, 1);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(200))]
+
+        #[test]
+        fn hanging_punctuation_line_widths(
+            text in random_text(
+                prop_oneof![
+                    2 => Just(' '),
+                    1 => Just('"'),
+                    1 => Just('“'),
+                    4 => prop::char::range('a', 'z'),
+                ],
+                40..41,
+            ),
+            width in 3..15usize,
+        ) {
+            let options = Options::new(width).hanging_punctuation(true);
+            for line in wrap_ranges(&text, &options) {
+                let content = &text[line.range.clone()];
+                let hanging = match content.chars().next() {
+                    Some(ch @ ('"' | '“')) => core::display_width(&content[..ch.len_utf8()]),
+                    _ => 0,
+                };
+                prop_assert_eq!(line.hanging_punctuation, hanging, "{:?}", content);
+                prop_assert!(
+                    core::display_width(content) <= width + hanging,
+                    "{:?} in {:?} at {}",
+                    content,
+                    text,
+                    width
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn line_ending_html_break() {
+        let text = "foo bar\n\n\nbaz";
+        let options = Options::new(3).line_ending("<br>\n");
+        let expected = "foo<br>\nbar<br>\n<br>\n<br>\nbaz";
+        assert_eq!(fill(text, &options), expected);
+        assert_eq!(fill_paragraphs(text, &options), expected);
+        assert_eq!(Wrapped::new(text, &options).to_string(), expected);
+        assert_eq!(wrap(text, &options), vec!["foo", "bar", "", "", "baz"]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn wrap_stream_line_ending() {
+        let mut output = Vec::new();
+        let options = Options::new(3).line_ending("\r\n");
+        wrap_stream(&b"foo bar\n\nbaz"[..], &mut output, &options).unwrap();
+        assert_eq!(output, b"foo\r\nbar\r\n\r\nbaz");
+    }
+
+    #[test]
+    fn sanitize_control_chars_disabled() {
+        assert_eq!(wrap("foo\u{7}bar baz", 6), vec!["foo\u{7}bar", "baz"]);
+    }
+
+    #[test]
+    fn sanitize_control_chars_removes_c0_and_c1() {
+        let options = Options::new(80).sanitize_control_chars(true);
+        assert_eq!(
+            wrap("a\u{0}b\u{b}c\u{7f}d\u{85}e\u{9b}f", &options),
+            vec!["abcdef"]
+        );
+        assert_eq!(wrap("a\tb\r\nc\rd", &options), vec!["a\tb", "cd"]);
+    }
+
+    #[test]
+    fn sanitize_control_chars_keeps_escape_sequences() {
+        let options = Options::new(80).sanitize_control_chars(true);
+        let link = "\u{1b}]8;;https://example.com\u{7}link\u{1b}]8;;\u{1b}\\";
+        assert_eq!(wrap(link, &options), vec![link]);
+        // A lone ESC is removed, the following text is kept.
+        assert_eq!(wrap("\u{1b}x\u{1b}", &options), vec!["x"]);
+    }
+
+    #[test]
+    fn control_chars_have_zero_width() {
+        assert_eq!(core::display_width("a\u{7}\u{8}\u{b}\u{85}b"), 2);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(200))]
+
+        #[test]
+        fn sanitize_control_chars_fuzz(
+            text in random_text(
+                prop_oneof![
+                    prop::char::range('\u{0}', '\u{1f}'),
+                    prop::char::range('\u{7f}', '\u{9f}'),
+                    Just(' '),
+                    prop::char::range('a', 'z'),
+                ],
+                60..61,
+            ),
+        ) {
+            for sanitize in [false, true] {
+                let options = Options::new(7).sanitize_control_chars(sanitize);
+                for line in wrap(&text, &options) {
+                    prop_assert!(core::display_width(&line) <= 7, "{:?} in {:?}", line, text);
+                    if sanitize {
+                        prop_assert!(!line.contains(is_unsafe_control), "{:?}", line);
+                    }
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(300))]
+
+        #[test]
+        fn line_count_agrees_with_wrap(
+            text in random_text(
+                prop_oneof![
+                    1 => Just('\n'),
+                    1 => Just('-'),
+                    2 => Just(' '),
+                    8 => prop::char::range('a', 'z'),
+                ],
+                80..81,
+            ),
+            width in 1..16usize,
+            long_words in prop::sample::select(&[LongWords::Break, LongWords::Overflow][..]),
+            initial_indent in prop::sample::select(&["", "* ", ">>> "][..]),
+            subsequent_indent in prop::sample::select(&["", "  "][..]),
+            hyphenation in any::<bool>(),
+            max_lines in prop::option::weighted(1.0 / 3.0, 0..4usize),
+        ) {
+            let mut options: Options = Options::new(width)
+                .long_words(long_words)
+                .initial_indent(initial_indent)
+                .subsequent_indent(subsequent_indent)
+                .splitter_boxed(HyphenSplitter);
+            if !hyphenation {
+                options = options.splitter_boxed(NoHyphenation);
+            }
+            if let Some(max_lines) = max_lines {
+                options = options.max_lines(max_lines);
+            }
+            prop_assert_eq!(
+                options.line_count(&text),
+                wrap(&text, &options).len(),
+                "{:?} with {:?}",
+                text,
+                options
+            );
+        }
+    }
+
+    #[test]
+    fn wrapped_display_matches_fill() {
+        let options = Options::new(10)
+            .initial_indent("* ")
+            .subsequent_indent("  ")
+            .line_ending("\r\n");
+        for text in ["", "foo", "foo bar baz quux", "foo\n\nbar baz quux\n"] {
+            assert_eq!(
+                Wrapped::new(text, &options).to_string(),
+                fill(text, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn wrapped_display_ignores_formatter_flags() {
+        let options = Options::new(7);
+        let wrapped = Wrapped::new("foo bar baz", &options);
+        assert_eq!(format!("{:>20}", wrapped), "foo bar\nbaz");
+    }
+
+    #[test]
+    fn for_documentation_wraps_without_breaking_words() {
+        let options = Options::for_documentation();
+        let url = format!("https://example.com/{}", "x".repeat(80));
+        assert_eq!(
+            wrap(&format!("See {}.", url), &options),
+            vec![String::from("See"), format!("{}.", url)]
+        );
+    }
+
+    #[test]
+    fn for_documentation_ignores_locale() {
+        // Only the HyphenSplitter is used, so a word is split at its
+        // hyphens but never hyphenated with a dictionary.
+        let options = Options {
+            width: 8,
+            ..Options::for_documentation()
+        };
+        assert_eq!(
+            wrap("Documentation is well-known", &options),
+            vec!["Documentation", "is well-", "known"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn locale_languages_fallback_chain() {
+        use splitting::locale_languages;
+        assert_eq!(locale_languages("de-DE"), vec![Language::German1996]);
+        assert_eq!(
+            locale_languages("de_CH.UTF-8"),
+            vec![Language::GermanSwiss, Language::German1996]
+        );
+        assert_eq!(
+            locale_languages("en-GB"),
+            vec![Language::EnglishGB, Language::EnglishUS]
+        );
+        assert_eq!(locale_languages("EN"), vec![Language::EnglishUS]);
+        assert_eq!(locale_languages("fr-CA"), vec![Language::French]);
+        assert_eq!(locale_languages("pt-BR"), vec![Language::Portuguese]);
+        assert_eq!(
+            locale_languages("sr-Latn-RS"),
+            vec![Language::SerbocroatianLatin, Language::SerbianCyrillic]
+        );
+        assert_eq!(
+            locale_languages("de-1901"),
+            vec![Language::German1901, Language::German1996]
+        );
+        assert_eq!(locale_languages("C.UTF-8"), vec![]);
+        assert_eq!(locale_languages("POSIX"), vec![]);
+        assert_eq!(locale_languages(""), vec![]);
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn dictionary_for_locale_en_de_fr() {
+        let language = |tag| dictionary_for_locale(tag).map(|dictionary| dictionary.language());
+        assert_eq!(language("en"), Some(Language::EnglishUS));
+        assert_eq!(language("en_US.UTF-8"), Some(Language::EnglishUS));
+        assert_eq!(language("de-DE"), Some(Language::German1996));
+        assert_eq!(language("de-AT"), Some(Language::German1996));
+        assert_eq!(language("fr"), Some(Language::French));
+        assert_eq!(language("fr-BE"), Some(Language::French));
+        assert_eq!(language("tlh"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn hyphenate_locale() {
+        let options = Options::new(12).hyphenate_locale("de-DE");
+        assert_eq!(
+            wrap("Donaudampfschifffahrt", &options),
+            vec!["Donaudampf-", "schifffahrt"]
+        );
+        let options = Options::new(12).hyphenate_locale("xx-YY");
+        assert_eq!(
+            wrap("foo-bar-baz-qux", &options),
+            vec!["foo-bar-baz-", "qux"]
+        );
+    }
+
+    #[test]
+    fn for_help_matches_clap_style() {
+        let options = Options::for_help_with_width(60, 26);
+        let help = [
+            "    -o, --output <FILE>   Write the generated report to FILE instead of the standard output",
+            "        --no-color        Disable colored output, even when the standard output is a terminal",
+            "        --max-line-length=<WIDTH>",
+        ];
+        let expected = "    -o, --output <FILE>   Write the generated report to FILE\n\
+                        \x20                         instead of the standard output\n\
+                        \x20       --no-color        Disable colored output, even when\n\
+                        \x20                         the standard output is a terminal\n\
+                        \x20       --max-line-length=<WIDTH>";
+        let wrapped = help
+            .iter()
+            .map(|line| fill(line, &options))
+            .collect::<Vec<_>>();
+        assert_eq!(wrapped.join("\n"), expected);
+    }
+
+    #[test]
+    fn wrap_table_row_pads_cells() {
+        let options = Options::new(80).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap_table_row(&["foo bar baz", "x", ""], &[7, 3, 2], &options),
+            vec![vec!["foo bar", "x  ", "  "], vec!["baz    ", "   ", "  "]]
+        );
+        assert_eq!(
+            wrap_table_row(&[], &[], &options),
+            Vec::<Vec<String>>::new()
+        );
+    }
+
+    #[test]
+    fn wrap_table_row_wide_characters() {
+        let lines = wrap_table_row(&["😊😊😊 ab", "表格单元格"], &[5, 4], 80);
+        for cells in &lines {
+            assert_eq!(core::display_width(&cells[0]), 5);
+            assert_eq!(core::display_width(&cells[1]), 4);
+        }
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrap_table_row_mismatched_widths() {
+        wrap_table_row(&["foo", "bar"], &[5], 80);
+    }
+
+    #[test]
+    fn pad_alignments() {
+        assert_eq!(pad("ab", 5, Alignment::Left), "ab   ");
+        assert_eq!(pad("ab", 5, Alignment::Center), " ab  ");
+        assert_eq!(pad("ab", 5, Alignment::Right), "   ab");
+        assert_eq!(pad("ab", 5, Alignment::Justified), "ab   ");
+        assert_eq!(pad("", 2, Alignment::Left), "  ");
+    }
+
+    #[test]
+    fn pad_wide_characters() {
+        assert_eq!(pad("表", 4, Alignment::Right), "  表");
+        assert_eq!(pad("😊a", 4, Alignment::Center), "😊a ");
+    }
+
+    #[test]
+    fn pad_does_not_allocate_without_padding() {
+        for alignment in [Alignment::Left, Alignment::Center, Alignment::Right] {
+            assert!(matches!(pad("abc", 3, alignment), Cow::Borrowed("abc")));
+            assert!(matches!(pad("表格", 3, alignment), Cow::Borrowed("表格")));
+        }
+    }
+
+    #[test]
+    fn center_lines() {
+        assert_eq!(center("", 2), "  ");
+        assert_eq!(center("a\nbcd\ntoo long", 5), "  a  \n bcd \ntoo long");
+    }
+
+    #[test]
+    fn fill_boxed_ascii() {
+        let options = Options::new(7).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            fill_boxed("foo bar baz", &options, BoxStyle::ASCII),
+            "+-------+\n|foo bar|\n|baz    |\n+-------+"
+        );
+    }
+
+    #[test]
+    fn fill_boxed_wide_characters() {
+        assert_eq!(
+            fill_boxed("表格 ab", 4, BoxStyle::UNICODE),
+            "┌────┐\n│表格│\n│ab  │\n└────┘"
+        );
+    }
+
+    #[test]
+    fn fill_boxed_widens_for_long_lines() {
+        let options = Options::new(3).long_words(LongWords::Overflow);
+        assert_eq!(
+            fill_boxed("abcde f", &options, BoxStyle::ASCII),
+            "+-----+\n|abcde|\n|f    |\n+-----+"
+        );
+    }
+
+    #[test]
+    fn fill_boxed_truncates_title() {
+        // The title does not widen the box.
+        assert_eq!(
+            fill_boxed("a", 8, BoxStyle::ASCII.title("Title")),
+            "+- Title +\n|a       |\n+--------+"
+        );
+        let options = Options::new(7).placeholder("...");
+        assert_eq!(
+            fill_boxed("a", &options, BoxStyle::ASCII.title("Title")),
+            "+- T... +\n|a      |\n+-------+"
+        );
+        let options = Options::new(5).placeholder("...");
+        assert_eq!(
+            fill_boxed("a", &options, BoxStyle::ASCII.title("Title")),
+            "+- Ti +\n|a    |\n+-----+"
+        );
+        // The title is left out when there is no room for it.
+        assert_eq!(
+            fill_boxed("a", 3, BoxStyle::ASCII.title("Title")),
+            "+---+\n|a  |\n+---+"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn fill_boxed_truncates_title_unicode() {
+        assert_eq!(
+            fill_boxed("a", 7, BoxStyle::ASCII.title("Title")),
+            "+- Tit… +\n|a      |\n+-------+"
+        );
+        // A wide character is not cut in half.
+        assert_eq!(
+            fill_boxed("a", 6, BoxStyle::ASCII.title("表格")),
+            "+- 表… +\n|a     |\n+------+"
+        );
+        assert_eq!(
+            fill_boxed("a", 5, BoxStyle::ASCII.title("表格")),
+            "+- … -+\n|a    |\n+-----+"
+        );
+    }
+
+    #[test]
+    fn fill_boxed_empty() {
+        assert_eq!(fill_boxed("", 0, BoxStyle::UNICODE), "┌┐\n└┘");
+        assert_eq!(fill_boxed("", 0, BoxStyle::UNICODE.title("x")), "┌┐\n└┘");
+        assert_eq!(
+            fill_boxed("", 4, BoxStyle::UNICODE.title("x")),
+            "┌─ x ┐\n└────┘"
+        );
+    }
+
+    #[test]
+    fn respect_newlines_blank_lines() {
+        let options = Options::new(10).subsequent_indent("> ");
+        assert_eq!(
+            wrap("foo bar baz\n\nquux\n", &options),
+            vec!["foo bar", "> baz", "", "> quux", ""]
+        );
+    }
+
+    #[test]
+    fn respect_newlines_disabled() {
+        let options = Options::new(10)
+            .respect_newlines(false)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap("foo\r\nbar\nbaz quux", &options),
+            vec!["foo bar", "baz quux"]
+        );
+        assert_eq!(options.line_count("foo\r\nbar\nbaz quux"), 2);
+        assert_eq!(fill("a\n\nb", &options), "a  b");
+    }
+
+    #[test]
+    fn try_wrap_long_words() {
+        let text = "foo verylongword bar";
+        for long_words in [LongWords::Break, LongWords::Overflow] {
+            let options = Options::new(6).long_words(long_words);
+            assert_eq!(try_wrap(text, &options).unwrap(), wrap(text, &options));
+        }
+        let options = Options::new(6).long_words(LongWords::Error);
+        assert_eq!(wrap(text, &options), vec!["foo", "verylongword", "bar"]);
+        assert_eq!(
+            try_wrap(text, &options),
+            Err(WrapError::WordTooLong {
+                word: String::from("verylongword"),
+                word_width: 12,
+                available_width: 6,
+            })
+        );
+        assert_eq!(
+            try_wrap("foo-bar baz", &options).unwrap(),
+            vec!["foo-", "bar", "baz"]
+        );
+    }
+
+    #[test]
+    fn try_wrap_reports_overflowing_word() {
+        // The words are glued together, and "!" is the word which
+        // crosses the end of the line.
+        let options = Options::new(8)
+            .long_words(LongWords::Error)
+            .break_rules(core::BreakRules::new("!", ""));
+        assert_eq!(wrap("ab abcdefg !", &options), vec!["ab", "abcdefg !"]);
+        assert_eq!(
+            try_wrap("ab abcdefg !", &options),
+            Err(WrapError::WordTooLong {
+                word: String::from("!"),
+                word_width: 1,
+                available_width: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn try_wrap_long_words_with_indent() {
+        let options = Options::new(8)
+            .subsequent_indent("    ")
+            .long_words(LongWords::Error);
+        assert_eq!(
+            try_wrap("abcdefgh ijklm", &options),
+            Err(WrapError::WordTooLong {
+                word: String::from("ijklm"),
+                word_width: 5,
+                available_width: 4,
+            })
+        );
+        assert_eq!(
+            try_wrap("abcdefgh ijklm", &options)
+                .unwrap_err()
+                .to_string(),
+            "word \"ijklm\" of width 5 does not fit in 4 columns"
+        );
+    }
+
+    #[test]
+    fn verify_errors() {
+        let text = "foo bar baz";
+        let options = Options::new(7).subsequent_indent("> ");
+        let lines =
+            |lines: &[&'static str]| lines.iter().copied().map(Cow::from).collect::<Vec<_>>();
+        assert_eq!(
+            verify(text, &lines(&["foo bar", "> baz"]), &options),
+            Ok(())
+        );
+        assert_eq!(
+            verify(text, &lines(&["foo bar baz"]), &options),
+            Err(WrapError::LineTooWide {
+                line: 0,
+                line_width: 11,
+                width: 7
+            })
+        );
+        assert_eq!(
+            verify(text, &lines(&["foo bar", "baz"]), &options),
+            Err(WrapError::MissingIndent { line: 1 })
+        );
+        assert_eq!(
+            verify(text, &lines(&["foo ", "> bar", "> baz"]), &options),
+            Err(WrapError::StrayWhitespace { line: 0 })
+        );
+        assert_eq!(
+            verify(text, &lines(&["foo", ">  bar", "> baz"]), &options),
+            Err(WrapError::StrayWhitespace { line: 1 })
+        );
+        assert_eq!(
+            verify(text, &lines(&["foo baz", "> bar"]), &options),
+            Err(WrapError::WordsChanged { line: 0 })
+        );
+        assert_eq!(
+            verify(text, &lines(&["foo bar"]), &options),
+            Err(WrapError::WordsChanged { line: 1 })
+        );
+        assert_eq!(
+            WrapError::StrayWhitespace { line: 3 }.to_string(),
+            "line 3 has leading or trailing whitespace"
+        );
+    }
+
+    #[test]
+    fn verify_allows_breaks_and_truncation() {
+        let lines =
+            |lines: &[&'static str]| lines.iter().copied().map(Cow::from).collect::<Vec<_>>();
+        let options = Options::new(4);
+        assert_eq!(verify("abcd", &lines(&["ab-", "cd"]), &options), Ok(()));
+        assert_eq!(verify("ab-cd", &lines(&["ab-", "cd"]), &options), Ok(()));
+        assert_eq!(
+            verify("abcdefgh", &lines(&["abcd", "efgh"]), &options),
+            Ok(())
+        );
+        assert_eq!(
+            verify("abcdefgh", &lines(&["abcdefgh"]), &options),
+            Err(WrapError::LineTooWide {
+                line: 0,
+                line_width: 8,
+                width: 4
+            })
+        );
+        let overflow = Options::new(4).long_words(LongWords::Overflow);
+        assert_eq!(verify("abcdefgh", &lines(&["abcdefgh"]), &overflow), Ok(()));
+        assert_eq!(
+            verify(
+                "foo\n\n  bar",
+                &lines(&["foo", "", "  bar"]),
+                &Options::new(6)
+            ),
+            Ok(())
+        );
+
+        let options = Options::new(4).word_break_marker("\\");
+        assert_eq!(
+            verify("abcdef", &lines(&["abc\\", "def"]), &options),
+            Ok(())
+        );
+
+        let options = Options::new(10).max_lines(1);
+        assert_eq!(
+            verify("foo bar baz qux", &lines(&["foo bar…"]), &options),
+            Ok(())
+        );
+        assert_eq!(
+            verify("foo bar baz qux", &lines(&["foo baz…"]), &options),
+            Err(WrapError::WordsChanged { line: 0 })
+        );
+    }
+
+    const VERIFY_PIECES: &[&str] = &[
+        "a",
+        "b",
+        "c",
+        "x",
+        " ",
+        " ",
+        " ",
+        "\n",
+        "\r\n",
+        "-",
+        "\t",
+        "\u{a0}",
+        "\u{202f}",
+        "\u{ad}",
+        "\u{200b}",
+        "\u{2060}",
+        "e\u{301}",
+        "世",
+        "😀",
+        "👩\u{200d}🔬",
+        "10",
+        "MB",
+        "J.",
+        "«",
+        "»",
+        "!",
+    ];
+
+    const VERIFY_RULES: &[core::BreakRules<'_>] = &[
+        core::BreakRules::none(),
+        core::BreakRules::none().glue(core::GlueRule::DEFAULTS),
+        core::BreakRules::french(),
+    ];
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn verify_random_wraps(
+            text in random_text(prop::sample::select(VERIFY_PIECES), 0..40),
+            width in 3..15usize,
+            initial_indent in prop::sample::select(&["", "> ", "😀"][..]),
+            subsequent_indent in prop::sample::select(&["", "  ", "😀"][..]),
+            long_words in prop::sample::select(&[LongWords::Break, LongWords::Overflow][..]),
+            break_rules in prop::sample::select(VERIFY_RULES),
+            alignment in prop::sample::select(
+                &[
+                    Alignment::Left,
+                    Alignment::Right,
+                    Alignment::Center,
+                    Alignment::Justified,
+                ][..],
+            ),
+            max_lines in prop::option::weighted(0.25, 1..5usize),
+            first_fit in any::<bool>(),
+            word_break_marker in prop::bool::weighted(0.25),
+            continuation_marker in prop::option::weighted(
+                0.25,
+                prop::sample::select(&["↪ ", " \\"][..]),
+            ),
+        ) {
+            let mut options = Options::new(width)
+                .initial_indent(initial_indent)
+                .subsequent_indent(subsequent_indent)
+                .long_words(long_words)
+                .break_rules(break_rules)
+                .alignment(alignment);
+            if let Some(max_lines) = max_lines {
+                options = options.max_lines(max_lines);
+            }
+            if first_fit {
+                options = options.wrap_algorithm(core::WrapAlgorithm::FirstFit);
+            }
+            if word_break_marker {
+                options = options.word_break_marker("\\");
+            }
+            if let Some(continuation_marker) = continuation_marker {
+                options = options.continuation_marker(continuation_marker);
+            }
+            let lines = wrap(&text, &options);
+            prop_assert_eq!(
+                verify(&text, &lines, &options),
+                Ok(()),
+                "{:?} wrapped into {:?}",
+                text,
+                lines
+            );
+        }
+    }
+
+    #[test]
+    fn lone_soft_hyphen_leaves_no_whitespace() {
+        assert_eq!(wrap("foo \u{ad} bar", 4), vec!["foo", "bar"]);
+        assert_eq!(
+            wrap(
+                "\u{ad} ab cd ef",
+                Options::new(7).alignment(Alignment::Justified)
+            ),
+            vec!["ab   cd", "ef"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "smawk")]
+    fn optimal_fit_only_overflows_with_single_word() {
+        let options = Options::new(5)
+            .subsequent_indent("  ")
+            .long_words(LongWords::Overflow);
+        assert_eq!(wrap("a bcdefghij", &options), vec!["a", "  bcdefghij"]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn break_words_maps_to_long_words() {
+        assert_eq!(
+            Options::new(5).break_words(true).long_words,
+            LongWords::Break
+        );
+        assert_eq!(
+            Options::new(5).break_words(false).long_words,
+            LongWords::Overflow
+        );
+    }
+
+    #[test]
+    fn hanging_indent_from_input_mixed_whitespace() {
+        let options = Options::new(8).hanging_indent_from_input(true);
+        assert_eq!(
+            wrap(" \t foo bar baz", &options),
+            vec![" \t foo", " \t bar", " \t baz"]
+        );
+    }
+
+    #[test]
+    fn hanging_indent_from_input_per_paragraph() {
+        let options = Options::new(10)
+            .subsequent_indent("> ")
+            .hanging_indent_from_input(true);
+        assert_eq!(
+            wrap("  aaa bbb ccc\nddd eee fff", &options),
+            vec!["  aaa bbb", "  ccc", "> ddd eee", "> fff"]
+        );
+    }
+
+    #[test]
+    fn hanging_indent_from_input_ranges() {
+        let text = "  foo bar";
+        let options = Options::new(6).hanging_indent_from_input(true);
+        assert_eq!(
+            wrap_ranges(text, &options)[1],
+            LineRange {
+                indent: "".into(),
+                hanging_indent: 0..2,
+                prefix: "".into(),
+                range: 6..9,
+                penalty: "".into(),
+                hanging_punctuation: 0
+            }
+        );
+    }
+
+    #[test]
+    fn hanging_indent_from_input_justified() {
+        let options = Options::new(10)
+            .hanging_indent_from_input(true)
+            .alignment(Alignment::Justified);
+        assert_eq!(
+            wrap("  a b c d e f g h i j", &options),
+            vec!["  a b  c d", "  e f  g h", "  i j"]
+        );
+    }
+
+    #[test]
+    fn owned_indents_give_static_options() {
+        struct Formatter {
+            options: Options<'static>,
+        }
+
+        fn formatter(depth: usize) -> Formatter {
+            let indent = "  ".repeat(depth);
+            Formatter {
+                options: Options::new(12)
+                    .initial_indent(format!("{}- ", indent))
+                    .subsequent_indent(indent + "  ")
+                    .splitter(Box::new(HyphenSplitter) as Box<dyn WordSplitter>),
+            }
+        }
+
+        let formatter = formatter(1);
+        assert_eq!(
+            wrap("foo bar baz", &formatter.options),
+            vec!["  - foo bar", "    baz"]
+        );
+    }
+
+    #[test]
+    fn refill_prefixed_keeps_doc_comments_apart() {
+        let text = "//! Crate\n//! docs.\n/// Item\n/// docs.\n// Plain\n// comment.\n";
+        assert_eq!(
+            refill_prefixed(text, 80, &["//", "///", "//!"]),
+            "//! Crate docs.\n/// Item docs.\n// Plain comment.\n"
+        );
+    }
+
+    #[test]
+    fn refill_prefixed_subtracts_prefix_width() {
+        let text = "    # aaa bbb ccc ddd\n    # eee";
+        assert_eq!(
+            refill_prefixed(text, 14, &["#"]),
+            "    # aaa bbb\n    # ccc ddd\n    # eee"
+        );
+    }
+
+    #[test]
+    fn refill_prefixed_without_space() {
+        let text = "#foo\n#bar\n#\n#baz\r\n";
+        assert_eq!(refill_prefixed(text, 80, &["#"]), "#foo bar\n#\n#baz\r\n");
+    }
+
+    #[test]
+    fn refill_prefixed_other_lines() {
+        let text = "code();\n// a\n\n// b\n";
+        assert_eq!(refill_prefixed(text, 80, &["//"]), text);
+        assert_eq!(refill_prefixed(text, 80, &[]), text);
+    }
+
+    #[test]
+    fn quote_depth_prefixes() {
+        assert_eq!(quote_depth("foo"), (0, "foo"));
+        assert_eq!(quote_depth("  foo"), (0, "  foo"));
+        assert_eq!(quote_depth(">foo"), (1, "foo"));
+        assert_eq!(quote_depth("> > foo"), (2, "foo"));
+        assert_eq!(quote_depth(">>> foo > bar"), (3, "foo > bar"));
+        assert_eq!(quote_depth("> >"), (2, ""));
+    }
+
+    #[test]
+    fn reflow_quoted_normalizes_prefixes() {
+        let text = ">>aaa\n> > bbb\n>>  ccc\n>\n> >\n>> ddd";
+        assert_eq!(reflow_quoted(text, 80), "> > aaa bbb ccc\n>\n> >\n> > ddd");
+    }
+
+    #[test]
+    fn reflow_quoted_unquoted_text() {
+        assert_eq!(
+            reflow_quoted("foo\nbar\n\nbaz\r\n", 80),
+            "foo bar\n\nbaz\r\n"
+        );
+    }
+
+    #[test]
+    fn reflow_quoted_signature() {
+        let text = "Thanks\nfor that.\n-- \nBob\nbob@example.com\n";
+        assert_eq!(
+            reflow_quoted(text, 80),
+            "Thanks for that.\n-- \nBob\nbob@example.com\n"
+        );
+    }
+
+    #[test]
+    fn url_breaks_never_with_hyphenation_splitter() {
+        let options = Options::new(10)
+            .url_breaks(UrlBreaks::Never)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap("a http://foo-bar.example/baz-qux b", &options),
+            vec!["a", "http://foo-bar.example/baz-qux", "b"]
+        );
+    }
+
+    #[test]
+    fn url_breaks_email_addresses() {
+        let text = "mail someone@example.com";
+        let options = Options::new(8).url_breaks(UrlBreaks::Never);
+        assert_eq!(wrap(text, &options), vec!["mail", "someone@example.com"]);
+        assert_eq!(wrap(text, 8), vec!["mail", "someone@", "example.", "com"]);
+    }
+
+    #[test]
+    fn url_breaks_after_separators() {
+        let options = Options::new(12)
+            .url_breaks(UrlBreaks::AfterSeparators)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap("https://a.example/x-y?p=1&q=2#top", &options),
+            vec!["https://", "a.example/", "x-y?p=1&q=2#", "top"]
+        );
+    }
+
+    #[test]
+    fn url_breaks_ignores_other_words() {
+        let options = Options::new(5).url_breaks(UrlBreaks::Never);
+        assert_eq!(wrap("foo-barbaz", &options), vec!["foo-", "barba", "z"]);
+        assert_eq!(wrap("a@b", &options), vec!["a@b"]);
+    }
+
+    #[test]
+    fn is_url_like() {
+        assert!(core::is_url_like("https://example.com"));
+        assert!(core::is_url_like("<git+ssh://host/repo>"));
+        assert!(core::is_url_like("user@example.com"));
+        assert!(!core::is_url_like("://example.com"));
+        assert!(!core::is_url_like("1://x"));
+        assert!(!core::is_url_like("@example.com"));
+        assert!(!core::is_url_like("foo@bar"));
+        assert!(!core::is_url_like("foo@bar."));
+        assert!(!core::is_url_like("foo-bar"));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn wrap_with_protection_splitter() {
+        let options = Options::new(6).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("foo-bar-baz", &options), vec!["foo-", "bar-", "baz"]);
+        assert_eq!(
+            wrap_with_protection("foo-bar-baz", &options, &[0..4]),
+            vec!["foo-", "bar-", "baz"]
+        );
+        assert_eq!(
+            wrap_with_protection("foo-bar-baz", &options, &[3..5]),
+            vec!["foo-bar-", "baz"]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn wrap_with_protection_range_boundaries() {
+        // Ranges which end at a break or start after one allow it.
+        let text = "aaa bbb ccc";
+        assert_eq!(wrap_with_protection(text, 3, &[0..3, 4..7]), wrap(text, 3));
+        assert_eq!(
+            wrap_with_protection(text, 3, &[0..5]),
+            vec!["aaa bbb", "ccc"]
+        );
+    }
+
+    #[test]
+    fn wrap_with_protection_multiple_lines() {
+        let text = "x {a b}\ny {c d}";
+        assert_eq!(
+            wrap_with_protection(text, 3, &[2..7, 10..15]),
+            vec!["x", "{a b}", "y", "{c d}"]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn wrap_with_protection_partial_word() {
+        let options = Options::new(4).long_words(LongWords::Break);
+        assert_eq!(wrap("ab{cd}", &options), vec!["ab{c", "d}"]);
+        assert_eq!(
+            wrap_with_protection("ab{cd}", &options, &[2..6]),
+            vec!["ab{cd}"]
+        );
+    }
+
+    #[test]
+    fn balance_keeps_line_count() {
+        let text = "aaa bb cccc d eeeee ff g";
+        let options = Options::new(10).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap(text, &options), vec!["aaa bb", "cccc d", "eeeee ff g"]);
+        let options = options.balance(3);
+        assert_eq!(wrap(text, &options), vec!["aaa bb", "cccc d", "eeeee ff g"]);
+        assert_eq!(
+            wrap("aaa bb cccc d eeeee", &options),
+            vec!["aaa bb", "cccc d", "eeeee"]
+        );
+    }
+
+    #[test]
+    fn balance_with_indents() {
+        let options = Options::new(14)
+            .subsequent_indent("  ")
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        let text = "aa bb cc dd ee ff gg hh";
+        assert_eq!(wrap(text, &options), vec!["aa bb cc dd ee", "  ff gg hh"]);
+        assert_eq!(
+            wrap(text, &options.balance(2)),
+            vec!["aa bb cc dd", "  ee ff gg hh"]
+        );
+    }
+
+    #[test]
+    fn balance_only_short_paragraphs() {
+        let text = "aaaa bb cc dd ee ff gg";
+        let options = Options::new(10).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap(text, &options.clone().balance(1)),
+            wrap(text, &options)
+        );
+        assert_eq!(
+            wrap(text, &options.balance(2)),
+            vec!["aaaa bb cc", "dd ee ff", "gg"]
+        );
+    }
 
-    let initial_width = options
-        .width
-        .saturating_sub(core::display_width(options.initial_indent));
-    let subsequent_width = options
-        .width
-        .saturating_sub(core::display_width(options.subsequent_indent));
+    #[test]
+    fn balance_unbreakable_words() {
+        let options = Options::new(8).long_words(LongWords::Overflow).balance(4);
+        assert_eq!(
+            wrap("abcdefghijk a b", &options),
+            vec!["abcdefghijk", "a b"]
+        );
+    }
 
-    let mut lines = Vec::new();
-    for line in text.split('\n') {
-        let words = core::find_words(line);
-        let split_words = core::split_words(words, &options);
-        let broken_words = if options.break_words {
-            let mut broken_words = core::break_words(split_words, subsequent_width);
-            if !options.initial_indent.is_empty() {
-                // Without this, the first word will always go into
-                // the first line. However, since we break words based
-                // on the _second_ line width, it can be wrong to
-                // unconditionally put the first word onto the first
-                // line. An empty zero-width word fixed this.
-                broken_words.insert(0, core::Word::from(""));
-            }
-            broken_words
-        } else {
-            split_words.collect::<Vec<_>>()
-        };
+    #[test]
+    fn balance_with_narrower_later_lines() {
+        // Shrinking the lines can push words onto the narrow lines
+        // after the last line.
+        let shape = |i| if i < 2 { 20 } else { 3 };
+        let options = Options::new(80)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .width_fn(&shape)
+            .balance(4);
+        let text = "aaaa bbbb cccc dddd eeee ffff gggg hh";
+        assert_eq!(
+            wrap(text, &options),
+            vec!["aaaa bbbb cccc dddd", "eeee ffff gggg hh"]
+        );
+    }
 
-        #[rustfmt::skip]
-        let line_lengths = |i| if i == 0 { initial_width } else { subsequent_width };
-        let wrapped_words = match options.wrap_algorithm {
-            #[cfg(feature = "smawk")]
-            core::WrapAlgorithm::OptimalFit => core::wrap_optimal_fit(&broken_words, line_lengths),
-            core::WrapAlgorithm::FirstFit => core::wrap_first_fit(&broken_words, line_lengths),
-        };
+    #[test]
+    fn fit_width_for_lines_finds_narrowest_width() {
+        let text = "aaa bb cccc d eeeee ff g";
+        let options = Options::new(80).wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        for max_lines in 1..=6 {
+            let width = fit_width_for_lines(text, max_lines, &options).unwrap();
+            assert!(
+                wrap(
+                    text,
+                    &Options {
+                        width,
+                        ..options.clone()
+                    }
+                )
+                .len()
+                    <= max_lines
+            );
+            assert!(
+                wrap(
+                    text,
+                    &Options {
+                        width: width - 1,
+                        ..options.clone()
+                    }
+                )
+                .len()
+                    > max_lines
+            );
+        }
+        assert_eq!(fit_width_for_lines(text, 1, &options), Some(24));
+        assert_eq!(fit_width_for_lines(text, 100, &options), Some(1));
+    }
 
-        let mut idx = 0;
-        for words in wrapped_words {
-            let last_word = match words.last() {
-                None => {
-                    lines.push(Cow::from(""));
-                    continue;
+    #[test]
+    fn fit_width_for_lines_unbreakable_words() {
+        let options = Options::new(80).long_words(LongWords::Overflow);
+        assert_eq!(fit_width_for_lines("abcdefgh a b c", 10, &options), Some(8));
+        let options = options.initial_indent("> ").subsequent_indent("> ");
+        assert_eq!(
+            fit_width_for_lines("abcdefgh a b c", 10, &options),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn fit_width_for_lines_hard_newlines() {
+        assert_eq!(fit_width_for_lines("a\nb\nc", 2, 80), None);
+        assert_eq!(fit_width_for_lines("a\nb\nc", 3, 80), Some(1));
+        assert_eq!(fit_width_for_lines("", 0, 80), None);
+        assert_eq!(fit_width_for_lines("", 1, 80), Some(1));
+    }
+
+    #[test]
+    fn wrap_preserves_interior_spaces() {
+        let text = "One.  Two.  Three.";
+        assert_eq!(wrap(text, 18), vec!["One.  Two.  Three."]);
+        assert_eq!(wrap(text, 17), vec!["One.  Two.", "Three."]);
+        assert_eq!(wrap(text, 10), vec!["One.  Two.", "Three."]);
+        assert_eq!(wrap(text, 9), vec!["One.", "Two.", "Three."]);
+    }
+
+    #[test]
+    fn fill_paragraphs_empty() {
+        assert_eq!(fill_paragraphs("", 10), "");
+        assert_eq!(fill_paragraphs("\n", 10), "\n");
+    }
+
+    #[test]
+    fn fill_paragraphs_joins_lines() {
+        assert_eq!(
+            fill_paragraphs("foo\nbar\n  baz  \nqux", 20),
+            "foo bar baz qux"
+        );
+    }
+
+    #[test]
+    fn fill_paragraphs_keeps_blank_lines() {
+        assert_eq!(
+            fill_paragraphs("\nfoo\nbar\n\n\nbaz qux\n", 7),
+            "\nfoo bar\n\n\nbaz qux\n"
+        );
+    }
+
+    #[test]
+    fn fill_paragraphs_whitespace_lines() {
+        assert_eq!(fill_paragraphs("foo\n  \t\nbar", 7), "foo\n\nbar");
+    }
+
+    #[test]
+    fn fill_paragraphs_initial_indent() {
+        let options = Options::new(10).initial_indent("> ");
+        assert_eq!(
+            fill_paragraphs("foo bar baz\n\nqux", &options),
+            "> foo bar\nbaz\n\n> qux"
+        );
+    }
+
+    #[test]
+    fn fill_paragraphs_with_indices() {
+        let options = Options::new(10);
+        let seen = ::core::cell::RefCell::new(Vec::new());
+        let filled = fill_paragraphs_with("foo\nbar\n\n\nbaz  \n", 10, |i, paragraph| {
+            seen.borrow_mut().push((i, paragraph.to_string()));
+            ParagraphAction::Wrap(&options)
+        });
+        assert_eq!(filled, "foo bar\n\n\nbaz\n");
+        assert_eq!(
+            seen.into_inner(),
+            vec![(0, String::from("foo\nbar")), (1, String::from("baz  "))]
+        );
+    }
+
+    #[test]
+    fn fill_paragraphs_with_pass_through() {
+        let options = Options::new(10).line_ending("\r\n");
+        assert_eq!(
+            fill_paragraphs_with("foo\r\n  bar  \r\n\r\nbaz", &options, |_, _| {
+                ParagraphAction::<HyphenSplitter>::PassThrough
+            }),
+            "foo\r\n  bar  \r\n\r\nbaz"
+        );
+    }
+
+    #[test]
+    fn fill_paragraphs_with_skip() {
+        let options = Options::new(10);
+        assert_eq!(
+            fill_paragraphs_with("foo\n\nbar\nbaz\n\nqux", 10, |i, _| {
+                if i == 1 {
+                    ParagraphAction::Skip
+                } else {
+                    ParagraphAction::Wrap(&options)
                 }
-                Some(word) => word,
-            };
+            }),
+            "foo\n\n\nqux"
+        );
+    }
 
-            // We assume here that all words are contiguous in `line`.
-            // That is, the sum of their lengths should add up to the
-            // length of `line`.
-            let len = words
-                .iter()
-                .map(|word| word.len() + word.whitespace.len())
-                .sum::<usize>()
-                - last_word.whitespace.len();
-
-            // The result is owned if we have indentation, otherwise
-            // we can simply borrow an empty string.
-            let mut result = if lines.is_empty() && !options.initial_indent.is_empty() {
-                Cow::Owned(options.initial_indent.to_owned())
-            } else if !lines.is_empty() && !options.subsequent_indent.is_empty() {
-                Cow::Owned(options.subsequent_indent.to_owned())
-            } else {
-                // We can use an empty string here since string
-                // concatenation for `Cow` preserves a borrowed value
-                // when either side is empty.
-                Cow::from("")
-            };
+    #[test]
+    fn fill_paragraphs_with_matches_fill_paragraphs() {
+        let options = Options::new(10).initial_indent("> ");
+        let text = "foo bar baz\n  \nqux quux corge\ngrault\n\n";
+        assert_eq!(
+            fill_paragraphs_with(text, &options, |_, _| ParagraphAction::Wrap(&options)),
+            fill_paragraphs(text, &options)
+        );
+    }
 
-            result += &line[idx..idx + len];
+    #[test]
+    fn cloning_works() {
+        static OPT: Options<HyphenSplitter> = Options::with_splitter(80, HyphenSplitter);
+        #[allow(clippy::clone_on_copy)]
+        let opt = OPT.clone();
+        assert_eq!(opt.width, 80);
+    }
 
-            if !last_word.penalty.is_empty() {
-                result.to_mut().push_str(&last_word.penalty);
-            }
+    #[test]
+    fn wrap_iter_agrees_with_wrap() {
+        let texts = [
+            "",
+            "\n\n",
+            "foo bar baz",
+            "  foo bar\n\nbaz  \n",
+            "Internationalization is hard\nfoo-bar-baz",
+        ];
+        let options = Options::new(5).initial_indent("* ").subsequent_indent("  ");
+        for text in texts.iter() {
+            assert_eq!(wrap_iter(text, 5).collect::<Vec<_>>(), wrap(text, 5));
+            assert_eq!(
+                wrap_iter(text, &options).collect::<Vec<_>>(),
+                wrap(text, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn wrap_iter_borrows() {
+        use alloc::borrow::Cow::Borrowed;
+        let mut lines = wrap_iter("foo bar\nbaz", 3);
+        assert!(matches!(lines.next(), Some(Borrowed("foo"))));
+        assert!(matches!(lines.next(), Some(Borrowed("bar"))));
+        assert!(matches!(lines.next(), Some(Borrowed("baz"))));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn wrap_iter_size_hint() {
+        let mut lines = wrap_iter("foo bar\nbaz", 3);
+        assert_eq!(lines.size_hint(), (1, None));
+        lines.next();
+        assert_eq!(lines.size_hint(), (2, None));
+        lines.next();
+        assert_eq!(lines.size_hint(), (1, None));
+        lines.next();
+        assert_eq!(lines.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn into_wrap_iter_agrees_with_wrap() {
+        let text = "  foo bar\n\nbaz-quux  \n";
+        let options = Options::new(5).initial_indent("* ").subsequent_indent("  ");
+        assert_eq!(
+            into_wrap_iter(String::from(text), &options).collect::<Vec<_>>(),
+            wrap(text, &options)
+        );
+    }
 
-            lines.push(result);
+    #[test]
+    fn into_wrap_iter_is_static() {
+        fn assert_static<T: 'static>(_: &T) {}
+        let lines = into_wrap_iter(String::from("foo bar baz"), Options::new(5));
+        assert_static(&lines);
+        assert_eq!(lines.collect::<Vec<_>>(), vec!["foo", "bar", "baz"]);
+    }
 
-            // Advance by the length of `result`, plus the length of
-            // `last_word.whitespace` -- even if we had a penalty, we
-            // need to skip over the whitespace.
-            idx += len + last_word.whitespace.len();
+    #[test]
+    fn wrap_ranges_reconstruct_wrap() {
+        let texts = [
+            "",
+            "foo\n\n",
+            "  foo bar baz",
+            "Internationalization is hard\nfoo-bar-baz",
+        ];
+        let options = Options::new(5).initial_indent("* ").subsequent_indent("  ");
+        for text in texts.iter() {
+            let lines = wrap_ranges(text, &options)
+                .into_iter()
+                .map(|line| format!("{}{}{}", line.indent, &text[line.range], line.penalty))
+                .collect::<Vec<_>>();
+            assert_eq!(lines, wrap(text, &options));
         }
     }
 
-    lines
-}
+    #[test]
+    fn wrap_ranges_multiple_lines() {
+        assert_eq!(
+            wrap_ranges("foo bar\nbaz", 3),
+            vec![
+                LineRange {
+                    indent: "".into(),
+                    hanging_indent: 0..0,
+                    prefix: "".into(),
+                    range: 0..3,
+                    penalty: "".into(),
+                    hanging_punctuation: 0
+                },
+                LineRange {
+                    indent: "".into(),
+                    hanging_indent: 0..0,
+                    prefix: "".into(),
+                    range: 4..7,
+                    penalty: "".into(),
+                    hanging_punctuation: 0
+                },
+                LineRange {
+                    indent: "".into(),
+                    hanging_indent: 0..0,
+                    prefix: "".into(),
+                    range: 8..11,
+                    penalty: "".into(),
+                    hanging_punctuation: 0
+                },
+            ]
+        );
+    }
 
-/// Wrap text into columns with a given total width.
-///
-/// The `left_gap`, `middle_gap` and `right_gap` arguments specify the
-/// strings to insert before, between, and after the columns. The
-/// total width of all columns and all gaps is specified using the
-/// `total_width_or_options` argument. This argument can simply be an
-/// integer if you want to use default settings when wrapping, or it
-/// can be a [`Options`] value if you want to customize the wrapping.
-///
-/// If the columns are narrow, it is recommended to set
-/// [`Options::break_words`] to `true` to prevent words from
-/// protruding into the margins.
-///
-/// The per-column width is computed like this:
-///
-/// ```
-/// # let (left_gap, middle_gap, right_gap) = ("", "", "");
-/// # let columns = 2;
-/// # let options = textwrap::Options::new(80);
-/// let inner_width = options.width
-///     - textwrap::core::display_width(left_gap)
-///     - textwrap::core::display_width(right_gap)
-///     - textwrap::core::display_width(middle_gap) * (columns - 1);
-/// let column_width = inner_width / columns;
-/// ```
-///
-/// The `text` is wrapped using [`wrap`] and the given `options`
-/// argument, but the width is overwritten to the computed
-/// `column_width`.
-///
-/// # Panics
-///
-/// Panics if `columns` is zero.
-///
-/// # Examples
-///
-/// ```
-/// use textwrap::wrap_columns;
-///
-/// let text = "\
-/// This is an example text, which is wrapped into three columns. \
-/// Notice how the final column can be shorter than the others.";
-///
-/// #[cfg(feature = "smawk")]
-/// assert_eq!(wrap_columns(text, 3, 50, "| ", " | ", " |"),
-///            vec!["| This is       | into three    | column can be  |",
-///                 "| an example    | columns.      | shorter than   |",
-///                 "| text, which   | Notice how    | the others.    |",
-///                 "| is wrapped    | the final     |                |"]);
-///
-/// // Without the `smawk` feature, the middle column is a little more uneven:
-/// #[cfg(not(feature = "smawk"))]
-/// assert_eq!(wrap_columns(text, 3, 50, "| ", " | ", " |"),
-///            vec!["| This is an    | three         | column can be  |",
-///                 "| example text, | columns.      | shorter than   |",
-///                 "| which is      | Notice how    | the others.    |",
-///                 "| wrapped into  | the final     |                |"]);
-pub fn wrap_columns<'a, S, Opt>(
-    text: &str,
-    columns: usize,
-    total_width_or_options: Opt,
-    left_gap: &str,
-    middle_gap: &str,
-    right_gap: &str,
-) -> Vec<String>
-where
-    S: WordSplitter,
-    Opt: Into<Options<'a, S>>,
-{
-    assert!(columns > 0);
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn wrap_ranges_hyphenation() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(10).splitter(dictionary);
+        let ranges = wrap_ranges("Internationalization", &options);
+        assert_eq!(ranges[0].range, 0..7);
+        assert_eq!(ranges[0].penalty, "-");
+    }
 
-    let mut options = total_width_or_options.into();
+    #[test]
+    fn word_break_marker_simple() {
+        let options = Options::new(4).word_break_marker("-");
+        assert_eq!(
+            wrap("foo abcdefgh", &options),
+            vec!["foo", "abc-", "def-", "gh"]
+        );
+    }
 
-    let inner_width = options
-        .width
-        .saturating_sub(core::display_width(left_gap))
-        .saturating_sub(core::display_width(right_gap))
-        .saturating_sub(core::display_width(middle_gap) * (columns - 1));
+    #[test]
+    fn word_break_marker_counts_width() {
+        let options = Options::new(6).word_break_marker("<>");
+        for line in wrap("abcdefghijklmnop", &options) {
+            assert!(core::display_width(&line) <= 6, "{:?}", line);
+        }
+        assert_eq!(
+            wrap("abcdefghijklmnop", &options),
+            vec!["abcd<>", "efgh<>", "ijkl<>", "mnop"]
+        );
+    }
 
-    let column_width = std::cmp::max(inner_width / columns, 1);
-    options.width = column_width;
-    let last_column_padding = " ".repeat(inner_width % column_width);
-    let wrapped_lines = wrap(text, options);
-    let lines_per_column =
-        wrapped_lines.len() / columns + usize::from(wrapped_lines.len() % columns > 0);
-    let mut lines = Vec::new();
-    for line_no in 0..lines_per_column {
-        let mut line = String::from(left_gap);
-        for column_no in 0..columns {
-            match wrapped_lines.get(line_no + column_no * lines_per_column) {
-                Some(column_line) => {
-                    line.push_str(&column_line);
-                    line.push_str(&" ".repeat(column_width - core::display_width(&column_line)));
-                }
-                None => {
-                    line.push_str(&" ".repeat(column_width));
-                }
-            }
-            if column_no == columns - 1 {
-                line.push_str(&last_column_padding);
-            } else {
-                line.push_str(middle_gap);
-            }
+    #[test]
+    fn word_break_marker_not_at_split_points() {
+        let options = Options::new(8).word_break_marker("+");
+        assert_eq!(wrap("foo-bar-baz", &options), vec!["foo-bar-", "baz"]);
+    }
+
+    #[test]
+    fn word_break_marker_without_break_words() {
+        let options = Options::new(4)
+            .long_words(LongWords::Overflow)
+            .word_break_marker("+");
+        assert_eq!(wrap("abcdefgh", &options), vec!["abcdefgh"]);
+    }
+
+    #[test]
+    fn word_break_marker_too_narrow() {
+        let options = Options::new(2).word_break_marker("->");
+        assert_eq!(wrap("abcd", &options), vec!["ab", "cd"]);
+        let options = Options::new(1).word_break_marker("+");
+        assert_eq!(wrap("abc", &options), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn word_break_marker_wrap_ranges() {
+        let options = Options::new(4).word_break_marker("+");
+        let ranges = wrap_ranges("abcdefgh", &options);
+        assert_eq!(ranges[0].range, 0..3);
+        assert_eq!(ranges[0].penalty, "+");
+        assert_eq!(ranges[1].range, 3..6);
+        assert_eq!(ranges[1].penalty, "+");
+        assert_eq!(ranges[2].range, 6..8);
+        assert_eq!(ranges[2].penalty, "");
+    }
+
+    #[test]
+    fn non_breaking_space_figure_number() {
+        // The NBSP in "Fig. 7" must never become a line break.
+        let text = "See Fig.\u{a0}7 for details";
+        assert_eq!(wrap(text, 8), vec!["See", "Fig.\u{a0}7", "for", "details"]);
+        assert_eq!(
+            wrap(text, &Options::new(4).long_words(LongWords::Overflow)),
+            vec!["See", "Fig.\u{a0}7", "for", "details"]
+        );
+        let options = Options::new(8).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(
+            wrap(text, &options),
+            vec!["See", "Fig.\u{a0}7", "for", "details"]
+        );
+    }
+
+    #[test]
+    fn non_breaking_space_width() {
+        assert_eq!(core::display_width("Fig.\u{a0}7"), 6);
+        assert_eq!(wrap("ab 10\u{a0}kB", 7), vec!["ab", "10\u{a0}kB"]);
+        assert_eq!(wrap("ab 10\u{a0}kB", 8), vec!["ab 10\u{a0}kB"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn narrow_non_breaking_space() {
+        let text = "ab 10\u{202f}kB";
+        assert_eq!(wrap(text, 5), vec!["ab", "10\u{202f}kB"]);
+        let options = Options::new(5).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(wrap(text, &options), vec!["ab", "10\u{202f}kB"]);
+    }
+
+    #[test]
+    fn narrow_non_breaking_space_long_word() {
+        // A word which is too long is broken at the non-breaking
+        // space, not between two other clusters.
+        let text = "ab 10\u{202f}kB";
+        for width in 2..5 {
+            let lines = wrap(text, width);
+            assert_eq!(lines, vec!["ab", "10", "kB"]);
+            assert_eq!(verify(text, &lines, &Options::new(width)), Ok(()));
         }
-        line.push_str(right_gap);
-        lines.push(line);
+        let options = Options::new(3).long_words(LongWords::Overflow);
+        assert_eq!(wrap(text, &options), vec!["ab", "10\u{202f}kB"]);
+    }
+
+    #[test]
+    fn shorten_keeps_non_breaking_space() {
+        assert_eq!(shorten("10\u{a0}kB  free", 20, "..."), "10\u{a0}kB free");
+        assert_eq!(shorten("a 10\u{a0}kB", 6, "..."), "a...");
     }
 
-    lines
-}
+    #[test]
+    fn zero_width_space_break() {
+        let text = "long\u{200b}identifier\u{200b}name";
+        assert_eq!(wrap(text, 15), vec!["long\u{200b}identifier", "name"]);
+        assert_eq!(wrap(text, 4), vec!["long", "iden", "tifi", "er", "name"]);
+        let options = Options::new(10).long_words(LongWords::Overflow);
+        assert_eq!(wrap(text, &options), vec!["long", "identifier", "name"]);
+    }
 
-/// Fill `text` in-place without reallocating the input string.
-///
-/// This function works by modifying the input string: some `' '`
-/// characters will be replaced by `'\n'` characters. The rest of the
-/// text remains untouched.
-///
-/// Since we can only replace existing whitespace in the input with
-/// `'\n'`, we cannot do hyphenation nor can we split words longer
-/// than the line width. Indentation is also ruled out. In other
-/// words, `fill_inplace(width)` behaves as if you had called [`fill`]
-/// with these options:
-///
-/// ```
-/// # use textwrap::{Options, NoHyphenation};
-/// # let width = 80;
-/// Options {
-///     width: width,
-///     initial_indent: "",
-///     subsequent_indent: "",
-///     break_words: false,
-///     wrap_algorithm: textwrap::core::WrapAlgorithm::FirstFit,
-///     splitter: NoHyphenation,
-/// };
-/// ```
-///
-/// The wrap algorithm is [`core::WrapAlgorithm::FirstFit`] since this
-/// is the fastest algorithm — and the main reason to use
-/// `fill_inplace` is to get the string broken into newlines as fast
-/// as possible.
-///
-/// A last difference is that (unlike [`fill`]) `fill_inplace` can
-/// leave trailing whitespace on lines. This is because we wrap by
-/// inserting a `'\n'` at the final whitespace in the input string:
-///
-/// ```
-/// let mut text = String::from("Hello   World!");
-/// textwrap::fill_inplace(&mut text, 10);
-/// assert_eq!(text, "Hello  \nWorld!");
-/// ```
-///
-/// If we didn't do this, the word `World!` would end up being
-/// indented. You can avoid this if you make sure that your input text
-/// has no double spaces.
-///
-/// # Performance
-///
-/// In benchmarks, `fill_inplace` is about twice as fast as [`fill`].
-/// Please see the [`linear`
-/// benchmark](https://github.com/mgeisler/textwrap/blob/master/benches/linear.rs)
-/// for details.
-pub fn fill_inplace(text: &mut String, width: usize) {
-    let mut indices = Vec::new();
+    #[test]
+    fn zero_width_space_no_hyphenation() {
+        let options = Options::new(8).splitter(NoHyphenation);
+        assert_eq!(
+            wrap("foo\u{200b}bar\u{200b}baz", &options),
+            vec!["foo\u{200b}bar", "baz"]
+        );
+    }
 
-    let mut offset = 0;
-    for line in text.split('\n') {
-        let words = core::find_words(line).collect::<Vec<_>>();
-        let wrapped_words = core::wrap_first_fit(&words, |_| width);
+    #[test]
+    fn zero_width_space_unicode_break_properties() {
+        let options = Options::new(8).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(
+            wrap("foo\u{200b}bar\u{200b}baz", &options),
+            vec!["foo\u{200b}bar", "baz"]
+        );
+    }
 
-        let mut line_offset = offset;
-        for words in &wrapped_words[..wrapped_words.len() - 1] {
-            let line_len = words
-                .iter()
-                .map(|word| word.len() + word.whitespace.len())
-                .sum::<usize>();
+    #[test]
+    fn zero_width_space_fill_inplace() {
+        let mut text = String::from("foo\u{200b}bar baz");
+        fill_inplace(&mut text, 5);
+        assert_eq!(text, "foo\u{200b}bar\nbaz");
+    }
 
-            line_offset += line_len;
-            // We've advanced past all ' ' characters -- want to move
-            // one ' ' backwards and insert our '\n' there.
-            indices.push(line_offset - 1);
-        }
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn zero_width_space_justified() {
+        // Extra spaces only go into the gaps with real spaces.
+        let options = Options::new(9)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .alignment(Alignment::Justified);
+        assert_eq!(
+            wrap("ab\u{200b}cd ef gh", &options),
+            vec!["ab\u{200b}cd   ef", "gh"]
+        );
+    }
 
-        // Advance past entire line, plus the '\n' which was removed
-        // by the split call above.
-        offset += line.len() + 1;
+    #[test]
+    fn word_joiner_prevents_hyphen_split() {
+        let options = Options::new(5);
+        assert_eq!(wrap("foo-bar", &options), vec!["foo-", "bar"]);
+        assert_eq!(
+            wrap("foo\u{2060}-bar", &options.long_words(LongWords::Overflow)),
+            vec!["foo-bar"]
+        );
     }
 
-    let mut bytes = std::mem::take(text).into_bytes();
-    for idx in indices {
-        bytes[idx] = b'\n';
+    #[test]
+    fn word_joiner_removed_from_output() {
+        let text = "a\u{2060}b c\u{2060}d";
+        assert_eq!(wrap(text, 80), vec!["ab cd"]);
+        assert_eq!(fill(text, 80), "ab cd");
+        let ranges = wrap_ranges(text, 80);
+        assert_eq!(&text[ranges[0].range.clone()], text);
     }
-    *text = String::from_utf8(bytes).unwrap();
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn word_joiner_justified() {
+        let options = Options::new(7).alignment(Alignment::Justified);
+        assert_eq!(
+            wrap("a\u{2060}b c d ef", &options),
+            wrap("ab c d ef", &options)
+        );
+    }
+
+    #[test]
+    fn non_breaking_space_prevents_hyphen_split() {
+        let options = Options::new(6).long_words(LongWords::Overflow);
+        assert_eq!(
+            wrap("ab foo-\u{a0}bar", &options),
+            vec!["ab", "foo-\u{a0}bar"]
+        );
+    }
+
+    #[test]
     #[cfg(feature = "hyphenation")]
-    use hyphenation::{Language, Load, Standard};
+    fn word_joiner_prevents_hyphenation() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let options = Options::new(10)
+            .long_words(LongWords::Overflow)
+            .splitter(dictionary);
+        assert_eq!(
+            wrap("Internationalization", &options),
+            vec!["Interna-", "tionaliza-", "tion"]
+        );
+        let lines = wrap("Interna\u{2060}tionalization", &options);
+        assert!(!lines.contains(&Cow::from("Interna-")), "{:?}", lines);
+    }
 
     #[test]
-    fn options_agree_with_usize() {
-        let opt_usize = Options::from(42_usize);
-        let opt_options = Options::new(42);
+    fn word_separator_default_unchanged() {
+        let options = Options::new(5).long_words(LongWords::Overflow);
+        assert_eq!(wrap("foo—bar", &options), vec!["foo—bar"]);
+        assert_eq!(wrap("a\u{85}b", &options), vec!["a\u{85}b"]);
+    }
 
-        assert_eq!(opt_usize.width, opt_options.width);
-        assert_eq!(opt_usize.initial_indent, opt_options.initial_indent);
-        assert_eq!(opt_usize.subsequent_indent, opt_options.subsequent_indent);
-        assert_eq!(opt_usize.break_words, opt_options.break_words);
+    #[test]
+    fn word_separator_ideographs() {
+        let options = Options::new(6).word_separator(core::WordSeparator::UnicodeBreakProperties);
         assert_eq!(
-            opt_usize.splitter.split_points("hello-world"),
-            opt_options.splitter.split_points("hello-world")
+            wrap("你好，世界。你好！", &options),
+            vec!["你好，", "世界。", "你好！"]
         );
     }
 
     #[test]
-    fn no_wrap() {
-        assert_eq!(wrap("foo", 10), vec!["foo"]);
+    fn word_separator_japanese_paragraph() {
+        let text = "吾輩は猫である名前はまだ無いどこで生れたかとんと見当がつかぬ";
+        let options = Options::new(20).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(
+            fill(text, &options),
+            "吾輩は猫である名前は\nまだ無いどこで生れた\nかとんと見当がつかぬ"
+        );
+        let options = options.long_words(LongWords::Overflow);
+        for line in wrap(text, &options) {
+            assert_eq!(core::display_width(&line), 20);
+        }
     }
 
     #[test]
-    fn wrap_simple() {
-        assert_eq!(wrap("foo bar baz", 5), vec!["foo", "bar", "baz"]);
+    fn word_separator_ascii_in_cjk() {
+        let options = Options::new(8)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(
+            wrap("これはRustで書かれた", &options),
+            vec!["これは", "Rustで書", "かれた"]
+        );
     }
 
     #[test]
-    fn to_be_or_not() {
+    fn break_rules_japanese() {
+        let options = Options::new(6)
+            .long_words(LongWords::Overflow)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .word_separator(core::WordSeparator::UnicodeBreakProperties);
+        // Without kinsoku shori, the small っ can start a line.
         assert_eq!(
-            wrap(
-                "To be, or not to be, that is the question.",
-                Options::new(10).wrap_algorithm(core::WrapAlgorithm::FirstFit)
-            ),
-            vec!["To be, or", "not to be,", "that is", "the", "question."]
+            wrap("あちょっと待って", &options),
+            vec!["あちょ", "っと待", "って"]
+        );
+        let options = options.break_rules(core::BreakRules::japanese());
+        assert_eq!(
+            wrap("あちょっと待って", &options),
+            vec!["あ", "ちょっ", "と待っ", "て"]
         );
     }
 
     #[test]
-    fn multiple_words_on_first_line() {
-        assert_eq!(wrap("foo bar baz", 10), vec!["foo bar", "baz"]);
+    fn break_rules_no_break_after() {
+        let rules = core::BreakRules::new("", "(");
+        let options = Options::new(5)
+            .long_words(LongWords::Overflow)
+            .break_rules(rules);
+        assert_eq!(wrap("foo ( bar", &options), vec!["foo", "( bar"]);
     }
 
     #[test]
-    fn long_word() {
-        assert_eq!(wrap("foo", 0), vec!["f", "o", "o"]);
+    fn glue_rules_number_unit() {
+        let rules = core::BreakRules::none().glue(&[core::GlueRule::NUMBER_UNIT]);
+        let options = Options::new(8)
+            .break_rules(rules)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap("It is 20 °C, 5 kg", &options),
+            vec!["It is", "20 °C,", "5 kg"]
+        );
+        assert_eq!(wrap("size 3.5 GB.", &options), vec!["size", "3.5 GB."]);
+        // Other words are not glued to numbers.
+        assert_eq!(wrap("take 3 apples", &options), vec!["take 3", "apples"]);
     }
 
     #[test]
-    fn long_words() {
-        assert_eq!(wrap("foo bar", 0), vec!["f", "o", "o", "b", "a", "r"]);
+    fn glue_rules_abbreviations_and_initials() {
+        let rules = core::BreakRules::none().glue(core::GlueRule::DEFAULTS);
+        let options = Options::new(10)
+            .long_words(LongWords::Overflow)
+            .break_rules(rules)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(
+            wrap("by J. R. R. Tolkien", &options),
+            vec!["by", "J. R. R. Tolkien"]
+        );
+        assert_eq!(wrap("see the fig. 3", &options), vec!["see the", "fig. 3"]);
+        assert_eq!(
+            wrap("ask Dr. Who said", &options),
+            vec!["ask", "Dr. Who", "said"]
+        );
+        // A sentence ending is not an abbreviation.
+        assert_eq!(
+            wrap("at the end. The", &options),
+            vec!["at the", "end. The"]
+        );
+        assert_eq!(
+            wrap("at the end. The start", &options),
+            vec!["at the", "end. The", "start"]
+        );
     }
 
     #[test]
-    fn max_width() {
-        assert_eq!(wrap("foo bar", usize::max_value()), vec!["foo bar"]);
+    fn glue_rules_long_groups_broken_at_spaces() {
+        // A glued group which is too long for a line is broken at its
+        // spaces, and the spaces are removed at the end of the lines.
+        let rules = core::BreakRules::none().glue(core::GlueRule::DEFAULTS);
+        let options = Options::new(4).break_rules(rules);
+        let lines = wrap("see 100 MB", &options);
+        assert_eq!(lines, vec!["see", "100", "MB"]);
+        assert_eq!(verify("see 100 MB", &lines, &options), Ok(()));
+
+        // The words of the group are kept together as far as they fit.
+        let options = Options::new(10)
+            .break_rules(rules)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        let lines = wrap("by J. R. R. Tolkien x", &options);
+        assert_eq!(lines, vec!["by", "J. R. R.", "Tolkien x"]);
+        assert_eq!(verify("by J. R. R. Tolkien x", &lines, &options), Ok(()));
     }
 
     #[test]
-    fn leading_whitespace() {
-        assert_eq!(wrap("  foo bar", 6), vec!["  foo", "bar"]);
+    fn glue_rules_optimal_fit() {
+        let rules = core::BreakRules::none().glue(core::GlueRule::DEFAULTS);
+        let options = Options::new(12).break_rules(rules);
+        for line in wrap("It weighs 100 kg and costs 5 € today.", &options) {
+            assert!(!line.ends_with("100") && !line.ends_with('5'), "{:?}", line);
+        }
     }
 
     #[test]
-    fn leading_whitespace_empty_first_line() {
-        // If there is no space for the first word, the first line
-        // will be empty. This is because the string is split into
-        // words like [" ", "foobar ", "baz"], which puts "foobar " on
-        // the second line. We never output trailing whitespace
-        assert_eq!(wrap(" foobar baz", 6), vec!["", "foobar", "baz"]);
+    fn break_rules_none() {
+        let options = Options::new(5).break_rules(core::BreakRules::none());
+        assert_eq!(wrap("foo ( bar", &options), vec!["foo (", "bar"]);
     }
 
     #[test]
-    fn trailing_whitespace() {
-        // Whitespace is only significant inside a line. After a line
-        // gets too long and is broken, the first word starts in
-        // column zero and is not indented.
-        assert_eq!(wrap("foo     bar     baz  ", 5), vec!["foo", "bar", "baz"]);
+    fn break_rules_wrap_ranges() {
+        let rules = core::BreakRules::new("!", "");
+        let options = Options::new(5)
+            .long_words(LongWords::Overflow)
+            .break_rules(rules);
+        let text = "foo bar !";
+        let ranges = wrap_ranges(text, &options);
+        assert_eq!(&text[ranges[0].range.clone()], "foo");
+        assert_eq!(&text[ranges[1].range.clone()], "bar !");
     }
 
     #[test]
-    fn issue_99() {
-        // We did not reset the in_whitespace flag correctly and did
-        // not handle single-character words after a line break.
+    fn soft_hyphen_splitter_break() {
+        let options = Options::new(8)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .splitter(SoftHyphenSplitter::new());
+        assert_eq!(wrap("co\u{ad}operate", &options), vec!["co-", "operate"]);
+    }
+
+    #[test]
+    fn soft_hyphen_removed_when_unused() {
+        let options = Options::new(20).splitter(SoftHyphenSplitter::new());
+        assert_eq!(wrap("co\u{ad}operate", &options), vec!["cooperate"]);
+        assert_eq!(wrap("co\u{ad}operate", 20), vec!["cooperate"]);
+    }
+
+    #[test]
+    fn soft_hyphen_width() {
+        let options = Options::new(7).splitter(SoftHyphenSplitter::new());
+        assert_eq!(wrap("ab\u{ad}cd ef", &options), vec!["abcd ef"]);
+        assert_eq!(core::display_width("ab\u{ad}cd"), 4);
+    }
+
+    #[test]
+    fn soft_hyphen_at_word_edges() {
+        let splitter = SoftHyphenSplitter::new();
         assert_eq!(
-            wrap("aaabbbccc x yyyzzzwww", 9),
-            vec!["aaabbbccc", "x", "yyyzzzwww"]
+            splitter.split_points("\u{ad}foo\u{ad}"),
+            Vec::<usize>::new()
         );
+        assert_eq!(splitter.split_points("foo\u{ad}bar"), vec![5]);
     }
 
     #[test]
-    fn issue_129() {
-        // The dash is an em-dash which takes up four bytes. We used
-        // to panic since we tried to index into the character.
-        assert_eq!(wrap("x – x", 1), vec!["x", "–", "x"]);
+    fn soft_hyphen_fallback() {
+        let splitter = SoftHyphenSplitter::with_fallback(HyphenSplitter);
+        assert_eq!(splitter.split_points("foo-bar"), vec![4]);
+        assert_eq!(splitter.split_points("foo-b\u{ad}ar"), vec![7]);
     }
 
     #[test]
-    #[cfg(feature = "unicode-width")]
-    fn wide_character_handling() {
-        assert_eq!(wrap("Hello, World!", 15), vec!["Hello, World!"]);
+    fn soft_hyphen_not_used_by_min_last_line_width() {
+        let options = Options::new(9)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .min_last_line_width(4)
+            .splitter(SoftHyphenSplitter::new());
         assert_eq!(
-            wrap("Ｈｅｌｌｏ, Ｗｏｒｌｄ!", 15),
-            vec!["Ｈｅｌｌｏ,", "Ｗｏｒｌｄ!"]
+            wrap("ab\u{ad}cdefg\u{ad}hij", &options),
+            vec!["abcdefg-", "hij"]
         );
     }
 
     #[test]
-    fn empty_line_is_indented() {
-        // Previously, indentation was not applied to empty lines.
-        // However, this is somewhat inconsistent and undesirable if
-        // the indentation is something like a border ("| ") which you
-        // want to apply to all lines, empty or not.
-        let options = Options::new(10).initial_indent("!!!");
-        assert_eq!(fill("", &options), "!!!");
+    #[cfg(feature = "hyphenation")]
+    fn soft_hyphen_with_dictionary() {
+        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
+        let splitter = SoftHyphenSplitter::with_fallback(dictionary);
+        let options = Options::new(10)
+            .long_words(LongWords::Overflow)
+            .splitter(splitter);
+        assert_eq!(
+            wrap("Internationalization", &options),
+            vec!["Interna-", "tionaliza-", "tion"]
+        );
+        assert_eq!(
+            wrap("Internation\u{ad}alization", &options),
+            vec!["Internation-", "alization"]
+        );
     }
 
     #[test]
-    fn indent_single_line() {
-        let options = Options::new(10).initial_indent(">>>"); // No trailing space
-        assert_eq!(fill("foo", &options), ">>>foo");
+    fn soft_hyphen_wrap_ranges() {
+        let options = Options::new(6).splitter(SoftHyphenSplitter::new());
+        let text = "soft\u{ad}ware";
+        let ranges = wrap_ranges(text, &options);
+        assert_eq!(&text[ranges[0].range.clone()], "soft");
+        assert_eq!(ranges[0].penalty, "-");
+        assert_eq!(&text[ranges[1].range.clone()], "ware");
     }
 
     #[test]
-    #[cfg(feature = "unicode-width")]
-    fn indent_first_emoji() {
-        let options = Options::new(10).initial_indent("👉👉");
+    fn word_separator_no_break_before_closing_punctuation() {
+        let options = Options::new(4)
+            .long_words(LongWords::Overflow)
+            .word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(wrap("你好」", &options), vec!["你", "好」"]);
+        assert_eq!(wrap("(foo)", &options), vec!["(foo)"]);
+    }
+
+    #[test]
+    fn word_separator_dashes() {
+        let options = Options::new(5).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        assert_eq!(wrap("foo—bar", &options), vec!["foo—", "bar"]);
+        assert_eq!(wrap("foo-bar", &options), vec!["foo-", "bar"]);
+    }
+
+    #[test]
+    fn word_separator_mandatory_breaks() {
+        let options = Options::new(10).word_separator(core::WordSeparator::UnicodeBreakProperties);
         assert_eq!(
-            wrap("x x x x x x x x x x x x x", &options),
-            vec!["👉👉x x x", "x x x x x", "x x x x x"]
+            wrap("foo\u{85}bar\u{2028}baz\r\nqux", &options),
+            vec!["foo", "bar", "baz", "qux"]
+        );
+        assert_eq!(wrap("foo\r\n", &options), vec!["foo", ""]);
+    }
+
+    #[test]
+    fn word_separator_wrap_ranges() {
+        let options = Options::new(10).word_separator(core::WordSeparator::UnicodeBreakProperties);
+        let text = "foo\u{85}bar";
+        let ranges = wrap_ranges(text, &options);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(&text[ranges[0].range.clone()], "foo");
+        assert_eq!(&text[ranges[1].range.clone()], "bar");
+    }
+
+    #[test]
+    fn width_fn_irregular_shape() {
+        let shape = |line| [10, 10, 6, 6, 10][line % 5];
+        let options = Options::new(80)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .width_fn(&shape);
+        assert_eq!(
+            wrap("aaa bbb ccc ddd eee fff ggg hhh iii", &options),
+            vec!["aaa bbb", "ccc ddd", "eee", "fff", "ggg hhh", "iii"]
+        );
+    }
+
+    #[test]
+    fn width_fn_across_paragraphs() {
+        let shape = |line| if line == 1 { 3 } else { 7 };
+        let options = Options::new(80)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .width_fn(&shape);
+        assert_eq!(
+            wrap(
+                "foo bar
+baz qux",
+                &options
+            ),
+            vec!["foo bar", "baz", "qux"]
+        );
+    }
+
+    #[test]
+    fn width_fn_subtracts_indent() {
+        let shape = |line| if line == 0 { 8 } else { 6 };
+        let options = Options::new(80)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .initial_indent("> ")
+            .subsequent_indent("| ")
+            .width_fn(&shape);
+        assert_eq!(
+            wrap("foo bar baz qux", &options),
+            vec!["> foo", "| bar", "| baz", "| qux"]
+        );
+    }
+
+    #[test]
+    fn width_fn_break_words() {
+        let shape = |line| if line == 0 { 10 } else { 4 };
+        let options = Options::new(80).width_fn(&shape);
+        let lines = wrap("abcdefghijkl", &options);
+        for (idx, line) in lines.iter().enumerate() {
+            assert!(core::display_width(line) <= shape(idx), "{:?}", lines);
+        }
+        assert_eq!(lines.concat(), "abcdefghijkl");
+
+        // The word is broken against the wider lines after the narrow
+        // first line.
+        let shape = |line| if line == 0 { 4 } else { 10 };
+        let options = Options::new(80).width_fn(&shape);
+        assert_eq!(
+            wrap("abcdefghijklmnopqrstuvwxyz", &options),
+            vec!["abcd", "efghijklmn", "opqrstuvwx", "yz"]
+        );
+        assert_eq!(
+            wrap("ab abcdefghijklmnopqrstuvwxyz", &options),
+            vec!["ab", "abcdefghij", "klmnopqrst", "uvwxyz"]
+        );
+        let options = options.word_break_marker("\\");
+        assert_eq!(
+            wrap("ab abcdefghijklmnopqrstuvwxyz", &options),
+            vec!["ab", "abcdefghi\\", "jklmnopqr\\", "stuvwxyz"]
         );
     }
 
     #[test]
-    fn indent_multiple_lines() {
-        let options = Options::new(6).initial_indent("* ").subsequent_indent("  ");
-        assert_eq!(
-            wrap("foo bar baz", &options),
-            vec!["* foo", "  bar", "  baz"]
-        );
+    fn width_fn_alignment() {
+        let shape = |line| if line == 0 { 5 } else { 7 };
+        let options = Options::new(80)
+            .alignment(Alignment::Right)
+            .width_fn(&shape);
+        assert_eq!(wrap("foo bar", &options), vec!["  foo", "    bar"]);
     }
 
     #[test]
-    fn indent_break_words() {
-        let options = Options::new(5).initial_indent("* ").subsequent_indent("  ");
-        assert_eq!(wrap("foobarbaz", &options), vec!["* foo", "  bar", "  baz"]);
+    fn min_last_line_width_moves_word() {
+        let options = Options::new(10).min_last_line_width(3);
+        assert_eq!(wrap("foo bar baz x", &options), vec!["foo bar", "baz x"]);
     }
 
     #[test]
-    fn initial_indent_break_words() {
-        // This is a corner-case showing how the long word is broken
-        // according to the width of the subsequent lines. The first
-        // fragment of the word no longer fits on the first line,
-        // which ends up being pure indentation.
-        let options = Options::new(5).initial_indent("-->");
-        assert_eq!(wrap("foobarbaz", &options), vec!["-->", "fooba", "rbaz"]);
+    fn min_last_line_width_long_enough() {
+        let options = Options::new(10).min_last_line_width(3);
+        assert_eq!(
+            wrap("foo bar baz quux", &options),
+            vec!["foo bar", "baz quux"]
+        );
+        assert_eq!(wrap("foo bar", &options), vec!["foo bar"]);
     }
 
     #[test]
-    fn hyphens() {
-        assert_eq!(wrap("foo-bar", 5), vec!["foo-", "bar"]);
+    fn min_last_line_width_does_not_fit() {
+        let options = Options::new(7).min_last_line_width(3);
+        assert_eq!(wrap("foo bar baz x", &options), vec!["foo bar", "baz x"]);
+        assert_eq!(wrap("foo barbaz x", &options), vec!["foo", "barbaz", "x"]);
     }
 
     #[test]
-    fn trailing_hyphen() {
-        let options = Options::new(5).break_words(false);
-        assert_eq!(wrap("foobar-", &options), vec!["foobar-"]);
+    fn min_last_line_width_keeps_previous_line() {
+        let options = Options::new(6).min_last_line_width(3);
+        assert_eq!(wrap("foobar x", &options), vec!["foobar", "x"]);
     }
 
     #[test]
-    fn multiple_hyphens() {
-        assert_eq!(wrap("foo-bar-baz", 5), vec!["foo-", "bar-", "baz"]);
+    fn min_last_line_width_moves_whole_words() {
+        let options = Options::new(10)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .min_last_line_width(3);
+        assert_eq!(wrap("aa foo-bar x", &options), vec!["aa", "foo-bar x"]);
+        let options = Options {
+            width: 8,
+            ..options
+        };
+        assert_eq!(wrap("foo-bar x", &options), vec!["foo-bar", "x"]);
     }
 
     #[test]
-    fn hyphens_flag() {
-        let options = Options::new(5).break_words(false);
+    fn min_last_line_width_per_paragraph() {
+        let options = Options::new(10).min_last_line_width(3);
         assert_eq!(
-            wrap("The --foo-bar flag.", &options),
-            vec!["The", "--foo-", "bar", "flag."]
+            wrap(
+                "foo bar baz x
+foo bar baz x",
+                &options
+            ),
+            vec!["foo bar", "baz x", "foo bar", "baz x"]
         );
     }
 
     #[test]
-    fn repeated_hyphens() {
-        let options = Options::new(4).break_words(false);
-        assert_eq!(wrap("foo--bar", &options), vec!["foo--bar"]);
+    fn max_lines_simple() {
+        let options = Options::new(7).max_lines(2);
+        assert_eq!(
+            wrap("foo bar baz qux quux", &options),
+            vec!["foo bar", "baz…"]
+        );
     }
 
     #[test]
-    fn hyphens_alphanumeric() {
-        assert_eq!(wrap("Na2-CH4", 5), vec!["Na2-", "CH4"]);
+    fn max_lines_nothing_omitted() {
+        let options = Options::new(7).max_lines(2);
+        assert_eq!(wrap("foo bar baz", &options), vec!["foo bar", "baz"]);
+        assert_eq!(wrap("foo bar baz\n\n", &options), vec!["foo bar", "baz"]);
     }
 
     #[test]
-    fn hyphens_non_alphanumeric() {
-        let options = Options::new(5).break_words(false);
-        assert_eq!(wrap("foo(-)bar", &options), vec!["foo(-)bar"]);
+    fn max_lines_zero() {
+        let options = Options::new(7).max_lines(0);
+        assert!(wrap("foo bar baz", &options).is_empty());
     }
 
     #[test]
-    fn multiple_splits() {
-        assert_eq!(wrap("foo-bar-baz", 9), vec!["foo-bar-", "baz"]);
+    fn max_lines_across_newlines() {
+        let options = Options::new(10).max_lines(2);
+        assert_eq!(wrap("foo\nbar\nbaz", &options), vec!["foo", "bar…"]);
     }
 
     #[test]
-    fn forced_split() {
-        let options = Options::new(5).break_words(false);
-        assert_eq!(wrap("foobar-baz", &options), vec!["foobar-", "baz"]);
+    fn max_lines_drops_words() {
+        let options = Options::new(10).max_lines(1).placeholder(" [...]");
+        assert_eq!(wrap("foo bar baz", &options), vec!["foo [...]"]);
     }
 
     #[test]
-    fn multiple_unbroken_words_issue_193() {
-        let options = Options::new(3).break_words(false);
-        assert_eq!(
-            wrap("small large tiny", &options),
-            vec!["small", "large", "tiny"]
-        );
+    fn max_lines_long_word() {
+        let options = Options::new(6)
+            .long_words(LongWords::Overflow)
+            .max_lines(2)
+            .placeholder("...");
         assert_eq!(
-            wrap("small  large   tiny", &options),
-            vec!["small", "large", "tiny"]
+            wrap("foo Internationalization bar", &options),
+            vec!["foo", "Int..."]
         );
     }
 
     #[test]
-    fn very_narrow_lines_issue_193() {
-        let options = Options::new(1).break_words(false);
-        assert_eq!(wrap("fooo x y", &options), vec!["fooo", "x", "y"]);
-        assert_eq!(wrap("fooo   x     y", &options), vec!["fooo", "x", "y"]);
+    fn max_lines_wide_placeholder() {
+        let options = Options::new(4).max_lines(1).placeholder("[...]");
+        assert_eq!(wrap("foo bar", &options), vec!["[..."]);
     }
 
     #[test]
-    fn simple_hyphens_static() {
-        let options = Options::new(8).splitter(HyphenSplitter);
-        assert_eq!(wrap("foo bar-baz", &options), vec!["foo bar-", "baz"]);
+    fn max_lines_subsequent_indent() {
+        let options = Options::new(8)
+            .subsequent_indent("    ")
+            .max_lines(2)
+            .placeholder("...");
+        assert_eq!(
+            wrap("foo bar baz qux", &options),
+            vec!["foo bar", "    b..."]
+        );
     }
 
     #[test]
-    fn simple_hyphens_dynamic() {
-        let options: Options = Options::new(8).splitter(Box::new(HyphenSplitter));
-        assert_eq!(wrap("foo bar-baz", &options), vec!["foo bar-", "baz"]);
+    fn max_lines_replaces_hyphen() {
+        let options = Options::new(8).max_lines(1).placeholder("~");
+        assert_eq!(wrap("foo-bar-baz", &options), vec!["foo-bar~"]);
     }
 
     #[test]
-    fn no_hyphenation_static() {
-        let options = Options::new(8).splitter(NoHyphenation);
-        assert_eq!(wrap("foo bar-baz", &options), vec!["foo", "bar-baz"]);
+    fn max_lines_wrap_ranges() {
+        let text = "foo bar baz qux quux";
+        let options = Options::new(7).max_lines(2);
+        let ranges = wrap_ranges(text, &options);
+        assert_eq!(
+            ranges[1],
+            LineRange {
+                indent: "".into(),
+                hanging_indent: 0..0,
+                prefix: "".into(),
+                range: 8..11,
+                penalty: "…".into(),
+                hanging_punctuation: 0
+            }
+        );
+        let lines = ranges
+            .iter()
+            .map(|line| {
+                format!(
+                    "{}{}{}",
+                    line.indent,
+                    &text[line.range.clone()],
+                    line.penalty
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, wrap(text, &options));
     }
 
     #[test]
-    fn no_hyphenation_dynamic() {
-        let options: Options = Options::new(8).splitter(Box::new(NoHyphenation));
-        assert_eq!(wrap("foo bar-baz", &options), vec!["foo", "bar-baz"]);
+    fn max_lines_size_hint() {
+        let options = Options::new(3).max_lines(2);
+        let mut lines = wrap_iter("foo bar baz", &options);
+        assert_eq!(lines.size_hint(), (1, Some(2)));
+        lines.next();
+        assert_eq!(lines.size_hint(), (1, Some(1)));
+        lines.next();
+        assert_eq!(lines.size_hint(), (0, Some(0)));
+        assert_eq!(lines.next(), None);
     }
 
     #[test]
-    #[cfg(feature = "hyphenation")]
-    fn auto_hyphenation_double_hyphenation_static() {
-        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
-        let options = Options::new(10);
-        assert_eq!(
-            wrap("Internationalization", &options),
-            vec!["Internatio", "nalization"]
-        );
+    fn justify_simple() {
+        let options = Options::new(9)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("aa bb cc dd", &options), vec!["aa bb  cc", "dd"]);
+    }
 
-        let options = Options::new(10).splitter(dictionary);
+    #[test]
+    fn justify_spreads_spaces_evenly() {
+        let options = Options::new(17)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
         assert_eq!(
-            wrap("Internationalization", &options),
-            vec!["Interna-", "tionaliza-", "tion"]
+            wrap("a b c d e f g h iiiiii", &options),
+            vec!["a b  c d e f  g h", "iiiiii"]
         );
     }
 
     #[test]
-    #[cfg(feature = "hyphenation")]
-    fn auto_hyphenation_double_hyphenation_dynamic() {
-        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
-        let mut options: Options = Options::new(10).splitter(Box::new(HyphenSplitter));
+    fn justify_after_punctuation_first() {
+        let options = Options::new(13)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
         assert_eq!(
-            wrap("Internationalization", &options),
-            vec!["Internatio", "nalization"]
+            wrap("foo, bar baz qux", &options),
+            vec!["foo,  bar baz", "qux"]
         );
-
-        options = Options::new(10).splitter(Box::new(dictionary));
         assert_eq!(
-            wrap("Internationalization", &options),
-            vec!["Interna-", "tionaliza-", "tion"]
+            wrap("foo bar. baz qux", &options),
+            vec!["foo bar.  baz", "qux"]
         );
     }
 
     #[test]
-    #[cfg(feature = "hyphenation")]
-    fn auto_hyphenation_issue_158() {
-        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
-        let options = Options::new(10);
+    fn justify_keeps_last_line_and_single_words() {
+        let options = Options::new(10)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
         assert_eq!(
-            wrap("participation is the key to success", &options),
-            vec!["participat", "ion is", "the key to", "success"]
+            wrap(
+                "Internationalization foo bar baz
+foo bar",
+                &options
+            ),
+            vec!["Internatio", "nalization", "foo    bar", "baz", "foo bar"]
         );
+    }
 
-        let options = Options::new(10).splitter(dictionary);
+    #[test]
+    fn justify_with_indent() {
+        let options = Options::new(12)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .subsequent_indent("  ");
         assert_eq!(
-            wrap("participation is the key to success", &options),
-            vec!["partici-", "pation is", "the key to", "success"]
+            wrap("foo bar baz a qux-quuxeses", &options),
+            vec!["foo bar  baz", "  a     qux-", "  quuxeses"]
         );
     }
 
     #[test]
-    #[cfg(feature = "hyphenation")]
-    fn split_len_hyphenation() {
-        // Test that hyphenation takes the width of the wihtespace
-        // into account.
-        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
-        let options = Options::new(15).splitter(dictionary);
-        assert_eq!(
-            wrap("garbage   collection", &options),
-            vec!["garbage   col-", "lection"]
-        );
+    fn justify_keeps_leading_whitespace() {
+        let options = Options::new(10)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit);
+        assert_eq!(wrap("  foo bar baz", &options), vec!["  foo  bar", "baz"]);
     }
 
     #[test]
-    #[cfg(feature = "hyphenation")]
-    fn borrowed_lines() {
-        // Lines that end with an extra hyphen are owned, the final
-        // line is borrowed.
-        use std::borrow::Cow::{Borrowed, Owned};
-        let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
-        let options = Options::new(10).splitter(dictionary);
-        let lines = wrap("Internationalization", &options);
-        if let Borrowed(s) = lines[0] {
-            assert!(false, "should not have been borrowed: {:?}", s);
-        }
-        if let Borrowed(s) = lines[1] {
-            assert!(false, "should not have been borrowed: {:?}", s);
-        }
-        if let Owned(ref s) = lines[2] {
-            assert!(false, "should not have been owned: {:?}", s);
-        }
+    fn justify_truncated_line() {
+        let options = Options::new(10)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .max_lines(1)
+            .placeholder("...");
+        assert_eq!(wrap("foo bar baz", &options), vec!["foo bar..."]);
     }
 
     #[test]
     #[cfg(feature = "hyphenation")]
-    fn auto_hyphenation_with_hyphen() {
+    fn justify_hyphenation() {
         let dictionary = Standard::from_embedded(Language::EnglishUS).unwrap();
-        let options = Options::new(8).break_words(false);
-        assert_eq!(
-            wrap("over-caffinated", &options),
-            vec!["over-", "caffinated"]
-        );
-
-        let options = options.splitter(dictionary);
+        let options = Options::new(12)
+            .alignment(Alignment::Justified)
+            .wrap_algorithm(core::WrapAlgorithm::FirstFit)
+            .splitter(dictionary);
         assert_eq!(
-            wrap("over-caffinated", &options),
-            vec!["over-", "caffi-", "nated"]
+            wrap("a Internationalization", &options),
+            vec!["a   Interna-", "tionaliza-", "tion"]
         );
     }
 
     #[test]
-    fn break_words() {
-        assert_eq!(wrap("foobarbaz", 3), vec!["foo", "bar", "baz"]);
-    }
-
-    #[test]
-    fn break_words_wide_characters() {
-        // Even the poor man's version of `ch_width` counts these
-        // characters as wide.
-        assert_eq!(wrap("Ｈｅｌｌｏ", 5), vec!["Ｈｅ", "ｌｌ", "ｏ"]);
+    fn align_right() {
+        let options = Options::new(7).alignment(Alignment::Right);
+        assert_eq!(
+            wrap("foo bar baz\n\nquuxes", &options),
+            vec!["foo bar", "    baz", "", " quuxes"]
+        );
     }
 
     #[test]
-    fn break_words_zero_width() {
-        assert_eq!(wrap("foobar", 0), vec!["f", "o", "o", "b", "a", "r"]);
+    fn align_center() {
+        let options = Options::new(8).alignment(Alignment::Center);
+        assert_eq!(
+            wrap("foo bar baz\nquuxes", &options),
+            vec!["foo bar ", "  baz   ", " quuxes "]
+        );
     }
 
     #[test]
-    fn break_long_first_word() {
-        assert_eq!(wrap("testx y", 4), vec!["test", "x y"]);
+    fn align_with_indent() {
+        let options = Options::new(10)
+            .alignment(Alignment::Right)
+            .initial_indent("* ")
+            .subsequent_indent("  ");
+        assert_eq!(
+            wrap("foo bar baz", &options),
+            vec![" * foo bar", "       baz"]
+        );
     }
 
     #[test]
-    fn break_words_line_breaks() {
-        assert_eq!(fill("ab\ncdefghijkl", 5), "ab\ncdefg\nhijkl");
-        assert_eq!(fill("abcdefgh\nijkl", 5), "abcde\nfgh\nijkl");
+    fn align_skips_full_and_long_lines() {
+        let options = Options::new(3)
+            .alignment(Alignment::Center)
+            .long_words(LongWords::Overflow);
+        assert_eq!(wrap("foo quuxes", &options), vec!["foo", "quuxes"]);
     }
 
     #[test]
-    fn break_words_empty_lines() {
-        assert_eq!(
-            fill("foo\nbar", &Options::new(2).break_words(false)),
-            "foo\nbar"
-        );
+    fn align_center_wide_characters() {
+        let options = Options::new(9).alignment(Alignment::Center);
+        assert_eq!(wrap("你好", &options), vec!["  你好   "]);
+        assert_eq!(fill("你好 世界", &options), "你好 世界");
     }
 
     #[test]
-    fn preserve_line_breaks() {
-        assert_eq!(fill("", 80), "");
-        assert_eq!(fill("\n", 80), "\n");
-        assert_eq!(fill("\n\n\n", 80), "\n\n\n");
-        assert_eq!(fill("test\n", 80), "test\n");
-        assert_eq!(fill("test\n\na\n\n", 80), "test\n\na\n\n");
-        assert_eq!(
-            fill(
-                "1 3 5 7\n1 3 5 7",
-                Options::new(7).wrap_algorithm(core::WrapAlgorithm::FirstFit)
-            ),
-            "1 3 5 7\n1 3 5 7"
-        );
-        assert_eq!(
-            fill(
-                "1 3 5 7\n1 3 5 7",
-                Options::new(5).wrap_algorithm(core::WrapAlgorithm::FirstFit)
-            ),
-            "1 3 5\n7\n1 3 5\n7"
-        );
+    fn list_marker_len_markers() {
+        assert_eq!(list_marker_len("- foo"), Some(2));
+        assert_eq!(list_marker_len("* foo"), Some(2));
+        assert_eq!(list_marker_len("+   foo"), Some(4));
+        assert_eq!(list_marker_len("1. foo"), Some(3));
+        assert_eq!(list_marker_len("42) foo"), Some(4));
+        assert_eq!(list_marker_len("a. foo"), Some(3));
+        assert_eq!(list_marker_len("  - foo"), Some(4));
     }
 
     #[test]
-    fn preserve_line_breaks_with_whitespace() {
-        assert_eq!(fill("  ", 80), "");
-        assert_eq!(fill("  \n  ", 80), "\n");
-        assert_eq!(fill("  \n \n  \n ", 80), "\n\n\n");
+    fn list_marker_len_no_markers() {
+        assert_eq!(list_marker_len(""), None);
+        assert_eq!(list_marker_len("foo"), None);
+        assert_eq!(list_marker_len("-5 degrees"), None);
+        assert_eq!(list_marker_len("- "), None);
+        assert_eq!(list_marker_len("--foo"), None);
+        assert_eq!(list_marker_len("1.5 liters"), None);
+        assert_eq!(list_marker_len("ab. foo"), None);
+        assert_eq!(list_marker_len("é. foo"), None);
     }
 
     #[test]
-    fn non_breaking_space() {
-        let options = Options::new(5).break_words(false);
-        assert_eq!(fill("foo bar baz", &options), "foo bar baz");
+    fn detect_list_markers_disabled() {
+        assert_eq!(fill("- foo bar baz", 8), "- foo\nbar baz");
     }
 
     #[test]
-    fn non_breaking_hyphen() {
-        let options = Options::new(5).break_words(false);
-        assert_eq!(fill("foo‑bar‑baz", &options), "foo‑bar‑baz");
+    fn detect_list_markers_paragraphs() {
+        let options = Options::new(8).detect_list_markers(true);
+        assert_eq!(
+            fill("- foo bar baz\n\nplain text here\n  * a b c d e", &options),
+            "- foo\n  bar\n  baz\n\nplain\ntext\nhere\n  * a b\n    c d\n    e"
+        );
     }
 
     #[test]
-    fn fill_simple() {
-        assert_eq!(fill("foo bar baz", 10), "foo bar\nbaz");
+    fn detect_list_markers_negative_number() {
+        let options = Options::new(8).detect_list_markers(true);
+        assert_eq!(fill("-5 is less than 0", &options), "-5 is\nless\nthan 0");
     }
 
     #[test]
-    fn fill_colored_text() {
-        // The words are much longer than 6 bytes, but they remain
-        // intact after filling the text.
-        let green_hello = "\u{1b}[0m\u{1b}[32mHello\u{1b}[0m";
-        let blue_world = "\u{1b}[0m\u{1b}[34mWorld!\u{1b}[0m";
+    fn detect_list_markers_with_indent() {
+        let options = Options::new(12)
+            .initial_indent("> ")
+            .subsequent_indent("| ")
+            .detect_list_markers(true);
         assert_eq!(
-            fill(&(String::from(green_hello) + " " + &blue_world), 6),
-            String::from(green_hello) + "\n" + &blue_world
+            fill("a) foo bar baz\nb) qux quux quuux", &options),
+            "> a) foo bar\n     baz\n| b) qux\n     quux\n     quuux"
         );
     }
 
     #[test]
-    fn cloning_works() {
-        static OPT: Options<HyphenSplitter> = Options::with_splitter(80, HyphenSplitter);
-        #[allow(clippy::clone_on_copy)]
-        let opt = OPT.clone();
-        assert_eq!(opt.width, 80);
+    fn justify_never_exceeds_width() {
+        let text = "Memory safety without garbage collection. \
+                    Concurrency without data races. \
+                    Zero-cost abstractions.";
+        for width in 1..60 {
+            let options = Options::new(width).alignment(Alignment::Justified);
+            let lines = wrap(text, &options);
+            let (last, lines) = lines.split_last().unwrap();
+            assert!(core::display_width(last) <= width, "width: {}", width);
+            for line in lines {
+                let line_width = core::display_width(line);
+                assert!(line_width <= width, "width: {}", width);
+                assert!(
+                    line_width == width || !line.contains(' '),
+                    "width: {}",
+                    width
+                );
+            }
+        }
     }
 
     #[test]
@@ -1773,6 +11169,17 @@ mod tests {
         assert_eq!(text, "foo  bar   \nbaz");
     }
 
+    #[test]
+    fn fill_inplace_keeps_buffer() {
+        let mut text = String::with_capacity(100);
+        text.push_str("Some text to wrap over multiple lines\nand more");
+        let ptr = text.as_ptr();
+        fill_inplace(&mut text, 12);
+        assert_eq!(text, "Some text to\nwrap over\nmultiple\nlines\nand more");
+        assert_eq!(text.capacity(), 100);
+        assert_eq!(text.as_ptr(), ptr);
+    }
+
     #[test]
     fn unfill_simple() {
         let (text, options) = unfill("foo\nbar");
@@ -1836,6 +11243,83 @@ mod tests {
         assert_eq!(unfill("foo   bar").0, "foo   bar");
     }
 
+    #[test]
+    fn unfill_hyphens() {
+        let (text, options) = unfill("> The --foo-\n> bar flag is\n> not -\n> useful.");
+        assert_eq!(text, "The --foo-bar flag is not - useful.");
+        assert_eq!(options.subsequent_indent, "> ");
+    }
+
+    #[test]
+    fn refill_paragraphs() {
+        let text = "foo\nbar\n\n\n  baz\n  quux\n \nend";
+        assert_eq!(refill(text, 20), "foo bar\n\n\n  baz quux\n \nend");
+    }
+
+    #[test]
+    fn refill_is_idempotent() {
+        let text = "\
+> Memory safety without garbage collection. Concurrency without
+> data races. Zero-cost abstractions.
+
+- Move semantics, guaranteed memory safety, threads without
+  data races, trait-based generics, pattern matching.
+";
+        for width in 10..60 {
+            let refilled = refill(text, width);
+            assert_eq!(refill(&refilled, width), refilled, "width: {}", width);
+        }
+    }
+
+    #[test]
+    fn shorten_empty() {
+        assert_eq!(shorten("", 10, "..."), "");
+        assert_eq!(shorten("   ", 0, "..."), "");
+    }
+
+    #[test]
+    fn shorten_borrows() {
+        assert!(matches!(
+            shorten(" foo bar ", 10, "..."),
+            Cow::Borrowed("foo bar")
+        ));
+        assert!(matches!(shorten("foo  bar", 10, "..."), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn shorten_collapses_whitespace() {
+        assert_eq!(shorten("foo \t bar\n\nbaz", 20, "..."), "foo bar baz");
+    }
+
+    #[test]
+    fn shorten_whole_words() {
+        assert_eq!(shorten("foo bar baz", 10, "..."), "foo bar...");
+        assert_eq!(shorten("foo bar baz", 9, "..."), "foo...");
+        assert_eq!(shorten("foo bar baz", 10, " [...]"), "foo [...]");
+    }
+
+    #[test]
+    fn shorten_hard_truncation() {
+        assert_eq!(shorten("Internationalization", 7, "..."), "Inte...");
+        assert_eq!(shorten("foo", 2, "..."), "..");
+        assert_eq!(shorten("foo", 0, "..."), "");
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn shorten_combining_marks() {
+        assert_eq!(
+            shorten("e\u{301}e\u{301}e\u{301}", 2, ""),
+            "e\u{301}e\u{301}"
+        );
+    }
+
+    #[test]
+    fn shorten_display_width() {
+        assert_eq!(shorten("你好 世界", 6, "."), "你好.");
+        assert_eq!(shorten("你好世界", 6, "."), "你好.");
+    }
+
     #[test]
     fn trait_object() {
         let opt_a: Options<NoHyphenation> = Options::with_splitter(20, NoHyphenation);
@@ -1871,7 +11355,7 @@ mod tests {
 
         // Actually: Options<Box<dyn WordSplitter>>
         let opt_box: Options = Options::new(10)
-            .break_words(false)
+            .long_words(LongWords::Overflow)
             .splitter(Box::new(NoHyphenation));
         vector.push(&opt_box);
         results.push(vec!["over-caffinated"]);
@@ -1897,7 +11381,7 @@ mod tests {
         wrapper = Box::new(Options::from(5));
 
         // Deref per-se works as well, it already returns a reference
-        use std::ops::Deref;
+        use ::core::ops::Deref;
         assert_eq!(
             wrap("foo bar baz", wrapper.deref()),
             vec!["foo", "bar", "baz"]
@@ -1913,7 +11397,22 @@ mod tests {
     fn wrap_columns_single_column() {
         assert_eq!(
             wrap_columns("Foo", 3, 30, "| ", " | ", " |"),
-            vec!["| Foo    |        |          |"]
+            vec!["| Foo    |"]
+        );
+    }
+
+    #[test]
+    fn wrap_columns_skips_empty_columns() {
+        assert_eq!(
+            wrap_columns("Foo Bar Baz", 4, 21, "|", "|", "|"),
+            vec!["|Foo |Bar |Baz |"]
+        );
+        assert_eq!(
+            wrap_columns("1\n2\n3\n4\n5", 4, 21, "|", "|", "|"),
+            vec![
+                "|1   |3   |5   |", //
+                "|2   |4   |    |"
+            ]
         );
     }
 
@@ -0,0 +1,483 @@
+//! Functions for filling Markdown documents.
+//!
+//! The structure of the document is found by looking at one line at a
+//! time. This is not a full Markdown parser, but it recognizes the
+//! blocks which must not be reflowed, as well as lists and block
+//! quotes, which can be nested.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{wrap, Options, WordSplitter};
+
+/// Fill the paragraphs of a Markdown document, keeping its structure.
+///
+/// Ordinary paragraphs are filled like with [`fill_paragraphs`].
+/// These blocks are kept unchanged:
+///
+/// * Fenced code blocks, starting with ```` ``` ```` or `~~~`, and
+///   code blocks indented by four spaces or a tab.
+/// * Headings, both `# Heading` and headings underlined with `===`
+///   or `---`.
+/// * Tables, i.e., lines starting with `|`.
+/// * Horizontal rules such as `---` and `* * *`.
+///
+/// List items and block quotes are filled with a hanging indent:
+/// the lines after the first line of a list item are indented to
+/// line up with the text after the list marker, and every line of a
+/// block quote starts with `> `. Lists and block quotes can be nested
+/// inside each other. The blank lines are kept and the lines of the
+/// result are separated by [`Options::line_ending`].
+///
+/// # Examples
+///
+/// ````
+/// use textwrap::fill_markdown;
+///
+/// let text = "\
+/// # Memory safety
+///
+/// Without garbage
+/// collection.
+///
+/// * No data races when
+///   accessing memory from threads.
+///
+/// ```
+/// let x = 42; // A comment which must not be wrapped.
+/// ```";
+///
+/// assert_eq!(fill_markdown(text, 20), "\
+/// # Memory safety
+///
+/// Without garbage
+/// collection.
+///
+/// * No data races when
+///   accessing memory
+///   from threads.
+///
+/// ```
+/// let x = 42; // A comment which must not be wrapped.
+/// ```");
+/// ````
+///
+/// [`fill_paragraphs`]: crate::fill_paragraphs
+pub fn fill_markdown<'a, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    S: WordSplitter,
+    Opt: Into<Options<'a, S>>,
+{
+    let options = width_or_options.into();
+    let lines = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    let mut result = Vec::new();
+    fill_blocks(&lines, &options, options.width, &mut result);
+    result.join(options.line_ending)
+}
+
+/// Fill the `lines` of a sequence of blocks at `width` and push the
+/// resulting lines onto `result`.
+fn fill_blocks<S: WordSplitter>(
+    lines: &[&str],
+    options: &Options<'_, S>,
+    width: usize,
+    result: &mut Vec<String>,
+) {
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim().is_empty() {
+            result.push(String::new());
+            i += 1;
+        } else if let Some(fence) = fence(line) {
+            // Keep everything up to and including the closing fence.
+            let end = lines[i + 1..]
+                .iter()
+                .position(|line| is_closing_fence(line, fence))
+                .map_or(lines.len(), |idx| i + idx + 2);
+            result.extend(lines[i..end].iter().map(|line| String::from(*line)));
+            i = end;
+        } else if is_verbatim(line) || indentation(line) >= 4 {
+            result.push(String::from(line));
+            i += 1;
+        } else if let Some(content) = quote_content(line) {
+            let mut inner = vec![content];
+            i += 1;
+            while let Some(content) = lines.get(i).and_then(|line| quote_content(line)) {
+                inner.push(content);
+                i += 1;
+            }
+            let start = result.len();
+            fill_blocks(&inner, options, width.saturating_sub(2).max(1), result);
+            for line in &mut result[start..] {
+                line.insert_str(0, if line.is_empty() { ">" } else { "> " });
+            }
+        } else if let Some(marker_len) = list_marker_len(line) {
+            let mut inner = vec![Cow::Borrowed(&line[marker_len..])];
+            i += 1;
+            let mut in_paragraph = true;
+            while let Some(&line) = lines.get(i) {
+                if line.trim().is_empty() {
+                    // A blank line only belongs to the item if the
+                    // item continues after it.
+                    let next = lines[i..].iter().find(|line| !line.trim().is_empty());
+                    if next.is_none_or(|next| indentation(next) < marker_len) {
+                        break;
+                    }
+                    inner.push(Cow::Borrowed(""));
+                    in_paragraph = false;
+                } else if indentation(line) >= marker_len {
+                    inner.push(strip_indentation(line, marker_len));
+                    in_paragraph = true;
+                } else if in_paragraph && !starts_block(line) {
+                    // A lazy continuation line of a paragraph.
+                    inner.push(Cow::Borrowed(line.trim_start()));
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+            let inner = inner.iter().map(|line| &**line).collect::<Vec<_>>();
+            let start = result.len();
+            fill_blocks(
+                &inner,
+                options,
+                width.saturating_sub(marker_len).max(1),
+                result,
+            );
+            let hanging = " ".repeat(marker_len);
+            for (idx, item_line) in result[start..].iter_mut().enumerate() {
+                if idx == 0 {
+                    item_line.insert_str(0, &line[..marker_len]);
+                    item_line.truncate(item_line.trim_end().len());
+                } else if !item_line.is_empty() {
+                    item_line.insert_str(0, &hanging);
+                }
+            }
+        } else {
+            let start = i;
+            let mut paragraph = String::from(line.trim());
+            i += 1;
+            while let Some(&line) = lines.get(i) {
+                if line.trim().is_empty() || starts_block(line) || is_heading_underline(line) {
+                    break;
+                }
+                paragraph.push(' ');
+                paragraph.push_str(line.trim());
+                i += 1;
+            }
+            if lines.get(i).is_some_and(|line| is_heading_underline(line)) {
+                // The paragraph is a heading, keep it unchanged.
+                i += 1;
+                result.extend(lines[start..i].iter().map(|line| String::from(*line)));
+            } else {
+                let mut options = Options::from(options);
+                options.width = width;
+                result.extend(wrap(&paragraph, options).into_iter().map(Cow::into_owned));
+            }
+        }
+    }
+}
+
+/// Number of columns of leading whitespace in `line`. Tabs advance
+/// to the next multiple of four columns, so a tab is enough to start
+/// an indented code block.
+fn indentation(line: &str) -> usize {
+    let mut columns = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => columns += 1,
+            '\t' => columns += 4 - columns % 4,
+            _ => break,
+        }
+    }
+    columns
+}
+
+/// Remove `columns` columns of leading whitespace from `line`, which
+/// must be indented by at least that much, see [`indentation`]. The
+/// rest of a tab which is only partly removed is kept as spaces.
+fn strip_indentation(line: &str, columns: usize) -> Cow<'_, str> {
+    let mut removed = 0;
+    for (idx, ch) in line.char_indices() {
+        if removed >= columns {
+            return Cow::Borrowed(&line[idx..]);
+        }
+        match ch {
+            ' ' => removed += 1,
+            '\t' => removed += 4 - removed % 4,
+            _ => return Cow::Borrowed(&line[idx..]),
+        }
+        if removed > columns {
+            let rest = &line[idx + ch.len_utf8()..];
+            return Cow::Owned(" ".repeat(removed - columns) + rest);
+        }
+    }
+    Cow::Borrowed("")
+}
+
+/// Return the line without up to three spaces of indentation, or
+/// `None` if it is indented more.
+fn unindented(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(' ');
+    match line.len() - rest.len() {
+        0..=3 if !rest.starts_with('\t') => Some(rest),
+        _ => None,
+    }
+}
+
+/// Find the character and length of the opening code fence in
+/// `line`, such as `('`', 3)` for ```` ```rust ````.
+fn fence(line: &str) -> Option<(char, usize)> {
+    let rest = unindented(line)?;
+    let ch = rest.chars().next().filter(|&ch| ch == '`' || ch == '~')?;
+    let len = rest.len() - rest.trim_start_matches(ch).len();
+    // An info string after a backtick fence cannot contain backticks.
+    let info = &rest[len..];
+    match len >= 3 && !(ch == '`' && info.contains('`')) {
+        true => Some((ch, len)),
+        false => None,
+    }
+}
+
+/// Does `line` close a code block opened by `fence`?
+fn is_closing_fence(line: &str, (ch, len): (char, usize)) -> bool {
+    unindented(line).is_some_and(|rest| {
+        let rest = rest.trim_end();
+        rest.len() >= len && rest.chars().all(|c| c == ch)
+    })
+}
+
+/// Is `line` a heading, a table row, or a horizontal rule? These are
+/// single lines which are kept unchanged.
+fn is_verbatim(line: &str) -> bool {
+    let rest = match unindented(line) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    let is_heading = (1..=6).contains(&hashes)
+        && (rest.len() == hashes || rest[hashes..].starts_with([' ', '\t']));
+    is_heading || rest.starts_with('|') || is_rule(rest)
+}
+
+/// Is `line` a horizontal rule, such as `---` or `* * *`?
+fn is_rule(line: &str) -> bool {
+    let line = line.trim();
+    let ch = match line.chars().next() {
+        Some(ch @ ('-' | '*' | '_')) => ch,
+        _ => return false,
+    };
+    line.chars().all(|c| c == ch || c == ' ' || c == '\t')
+        && line.chars().filter(|&c| c == ch).count() >= 3
+}
+
+/// Is `line` the underline of a heading, such as `===` or `---`?
+fn is_heading_underline(line: &str) -> bool {
+    unindented(line).is_some_and(|rest| {
+        let rest = rest.trim_end();
+        !rest.is_empty() && (rest.chars().all(|c| c == '=') || rest.chars().all(|c| c == '-'))
+    })
+}
+
+/// Return the content of a block quote line, i.e., the text after
+/// the `>` and an optional space.
+fn quote_content(line: &str) -> Option<&str> {
+    let rest = unindented(line)?.strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+/// Return the length of the list marker at the start of `line`,
+/// including the indentation before it and the spaces after it. The
+/// markers `-`, `*`, and `+` are recognized, as well as numbers
+/// followed by `.` or `)`.
+fn list_marker_len(line: &str) -> Option<usize> {
+    let rest = unindented(line)?;
+    let marker_len = match rest.as_bytes().first()? {
+        b'-' | b'*' | b'+' => 1,
+        b'0'..=b'9' => {
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            match rest.as_bytes().get(digits)? {
+                b'.' | b')' if digits <= 9 => digits + 1,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let after = &rest[marker_len..];
+    let text = after.trim_start_matches(' ');
+    let spaces = match after.len() - text.len() {
+        0 if !text.is_empty() => return None,
+        // An indented code block in the item starts one space after
+        // the marker.
+        5.. if !text.is_empty() => 1,
+        spaces => spaces.max(1),
+    };
+    Some((line.len() - rest.len() + marker_len + spaces).min(line.len()))
+}
+
+/// Does `line` start a block which interrupts a paragraph?
+fn starts_block(line: &str) -> bool {
+    fence(line).is_some()
+        || is_verbatim(line)
+        || quote_content(line).is_some()
+        || list_marker_len(line).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_markers() {
+        assert_eq!(list_marker_len("- foo"), Some(2));
+        assert_eq!(list_marker_len("  * foo"), Some(4));
+        assert_eq!(list_marker_len("10. foo"), Some(4));
+        assert_eq!(list_marker_len("1)   foo"), Some(5));
+        assert_eq!(list_marker_len("-      code"), Some(2));
+        assert_eq!(list_marker_len("-"), Some(1));
+        assert_eq!(list_marker_len("-foo"), None);
+        assert_eq!(list_marker_len("1.5 apples"), None);
+        assert_eq!(list_marker_len("    - code"), None);
+    }
+
+    #[test]
+    fn verbatim_lines() {
+        assert!(is_verbatim("# Heading"));
+        assert!(is_verbatim("###"));
+        assert!(!is_verbatim("#hashtag"));
+        assert!(is_verbatim("| a | b |"));
+        assert!(is_verbatim("* * *"));
+        assert!(is_verbatim("___"));
+        assert!(!is_verbatim("-- foo"));
+        assert_eq!(fence("```rust"), Some(('`', 3)));
+        assert_eq!(fence("~~~~"), Some(('~', 4)));
+        assert_eq!(fence("``code``"), None);
+        assert!(is_closing_fence("````", ('`', 3)));
+        assert!(!is_closing_fence("~~~", ('`', 3)));
+    }
+
+    #[test]
+    fn paragraphs_and_blank_lines() {
+        assert_eq!(fill_markdown("", 10), "");
+        assert_eq!(
+            fill_markdown("foo bar\nbaz\n\n\nfoo bar baz\n", 10),
+            "foo bar\nbaz\n\n\nfoo bar\nbaz\n"
+        );
+    }
+
+    #[test]
+    fn blocks_are_kept() {
+        let text = [
+            "Heading which is long",
+            "=====================",
+            "",
+            "| a | b | c | d | e |",
+            "|---|---|---|---|---|",
+            "",
+            "    let indented = code;",
+            "",
+            "~~~",
+            "inside a fence",
+            "```",
+            "~~~",
+            "- - - - - - - - - - -",
+        ]
+        .join("\n");
+        assert_eq!(fill_markdown(&text, 10), text);
+    }
+
+    #[test]
+    fn unclosed_fence() {
+        let text = "```\nlong code which is kept\n\nmore code";
+        assert_eq!(fill_markdown(text, 5), text);
+    }
+
+    #[test]
+    fn nested_lists() {
+        let text = [
+            "1. First item which is long.",
+            "   - Nested item which is also long.",
+            "     more text",
+            "",
+            "   Second paragraph of the first item.",
+            "2. Second item",
+            "lazy continuation line",
+        ]
+        .join("\n");
+        let expected = [
+            "1. First item which is",
+            "   long.",
+            "   - Nested item which",
+            "     is also long.",
+            "     more text",
+            "",
+            "   Second paragraph of",
+            "   the first item.",
+            "2. Second item lazy",
+            "   continuation line",
+        ]
+        .join("\n");
+        assert_eq!(fill_markdown(&text, 22), expected);
+        assert_eq!(fill_markdown(&expected, 22), expected);
+    }
+
+    #[test]
+    fn list_items_with_tabs_and_multibyte_text() {
+        assert_eq!(fill_markdown("- item\n\tfoo bar", 20), "- item foo bar");
+        assert_eq!(fill_markdown("10. item\n\téé bar", 20), "10. item éé bar");
+        // The code block stays at column 8.
+        assert_eq!(
+            fill_markdown("- item\n\n\t\tcode", 20),
+            "- item\n\n    \tcode"
+        );
+        assert_eq!(strip_indentation("\tfoo", 2), "  foo");
+        assert_eq!(strip_indentation("  \tfoo", 4), "foo");
+        assert_eq!(strip_indentation("    éé", 4), "éé");
+        assert_eq!(indentation(" \t"), 4);
+    }
+
+    #[test]
+    fn quotes_in_lists_and_lists_in_quotes() {
+        let text = [
+            "- Item with a quote:",
+            "",
+            "  > Quoted text inside of the list item.",
+            "  >",
+            "  > > Nested quote.",
+            "",
+            "> * List inside of a",
+            "> quote which is long.",
+        ]
+        .join("\n");
+        let expected = [
+            "- Item with a quote:",
+            "",
+            "  > Quoted text inside",
+            "  > of the list item.",
+            "  >",
+            "  > > Nested quote.",
+            "",
+            "> * List inside of a",
+            ">   quote which is",
+            ">   long.",
+        ]
+        .join("\n");
+        let options = Options::new(22).wrap_algorithm(crate::core::WrapAlgorithm::FirstFit);
+        assert_eq!(fill_markdown(&text, &options), expected);
+    }
+
+    #[test]
+    fn line_endings() {
+        let options = Options::new(10).line_ending("\r\n");
+        assert_eq!(
+            fill_markdown("# Title\r\n\r\nfoo bar baz\r\n", &options),
+            "# Title\r\n\r\nfoo bar\r\nbaz\r\n"
+        );
+    }
+}
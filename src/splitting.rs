@@ -5,6 +5,17 @@
 //! functionality. [`HyphenSplitter`] is the default implementation of
 //! this treat: it will simply split words on existing hyphens.
 
+use ::core::iter::FromIterator;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
 /// The `WordSplitter` trait describes where words can be split.
 ///
 /// If the textwrap crate has been compiled with the `hyphenation`
@@ -32,8 +43,31 @@
 /// Please see the documentation for the [hyphenation] crate for more
 /// details.
 ///
+/// # Thread Safety
+///
+/// A `WordSplitter` must be `Send` and `Sync`. This makes
+/// [`Options`](crate::Options) with any splitter, including the
+/// default `Box<dyn WordSplitter>`, `Send` and `Sync` as well, so one
+/// `Options` can be stored in a `static` and used from several
+/// threads at once:
+///
+/// ```
+/// use std::sync::OnceLock;
+/// use textwrap::{wrap, HyphenSplitter, Options};
+///
+/// static OPTIONS: OnceLock<Options<'static>> = OnceLock::new();
+///
+/// let options = OPTIONS.get_or_init(|| Options::new(10).splitter_boxed(HyphenSplitter));
+/// let thread = std::thread::spawn(move || wrap("Memory safety", options).len());
+/// assert_eq!(thread.join().unwrap(), 2);
+/// ```
+///
+/// All the splitters in this crate and the `hyphenation::Standard`
+/// dictionary are thread safe. A custom splitter which uses, e.g.,
+/// `Rc` or `RefCell` internally must switch to `Arc` and `Mutex`.
+///
 /// [hyphenation]: https://docs.rs/hyphenation/
-pub trait WordSplitter: std::fmt::Debug {
+pub trait WordSplitter: ::core::fmt::Debug + Send + Sync {
     /// Return all possible indices where `word` can be split.
     ///
     /// The indices returned must be in range `0..word.len()`. They
@@ -49,19 +83,302 @@ pub trait WordSplitter: std::fmt::Debug {
     /// assert_eq!(HyphenSplitter.split_points("can-be-split"), vec![4, 7]);
     /// ```
     fn split_points(&self, word: &str) -> Vec<usize>;
+
+    /// Return the indices where `word` can be split, together with
+    /// the change of spelling needed if the word is split there.
+    ///
+    /// The indices are the same as the ones returned by
+    /// [`WordSplitter::split_points`], but they are returned as
+    /// [`SplitPoints`]. This does not allocate for words with a few
+    /// split points, which is nearly all words in prose. The text is
+    /// wrapped using this method.
+    ///
+    /// The default implementation returns the indices found by
+    /// [`WordSplitter::split_points`] without any [`Substitution`].
+    /// Override it to avoid the allocation of the `Vec`, or for
+    /// languages where the spelling of a word changes when it is
+    /// hyphenated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{HyphenSplitter, WordSplitter};
+    /// let points = HyphenSplitter.split("can-be-split");
+    /// assert_eq!(points.indices().collect::<Vec<_>>(), vec![4, 7]);
+    /// ```
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        self.split_points(word).into()
+    }
+}
+
+/// A split point and the change of spelling needed if the word is
+/// split there, see [`SplitPoints`].
+pub type SplitPoint<'a> = (usize, Option<Substitution<'a>>);
+
+/// Number of split points stored without allocating.
+const INLINE_SPLIT_POINTS: usize = 4;
+
+/// The split points of a word, see [`WordSplitter::split`].
+///
+/// This works like a `Vec`, but up to four split points are stored
+/// inline. Words with more split points are rare enough that they
+/// can pay for an allocation. The [`Substitution`]s are borrowed
+/// from the splitter.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::SplitPoints;
+///
+/// let mut points = SplitPoints::new();
+/// points.push(3);
+/// points.push(6);
+/// assert_eq!(points.indices().collect::<Vec<_>>(), vec![3, 6]);
+/// assert_eq!(points, [3, 6].iter().copied().collect());
+/// assert_eq!(points, SplitPoints::from(vec![3, 6]));
+/// ```
+#[derive(Clone)]
+pub struct SplitPoints<'a> {
+    len: usize,
+    inline: [SplitPoint<'a>; INLINE_SPLIT_POINTS],
+    /// All split points once there are too many to store inline.
+    heap: Vec<SplitPoint<'a>>,
+}
+
+impl<'a> SplitPoints<'a> {
+    /// Create an empty list of split points.
+    pub const fn new() -> Self {
+        SplitPoints {
+            len: 0,
+            inline: [(0, None); INLINE_SPLIT_POINTS],
+            heap: Vec::new(),
+        }
+    }
+
+    /// Add a split point without a [`Substitution`].
+    pub fn push(&mut self, idx: usize) {
+        self.push_with_substitution(idx, None);
+    }
+
+    /// Add a split point where the word is respelled as described by
+    /// `substitution`.
+    pub fn push_with_substitution(&mut self, idx: usize, substitution: Option<Substitution<'a>>) {
+        if self.len < INLINE_SPLIT_POINTS {
+            self.inline[self.len] = (idx, substitution);
+        } else {
+            if self.len == INLINE_SPLIT_POINTS {
+                self.heap.extend_from_slice(&self.inline);
+            }
+            self.heap.push((idx, substitution));
+        }
+        self.len += 1;
+    }
+
+    /// The split points and their substitutions.
+    pub fn as_slice(&self) -> &[SplitPoint<'a>] {
+        if self.len <= INLINE_SPLIT_POINTS {
+            &self.inline[..self.len]
+        } else {
+            &self.heap
+        }
+    }
+
+    /// The split points without their substitutions.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.as_slice().iter().map(|&(idx, _)| idx)
+    }
+
+    /// Number of split points.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no split points.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for SplitPoints<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ::core::fmt::Debug for SplitPoints<'_> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl PartialEq for SplitPoints<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for SplitPoints<'_> {}
+
+impl From<Vec<usize>> for SplitPoints<'_> {
+    fn from(indices: Vec<usize>) -> Self {
+        indices.into_iter().collect()
+    }
+}
+
+impl FromIterator<usize> for SplitPoints<'_> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut points = SplitPoints::new();
+        for idx in iter {
+            points.push(idx);
+        }
+        points
+    }
+}
+
+impl<'a> FromIterator<SplitPoint<'a>> for SplitPoints<'a> {
+    fn from_iter<I: IntoIterator<Item = SplitPoint<'a>>>(iter: I) -> Self {
+        let mut points = SplitPoints::new();
+        for (idx, substitution) in iter {
+            points.push_with_substitution(idx, substitution);
+        }
+        points
+    }
+}
+
+impl<'a> IntoIterator for SplitPoints<'a> {
+    type Item = SplitPoint<'a>;
+    type IntoIter = SplitPointsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SplitPointsIter {
+            points: self,
+            next: 0,
+        }
+    }
+}
+
+/// An iterator over [`SplitPoints`].
+#[derive(Clone, Debug)]
+pub struct SplitPointsIter<'a> {
+    points: SplitPoints<'a>,
+    next: usize,
+}
+
+impl<'a> Iterator for SplitPointsIter<'a> {
+    type Item = SplitPoint<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let point = self.points.as_slice().get(self.next).copied()?;
+        self.next += 1;
+        Some(point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.points.len() - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for SplitPointsIter<'_> {}
+
+/// A change of spelling which is needed when a word is split.
+///
+/// In some languages, the letters around a hyphen are spelled
+/// differently than when the word is written in one piece. An example
+/// is the old German spelling of “Zucker” which is hyphenated as
+/// “Zuk-ker”. The split point is between the “c” and the “k”, and the
+/// single letter before it becomes “k-” when the word is split.
+///
+/// When a word is split, the `left` bytes before the split point are
+/// replaced by [`Substitution::before_break`] and the
+/// [`Substitution::hyphen`] at the end of the line, and the `right`
+/// bytes after the split point are replaced by
+/// [`Substitution::after_break`] at the start of the next line. The
+/// word is unchanged when it is not split.
+///
+/// The replacement text is borrowed, e.g., from a hyphenation
+/// dictionary, so that splitting a word does not allocate.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, Options, SplitPoints, Substitution, WordSplitter};
+///
+/// #[derive(Debug)]
+/// struct OldGerman;
+///
+/// impl WordSplitter for OldGerman {
+///     fn split_points(&self, word: &str) -> Vec<usize> {
+///         self.split(word).indices().collect()
+///     }
+///
+///     fn split(&self, word: &str) -> SplitPoints<'_> {
+///         const CK: Substitution = Substitution {
+///             left: 1,
+///             right: 0,
+///             before_break: "k",
+///             hyphen: "-",
+///             after_break: "",
+///         };
+///         word.match_indices("ck").map(|(idx, _)| (idx + 1, Some(CK))).collect()
+///     }
+/// }
+///
+/// let options = Options::new(10).splitter(OldGerman);
+/// assert_eq!(wrap("Zucker und Zimt", &options), vec!["Zucker und", "Zimt"]);
+/// assert_eq!(wrap("Viel Zucker", &options), vec!["Viel Zuk-", "ker"]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Substitution<'a> {
+    /// Number of bytes before the split point which are replaced
+    /// when the word is split.
+    pub left: usize,
+    /// Number of bytes after the split point which are replaced
+    /// when the word is split.
+    pub right: usize,
+    /// Text which replaces the `left` bytes at the end of the line,
+    /// before the hyphen.
+    pub before_break: &'a str,
+    /// The hyphen inserted after [`Substitution::before_break`],
+    /// usually `"-"`. This is empty for a split without a hyphen.
+    pub hyphen: &'static str,
+    /// Text which replaces the `right` bytes at the start of the
+    /// next line.
+    pub after_break: &'a str,
 }
 
 impl<S: WordSplitter + ?Sized> WordSplitter for Box<S> {
     fn split_points(&self, word: &str) -> Vec<usize> {
-        use std::ops::Deref;
+        use ::core::ops::Deref;
         self.deref().split_points(word)
     }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        use ::core::ops::Deref;
+        self.deref().split(word)
+    }
 }
 
 impl<T: ?Sized + WordSplitter> WordSplitter for &T {
     fn split_points(&self, word: &str) -> Vec<usize> {
         (*self).split_points(word)
     }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        (*self).split(word)
+    }
+}
+
+impl<S: WordSplitter + ?Sized> WordSplitter for Arc<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        use ::core::ops::Deref;
+        self.deref().split_points(word)
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        use ::core::ops::Deref;
+        self.deref().split(word)
+    }
 }
 
 /// Use this as a [`Options.splitter`] to avoid any kind of
@@ -75,6 +392,9 @@ impl<T: ?Sized + WordSplitter> WordSplitter for &T {
 ///            vec!["foo", "bar-baz"]);
 /// ```
 ///
+/// Lines are still broken at zero width spaces (U+200B) since these
+/// separate words, see [`find_words`](super::core::find_words).
+///
 /// [`Options.splitter`]: super::Options::splitter
 #[derive(Clone, Copy, Debug)]
 pub struct NoHyphenation;
@@ -85,6 +405,10 @@ impl WordSplitter for NoHyphenation {
     fn split_points(&self, _: &str) -> Vec<usize> {
         Vec::new()
     }
+
+    fn split(&self, _: &str) -> SplitPoints<'_> {
+        SplitPoints::new()
+    }
 }
 
 /// Simple and default way to split words: splitting on existing
@@ -92,41 +416,719 @@ impl WordSplitter for NoHyphenation {
 ///
 /// You probably don't need to use this type since it's already used
 /// by default by [`Options::new`](super::Options::new).
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct HyphenSplitter;
 
+impl HyphenSplitter {
+    /// Create a new `HyphenSplitter`. This is the same as using
+    /// `HyphenSplitter` directly.
+    pub const fn new() -> Self {
+        HyphenSplitter
+    }
+
+    /// Only split words where at least `min` characters are left on
+    /// either side of the hyphen. See [`MinFragment`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, HyphenSplitter, LongWords, Options};
+    ///
+    /// let options = Options::new(4).long_words(LongWords::Overflow);
+    /// assert_eq!(wrap("e-mail", &options), vec!["e-", "mail"]);
+    ///
+    /// let options = options.splitter(HyphenSplitter::new().min_fragment(2));
+    /// assert_eq!(wrap("e-mail", &options), vec!["e-mail"]);
+    /// ```
+    pub const fn min_fragment(self, min: usize) -> MinFragment<Self> {
+        MinFragment::new(self, min)
+    }
+}
+
 /// `HyphenSplitter` is the default `WordSplitter` used by
 /// [`Options::new`](super::Options::new). It will split words on any
 /// existing hyphens in the word.
 ///
 /// It will only use hyphens that are surrounded by alphanumeric
 /// characters, which prevents a word like `"--foo-bar"` from being
-/// split into `"--"` and `"foo-bar"`.
+/// split into `"--"` and `"foo-bar"`. Hyphens followed by a digit are
+/// not used either since they are likely minus signs or part of a
+/// number range, as in `"-15°C"` or `"10-20"`.
 impl WordSplitter for HyphenSplitter {
     fn split_points(&self, word: &str) -> Vec<usize> {
-        let mut splits = Vec::new();
-
-        for (idx, _) in word.match_indices('-') {
-            // We only use hyphens that are surrounded by alphanumeric
-            // characters. This is to avoid splitting on repeated hyphens,
-            // such as those found in --foo-bar.
-            let prev = word[..idx].chars().next_back();
-            let next = word[idx + 1..].chars().next();
-
-            if prev.filter(|ch| ch.is_alphanumeric()).is_some()
-                && next.filter(|ch| ch.is_alphanumeric()).is_some()
-            {
-                splits.push(idx + 1); // +1 due to width of '-'.
+        hyphen_split_points(word).collect()
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        hyphen_split_points(word).collect()
+    }
+}
+
+/// The split points used by [`HyphenSplitter`].
+fn hyphen_split_points(word: &str) -> impl Iterator<Item = usize> + '_ {
+    word.match_indices('-').filter_map(move |(idx, _)| {
+        // We only use hyphens that are surrounded by alphanumeric
+        // characters. This is to avoid splitting on repeated hyphens,
+        // such as those found in --foo-bar. We also skip hyphens
+        // before digits, such as those found in -15 or 10-20.
+        let prev = word[..idx].chars().next_back();
+        let next = word[idx + 1..].chars().next();
+
+        if prev.filter(|ch| ch.is_alphanumeric()).is_some()
+            && next
+                .filter(|ch| ch.is_alphanumeric() && !ch.is_numeric())
+                .is_some()
+        {
+            Some(idx + 1) // +1 due to width of '-'.
+        } else {
+            None
+        }
+    })
+}
+
+/// Split words at soft hyphens (U+00AD) only.
+///
+/// A soft hyphen marks a place where a word may be hyphenated. It is
+/// invisible and takes up no room unless the word is broken there, in
+/// which case it is shown as a `-`:
+///
+/// ```
+/// use textwrap::{wrap, Options, SoftHyphenSplitter};
+///
+/// let text = "Super\u{ad}cali\u{ad}fragilistic";
+/// let options = Options::new(12).splitter(SoftHyphenSplitter::new());
+/// assert_eq!(wrap(text, &options), vec!["Supercali-", "fragilistic"]);
+/// assert_eq!(wrap(text, 30), vec!["Supercalifragilistic"]);
+/// ```
+///
+/// Words without soft hyphens are split by the fallback splitter,
+/// which is [`NoHyphenation`] by default. This lets you combine
+/// manual hyphenation with a hyphenation dictionary: the soft hyphens
+/// in a word take precedence over the dictionary.
+///
+/// ```
+/// use textwrap::{wrap, HyphenSplitter, Options, SoftHyphenSplitter};
+///
+/// let splitter = SoftHyphenSplitter::with_fallback(HyphenSplitter);
+/// let options = Options::new(6).splitter(splitter);
+/// assert_eq!(wrap("soft\u{ad}ware well-known", &options),
+///            vec!["soft-", "ware", "well-", "known"]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SoftHyphenSplitter<S = NoHyphenation> {
+    fallback: S,
+}
+
+impl SoftHyphenSplitter {
+    /// Create a splitter which only splits at soft hyphens.
+    pub const fn new() -> Self {
+        SoftHyphenSplitter {
+            fallback: NoHyphenation,
+        }
+    }
+}
+
+impl Default for SoftHyphenSplitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: WordSplitter> SoftHyphenSplitter<S> {
+    /// Create a splitter which splits at soft hyphens and uses
+    /// `fallback` for words without soft hyphens.
+    pub const fn with_fallback(fallback: S) -> Self {
+        SoftHyphenSplitter { fallback }
+    }
+}
+
+/// `SoftHyphenSplitter` splits words after every soft hyphen which is
+/// not at the very start or end of the word.
+impl<S: WordSplitter> WordSplitter for SoftHyphenSplitter<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        if !word.contains(SHY) {
+            return self.fallback.split_points(word);
+        }
+        soft_hyphen_split_points(word).collect()
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        if !word.contains(SHY) {
+            return self.fallback.split(word);
+        }
+        soft_hyphen_split_points(word).collect()
+    }
+}
+
+/// Soft hyphen (U+00AD).
+const SHY: char = '\u{ad}';
+
+/// The split points used by [`SoftHyphenSplitter`].
+fn soft_hyphen_split_points(word: &str) -> impl Iterator<Item = usize> + '_ {
+    word.match_indices(SHY)
+        .map(|(idx, _)| idx + SHY.len_utf8())
+        .filter(move |&idx| idx > SHY.len_utf8() && idx < word.len())
+}
+
+/// Split identifiers such as `camelCase` and `snake_case` names.
+///
+/// Generated documentation is often full of long identifiers like
+/// `ReallyLongConfigurationManager` or `max_request_body_size`. This
+/// splitter breaks them where a reader sees a boundary between the
+/// parts of a name:
+///
+/// * after an underscore, as in `max_|size`,
+/// * before an interior capital letter, as in `camel|Case` and
+///   `sha256|Sum`. In a run
+///   of capitals, the break is put before the last capital so that
+///   acronyms are kept together, as in `HTTP|Request`,
+/// * between a letter and a digit, as in `sha|256`.
+///
+/// No hyphen is inserted when an identifier is broken: the break
+/// itself is the separator. Breaks which would leave a part shorter
+/// than `min` characters are skipped. A trailing underscore is not
+/// counted as part of the length.
+///
+/// Words which are not identifiers are left alone. Use a
+/// [`ChainSplitter`] to combine this with another splitter for the
+/// prose around the identifiers:
+///
+/// ```
+/// use textwrap::{wrap, ChainSplitter, HyphenSplitter, IdentifierSplitter, Options, WordSplitter};
+///
+/// let splitter = IdentifierSplitter::new(3);
+/// assert_eq!(splitter.split_points("max_http_body_size"), vec![4, 9, 14]);
+/// assert_eq!(splitter.split_points("HTTPRequestFactory"), vec![4, 11]);
+/// assert_eq!(splitter.split_points("getXValue"), vec![3]);
+///
+/// let splitter = ChainSplitter::new(vec![
+///     Box::new(IdentifierSplitter::new(3)) as Box<dyn WordSplitter>,
+///     Box::new(HyphenSplitter),
+/// ]);
+/// let options = Options::new(12).splitter(splitter);
+/// assert_eq!(wrap("A DatabaseConnection is well-known", &options),
+///            vec!["A Database", "Connection", "is well-", "known"]);
+/// ```
+///
+/// The breaks are returned with a [`Substitution`] without a hyphen
+/// by [`WordSplitter::split`], which is what tells the wrapping
+/// functions not to add one.
+#[derive(Clone, Copy, Debug)]
+pub struct IdentifierSplitter {
+    min: usize,
+}
+
+impl IdentifierSplitter {
+    /// Create a splitter which splits identifiers into parts of at
+    /// least `min` characters.
+    pub const fn new(min: usize) -> Self {
+        IdentifierSplitter { min }
+    }
+}
+
+/// The empty change of spelling used to break a word without a
+/// hyphen.
+pub(crate) const NO_HYPHEN: Substitution<'static> = Substitution {
+    left: 0,
+    right: 0,
+    before_break: "",
+    hyphen: "",
+    after_break: "",
+};
+
+impl WordSplitter for IdentifierSplitter {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        identifier_split_points(word, self.min).collect()
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        identifier_split_points(word, self.min)
+            .map(|idx| (idx, Some(NO_HYPHEN)))
+            .collect()
+    }
+}
+
+/// The split points used by [`IdentifierSplitter`].
+fn identifier_split_points(word: &str, min: usize) -> impl Iterator<Item = usize> + '_ {
+    // Number of characters in `part`, not counting an underscore at
+    // the end.
+    let len = |part: &str| part.strip_suffix('_').unwrap_or(part).chars().count();
+    let mut prev = 0;
+    word.char_indices()
+        .zip(word.chars().skip(1))
+        .zip(word.chars().skip(2).map(Some).chain(Some(None)))
+        .filter_map(move |(((idx, before), after), next)| {
+            let boundary = match (before, after) {
+                ('_', after) => {
+                    after.is_alphanumeric() && word[prev..idx].contains(char::is_alphanumeric)
+                }
+                (before, after) if after.is_uppercase() => {
+                    before.is_lowercase()
+                        || before.is_ascii_digit()
+                        || before.is_uppercase() && next.is_some_and(char::is_lowercase)
+                }
+                (before, after) => before.is_alphabetic() && after.is_ascii_digit(),
+            };
+            let split = idx + before.len_utf8();
+            if boundary && len(&word[prev..split]) >= min && len(&word[split..]) >= min {
+                prev = split;
+                Some(split)
+            } else {
+                None
             }
+        })
+}
+
+/// Restrict another [`WordSplitter`] to split points which leave at
+/// least `min` characters before and after the split.
+///
+/// A hyphen at the end of the first fragment is not counted. This
+/// works with any splitter, including a hyphenation dictionary:
+///
+/// ```
+/// use textwrap::{MinFragment, WordSplitter};
+///
+/// #[derive(Debug)]
+/// struct EveryChar;
+///
+/// impl WordSplitter for EveryChar {
+///     fn split_points(&self, word: &str) -> Vec<usize> {
+///         (1..word.len()).collect()
+///     }
+/// }
+///
+/// assert_eq!(EveryChar.split_points("abcde"), vec![1, 2, 3, 4]);
+/// assert_eq!(MinFragment::new(EveryChar, 2).split_points("abcde"), vec![2, 3]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MinFragment<S> {
+    splitter: S,
+    min: usize,
+}
+
+impl<S: WordSplitter> MinFragment<S> {
+    /// Wrap `splitter` so that fragments shorter than `min`
+    /// characters are avoided.
+    pub const fn new(splitter: S, min: usize) -> Self {
+        MinFragment { splitter, min }
+    }
+}
+
+impl<S: WordSplitter> MinFragment<S> {
+    /// Check if splitting `word` at `idx` leaves long enough fragments.
+    fn keeps(&self, word: &str, idx: usize) -> bool {
+        let head = word[..idx].strip_suffix('-').unwrap_or(&word[..idx]);
+        head.chars().count() >= self.min && word[idx..].chars().count() >= self.min
+    }
+}
+
+impl<S: WordSplitter> WordSplitter for MinFragment<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        self.splitter
+            .split_points(word)
+            .into_iter()
+            .filter(|&idx| self.keeps(word, idx))
+            .collect()
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        self.splitter
+            .split(word)
+            .into_iter()
+            .filter(|&(idx, _)| self.keeps(word, idx))
+            .collect()
+    }
+}
+
+/// Use a closure as a [`WordSplitter`].
+///
+/// The closure is given a word and must return the split points like
+/// [`WordSplitter::split_points`]. This is handy for one-off rules,
+/// such as splitting long file paths after each `/`:
+///
+/// ```
+/// use textwrap::{wrap, FnSplitter, LongWords, Options};
+///
+/// let splitter = FnSplitter::new(|word: &str| {
+///     word.match_indices('/').map(|(idx, _)| idx + 1).collect()
+/// })
+/// .without_hyphens();
+/// let options = Options::new(10).long_words(LongWords::Overflow).splitter(splitter);
+/// assert_eq!(wrap("See /usr/local/bin", &options),
+///            vec!["See /usr/", "local/bin"]);
+/// ```
+///
+/// The splitter can also be boxed to create an `Options` with a
+/// `Box<dyn WordSplitter>` splitter:
+///
+/// ```
+/// use textwrap::{wrap, FnSplitter, LongWords, Options};
+///
+/// let splitter = FnSplitter::new(|word: &str| {
+///     word.match_indices('/').map(|(idx, _)| idx + 1).collect()
+/// })
+/// .without_hyphens();
+/// let options: Options = Options::with_splitter(10, Box::new(splitter));
+/// let options = options.long_words(LongWords::Overflow);
+/// assert_eq!(wrap("See /usr/local/bin", &options),
+///            vec!["See /usr/", "local/bin"]);
+/// ```
+///
+/// Like with other splitters, a `-` is inserted where a word is
+/// broken, unless the fragment already ends with a `-`. Use
+/// [`FnSplitter::without_hyphens`] to break the words without adding
+/// anything, as is done for the paths above.
+#[derive(Clone, Copy)]
+pub struct FnSplitter<F> {
+    split_points: F,
+    hyphens: bool,
+}
+
+impl<F: Fn(&str) -> Vec<usize>> FnSplitter<F> {
+    /// Create a new splitter which calls `split_points` to find the
+    /// split points of a word.
+    pub const fn new(split_points: F) -> Self {
+        FnSplitter {
+            split_points,
+            hyphens: true,
         }
+    }
+
+    /// Do not insert a `-` where a word is broken.
+    ///
+    /// This only applies when the splitter is used directly, behind
+    /// a `Box`, `&`, or `Arc`, or in a [`ChainSplitter`], see
+    /// [`WordSplitter::split`].
+    pub fn without_hyphens(self) -> Self {
+        FnSplitter {
+            hyphens: false,
+            ..self
+        }
+    }
+}
+
+impl<F> ::core::fmt::Debug for FnSplitter<F> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.write_str("FnSplitter(..)")
+    }
+}
+
+impl<F: Fn(&str) -> Vec<usize> + Send + Sync> WordSplitter for FnSplitter<F> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        (self.split_points)(word)
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        let substitution = Some(NO_HYPHEN).filter(|_| !self.hyphens);
+        (self.split_points)(word)
+            .into_iter()
+            .map(|idx| (idx, substitution))
+            .collect()
+    }
+}
+
+/// Combine several [`WordSplitter`]s into one.
+///
+/// The split points proposed by all splitters are merged, sorted, and
+/// de-duplicated. Use this if different kinds of words need different
+/// splitters, e.g., a hyphenation dictionary for prose and a splitter
+/// which splits URLs after each `/`:
+///
+/// ```
+/// use textwrap::{wrap, ChainSplitter, FnSplitter, HyphenSplitter, LongWords, Options, WordSplitter};
+///
+/// let slashes = FnSplitter::new(|word: &str| {
+///     word.match_indices('/').map(|(idx, _)| idx + 1).collect()
+/// });
+/// let splitter = ChainSplitter::new(vec![
+///     Box::new(HyphenSplitter) as Box<dyn WordSplitter>,
+///     Box::new(slashes),
+/// ]);
+/// assert_eq!(splitter.split_points("well-known/path"), vec![5, 11]);
+///
+/// let options = Options::new(11).long_words(LongWords::Overflow).splitter(splitter);
+/// assert_eq!(wrap("a well-known/path", &options),
+///            vec!["a well-", "known/path"]);
+/// ```
+///
+/// The inner splitters can also be of a single concrete type, such as
+/// several hyphenation dictionaries.
+#[derive(Clone, Debug)]
+pub struct ChainSplitter<S = Box<dyn WordSplitter>> {
+    splitters: Vec<S>,
+}
 
+impl<S: WordSplitter> ChainSplitter<S> {
+    /// Create a splitter which uses all of `splitters`.
+    pub fn new(splitters: Vec<S>) -> Self {
+        ChainSplitter { splitters }
+    }
+}
+
+impl<S: WordSplitter> WordSplitter for ChainSplitter<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        let mut splits = self
+            .splitters
+            .iter()
+            .flat_map(|splitter| splitter.split_points(word))
+            .collect::<Vec<_>>();
+        splits.sort_unstable();
+        splits.dedup();
         splits
     }
+
+    /// Merge the split points like [`ChainSplitter::split_points`].
+    /// If several splitters propose the same split point, the
+    /// [`Substitution`] of the first one is used.
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        let mut splits = self
+            .splitters
+            .iter()
+            .flat_map(|splitter| splitter.split(word))
+            .collect::<Vec<_>>();
+        splits.sort_by_key(|&(idx, _)| idx);
+        splits.dedup_by_key(|&mut (idx, _)| idx);
+        splits.into_iter().collect()
+    }
+}
+
+/// The choice of splitter made by the classifier of a
+/// [`LanguageSplitter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LangChoice {
+    /// Split the word with the splitter at this index.
+    Use(usize),
+    /// Do not split the word at all.
+    Decline,
+}
+
+/// Choose a [`WordSplitter`] for each word, e.g., a hyphenation
+/// dictionary for each language in a mixed-language document.
+///
+/// The `classify` callback is given each word and returns which of
+/// the `splitters` to use for it. If the callback declines, or if it
+/// returns an index without a splitter, the word is not split. This
+/// way, the heuristics for finding the language of a word, such as
+/// looking it up in a word list, can live in your own code:
+///
+/// ```
+/// #[cfg(feature = "hyphenation")]
+/// {
+///     use hyphenation::{Language, Load, Standard};
+///     use textwrap::{wrap, LangChoice, LanguageSplitter, Options, WordSplitter};
+///
+///     let german = Standard::from_embedded(Language::German1996).unwrap();
+///     let english = Standard::from_embedded(Language::EnglishUS).unwrap();
+///     let splitter = LanguageSplitter::new(vec![german, english], |word: &str| {
+///         if word.starts_with('`') {
+///             LangChoice::Decline
+///         } else if ["documentation", "understanding"].contains(&word) {
+///             LangChoice::Use(1)
+///         } else {
+///             LangChoice::Use(0)
+///         }
+///     });
+///     assert_eq!(splitter.split_points("understanding"), vec![2, 5, 10]);
+///     assert_eq!(splitter.split_points("Anleitung"), vec![2, 5]);
+///     assert_eq!(splitter.split_points("`Anleitung`"), vec![]);
+///
+///     let options = Options::new(12).splitter(splitter);
+///     assert_eq!(wrap("Eine Anleitung for understanding", &options),
+///                vec!["Eine Anlei-", "tung for un-", "derstanding"]);
+/// }
+/// ```
+pub struct LanguageSplitter<F, S = Box<dyn WordSplitter>> {
+    splitters: Vec<S>,
+    classify: F,
+}
+
+impl<F: Fn(&str) -> LangChoice, S: WordSplitter> LanguageSplitter<F, S> {
+    /// Create a splitter which uses the result of `classify` to pick
+    /// one of `splitters` for each word.
+    pub fn new(splitters: Vec<S>, classify: F) -> Self {
+        LanguageSplitter {
+            splitters,
+            classify,
+        }
+    }
+
+    fn splitter(&self, word: &str) -> Option<&S> {
+        match (self.classify)(word) {
+            LangChoice::Use(idx) => self.splitters.get(idx),
+            LangChoice::Decline => None,
+        }
+    }
+}
+
+impl<F, S: ::core::fmt::Debug> ::core::fmt::Debug for LanguageSplitter<F, S> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_struct("LanguageSplitter")
+            .field("splitters", &self.splitters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, S> WordSplitter for LanguageSplitter<F, S>
+where
+    F: Fn(&str) -> LangChoice + Send + Sync,
+    S: WordSplitter,
+{
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        self.splitter(word)
+            .map_or_else(Vec::new, |splitter| splitter.split_points(word))
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        self.splitter(word)
+            .map_or_else(SplitPoints::new, |splitter| splitter.split(word))
+    }
+}
+
+/// Remember the split points found by another [`WordSplitter`].
+///
+/// Finding split points with a hyphenation dictionary is relatively
+/// slow. When the same text is wrapped again and again, such as when
+/// a terminal window is resized, caching the split points for each
+/// word can give a large speedup:
+///
+/// ```
+/// use textwrap::{wrap, CachedSplitter, HyphenSplitter, Options};
+///
+/// let splitter = CachedSplitter::new(HyphenSplitter);
+/// let options = Options::new(5).splitter(splitter.clone());
+/// assert_eq!(wrap("foo-bar foo-bar", &options), vec!["foo-", "bar", "foo-", "bar"]);
+/// assert_eq!(splitter.len(), 1);
+/// ```
+///
+/// Cloning a `CachedSplitter` is cheap: the clone shares both the
+/// inner splitter and the cache with the original. The cache is
+/// cleared when it holds `capacity` words, see
+/// [`CachedSplitter::with_capacity`].
+///
+/// The [`Substitution`]s of the inner splitter are kept. A word
+/// which is respelled when it is split, such as by an `Extended`
+/// hyphenation dictionary, borrows the new letters from the inner
+/// splitter, so such a word is split by the inner splitter every
+/// time.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct CachedSplitter<S> {
+    splitter: Arc<S>,
+    capacity: usize,
+    cache: Arc<Mutex<HashMap<String, CachedSplit>>>,
+}
+
+/// The split points of a word in a [`CachedSplitter`], or `None` if
+/// they borrow letters from the inner splitter.
+#[cfg(feature = "std")]
+type CachedSplit = Option<SplitPoints<'static>>;
+
+#[cfg(feature = "std")]
+impl<S: WordSplitter> CachedSplitter<S> {
+    /// Create a cache for `splitter` which holds up to 10,000 words.
+    pub fn new(splitter: S) -> Self {
+        CachedSplitter::with_capacity(splitter, 10_000)
+    }
+
+    /// Create a cache for `splitter` which holds up to `capacity`
+    /// words. Nothing is cached if `capacity` is zero.
+    pub fn with_capacity(splitter: S, capacity: usize) -> Self {
+        CachedSplitter {
+            splitter: Arc::new(splitter),
+            capacity,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Number of words currently in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remove all words from the cache.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedSplit>> {
+        // The cache is always in a consistent state, even if another
+        // thread panicked while holding the lock.
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S> Clone for CachedSplitter<S> {
+    fn clone(&self) -> Self {
+        CachedSplitter {
+            splitter: Arc::clone(&self.splitter),
+            capacity: self.capacity,
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: WordSplitter> WordSplitter for CachedSplitter<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        self.split(word).indices().collect()
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        if let Some(cached) = self.lock().get(word) {
+            return match cached {
+                Some(points) => points.clone(),
+                None => self.splitter.split(word),
+            };
+        }
+
+        // The lock is not held while the inner splitter runs.
+        let points = self.splitter.split(word);
+        if self.capacity > 0 {
+            let cached = points
+                .as_slice()
+                .iter()
+                .map(|&(idx, substitution)| match substitution {
+                    None => Some((idx, None)),
+                    // Only the letters are borrowed from the splitter.
+                    Some(substitution)
+                        if substitution.before_break.is_empty()
+                            && substitution.after_break.is_empty() =>
+                    {
+                        let substitution = Substitution {
+                            left: substitution.left,
+                            right: substitution.right,
+                            before_break: "",
+                            hyphen: substitution.hyphen,
+                            after_break: "",
+                        };
+                        Some((idx, Some(substitution)))
+                    }
+                    Some(_) => None,
+                })
+                .collect();
+            let mut cache = self.lock();
+            if cache.len() >= self.capacity {
+                cache.clear();
+            }
+            cache.insert(word.to_string(), cached);
+        }
+        points
+    }
 }
 
 /// A hyphenation dictionary can be used to do language-specific
 /// hyphenation using patterns from the [hyphenation] crate.
 ///
+/// [`WordSplitter::split`] matches the patterns without allocating
+/// for ASCII words of up to 64 bytes. Other words are hyphenated by
+/// the hyphenation crate.
+///
 /// **Note:** Only available when the `hyphenation` Cargo feature is
 /// enabled.
 ///
@@ -137,4 +1139,207 @@ impl WordSplitter for hyphenation::Standard {
         use hyphenation::Hyphenator;
         self.hyphenate(word).breaks
     }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        use hyphenation::Hyphenator;
+        hyphenate_ascii(self, word).unwrap_or_else(|| self.hyphenate(word).breaks.into())
+    }
+}
+
+/// Longest word, in bytes, which [`hyphenate_ascii`] handles.
+#[cfg(feature = "hyphenation")]
+const MAX_ASCII_WORD: usize = 64;
+
+/// Find the split points of an ASCII `word` like
+/// `Hyphenator::hyphenate`, but without allocating: the lowercased
+/// word and its scores are kept on the stack. Returns `None` for words
+/// which are not ASCII or longer than [`MAX_ASCII_WORD`] bytes.
+#[cfg(feature = "hyphenation")]
+fn hyphenate_ascii<'a>(dictionary: &hyphenation::Standard, word: &str) -> Option<SplitPoints<'a>> {
+    let len = word.len();
+    if !word.is_ascii() || len > MAX_ASCII_WORD {
+        return None;
+    }
+    let mut points = SplitPoints::new();
+    let (l_min, r_min) = dictionary.minima;
+    if len < l_min + r_min || len <= l_min {
+        return Some(points);
+    }
+    // The breaks must be at least `l_min` letters from the start and
+    // `r_min` letters from the end of the word, but not at the end.
+    let bounds = l_min..=len - r_min.max(1);
+
+    // The patterns are matched against the lowercased word between
+    // two dots, which mark the word boundaries.
+    let mut buffer = [b'.'; MAX_ASCII_WORD + 2];
+    for (dst, src) in buffer[1..=len].iter_mut().zip(word.bytes()) {
+        *dst = src.to_ascii_lowercase();
+    }
+    let dotted = &buffer[..len + 2];
+    let lowercase = ::core::str::from_utf8(&dotted[1..=len]).ok()?;
+
+    if let Some(breaks) = dictionary.exceptions.0.get(lowercase) {
+        for &idx in breaks.iter().filter(|idx| bounds.contains(idx)) {
+            points.push(idx);
+        }
+        return Some(points);
+    }
+
+    // The score of the break before byte `k - 1` of the word is the
+    // highest value any pattern gives it. Odd scores allow a break.
+    let mut scores = [0u8; MAX_ASCII_WORD];
+    for i in 0..dotted.len() - 1 {
+        for tally in dictionary.prefix_tallies(&dotted[i..]) {
+            for locus in tally {
+                let k = i + locus.index as usize;
+                if k > 1 && k <= len && locus.value > scores[k - 2] {
+                    scores[k - 2] = locus.value;
+                }
+            }
+        }
+    }
+    for idx in 1..len {
+        if scores[idx - 1] % 2 == 1 && bounds.contains(&idx) {
+            points.push(idx);
+        }
+    }
+    Some(points)
+}
+
+/// An extended hyphenation dictionary can be used for languages where
+/// the spelling of a word changes when it is hyphenated, such as
+/// Hungarian. The changes are applied when a word is split, see
+/// [`Substitution`].
+///
+/// ```
+/// #[cfg(feature = "hyphenation")]
+/// {
+///     use hyphenation::{extended::Extended, Language, Load};
+///     use textwrap::{wrap, Options};
+///
+///     let dictionary = Extended::from_embedded(Language::Hungarian).unwrap();
+///     let options = Options::new(10).splitter(dictionary);
+///     assert_eq!(wrap("egy asszonnyal", &options), vec!["egy asz-", "szonnyal"]);
+/// }
+/// ```
+///
+/// Only the English dictionary is embedded by default. Enable the
+/// `embed_all` feature of the hyphenation crate to embed the
+/// dictionaries for the other languages.
+///
+/// **Note:** Only available when the `hyphenation` Cargo feature is
+/// enabled.
+#[cfg(feature = "hyphenation")]
+impl WordSplitter for hyphenation::extended::Extended {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        use hyphenation::Hyphenator;
+        self.hyphenate(word)
+            .breaks
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn split(&self, word: &str) -> SplitPoints<'_> {
+        use hyphenation::Hyphenator;
+        self.hyphenate(word)
+            .breaks
+            .into_iter()
+            .map(|(idx, subregion)| {
+                let substitution = subregion.and_then(|subregion| {
+                    let (before_break, after_break) = subregion
+                        .substitution
+                        .split_at_checked(subregion.breakpoint)?;
+                    Some(Substitution {
+                        left: subregion.left,
+                        right: subregion.right,
+                        before_break,
+                        hyphen: "-",
+                        after_break,
+                    })
+                });
+                (idx, substitution)
+            })
+            .collect()
+    }
+}
+
+/// Find the hyphenation dictionary for a locale.
+///
+/// The `tag` is a [BCP 47] language tag such as `"de-DE"` or a POSIX
+/// locale such as `"pt_BR.UTF-8"`. Case does not matter. Subtags are
+/// removed from the end of the tag until a dictionary is found, and
+/// a language without a dictionary of its own is mapped to the usual
+/// variant: `"de-DE"` becomes `"de"` which uses the `"de-1996"`
+/// dictionary and `"en"` uses `"en-us"`.
+///
+/// Returns `None` if no dictionary is found for the locale or if the
+/// dictionary is not embedded. Only the English dictionary is
+/// embedded by default. Enable the `embed_all` feature of the
+/// hyphenation crate to embed the dictionaries for all languages.
+///
+/// # Examples
+///
+/// ```
+/// use hyphenation::Language;
+/// use textwrap::dictionary_for_locale;
+///
+/// let dictionary = dictionary_for_locale("en_GB.UTF-8").unwrap();
+/// assert_eq!(dictionary.language(), Language::EnglishGB);
+/// assert!(dictionary_for_locale("tlh").is_none());
+/// ```
+///
+/// **Note:** Only available when the `hyphenation` Cargo feature is
+/// enabled.
+///
+/// [BCP 47]: https://tools.ietf.org/html/bcp47
+#[cfg(feature = "hyphenation")]
+pub fn dictionary_for_locale(tag: &str) -> Option<hyphenation::Standard> {
+    use hyphenation::Load;
+    locale_languages(tag)
+        .into_iter()
+        .find_map(|language| hyphenation::Standard::from_embedded(language).ok())
+}
+
+/// Languages without a dictionary of their own and the dictionary
+/// used for them, see [`dictionary_for_locale`].
+#[cfg(feature = "hyphenation")]
+const LOCALE_ALIASES: [(&str, &str); 9] = [
+    ("de", "de-1996"),
+    ("de-ch", "de-ch-1901"),
+    ("el", "el-monoton"),
+    ("en", "en-us"),
+    ("mn", "mn-cyrl"),
+    ("no", "nb"),
+    ("sh", "sh-latn"),
+    ("sr", "sr-cyrl"),
+    ("sr-latn", "sh-latn"),
+];
+
+/// The languages to try for a locale `tag`, most specific first.
+#[cfg(feature = "hyphenation")]
+pub(crate) fn locale_languages(tag: &str) -> Vec<hyphenation::Language> {
+    let tag = tag
+        .split(['.', '@'])
+        .next()
+        .unwrap_or("")
+        .replace('_', "-")
+        .to_lowercase();
+    let subtags = tag.split('-').collect::<Vec<_>>();
+
+    let mut languages = Vec::new();
+    for n in (1..=subtags.len()).rev() {
+        let code = subtags[..n].join("-");
+        let alias = LOCALE_ALIASES
+            .iter()
+            .find(|(from, _)| *from == code)
+            .map(|(_, to)| *to);
+        let candidates = ::core::iter::once(code.as_str()).chain(alias);
+        for language in candidates.filter_map(hyphenation::Language::try_from_code) {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+    }
+    languages
 }